@@ -0,0 +1,171 @@
+//! Boilerplate shared by the node components: generating their WIT bindings, loading their
+//! settings, building and splitting the URLs of their requests and signing the requests of AWS
+//! nodes. Nodes
+//! generate their own bindings, from their own `wit` folder, so settings are read through the
+//! `wasi:config/runtime` getter a node passes in.
+
+use shared::FromConfig;
+use std::fmt::Debug;
+
+pub mod sigv4;
+
+/// Config key the node settings are provided under
+pub const SETTINGS_CONFIG_KEY: &str = "json";
+
+/// Generates the WIT bindings of a node into a `bindings` module and exports `$component` as
+/// its implementation
+#[macro_export]
+macro_rules! bindings {
+    ($component:ident) => {
+        mod bindings {
+            use super::$component;
+            wit_bindgen::generate!({ generate_all });
+            export!($component);
+        }
+    };
+}
+
+/// Failure to load the settings of a node. Its message is the one nodes log and output.
+#[derive(Debug, PartialEq, Eq)]
+pub enum NodeError {
+    /// The config store couldn't be read
+    ConfigUnavailable(String),
+    /// No settings are configured
+    MissingConfig,
+    /// The settings don't deserialize into the node's settings type
+    InvalidConfig(String),
+}
+
+impl std::fmt::Display for NodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConfigUnavailable(e) => write!(f, "Failed to get config: {e}"),
+            Self::MissingConfig => write!(f, "Failed to parse config: No configuration provided"),
+            Self::InvalidConfig(e) => write!(f, "Failed to parse config: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for NodeError {}
+
+/// The node settings, read with `get`, e.g. `bindings::wasi::config::runtime::get`
+pub fn load_settings<T, E>(
+    get: impl FnOnce(&str) -> Result<Option<String>, E>,
+) -> Result<T, NodeError>
+where
+    T: FromConfig,
+    E: Debug,
+{
+    match get(SETTINGS_CONFIG_KEY) {
+        Ok(None) => Err(NodeError::MissingConfig),
+        Ok(config) => T::from_config(config).map_err(|e| NodeError::InvalidConfig(e.to_string())),
+        Err(e) => Err(NodeError::ConfigUnavailable(format!("{e:?}"))),
+    }
+}
+
+/// Percent-encode a URL path segment, e.g. the RabbitMQ vhost `/` as `%2F`
+pub fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Parts of a request URL as they're set on a `wasi:http` outgoing request
+#[derive(Debug, PartialEq, Eq)]
+pub struct UrlParts<'a> {
+    /// Whether the request goes over `https`, which is the case for any scheme but `http` and
+    /// for URLs without a scheme
+    pub https: bool,
+    pub authority: &'a str,
+    /// Path and query of the URL, `/` when it has none
+    pub path_with_query: String,
+}
+
+/// Split a URL such as `https://example.com/path?query` into the parts of an outgoing request
+pub fn split_url(url: &str) -> UrlParts<'_> {
+    let (https, rest) = match url.split_once("://") {
+        Some(("http", rest)) => (false, rest),
+        Some((_, rest)) => (true, rest),
+        None => (true, url),
+    };
+    let (authority, path_with_query) = rest
+        .split_once('/')
+        .map_or((rest, "/".to_string()), |(authority, path)| {
+            (authority, format!("/{path}"))
+        });
+    UrlParts {
+        https,
+        authority,
+        path_with_query,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::OutLogSettings;
+
+    #[test]
+    fn test_load_settings() {
+        let settings: OutLogSettings = load_settings(|key| {
+            assert_eq!(key, SETTINGS_CONFIG_KEY);
+            Ok::<_, ()>(Some(r#"{"maxLogBytes": 100}"#.to_string()))
+        })
+        .unwrap();
+
+        assert_eq!(settings.max_log_bytes, Some(100));
+    }
+
+    #[test]
+    fn test_missing_config_is_an_error() {
+        let result: Result<OutLogSettings, _> = load_settings(|_| Ok::<_, ()>(None));
+        assert_eq!(result.unwrap_err(), NodeError::MissingConfig);
+
+        let result: Result<OutLogSettings, _> = load_settings(|_| Err("store unavailable"));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            r#"Failed to get config: "store unavailable""#
+        );
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("/"), "%2F");
+        assert_eq!(encode_path_segment("orders.eu-west_1"), "orders.eu-west_1");
+        assert_eq!(encode_path_segment("a b/ü"), "a%20b%2F%C3%BC");
+    }
+
+    #[test]
+    fn test_split_url() {
+        assert_eq!(
+            split_url("http://localhost:3100/loki/api/v1/push?tenant=a"),
+            UrlParts {
+                https: false,
+                authority: "localhost:3100",
+                path_with_query: "/loki/api/v1/push?tenant=a".to_string(),
+            }
+        );
+        assert_eq!(
+            split_url("https://example.com"),
+            UrlParts {
+                https: true,
+                authority: "example.com",
+                path_with_query: "/".to_string(),
+            }
+        );
+        assert_eq!(
+            split_url("example.com/feed.xml"),
+            UrlParts {
+                https: true,
+                authority: "example.com",
+                path_with_query: "/feed.xml".to_string(),
+            }
+        );
+    }
+}
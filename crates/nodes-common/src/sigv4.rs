@@ -0,0 +1,385 @@
+//! AWS Signature Version 4 request signing for the AWS nodes, see
+//! https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv-create-signed-request.html
+
+use crate::encode_path_segment;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Request to sign. Its URL is sent as is, so its path and query must already be
+/// percent-encoded, e.g. with `encode_path_segment` and `encode_query`.
+pub struct Request<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub body: &'a [u8],
+}
+
+/// Where requests are signed for, e.g. the `lambda` service in `eu-west-1`
+pub struct Scope<'a> {
+    pub region: &'a str,
+    pub service: &'a str,
+}
+
+/// Sign `request` at `unix_seconds`, adding the headers AWS authenticates it with to `headers`:
+/// `x-amz-date`, the session token of temporary credentials and `Authorization`. Every header
+/// already in `headers` is signed, along with the host of the URL.
+pub fn sign_request(
+    headers: &mut Vec<(String, String)>,
+    request: &Request,
+    credentials: &Credentials,
+    scope: &Scope,
+    unix_seconds: u64,
+) -> Result<(), String> {
+    let (host, path, query) = split_url(request.url)?;
+    let amz_date = amz_date(unix_seconds);
+    headers.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+
+    let mut signed = vec![("host".to_string(), host.to_string())];
+    signed.extend(headers.iter().cloned());
+    let (canonical, signed_headers) =
+        canonical_request(request.method, path, query, &signed, request.body);
+
+    let date = &amz_date[..8];
+    let credential_scope = format!("{date}/{}/{}/aws4_request", scope.region, scope.service);
+    let key = signing_key(&credentials.secret_access_key, date, scope);
+    let signature = hex::encode(hmac(
+        &key,
+        string_to_sign(&amz_date, &credential_scope, &canonical),
+    ));
+
+    headers.push((
+        "Authorization".to_string(),
+        format!(
+            "{ALGORITHM} Credential={}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    Ok(())
+}
+
+/// Query string of `params`, encoded and sorted by name as AWS signs it, e.g. `Qualifier=live`
+pub fn encode_query(params: &[(String, String)]) -> String {
+    let mut params: Vec<String> = params
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{}={}",
+                encode_path_segment(name),
+                encode_path_segment(value)
+            )
+        })
+        .collect();
+    params.sort();
+    params.join("&")
+}
+
+/// Host, path and query of a URL such as `https://lambda.eu-west-1.amazonaws.com/path?query`
+fn split_url(url: &str) -> Result<(&str, &str, &str), String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(format!("Error: {url:?} has no host"));
+    }
+    Ok((host, path, query))
+}
+
+/// The canonical form of a request with `headers`, and the names of its signed headers
+fn canonical_request(
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> (String, String) {
+    let mut headers: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            (name.to_lowercase(), value)
+        })
+        .collect();
+    headers.sort();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+
+    let canonical = [
+        method,
+        &canonical_uri(path),
+        &canonical_query(query),
+        &canonical_headers,
+        &signed_headers,
+        &hex::encode(Sha256::digest(body)),
+    ]
+    .join("\n");
+    (canonical, signed_headers)
+}
+
+/// Services other than S3 sign each path segment encoded once more, so the `%3A` of an ARN in
+/// the path is signed as `%253A`
+fn canonical_uri(path: &str) -> String {
+    path.split('/')
+        .map(encode_path_segment)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Parameters of an encoded query string sorted by name, each with a value, even if empty
+fn canonical_query(query: &str) -> String {
+    let mut params: Vec<String> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.contains('=') {
+            true => param.to_string(),
+            false => format!("{param}="),
+        })
+        .collect();
+    params.sort();
+    params.join("&")
+}
+
+fn string_to_sign(amz_date: &str, credential_scope: &str, canonical_request: &str) -> String {
+    format!(
+        "{ALGORITHM}\n{amz_date}\n{credential_scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    )
+}
+
+fn signing_key(secret_access_key: &str, date: &str, scope: &Scope) -> Vec<u8> {
+    let key = hmac(format!("AWS4{secret_access_key}").as_bytes(), date);
+    let key = hmac(&key, scope.region);
+    let key = hmac(&key, scope.service);
+    hmac(&key, "aws4_request")
+}
+
+fn hmac(key: &[u8], data: impl AsRef<[u8]>) -> Vec<u8> {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_ref());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Signing time in the `YYYYMMDDTHHMMSSZ` format AWS expects, from seconds since the Unix epoch
+fn amz_date(unix_seconds: u64) -> String {
+    let days = (unix_seconds / 86_400) as i64;
+    let seconds = unix_seconds % 86_400;
+
+    // Civil date from days since the epoch, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Credentials, scope and time of the AWS Signature Version 4 test suite
+    const TEST_SUITE_TIME: u64 = 1_440_938_160;
+
+    fn example_credentials() -> Credentials {
+        Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    const EXAMPLE_SCOPE: Scope = Scope {
+        region: "us-east-1",
+        service: "service",
+    };
+
+    fn sign(method: &str, url: &str, credentials: &Credentials) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        let request = Request {
+            method,
+            url,
+            body: b"",
+        };
+        sign_request(
+            &mut headers,
+            &request,
+            credentials,
+            &EXAMPLE_SCOPE,
+            TEST_SUITE_TIME,
+        )
+        .unwrap();
+        headers
+    }
+
+    fn authorization(headers: &[(String, String)]) -> &str {
+        &headers
+            .iter()
+            .find(|(name, _)| name == "Authorization")
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_get_vanilla_canonical_request() {
+        let headers = [
+            ("Host".to_string(), "example.amazonaws.com".to_string()),
+            ("X-Amz-Date".to_string(), "20150830T123600Z".to_string()),
+        ];
+        let (canonical, signed_headers) = canonical_request("GET", "/", "", &headers, b"");
+
+        assert_eq!(
+            canonical,
+            "GET\n/\n\nhost:example.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+             host;x-amz-date\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(signed_headers, "host;x-amz-date");
+        assert_eq!(
+            string_to_sign(
+                "20150830T123600Z",
+                "20150830/us-east-1/service/aws4_request",
+                &canonical
+            ),
+            "AWS4-HMAC-SHA256\n20150830T123600Z\n20150830/us-east-1/service/aws4_request\n\
+             bb579772317eb040ac9ed261061d46c1f17a8133879d6129b6e1c25292927e63"
+        );
+    }
+
+    #[test]
+    fn test_get_vanilla() {
+        let headers = sign(
+            "GET",
+            "https://example.amazonaws.com/",
+            &example_credentials(),
+        );
+
+        assert_eq!(
+            headers,
+            [
+                ("x-amz-date".to_string(), "20150830T123600Z".to_string()),
+                (
+                    "Authorization".to_string(),
+                    "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                     SignedHeaders=host;x-amz-date, \
+                     Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_vanilla_query_order_key_case() {
+        let headers = sign(
+            "GET",
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+            &example_credentials(),
+        );
+
+        assert!(authorization(&headers).ends_with(
+            "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        ));
+    }
+
+    #[test]
+    fn test_session_token_is_signed() {
+        let credentials = Credentials {
+            session_token: Some("session-token".to_string()),
+            ..example_credentials()
+        };
+        let headers = sign("GET", "https://example.amazonaws.com/", &credentials);
+
+        assert_eq!(
+            headers[1],
+            (
+                "x-amz-security-token".to_string(),
+                "session-token".to_string()
+            )
+        );
+        assert!(
+            authorization(&headers).contains("SignedHeaders=host;x-amz-date;x-amz-security-token,")
+        );
+    }
+
+    #[test]
+    fn test_arn_in_path_is_encoded_twice() {
+        let (canonical, _) = canonical_request(
+            "POST",
+            "/2015-03-31/functions/arn%3Aaws%3Alambda%3Aeu-west-1%3A123456789012%3Afunction%3Aprocess-order/invocations",
+            &encode_query(&[
+                ("Qualifier".to_string(), "$LATEST".to_string()),
+                ("A".to_string(), "b c".to_string()),
+            ]),
+            &[],
+            b"{}",
+        );
+        let lines: Vec<&str> = canonical.lines().collect();
+
+        assert_eq!(
+            lines[1],
+            "/2015-03-31/functions/arn%253Aaws%253Alambda%253Aeu-west-1%253A123456789012%253Afunction%253Aprocess-order/invocations"
+        );
+        assert_eq!(lines[2], "A=b%20c&Qualifier=%24LATEST");
+    }
+
+    #[test]
+    fn test_url_without_host_is_rejected() {
+        let mut headers = Vec::new();
+        let request = Request {
+            method: "GET",
+            url: "https:///path",
+            body: b"",
+        };
+
+        assert!(
+            sign_request(
+                &mut headers,
+                &request,
+                &example_credentials(),
+                &EXAMPLE_SCOPE,
+                TEST_SUITE_TIME
+            )
+            .is_err()
+        );
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_amz_date() {
+        assert_eq!(amz_date(0), "19700101T000000Z");
+        assert_eq!(amz_date(TEST_SUITE_TIME), "20150830T123600Z");
+        assert_eq!(amz_date(1_709_251_199), "20240229T235959Z");
+    }
+}
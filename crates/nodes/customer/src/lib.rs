@@ -17,4 +17,11 @@ impl Guest for Component {
             "Received: {input}. Hello there from the nodes/customer stub"
         )))
     }
+
+    fn run_bytes(input: Vec<u8>) -> Result<Result<Vec<u8>, RunError>, rpc::error::Error> {
+        // Binary input is passed through as is, after a text prefix
+        let mut output = b"Received bytes: ".to_vec();
+        output.extend(input);
+        Ok(Ok(output))
+    }
 }
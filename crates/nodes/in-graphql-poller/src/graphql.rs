@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use shared::InGraphqlPollerSettings;
+
+/// Variable the cursor of the next page is passed to the query as
+pub const CURSOR_VARIABLE: &str = "cursor";
+
+/// State persisted between polls of the endpoint
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PollState {
+    #[serde(default)]
+    pub last_poll_ms: Option<u64>,
+    /// Cursor of the next page, found in the previous response
+    #[serde(default)]
+    pub cursor: Option<Value>,
+}
+
+impl PollState {
+    /// Whether enough time has passed since the last poll to query the endpoint again
+    pub fn is_due(&self, now_ms: u64, interval_ms: u64) -> bool {
+        match self.last_poll_ms {
+            Some(last_poll_ms) => now_ms.saturating_sub(last_poll_ms) >= interval_ms,
+            None => true,
+        }
+    }
+}
+
+/// Body of the request sending the query, with the cursor of the previous poll, if any, as its
+/// `cursor` variable
+pub fn request_body(settings: &InGraphqlPollerSettings, cursor: Option<&Value>) -> Value {
+    let mut variables = match &settings.variables {
+        Some(Value::Object(variables)) => variables.clone(),
+        _ => Map::new(),
+    };
+    if let Some(cursor) = cursor {
+        variables.insert(CURSOR_VARIABLE.to_string(), cursor.clone());
+    }
+
+    let mut body = Map::new();
+    body.insert("query".to_string(), Value::String(settings.query.clone()));
+    if !variables.is_empty() {
+        body.insert("variables".to_string(), Value::Object(variables));
+    }
+    Value::Object(body)
+}
+
+/// The response body, failing when it reports errors or has no `data`
+pub fn parse_response(body: &str) -> Result<Value, String> {
+    let response: Value =
+        serde_json::from_str(body).map_err(|e| format!("Invalid GraphQL response: {e}"))?;
+    if let Some(errors) = response.get("errors").and_then(Value::as_array)
+        && !errors.is_empty()
+    {
+        let messages: Vec<&str> = errors
+            .iter()
+            .map(|error| error.get("message").and_then(Value::as_str).unwrap_or("?"))
+            .collect();
+        return Err(format!("GraphQL errors: {}", messages.join("; ")));
+    }
+    match response.get("data") {
+        Some(Value::Null) | None => Err("GraphQL response has no data".to_string()),
+        Some(_) => Ok(response),
+    }
+}
+
+/// Cursor for the next poll: the value at the `cursor` JSONPath of the response, or the
+/// previous cursor when the response has none, e.g. as there's no newer page yet
+pub fn next_cursor(
+    response: &Value,
+    path: &str,
+    previous: Option<Value>,
+) -> Result<Option<Value>, String> {
+    match json_path(response, path)? {
+        Some(Value::Null) | None => Ok(previous),
+        Some(cursor) => Ok(Some(cursor.clone())),
+    }
+}
+
+/// Value at a JSONPath of fields and indexes, such as `$.data.orders[0]['end cursor']`
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Result<Option<&'a Value>, String> {
+    let invalid = || format!("Unsupported JSONPath {path:?}");
+    let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+    let mut current = value;
+    while !rest.is_empty() {
+        let next = if let Some(field) = rest.strip_prefix('.') {
+            let end = field.find(['.', '[']).unwrap_or(field.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            rest = &field[end..];
+            current.get(&field[..end])
+        } else if let Some(bracketed) = rest.strip_prefix('[') {
+            let (selector, after) = bracketed.split_once(']').ok_or_else(invalid)?;
+            rest = after;
+            match selector
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+            {
+                Some(field) => current.get(field),
+                None => current.get(selector.parse::<usize>().map_err(|_| invalid())?),
+            }
+        } else {
+            return Err(invalid());
+        };
+        match next {
+            Some(next) => current = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings(variables: Option<Value>) -> InGraphqlPollerSettings {
+        InGraphqlPollerSettings {
+            url: "https://api.example.com/graphql".to_string(),
+            query: "query Orders($cursor: String) { orders(after: $cursor) { id } }".to_string(),
+            variables,
+            headers: None,
+            authentication: None,
+            interval_ms: 30_000,
+            cursor: Some("$.data.orders.pageInfo.endCursor".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_request_body() {
+        assert_eq!(
+            request_body(&settings(None), None),
+            json!({"query": "query Orders($cursor: String) { orders(after: $cursor) { id } }"})
+        );
+        assert_eq!(
+            request_body(&settings(Some(json!({"status": "PAID"}))), None)["variables"],
+            json!({"status": "PAID"})
+        );
+    }
+
+    #[test]
+    fn test_cursor_is_substituted() {
+        let settings = settings(Some(json!({"status": "PAID", "cursor": "initial"})));
+
+        assert_eq!(
+            request_body(&settings, None)["variables"],
+            json!({"status": "PAID", "cursor": "initial"})
+        );
+        assert_eq!(
+            request_body(&settings, Some(&json!("b3JkZXI6NDI=")))["variables"],
+            json!({"status": "PAID", "cursor": "b3JkZXI6NDI="})
+        );
+    }
+
+    #[test]
+    fn test_next_cursor() {
+        let response = json!({"data": {"orders": {"pageInfo": {"endCursor": "b3JkZXI6NDI="}}}});
+        let path = "$.data.orders.pageInfo.endCursor";
+        assert_eq!(
+            next_cursor(&response, path, None),
+            Ok(Some(json!("b3JkZXI6NDI=")))
+        );
+
+        // No newer page yet, the next poll starts from the same cursor
+        let response = json!({"data": {"orders": {"pageInfo": {"endCursor": null}}}});
+        assert_eq!(
+            next_cursor(&response, path, Some(json!("b3JkZXI6NDI="))),
+            Ok(Some(json!("b3JkZXI6NDI=")))
+        );
+    }
+
+    #[test]
+    fn test_json_path() {
+        let value = json!({"data": {"orders": [{"id": 1}, {"end cursor": "abc"}]}});
+        assert_eq!(json_path(&value, "$"), Ok(Some(&value)));
+        assert_eq!(
+            json_path(&value, "$.data.orders[0].id"),
+            Ok(Some(&json!(1)))
+        );
+        assert_eq!(
+            json_path(&value, "$['data'].orders[1]['end cursor']"),
+            Ok(Some(&json!("abc")))
+        );
+        assert_eq!(json_path(&value, "$.data.customers"), Ok(None));
+        assert!(json_path(&value, "data.orders").is_err());
+        assert!(json_path(&value, "$.data..orders").is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response(r#"{"data": {"orders": []}}"#),
+            Ok(json!({"data": {"orders": []}}))
+        );
+        assert_eq!(
+            parse_response(r#"{"data": null, "errors": [{"message": "Unauthorized"}]}"#),
+            Err("GraphQL errors: Unauthorized".to_string())
+        );
+        assert!(parse_response(r#"{}"#).is_err());
+    }
+}
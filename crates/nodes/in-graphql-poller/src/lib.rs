@@ -0,0 +1,140 @@
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use bindings::{exports::wasmcloud::messaging, wasmcloud::messaging::types::BrokerMessage};
+use graphql::PollState;
+use nodes_common::UrlParts;
+use shared::{InGraphqlPollerSettings, apply_authentication};
+use wasmcloud_component::{error, info};
+
+mod graphql;
+
+nodes_common::bindings!(Component);
+
+struct Component;
+
+const LOG_CONTEXT: &str = "in-graphql-poller";
+
+/// Every message on the node's poll subject is a tick; the endpoint is only queried once
+/// `intervalMs` has elapsed since the previous poll.
+impl messaging::handler::Guest for Component {
+    fn handle_message(_msg: BrokerMessage) -> Result<(), String> {
+        let settings: InGraphqlPollerSettings =
+            nodes_common::load_settings(bindings::wasi::config::runtime::get).map_err(|e| {
+                error!(context: LOG_CONTEXT, "{e}");
+                e.to_string()
+            })?;
+        let state_key = bindings::wasi::config::runtime::get("state-key")
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| settings.url.clone());
+
+        let bucket = bindings::wasi::keyvalue::store::open("")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {e:?}"))?;
+        let mut state: PollState = bucket
+            .get(&state_key)
+            .map_err(|e| format!("Failed to read poll state: {e:?}"))?
+            .and_then(|value| serde_json::from_slice(&value).ok())
+            .unwrap_or_default();
+
+        let now = bindings::wasi::clocks::wall_clock::now();
+        let now_ms = now.seconds * 1_000 + u64::from(now.nanoseconds / 1_000_000);
+        if !state.is_due(now_ms, settings.interval_ms) {
+            return Ok(());
+        }
+        state.last_poll_ms = Some(now_ms);
+
+        let body = graphql::request_body(&settings, state.cursor.as_ref()).to_string();
+        let response = query(&settings, &body).and_then(|body| graphql::parse_response(&body));
+        match response {
+            Ok(response) => {
+                let received = bindings::pipestack::out::out::run(&response["data"].to_string());
+                info!(context: LOG_CONTEXT, "Called out. Return value: {received}");
+                if let Some(path) = &settings.cursor {
+                    state.cursor = graphql::next_cursor(&response, path, state.cursor.take())?;
+                }
+            }
+            // The cursor is kept, so the next poll queries the same page again
+            Err(e) => error!(context: LOG_CONTEXT, "Polling {} failed: {e}", settings.url),
+        }
+
+        let value =
+            serde_json::to_vec(&state).map_err(|e| format!("Failed to serialize state: {e}"))?;
+        bucket
+            .set(&state_key, &value)
+            .map_err(|e| format!("Failed to store poll state: {e:?}"))?;
+
+        Ok(())
+    }
+}
+
+/// POST the query to the endpoint, returning the response body
+fn query(settings: &InGraphqlPollerSettings, body: &str) -> Result<String, String> {
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    for header in settings.headers.iter().flatten() {
+        headers.push((header.key.clone(), header.value.clone()));
+    }
+    let url = match &settings.authentication {
+        Some(auth) => apply_authentication(&mut headers, &settings.url, auth)?,
+        None => settings.url.clone(),
+    };
+
+    let UrlParts {
+        https,
+        authority,
+        path_with_query,
+    } = nodes_common::split_url(&url);
+    let scheme = if https { Scheme::Https } else { Scheme::Http };
+
+    let fields = Fields::new();
+    for (name, value) in &headers {
+        fields
+            .set(name, &[value.as_bytes().to_vec()])
+            .map_err(|e| format!("Failed to set header {name}: {e}"))?;
+    }
+
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&Method::Post).unwrap();
+    req.set_scheme(Some(&scheme)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(&path_with_query)).unwrap();
+
+    let request_body = req.body().unwrap();
+    let output_stream = request_body.write().unwrap();
+    output_stream
+        .blocking_write_and_flush(body.as_bytes())
+        .map_err(|e| format!("Failed to write request body: {e}"))?;
+    drop(output_stream);
+    bindings::wasi::http::types::OutgoingBody::finish(request_body, None)
+        .map_err(|e| format!("Failed to finish request body: {e}"))?;
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .ok_or("HTTP response missing")?
+        .map_err(|_| "HTTP response requested more than once")?
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+
+    let status = response.status();
+    let response_body = response
+        .consume()
+        .map_err(|_| "Failed to get response body")?;
+    let input_stream = response_body
+        .stream()
+        .map_err(|_| "Failed to get response body stream")?;
+    let mut body = Vec::new();
+    while let Ok(chunk) = input_stream.blocking_read(64 * 1024) {
+        if chunk.is_empty() {
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    match status {
+        200..300 => Ok(body),
+        status => Err(format!(
+            "Endpoint responded with status code {status}: {body}"
+        )),
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::Read;
+
+#[derive(Debug)]
+pub enum BodyError {
+    /// The body of a text request isn't valid UTF-8
+    InvalidUtf8(std::string::FromUtf8Error),
+    /// The body couldn't be read, e.g. the connection dropped mid-request
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for BodyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyError::InvalidUtf8(e) => write!(
+                f,
+                "Request body is not valid UTF-8 (invalid byte at offset {})",
+                e.utf8_error().valid_up_to()
+            ),
+            BodyError::Io(e) => write!(f, "Failed to read request body: {e}"),
+        }
+    }
+}
+
+/// Whether a content type carries text, which must be valid UTF-8. No content type is treated
+/// as text.
+pub fn is_text_content_type(content_type: Option<&str>) -> bool {
+    let Some(content_type) = content_type else {
+        return true;
+    };
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    mime.starts_with("text/")
+        || mime.ends_with("json")
+        || mime.ends_with("xml")
+        || mime == "application/x-www-form-urlencoded"
+        || mime == "application/javascript"
+}
+
+/// Read a request body. Text bodies must be valid UTF-8, other bodies are read lossily.
+pub fn read_body(mut body: impl Read, content_type: Option<&str>) -> Result<String, BodyError> {
+    let mut bytes = Vec::new();
+    body.read_to_end(&mut bytes).map_err(BodyError::Io)?;
+
+    if is_text_content_type(content_type) {
+        String::from_utf8(bytes).map_err(BodyError::InvalidUtf8)
+    } else {
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Yields `bytes` and then fails, like a body whose connection dropped
+    struct Truncated<'a> {
+        bytes: &'a [u8],
+    }
+
+    impl Read for Truncated<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.bytes.is_empty() {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+            }
+            let n = self.bytes.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.bytes[..n]);
+            self.bytes = &self.bytes[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_reads_valid_text_body() {
+        let body = read_body(&br#"{"a":1}"#[..], Some("application/json")).unwrap();
+        assert_eq!(body, r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_invalid_utf8_text_body_is_rejected() {
+        let err = read_body(&b"ok \xff\xfe"[..], None).unwrap_err();
+
+        assert!(matches!(err, BodyError::InvalidUtf8(_)));
+        assert_eq!(
+            err.to_string(),
+            "Request body is not valid UTF-8 (invalid byte at offset 3)"
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_binary_body_is_read_lossily() {
+        let body = read_body(&b"ok \xff"[..], Some("application/octet-stream")).unwrap();
+        assert_eq!(body, "ok \u{fffd}");
+    }
+
+    #[test]
+    fn test_truncated_body_is_an_io_error() {
+        let err = read_body(Truncated { bytes: b"{\"a\":" }, None).unwrap_err();
+
+        assert!(matches!(err, BodyError::Io(_)));
+        assert!(err.to_string().starts_with("Failed to read request body"));
+    }
+
+    #[test]
+    fn test_is_text_content_type() {
+        assert!(is_text_content_type(Some("text/plain; charset=utf-8")));
+        assert!(is_text_content_type(Some("application/vnd.api+json")));
+        assert!(is_text_content_type(Some("Application/XML")));
+        assert!(!is_text_content_type(Some("image/png")));
+    }
+}
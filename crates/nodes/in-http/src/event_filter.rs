@@ -0,0 +1,56 @@
+use shared::EventFilter;
+
+const DEFAULT_EVENT_HEADER: &str = "X-GitHub-Event";
+
+/// Name of the header the event type is read from
+pub fn event_header(filter: &EventFilter) -> &str {
+    filter
+        .event_header
+        .as_deref()
+        .unwrap_or(DEFAULT_EVENT_HEADER)
+}
+
+/// Whether a request carrying `event` in its event header should be forwarded. Requests without
+/// the header are dropped.
+pub fn is_allowed(filter: &EventFilter, event: Option<&str>) -> bool {
+    event.is_some_and(|event| {
+        filter
+            .allowed_events
+            .iter()
+            .any(|allowed| allowed == event.trim())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_only() -> EventFilter {
+        EventFilter {
+            event_header: None,
+            allowed_events: vec!["push".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_push_event_is_forwarded() {
+        assert!(is_allowed(&push_only(), Some("push")));
+    }
+
+    #[test]
+    fn test_star_event_is_dropped() {
+        assert!(!is_allowed(&push_only(), Some("star")));
+        assert!(!is_allowed(&push_only(), None));
+    }
+
+    #[test]
+    fn test_event_header_defaults_to_github() {
+        assert_eq!(event_header(&push_only()), "X-GitHub-Event");
+
+        let filter = EventFilter {
+            event_header: Some("X-Event-Key".to_string()),
+            allowed_events: vec![],
+        };
+        assert_eq!(event_header(&filter), "X-Event-Key");
+    }
+}
@@ -1,10 +1,16 @@
-use shared::{FromConfig, InHttpWebhookSettings};
-use std::io::Read;
+use shared::{FromConfig, InHttpWebhookSettings, REQUEST_ID_HEADER, RequestMetadata};
 use wasmcloud_component::{
     error,
     http::{self, ErrorCode, Response, StatusCode},
+    info,
 };
 
+mod body;
+mod event_filter;
+mod metadata;
+mod preflight;
+mod request_id;
+
 mod bindings {
     use wasmcloud_component::http;
 
@@ -19,52 +25,121 @@ const LOG_CONTEXT: &str = "in-http";
 
 impl http::Server for Component {
     fn handle(
-        mut request: http::IncomingRequest,
+        request: http::IncomingRequest,
     ) -> http::Result<http::Response<impl http::OutgoingBody>> {
-        let config = match bindings::wasi::config::runtime::get("json") {
-            Ok(config) => config,
-            Err(e) => {
-                error!(context: LOG_CONTEXT, "Failed to get config: {e:?}");
-                return Ok(http::Response::new("Internal server error\n".to_string()));
-            }
-        };
+        let inbound = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok());
+        let request_id = request_id::request_id(inbound, || {
+            let random = bindings::wasi::random::random::get_random_bytes(16);
+            let random: [u8; 16] = random.try_into().unwrap_or_default();
+            request_id::format_request_id(u128::from_be_bytes(random))
+        });
 
-        let settings = match InHttpWebhookSettings::from_config(config) {
-            Ok(settings) => settings,
-            Err(e) => {
-                error!(context: LOG_CONTEXT, "Failed to parse config: {e:?}");
-                return Ok(http::Response::new("Invalid configuration\n".to_string()));
-            }
-        };
+        // Callers can quote the id, which is in every log line for the request
+        let mut response = handle_request(request, &request_id)?;
+        if let Ok(value) = request_id.parse() {
+            response.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        Ok(response)
+    }
+}
+
+fn handle_request(
+    mut request: http::IncomingRequest,
+    request_id: &str,
+) -> http::Result<http::Response<String>> {
+    let config = match bindings::wasi::config::runtime::get("json") {
+        Ok(config) => config,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Request {request_id}: Failed to get config: {e:?}");
+            return Ok(http::Response::new("Internal server error\n".to_string()));
+        }
+    };
+
+    let settings = match InHttpWebhookSettings::from_config(config) {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Request {request_id}: Failed to parse config: {e:?}");
+            return Ok(http::Response::new("Invalid configuration\n".to_string()));
+        }
+    };
 
-        if request.method().to_string() != settings.method {
-            error!(context: LOG_CONTEXT, "Method mismatch: expected {:?}, got {:?}",
-            settings.method,
-            request.method());
+    if let Some(preflight) = preflight::preflight(&request.method().to_string(), &settings.method) {
+        let mut response = Response::builder().status(preflight.status);
+        if let Some(allow) = preflight.allow {
+            response = response.header("Allow", allow);
+        }
+        return response.body(String::new()).map_err(|e| {
+            ErrorCode::InternalError(Some(format!("failed to build response: {e:?}")))
+        });
+    }
+
+    if request.method().to_string() != settings.method {
+        error!(context: LOG_CONTEXT, "Request {request_id}: Method mismatch: expected {:?}, got {:?}",
+        settings.method,
+        request.method());
+        return Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body("Method not allowed".to_string())
+            .map_err(|e| {
+                ErrorCode::InternalError(Some(format!("failed to build response: {e:?}")))
+            });
+    }
+
+    if let Some(filter) = &settings.event_filter {
+        let event = request
+            .headers()
+            .get(event_filter::event_header(filter))
+            .and_then(|value| value.to_str().ok());
+        if !event_filter::is_allowed(filter, event) {
+            info!(context: LOG_CONTEXT, "Request {request_id}: Dropping request for event {event:?}");
             return Response::builder()
-                .status(StatusCode::METHOD_NOT_ALLOWED)
-                .body("Method not allowed".to_string())
+                .status(StatusCode::NO_CONTENT)
+                .body(String::new())
                 .map_err(|e| {
                     ErrorCode::InternalError(Some(format!("failed to build response: {e:?}")))
                 });
         }
+    }
 
-        let message = match request.method().to_string().to_uppercase().as_str() {
-            "POST" | "PUT" | "PATCH" => {
-                let mut body = String::new();
-                match request.body_mut().read_to_string(&mut body) {
-                    Ok(_) => body,
-                    Err(_e) => {
-                        return Ok(http::Response::new(
-                            "Failed to read request body\n".to_string(),
-                        ));
-                    }
+    let message = match request.method().to_string().to_uppercase().as_str() {
+        "POST" | "PUT" | "PATCH" => {
+            match body::read_body(request.body_mut(), settings.content_type.as_deref()) {
+                Ok(body) => body,
+                Err(e) => {
+                    error!(context: LOG_CONTEXT, "Request {request_id}: {e}");
+                    return Response::builder()
+                        .status(StatusCode::BAD_REQUEST)
+                        .body(format!("{e}\n"))
+                        .map_err(|e| {
+                            ErrorCode::InternalError(Some(format!(
+                                "failed to build response: {e:?}"
+                            )))
+                        });
                 }
             }
-            _ => "{}".to_string(),
-        };
+        }
+        _ => "{}".to_string(),
+    };
 
-        let received = bindings::pipestack::out::out::run(message.as_str());
-        Ok(http::Response::new(format!("{received}\n")))
-    }
+    // The request id is always forwarded, even when no other metadata is configured
+    let metadata = metadata::select(
+        settings
+            .metadata
+            .as_ref()
+            .unwrap_or(&RequestMetadata::default()),
+        &request.method().to_string(),
+        request_id,
+        |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        },
+    );
+    let received =
+        bindings::pipestack::out::metadata::run_with_metadata(message.as_str(), &metadata);
+    Ok(http::Response::new(format!("{received}\n")))
 }
@@ -0,0 +1,70 @@
+use shared::{METHOD_METADATA_KEY, REQUEST_ID_HEADER, RequestMetadata};
+
+/// Metadata forwarded with the body of a request, as name/value pairs: the request id, the
+/// method when included, and the selected headers the request has. `header` looks up a request
+/// header by name. Header names are lowercased.
+pub fn select<'a>(
+    settings: &RequestMetadata,
+    method: &str,
+    request_id: &str,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> Vec<(String, String)> {
+    let mut metadata = vec![(REQUEST_ID_HEADER.to_string(), request_id.to_string())];
+    if settings.include_method.unwrap_or(false) {
+        metadata.push((METHOD_METADATA_KEY.to_string(), method.to_string()));
+    }
+    for name in settings.headers.iter().flatten() {
+        let name = name.to_ascii_lowercase();
+        if name == REQUEST_ID_HEADER || metadata.iter().any(|(selected, _)| *selected == name) {
+            continue;
+        }
+        if let Some(value) = header(&name) {
+            metadata.push((name, value.to_string()));
+        }
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(name: &str) -> Option<&'static str> {
+        match name {
+            "content-type" => Some("text/csv"),
+            "x-tenant" => Some("acme"),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_selected_metadata() {
+        let settings = RequestMetadata {
+            include_method: Some(true),
+            headers: Some(vec![
+                "X-Tenant".to_string(),
+                "Content-Type".to_string(),
+                "X-Missing".to_string(),
+                "x-tenant".to_string(),
+            ]),
+        };
+
+        assert_eq!(
+            select(&settings, "POST", "req-1", header),
+            [
+                ("x-request-id".to_string(), "req-1".to_string()),
+                ("x-request-method".to_string(), "POST".to_string()),
+                ("x-tenant".to_string(), "acme".to_string()),
+                ("content-type".to_string(), "text/csv".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_id_is_always_included() {
+        assert_eq!(
+            select(&RequestMetadata::default(), "POST", "req-1", header),
+            [("x-request-id".to_string(), "req-1".to_string())]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+/// Response in-http gives itself, without running the downstream chain
+#[derive(Debug, PartialEq, Eq)]
+pub struct Preflight {
+    pub status: u16,
+    /// Value of the `Allow` header, if any
+    pub allow: Option<String>,
+}
+
+/// The response to a `method` request on a webhook configured for `configured`, or `None` when
+/// the request goes through the usual handling. HEAD on a GET webhook gets the headers a GET
+/// would, without a body. OPTIONS lists the allowed methods.
+pub fn preflight(method: &str, configured: &str) -> Option<Preflight> {
+    let configured = configured.to_uppercase();
+    match method.to_uppercase().as_str() {
+        "HEAD" if configured == "GET" => Some(Preflight {
+            status: 200,
+            allow: None,
+        }),
+        "OPTIONS" if configured != "OPTIONS" => Some(Preflight {
+            status: 204,
+            allow: Some(allowed_methods(&configured)),
+        }),
+        _ => None,
+    }
+}
+
+/// Methods a webhook configured for `configured` answers, as an `Allow` header value
+fn allowed_methods(configured: &str) -> String {
+    if configured == "GET" {
+        "GET, HEAD, OPTIONS".to_string()
+    } else {
+        format!("{configured}, OPTIONS")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_is_answered_like_get_without_body() {
+        assert_eq!(
+            preflight("HEAD", "GET"),
+            Some(Preflight {
+                status: 200,
+                allow: None
+            })
+        );
+        // Not a GET webhook, so HEAD is rejected as any other mismatched method
+        assert_eq!(preflight("HEAD", "POST"), None);
+        assert_eq!(preflight("GET", "GET"), None);
+    }
+
+    #[test]
+    fn test_options_lists_allowed_methods() {
+        assert_eq!(
+            preflight("OPTIONS", "post"),
+            Some(Preflight {
+                status: 204,
+                allow: Some("POST, OPTIONS".to_string())
+            })
+        );
+        assert_eq!(
+            preflight("OPTIONS", "GET").and_then(|p| p.allow).as_deref(),
+            Some("GET, HEAD, OPTIONS")
+        );
+        // A webhook configured for OPTIONS forwards them
+        assert_eq!(preflight("OPTIONS", "OPTIONS"), None);
+    }
+}
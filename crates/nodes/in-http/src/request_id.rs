@@ -0,0 +1,56 @@
+/// Longest inbound request id that is accepted rather than replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Format 128 random bits as a version 4 UUID
+pub fn format_request_id(random: u128) -> String {
+    let bits = (random & !(0xf << 76) | (0x4 << 76)) & !(0x3 << 62) | (0x2 << 62);
+    let hex = format!("{bits:032x}");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// The id a caller sent with the request, or a generated one when it sent none or one that can't
+/// be safely logged and echoed back, e.g. one containing whitespace.
+pub fn request_id(inbound: Option<&str>, generate: impl FnOnce() -> String) -> String {
+    match inbound.map(str::trim) {
+        Some(id)
+            if !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id.bytes().all(|b| b.is_ascii_graphic()) =>
+        {
+            id.to_string()
+        }
+        _ => generate(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_id_is_a_v4_uuid() {
+        assert_eq!(
+            format_request_id(u128::MAX),
+            "ffffffff-ffff-4fff-bfff-ffffffffffff"
+        );
+        assert_eq!(format_request_id(0), "00000000-0000-4000-8000-000000000000");
+    }
+
+    #[test]
+    fn test_inbound_id_is_preferred() {
+        let generated = || "generated".to_string();
+
+        assert_eq!(request_id(Some("ticket-42"), generated), "ticket-42");
+        assert_eq!(request_id(None, generated), "generated");
+        assert_eq!(request_id(Some(" "), generated), "generated");
+        assert_eq!(request_id(Some("a b"), generated), "generated");
+        assert_eq!(request_id(Some(&"a".repeat(129)), generated), "generated");
+    }
+}
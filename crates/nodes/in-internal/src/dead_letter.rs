@@ -0,0 +1,47 @@
+//! Messages a node gives up on are published to its dead-letter subject, from where the
+//! pipeline's dead-letter sink receives them
+
+use shared::DeadLetter;
+
+/// Why the next component gave up on a message, from what it returned. `None` when it handled
+/// the message. Sinks report failures as `Error: ...`, or as transient errors once the retries
+/// ran out.
+pub fn failure(received: &str) -> Option<&str> {
+    (received.starts_with("Error") || shared::is_transient_error(received)).then_some(received)
+}
+
+/// Body published to the dead-letter subject for a message the node gave up on
+pub fn body(message: &[u8], error: &str) -> Vec<u8> {
+    DeadLetter {
+        error: error.to_string(),
+        message: String::from_utf8_lossy(message).into_owned(),
+    }
+    .to_json()
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_are_dead_lettered() {
+        assert_eq!(failure("Done"), None);
+        assert_eq!(failure(""), None);
+        assert_eq!(
+            failure("Error: Endpoint responded with status code 400"),
+            Some("Error: Endpoint responded with status code 400")
+        );
+        let transient = shared::transient_error("Circuit breaker open");
+        assert_eq!(failure(&transient), Some(transient.as_str()));
+    }
+
+    #[test]
+    fn test_body_carries_message_and_error() {
+        let body = body(br#"{"order": 42}"#, "Error: Invalid order");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            r#"{"error":"Error: Invalid order","message":"{\"order\": 42}"}"#
+        );
+    }
+}
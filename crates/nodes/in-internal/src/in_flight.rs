@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Config key bounding the number of messages handled concurrently
+pub const MAX_IN_FLIGHT_CONFIG_KEY: &str = "max-in-flight";
+
+/// Parse the configured bound. Unset, zero or invalid values leave handling unbounded.
+pub fn parse_max_in_flight(value: Option<String>) -> Option<u32> {
+    value
+        .and_then(|value| value.trim().parse::<u32>().ok())
+        .filter(|max| *max > 0)
+}
+
+/// Counter of the messages currently being handled by a component instance
+pub struct InFlight {
+    count: AtomicU32,
+}
+
+impl InFlight {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU32::new(0),
+        }
+    }
+
+    /// Take a permit if fewer than `max` messages are in flight. The permit is released when it's
+    /// dropped.
+    pub fn try_acquire(&self, max: u32) -> Option<Permit<'_>> {
+        self.count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < max).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| Permit { in_flight: self })
+    }
+
+    pub fn current(&self) -> u32 {
+        self.count.load(Ordering::Acquire)
+    }
+}
+
+pub struct Permit<'a> {
+    in_flight: &'a InFlight,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.in_flight.count.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permits_respect_bound() {
+        let in_flight = InFlight::new();
+
+        let first = in_flight.try_acquire(2);
+        let second = in_flight.try_acquire(2);
+        assert!(first.is_some());
+        assert!(second.is_some());
+        assert!(in_flight.try_acquire(2).is_none());
+        assert_eq!(in_flight.current(), 2);
+
+        drop(first);
+        assert_eq!(in_flight.current(), 1);
+        let third = in_flight.try_acquire(2);
+        assert!(third.is_some());
+        assert!(in_flight.try_acquire(2).is_none());
+
+        drop(second);
+        drop(third);
+        assert_eq!(in_flight.current(), 0);
+    }
+
+    #[test]
+    fn test_parse_max_in_flight() {
+        assert_eq!(parse_max_in_flight(Some("8".to_string())), Some(8));
+        assert_eq!(parse_max_in_flight(Some(" 3 ".to_string())), Some(3));
+        assert_eq!(parse_max_in_flight(Some("0".to_string())), None);
+        assert_eq!(parse_max_in_flight(Some("many".to_string())), None);
+        assert_eq!(parse_max_in_flight(None), None);
+    }
+}
@@ -1,6 +1,21 @@
-use bindings::{exports::wasmcloud::messaging, wasmcloud::messaging::types::BrokerMessage};
+use bindings::{
+    exports::wasmcloud::messaging,
+    pipestack::customer::customer::{self, RunError},
+    wasmcloud::messaging::{consumer, types::BrokerMessage},
+};
+use in_flight::{InFlight, Permit};
+use shared::{
+    DEAD_LETTER_SUBJECT_CONFIG_KEY, FromConfig, PROCESSOR_INPUT_CONFIG_KEY, ProcessorInput,
+    RESILIENCE_CONFIG_KEY, Resilience,
+};
 use wasmcloud_component::{error, info, trace};
 
+mod dead_letter;
+mod in_flight;
+mod metrics;
+mod processor;
+mod reply;
+
 mod bindings {
     use super::WitComponent;
     wit_bindgen::generate!({ generate_all });
@@ -11,34 +26,235 @@ struct WitComponent;
 
 const LOG_CONTEXT: &str = "in-http";
 
+/// Delay between attempts to take an in-flight permit
+const PERMIT_RETRY_DELAY_NS: u64 = 10_000_000;
+
+static IN_FLIGHT: InFlight = InFlight::new();
+
+/// Wait until fewer than `max` messages are in flight in this component instance. The message
+/// isn't acked while waiting, which pushes back on the publisher.
+fn acquire_permit(max: u32) -> Permit<'static> {
+    loop {
+        if let Some(permit) = IN_FLIGHT.try_acquire(max) {
+            return permit;
+        }
+        bindings::wasi::clocks::monotonic_clock::subscribe_duration(PERMIT_RETRY_DELAY_NS).block();
+    }
+}
+
+/// Resilience settings of the node this component feeds. Defaults to no redelivery.
+fn resilience() -> Resilience {
+    match bindings::wasi::config::runtime::get(RESILIENCE_CONFIG_KEY) {
+        Ok(None) => Resilience::default(),
+        Ok(config) => Resilience::from_config(config).unwrap_or_else(|e| {
+            error!(context: LOG_CONTEXT, "Failed to parse resilience config: {e}");
+            Resilience::default()
+        }),
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Failed to get resilience config: {e:?}");
+            Resilience::default()
+        }
+    }
+}
+
+/// Hand the output to the next component, redelivering it while the component reports a
+/// transient failure, up to the configured number of retries
+fn deliver(output: &str) -> String {
+    let resilience = resilience();
+    let max_retries = resilience.retries.unwrap_or(0);
+    let mut retry = 0;
+
+    loop {
+        let received = bindings::pipestack::out::out::run(output);
+        if !shared::is_transient_error(&received) || retry >= max_retries {
+            return received;
+        }
+
+        retry += 1;
+        let delay_ms = resilience.delay_ms(retry);
+        info!(context: LOG_CONTEXT,
+            "Transient failure: {received}. Redelivering in {delay_ms}ms, retry {retry} of {max_retries}"
+        );
+        bindings::wasi::clocks::monotonic_clock::subscribe_duration(
+            delay_ms.saturating_mul(1_000_000),
+        )
+        .block();
+    }
+}
+
+/// How to hand messages to the linked processor. `None` when no processor is linked.
+fn processor_input() -> Result<Option<ProcessorInput>, String> {
+    bindings::wasi::config::runtime::get(PROCESSOR_INPUT_CONFIG_KEY)
+        .map_err(|e| format!("{e:?}"))
+        .and_then(|config| processor::parse_processor_input(config.as_deref()))
+}
+
 impl messaging::handler::Guest for WitComponent {
     fn handle_message(msg: BrokerMessage) -> Result<(), String> {
-        info!(context: LOG_CONTEXT,
-            "Message received in in-internal: {:?}",
-            String::from_utf8(msg.body.clone())
+        if msg.subject.ends_with(shared::FLUSH_SUBJECT_SUFFIX) {
+            return flush();
+        }
+
+        let started_ns = bindings::wasi::clocks::monotonic_clock::now();
+        let result = handle(msg);
+        shared::record_outcome(
+            &Metrics,
+            elapsed_ms(started_ns),
+            metrics::error_kind(&result),
         );
-        let response_from_custom_code = match bindings::pipestack::customer::customer::run(
-            String::from_utf8(msg.body.clone()).unwrap().as_str(),
-        ) {
-            Ok(Ok(res)) => {
-                info!(context: LOG_CONTEXT,"Called customer code: {res}");
-                res
-            }
-            Ok(Err(err)) => {
-                error!(context: LOG_CONTEXT,
-                    "Error calling customer code: {err:?}. Using original message as fallback."
-                );
-                return Err(format!("{err:?}"));
-            }
-            Err(_err) => {
-                trace!(context: LOG_CONTEXT, "AAA   Custom code not linked, using original message. This is expected for in-internal nodes that are not linking to a processor-* component.");
-                String::from_utf8(msg.body.clone()).unwrap()
-            }
-        };
-
-        info!(context: LOG_CONTEXT,"Calling out");
-        let received = bindings::pipestack::out::out::run(response_from_custom_code.as_str());
-        info!(context: LOG_CONTEXT,"Called out. Return value: {received}");
-        Ok(())
+        result
+    }
+}
+
+/// Ask the linked sink to send what it buffered. Only the in-internal components of batching
+/// sinks subscribe to a flush subject, so the sink exports `flush`.
+fn flush() -> Result<(), String> {
+    info!(context: LOG_CONTEXT, "Flush requested, flushing the linked sink");
+    let received = bindings::pipestack::out::flush::flush();
+    if received != "Done" {
+        error!(context: LOG_CONTEXT, "Failed to flush the linked sink: {received}");
+        return Err(received);
+    }
+    Ok(())
+}
+
+/// Reports to the linked metrics provider. Metrics are best effort, so failures, e.g. when no
+/// provider is linked, are ignored.
+struct Metrics;
+
+impl shared::MetricsRecorder for Metrics {
+    fn record_processed(&self, count: u64) {
+        let _ = bindings::pipestack::metrics::metrics::record_processed(count);
+    }
+
+    fn record_latency(&self, ms: u64) {
+        let _ = bindings::pipestack::metrics::metrics::record_latency(ms);
+    }
+
+    fn record_error(&self, kind: &str) {
+        let _ = bindings::pipestack::metrics::metrics::record_error(kind);
+    }
+}
+
+/// Milliseconds elapsed since the monotonic clock read `started_ns`
+fn elapsed_ms(started_ns: u64) -> u64 {
+    bindings::wasi::clocks::monotonic_clock::now().saturating_sub(started_ns) / 1_000_000
+}
+
+/// Reply to a request with what the next component returned, e.g. the reply of the next step
+/// or the output of the sink. Replies are best effort: the requester times out without one.
+fn send_reply(subject: &str, received: &str) {
+    let reply = BrokerMessage {
+        subject: subject.to_string(),
+        reply_to: None,
+        body: received.as_bytes().to_vec(),
+    };
+    if let Err(e) = consumer::publish(&reply) {
+        error!(context: LOG_CONTEXT, "Failed to reply on {subject:?}: {e}");
+    }
+}
+
+/// Publish a message the node gave up on to its dead-letter subject, when the pipeline has a
+/// dead-letter sink. Best effort: the failure is still logged and reported.
+fn send_to_dead_letter(message: &[u8], error: &str) {
+    let subject = match bindings::wasi::config::runtime::get(DEAD_LETTER_SUBJECT_CONFIG_KEY) {
+        Ok(Some(subject)) => subject,
+        Ok(None) => return,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Failed to get dead-letter subject: {e:?}");
+            return;
+        }
+    };
+    let dead_letter = BrokerMessage {
+        subject: subject.clone(),
+        reply_to: None,
+        body: dead_letter::body(message, error),
+    };
+    if let Err(e) = consumer::publish(&dead_letter) {
+        error!(context: LOG_CONTEXT, "Failed to publish to dead-letter subject {subject:?}: {e}");
+    }
+}
+
+/// Run a message through the processor and hand the output to the next component
+fn handle(msg: BrokerMessage) -> Result<(), String> {
+    info!(context: LOG_CONTEXT,
+        "Message received in in-internal: {:?}",
+        String::from_utf8(msg.body.clone())
+    );
+    let max_in_flight = in_flight::parse_max_in_flight(
+        bindings::wasi::config::runtime::get(in_flight::MAX_IN_FLIGHT_CONFIG_KEY)
+            .ok()
+            .flatten(),
+    );
+    let _permit = max_in_flight.map(acquire_permit);
+
+    let input = match processor_input() {
+        Ok(input) => input,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Failed to get processor-input config: {e}");
+            return Err(format!("Failed to get processor-input config: {e}"));
+        }
+    };
+    let processed = processor::run(
+        input,
+        &msg.body,
+        customer::run_bytes,
+        customer::run,
+        RunError::AppError,
+    );
+    let response_from_custom_code = match processed {
+        Some(Ok(Ok(res))) => {
+            info!(context: LOG_CONTEXT,"Called customer code: {}", String::from_utf8_lossy(&res));
+            res
+        }
+        Some(Ok(Err(err))) => {
+            error!(context: LOG_CONTEXT,
+                "Error calling customer code: {err:?}. Using original message as fallback."
+            );
+            send_to_dead_letter(&msg.body, &format!("{err:?}"));
+            return Err(format!("{err:?}"));
+        }
+        Some(Err(err)) => {
+            error!(context: LOG_CONTEXT, "Failed to call customer code: {err:?}");
+            send_to_dead_letter(&msg.body, &format!("{err:?}"));
+            return Err(format!("{err:?}"));
+        }
+        None => {
+            trace!(context: LOG_CONTEXT, "No processor linked, using original message");
+            msg.body.clone()
+        }
+    };
+    // Sinks take text, so only the input of processors can be binary
+    let response_from_custom_code = match String::from_utf8(response_from_custom_code) {
+        Ok(response) => response,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Customer code returned output that is not valid UTF-8: {e}");
+            send_to_dead_letter(&msg.body, &format!("Output is not valid UTF-8: {e}"));
+            return Err(format!("Output is not valid UTF-8: {e}"));
+        }
+    };
+
+    // Processors such as processor-filter drop a message by returning an empty output
+    if response_from_custom_code.is_empty() {
+        info!(context: LOG_CONTEXT, "Customer code returned no output, nothing to forward");
+        if let Some(subject) = reply::reply_subject(msg.reply_to.as_deref()) {
+            send_reply(subject, "");
+        }
+        return Ok(());
+    }
+
+    info!(context: LOG_CONTEXT,"Calling out");
+    let received = deliver(response_from_custom_code.as_str());
+    info!(context: LOG_CONTEXT,"Called out. Return value: {received}");
+    if let Some(subject) = reply::reply_subject(msg.reply_to.as_deref()) {
+        send_reply(subject, &received);
+    }
+    if let Some(error) = dead_letter::failure(&received) {
+        send_to_dead_letter(&msg.body, error);
+    }
+    // Surface transient sink failures, e.g. an open circuit breaker, to the messaging provider
+    if shared::is_transient_error(&received) {
+        return Err(received);
     }
+    Ok(())
 }
@@ -0,0 +1,55 @@
+/// Kind of error handling a message reported. `None` when it was handled.
+pub fn error_kind(result: &Result<(), String>) -> Option<&'static str> {
+    match result {
+        Ok(()) => None,
+        Err(e) if shared::is_transient_error(e) => Some("transient"),
+        Err(_) => Some("processing"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{MetricsRecorder, record_outcome};
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Recorded(RefCell<Vec<String>>);
+
+    impl MetricsRecorder for Recorded {
+        fn record_processed(&self, count: u64) {
+            self.0.borrow_mut().push(format!("processed {count}"));
+        }
+
+        fn record_latency(&self, ms: u64) {
+            self.0.borrow_mut().push(format!("latency {ms}"));
+        }
+
+        fn record_error(&self, kind: &str) {
+            self.0.borrow_mut().push(format!("error {kind}"));
+        }
+    }
+
+    fn recorded(result: Result<(), String>) -> Vec<String> {
+        let recorded = Recorded::default();
+        record_outcome(&recorded, 8, error_kind(&result));
+        recorded.0.into_inner()
+    }
+
+    #[test]
+    fn test_handled_message_records_processed() {
+        assert_eq!(recorded(Ok(())), ["latency 8", "processed 1"]);
+    }
+
+    #[test]
+    fn test_failed_message_records_error() {
+        assert_eq!(
+            recorded(Err("AppError(\"bad input\")".to_string())),
+            ["latency 8", "error processing"]
+        );
+        assert_eq!(
+            recorded(Err(shared::transient_error("Circuit breaker open"))),
+            ["latency 8", "error transient"]
+        );
+    }
+}
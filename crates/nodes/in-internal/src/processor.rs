@@ -0,0 +1,100 @@
+use shared::{ProcessorInput, text_input};
+
+/// How to hand messages to the processor from the `processor-input` config. Unset config means
+/// no processor is linked, e.g. for the in-internal of a sink.
+pub fn parse_processor_input(config: Option<&str>) -> Result<Option<ProcessorInput>, String> {
+    config.map(str::parse).transpose()
+}
+
+/// Run a message through the linked processor, through `run-bytes` or `run` as configured, so
+/// only one call is made per message. Text processors can't take input that isn't UTF-8, which
+/// is reported through `not_text`. `None` when no processor is linked.
+pub fn run<R, E>(
+    input: Option<ProcessorInput>,
+    body: &[u8],
+    run_bytes: impl FnOnce(&[u8]) -> Result<Result<Vec<u8>, R>, E>,
+    run_text: impl FnOnce(&str) -> Result<Result<String, R>, E>,
+    not_text: impl FnOnce(String) -> R,
+) -> Option<Result<Result<Vec<u8>, R>, E>> {
+    match input? {
+        ProcessorInput::Bytes => Some(run_bytes(body)),
+        ProcessorInput::Text => match text_input(body.to_vec()) {
+            Ok(text) => Some(run_text(&text).map(|output| output.map(String::into_bytes))),
+            Err(e) => Some(Ok(Err(not_text(e)))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Output = Option<Result<Result<Vec<u8>, String>, ()>>;
+
+    fn run_through(input: Option<ProcessorInput>, body: &[u8]) -> Output {
+        // A processor prefixing its input, like the customer stub
+        run(
+            input,
+            body,
+            |bytes| {
+                let mut output = b"bytes: ".to_vec();
+                output.extend(bytes);
+                Ok(Ok(output))
+            },
+            |text| Ok(Ok(format!("text: {text}"))),
+            |e| e,
+        )
+    }
+
+    #[test]
+    fn test_parse_processor_input() {
+        assert_eq!(parse_processor_input(None), Ok(None));
+        assert_eq!(
+            parse_processor_input(Some("bytes")),
+            Ok(Some(ProcessorInput::Bytes))
+        );
+        assert_eq!(
+            parse_processor_input(Some("text")),
+            Ok(Some(ProcessorInput::Text))
+        );
+        assert!(parse_processor_input(Some("binary")).is_err());
+    }
+
+    #[test]
+    fn test_binary_passes_through_byte_processor() {
+        let body = [0x00, 0xff, 0xfe, 0x80];
+
+        let mut expected = b"bytes: ".to_vec();
+        expected.extend(body);
+        assert_eq!(
+            run_through(Some(ProcessorInput::Bytes), &body),
+            Some(Ok(Ok(expected)))
+        );
+    }
+
+    #[test]
+    fn test_text_processor_gets_text() {
+        assert_eq!(
+            run_through(Some(ProcessorInput::Text), b"hello"),
+            Some(Ok(Ok(b"text: hello".to_vec())))
+        );
+        assert_eq!(
+            run_through(Some(ProcessorInput::Text), &[b'h', 0xff]),
+            Some(Ok(Err(
+                "Input is not valid UTF-8 (invalid byte at offset 1)".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_no_processor_linked_makes_no_call() {
+        let output: Output = run(
+            None,
+            b"hello",
+            |_| panic!("run-bytes should not be called"),
+            |_| panic!("run should not be called"),
+            |e| e,
+        );
+        assert_eq!(output, None);
+    }
+}
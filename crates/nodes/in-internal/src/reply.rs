@@ -0,0 +1,25 @@
+//! Replies to requests. out-internal sends a request instead of publishing when a webhook
+//! upstream responds with the result of the pipeline, and waits for the reply on the subject
+//! the messaging provider generated for it.
+
+/// Subject to reply to a message on, `None` when its sender isn't waiting for a reply
+pub fn reply_subject(reply_to: Option<&str>) -> Option<&str> {
+    reply_to
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_subject() {
+        assert_eq!(
+            reply_subject(Some("_INBOX.Xy8pQ.3")),
+            Some("_INBOX.Xy8pQ.3")
+        );
+        assert_eq!(reply_subject(Some(" ")), None);
+        assert_eq!(reply_subject(None), None);
+    }
+}
@@ -0,0 +1,188 @@
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use bindings::{exports::wasmcloud::messaging, wasmcloud::messaging::types::BrokerMessage};
+use nodes_common::UrlParts;
+use shared::{InRabbitmqSettings, RabbitmqAckMode, apply_authentication};
+use std::sync::atomic::{AtomicBool, Ordering};
+use wasmcloud_component::{error, info, warn};
+
+mod queue;
+
+nodes_common::bindings!(Component);
+
+struct Component;
+
+const LOG_CONTEXT: &str = "in-rabbitmq";
+
+/// Output of `out::run` for a message forwarded to the next step
+const FORWARDED: &str = "OK";
+
+/// Whether this component instance declared and bound the queue, so it's not done on every poll.
+/// wasmCloud can run a poll in a fresh instance, which declares and binds again; that's harmless as
+/// declaring and binding are idempotent.
+static BOUND: AtomicBool = AtomicBool::new(false);
+
+/// Every message on the node's poll subject is a tick fetching the next messages of the queue.
+impl messaging::handler::Guest for Component {
+    fn handle_message(_msg: BrokerMessage) -> Result<(), String> {
+        let settings: InRabbitmqSettings =
+            nodes_common::load_settings(bindings::wasi::config::runtime::get).map_err(|e| {
+                error!(context: LOG_CONTEXT, "{e}");
+                e.to_string()
+            })?;
+
+        if let Some(exchange) = &settings.exchange
+            && !BOUND.load(Ordering::Relaxed)
+        {
+            declare_and_bind(&settings, exchange)?;
+            BOUND.store(true, Ordering::Relaxed);
+        }
+
+        let (status, body) = send(
+            Method::Post,
+            &queue::get_url(&settings),
+            &queue::get_body(&settings),
+            &settings,
+        )?;
+        check_status("fetching messages", status)?;
+        let messages = queue::messages(&body)?;
+        if !messages.is_empty() {
+            info!(context: LOG_CONTEXT,
+                "Fetched {} message(s) from queue {}",
+                messages.len(),
+                settings.queue
+            );
+        }
+
+        let manual_ack = settings.ack_mode.unwrap_or_default() == RabbitmqAckMode::Manual;
+        for message in &messages {
+            let received = bindings::pipestack::out::out::run(&message.payload);
+            info!(context: LOG_CONTEXT, "Called out. Return value: {received}");
+            if received != FORWARDED && manual_ack {
+                requeue(message, &settings);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Declare the queue and bind it to `exchange`
+fn declare_and_bind(settings: &InRabbitmqSettings, exchange: &str) -> Result<(), String> {
+    let (status, _) = send(
+        Method::Put,
+        &queue::queue_url(settings),
+        &queue::declare_queue_body(),
+        settings,
+    )?;
+    check_status("declaring the queue", status)?;
+
+    let (status, _) = send(
+        Method::Post,
+        &queue::binding_url(settings, exchange),
+        &queue::binding_body(settings),
+        settings,
+    )?;
+    check_status("binding the queue", status)?;
+
+    info!(context: LOG_CONTEXT, "Bound queue {} to exchange {exchange}", settings.queue);
+    Ok(())
+}
+
+/// Put a message that failed to be forwarded back on the queue, to be fetched again
+fn requeue(message: &queue::Message, settings: &InRabbitmqSettings) {
+    let requeued = send(
+        Method::Post,
+        &queue::requeue_url(settings),
+        &queue::requeue_body(message, settings),
+        settings,
+    )
+    .and_then(|(status, _)| check_status("requeueing a message", status));
+    match requeued {
+        Ok(()) => warn!(context: LOG_CONTEXT,
+            "Message failed to be forwarded, put it back on queue {}",
+            settings.queue
+        ),
+        Err(e) => error!(context: LOG_CONTEXT,
+            "Message failed to be forwarded and couldn't be put back on queue {}: {e}",
+            settings.queue
+        ),
+    }
+}
+
+fn check_status(action: &str, status: u16) -> Result<(), String> {
+    match status {
+        200..300 => Ok(()),
+        _ => Err(format!(
+            "RabbitMQ responded to {action} with status code {status}"
+        )),
+    }
+}
+
+/// Send a request to the management API, returning the response status code and body. Errors
+/// are failures to get a response.
+fn send(
+    method: Method,
+    url: &str,
+    body: &str,
+    settings: &InRabbitmqSettings,
+) -> Result<(u16, String), String> {
+    let UrlParts {
+        https,
+        authority,
+        path_with_query: path,
+    } = nodes_common::split_url(url);
+    let scheme = if https { Scheme::Https } else { Scheme::Http };
+
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    let path_with_query = match &settings.auth {
+        Some(auth) => apply_authentication(&mut headers, &path, auth)?,
+        None => path,
+    };
+
+    let fields = Fields::new();
+    for (name, value) in &headers {
+        fields
+            .set(name, &[value.as_bytes().to_vec()])
+            .map_err(|e| format!("Failed to set header {name}: {e}"))?;
+    }
+
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&method).unwrap();
+    req.set_scheme(Some(&scheme)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(&path_with_query)).unwrap();
+
+    let request_body = req.body().unwrap();
+    let output_stream = request_body.write().unwrap();
+    output_stream
+        .blocking_write_and_flush(body.as_bytes())
+        .map_err(|e| format!("Failed to write request body: {e}"))?;
+    drop(output_stream);
+    bindings::wasi::http::types::OutgoingBody::finish(request_body, None)
+        .map_err(|e| format!("Failed to finish request body: {e}"))?;
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .ok_or("HTTP response missing")?
+        .map_err(|_| "HTTP response requested more than once")?
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+
+    let status = response.status();
+    let response_body = response
+        .consume()
+        .map_err(|_| "Failed to get response body")?;
+    let input_stream = response_body
+        .stream()
+        .map_err(|_| "Failed to get response body stream")?;
+    let mut body = Vec::new();
+    while let Ok(chunk) = input_stream.blocking_read(64 * 1024) {
+        if chunk.is_empty() {
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((status, String::from_utf8_lossy(&body).to_string()))
+}
@@ -0,0 +1,194 @@
+use nodes_common::encode_path_segment;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use shared::InRabbitmqSettings;
+
+const DEFAULT_VHOST: &str = "/";
+const DEFAULT_PREFETCH: u32 = 10;
+
+/// Name the management API gives the default exchange, whose actual name is empty
+const DEFAULT_EXCHANGE: &str = "amq.default";
+
+/// A message fetched from the queue
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Message {
+    /// The body, base64-encoded when it isn't valid UTF-8
+    pub payload: String,
+    /// `string` or `base64`
+    pub payload_encoding: String,
+    #[serde(default)]
+    pub properties: Value,
+}
+
+fn api_url(settings: &InRabbitmqSettings, path: &str) -> String {
+    format!("{}/api/{path}", settings.url.trim_end_matches('/'),)
+}
+
+fn vhost(settings: &InRabbitmqSettings) -> String {
+    encode_path_segment(settings.vhost.as_deref().unwrap_or(DEFAULT_VHOST))
+}
+
+/// URL of the configured queue in the management API
+pub fn queue_url(settings: &InRabbitmqSettings) -> String {
+    api_url(
+        settings,
+        &format!(
+            "queues/{}/{}",
+            vhost(settings),
+            encode_path_segment(&settings.queue)
+        ),
+    )
+}
+
+/// URL messages are fetched from
+pub fn get_url(settings: &InRabbitmqSettings) -> String {
+    format!("{}/get", queue_url(settings))
+}
+
+/// URL of the binding of the queue to `exchange`
+pub fn binding_url(settings: &InRabbitmqSettings, exchange: &str) -> String {
+    api_url(
+        settings,
+        &format!(
+            "bindings/{}/e/{}/q/{}",
+            vhost(settings),
+            encode_path_segment(exchange),
+            encode_path_segment(&settings.queue)
+        ),
+    )
+}
+
+/// URL messages are put back on the queue through, by publishing them to the default exchange
+pub fn requeue_url(settings: &InRabbitmqSettings) -> String {
+    api_url(
+        settings,
+        &format!("exchanges/{}/{DEFAULT_EXCHANGE}/publish", vhost(settings)),
+    )
+}
+
+/// Body of a request fetching the next messages. Fetched messages are removed from the queue.
+pub fn get_body(settings: &InRabbitmqSettings) -> String {
+    json!({
+        "count": settings.prefetch.unwrap_or(DEFAULT_PREFETCH),
+        "ackmode": "ack_requeue_false",
+        "encoding": "auto",
+    })
+    .to_string()
+}
+
+/// Body of a request declaring the queue, which keeps it when the broker restarts
+pub fn declare_queue_body() -> String {
+    json!({ "durable": true, "auto_delete": false, "arguments": {} }).to_string()
+}
+
+/// Body of a request binding the queue
+pub fn binding_body(settings: &InRabbitmqSettings) -> String {
+    json!({
+        "routing_key": settings.binding_key.as_deref().unwrap_or(&settings.queue),
+        "arguments": {},
+    })
+    .to_string()
+}
+
+/// Body of a request putting `message` back on the queue
+pub fn requeue_body(message: &Message, settings: &InRabbitmqSettings) -> String {
+    json!({
+        "properties": message.properties,
+        "routing_key": settings.queue,
+        "payload": message.payload,
+        "payload_encoding": message.payload_encoding,
+    })
+    .to_string()
+}
+
+/// Messages of a response to a fetch request
+pub fn messages(response_body: &str) -> Result<Vec<Message>, String> {
+    serde_json::from_str(response_body)
+        .map_err(|e| format!("Failed to parse fetched messages: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> InRabbitmqSettings {
+        InRabbitmqSettings {
+            url: "https://rabbitmq.example.com:15672/".to_string(),
+            vhost: None,
+            queue: "orders".to_string(),
+            exchange: Some("events".to_string()),
+            binding_key: None,
+            prefetch: None,
+            ack_mode: None,
+            auth: None,
+        }
+    }
+
+    #[test]
+    fn test_urls() {
+        let settings = settings();
+        assert_eq!(
+            get_url(&settings),
+            "https://rabbitmq.example.com:15672/api/queues/%2F/orders/get"
+        );
+        assert_eq!(
+            binding_url(&settings, "events"),
+            "https://rabbitmq.example.com:15672/api/bindings/%2F/e/events/q/orders"
+        );
+        assert_eq!(
+            requeue_url(&settings),
+            "https://rabbitmq.example.com:15672/api/exchanges/%2F/amq.default/publish"
+        );
+    }
+
+    #[test]
+    fn test_prefetch() {
+        let mut settings = settings();
+        let body: Value = serde_json::from_str(&get_body(&settings)).unwrap();
+        assert_eq!(body["count"], 10);
+
+        settings.prefetch = Some(50);
+        let body: Value = serde_json::from_str(&get_body(&settings)).unwrap();
+        assert_eq!(body["count"], 50);
+    }
+
+    #[test]
+    fn test_binding_key_defaults_to_queue() {
+        let mut settings = settings();
+        let body: Value = serde_json::from_str(&binding_body(&settings)).unwrap();
+        assert_eq!(body["routing_key"], "orders");
+
+        settings.binding_key = Some("orders.*".to_string());
+        let body: Value = serde_json::from_str(&binding_body(&settings)).unwrap();
+        assert_eq!(body["routing_key"], "orders.*");
+    }
+
+    #[test]
+    fn test_fetched_messages_are_requeued_as_is() {
+        let fetched = messages(
+            r#"[{"payload_bytes":8,"redelivered":false,"exchange":"events","routing_key":"orders.eu",
+                "message_count":0,"properties":{"delivery_mode":2},"payload":"{\"id\":1}",
+                "payload_encoding":"string"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            fetched,
+            [Message {
+                payload: r#"{"id":1}"#.to_string(),
+                payload_encoding: "string".to_string(),
+                properties: json!({ "delivery_mode": 2 }),
+            }]
+        );
+
+        let body: Value = serde_json::from_str(&requeue_body(&fetched[0], &settings())).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "properties": { "delivery_mode": 2 },
+                "routing_key": "orders",
+                "payload": "{\"id\":1}",
+                "payload_encoding": "string",
+            })
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+
+/// Number of item guids remembered between polls to detect items that were already emitted
+const MAX_SEEN_GUIDS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeedItem {
+    pub guid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+/// State persisted between polls of a feed
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedState {
+    #[serde(default)]
+    pub seen_guids: Vec<String>,
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    #[serde(default)]
+    pub last_poll_ms: Option<u64>,
+}
+
+impl FeedState {
+    /// Whether enough time has passed since the last poll to fetch the feed again
+    pub fn is_due(&self, now_ms: u64, poll_interval_ms: u64) -> bool {
+        match self.last_poll_ms {
+            Some(last_poll_ms) => now_ms.saturating_sub(last_poll_ms) >= poll_interval_ms,
+            None => true,
+        }
+    }
+
+    /// Items that haven't been emitted before, in feed order, limited to `max_items`, and whether
+    /// any were left out by the limit
+    pub fn new_items(&self, items: Vec<FeedItem>, max_items: Option<u32>) -> (Vec<FeedItem>, bool) {
+        let mut new_items: Vec<FeedItem> = Vec::new();
+        for item in items {
+            if self.seen_guids.contains(&item.guid)
+                || new_items.iter().any(|new_item| new_item.guid == item.guid)
+            {
+                continue;
+            }
+            new_items.push(item);
+        }
+
+        let truncated = max_items.is_some_and(|max_items| new_items.len() > max_items as usize);
+        if let Some(max_items) = max_items {
+            new_items.truncate(max_items as usize);
+        }
+        (new_items, truncated)
+    }
+
+    /// Record a fetch whose new items were emitted. The cursor only advances when no new item was
+    /// left out: otherwise the next conditional request would be answered with a 304 and the
+    /// remaining items would only be emitted once the feed changes again.
+    pub fn record_fetch(
+        &mut self,
+        emitted: &[FeedItem],
+        truncated: bool,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) {
+        self.remember(emitted);
+        if !truncated {
+            self.etag = etag;
+            self.last_modified = last_modified;
+        }
+    }
+
+    /// Emit new items in feed order through `forward` and record the fetch. Emitting stops at the
+    /// first item that fails to be forwarded: it's neither remembered nor is the cursor advanced,
+    /// so it and the items after it are emitted again on the next poll. Returns that failure.
+    pub fn emit(
+        &mut self,
+        new_items: &[FeedItem],
+        truncated: bool,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        mut forward: impl FnMut(&FeedItem) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut emitted = 0;
+        let mut failure = None;
+        for item in new_items {
+            if let Err(e) = forward(item) {
+                failure = Some(e);
+                break;
+            }
+            emitted += 1;
+        }
+
+        self.record_fetch(
+            &new_items[..emitted],
+            truncated || failure.is_some(),
+            etag,
+            last_modified,
+        );
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Remember the given items as emitted, keeping only the most recent guids
+    pub fn remember(&mut self, items: &[FeedItem]) {
+        self.seen_guids
+            .extend(items.iter().map(|item| item.guid.clone()));
+        if self.seen_guids.len() > MAX_SEEN_GUIDS {
+            let excess = self.seen_guids.len() - MAX_SEEN_GUIDS;
+            self.seen_guids.drain(..excess);
+        }
+    }
+}
+
+/// Parse the items of an RSS 2.0 or Atom feed
+pub fn parse_feed(xml: &str) -> Result<Vec<FeedItem>, String> {
+    let document = roxmltree::Document::parse(xml).map_err(|e| format!("Invalid feed: {e}"))?;
+
+    let items = document
+        .descendants()
+        .filter(|node| node.has_tag_name("item") || node.has_tag_name("entry"))
+        .filter_map(|node| {
+            let child_text = |name: &str| {
+                node.children()
+                    .find(|child| child.has_tag_name(name))
+                    .and_then(|child| child.text())
+                    .map(|text| text.trim().to_string())
+                    .filter(|text| !text.is_empty())
+            };
+
+            // Atom links carry the URL in the href attribute
+            let link = child_text("link").or_else(|| {
+                node.children()
+                    .find(|child| child.has_tag_name("link"))
+                    .and_then(|child| child.attribute("href"))
+                    .map(str::to_string)
+            });
+            let title = child_text("title");
+
+            // Items without any identifier can't be deduplicated, so they're skipped
+            let guid = child_text("guid")
+                .or_else(|| child_text("id"))
+                .or_else(|| link.clone())
+                .or_else(|| title.clone())?;
+
+            Some(FeedItem {
+                guid,
+                title,
+                link,
+                description: child_text("description").or_else(|| child_text("summary")),
+                published: child_text("pubDate")
+                    .or_else(|| child_text("published"))
+                    .or_else(|| child_text("updated")),
+            })
+        })
+        .collect();
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Example</title>
+    <item>
+      <title>Second post</title>
+      <link>https://example.com/2</link>
+      <guid>post-2</guid>
+      <pubDate>Tue, 02 Jan 2024 00:00:00 GMT</pubDate>
+    </item>
+    <item>
+      <title>First post</title>
+      <link>https://example.com/1</link>
+      <guid>post-1</guid>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example</title>
+  <entry>
+    <title>Atom entry</title>
+    <link href="https://example.com/atom/1"/>
+    <id>urn:uuid:1</id>
+    <updated>2024-01-01T00:00:00Z</updated>
+    <summary>Hello</summary>
+  </entry>
+</feed>"#;
+
+    fn item(guid: &str) -> FeedItem {
+        FeedItem {
+            guid: guid.to_string(),
+            title: None,
+            link: None,
+            description: None,
+            published: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_rss_feed() {
+        let items = parse_feed(RSS).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].guid, "post-2");
+        assert_eq!(items[0].title.as_deref(), Some("Second post"));
+        assert_eq!(items[0].link.as_deref(), Some("https://example.com/2"));
+        assert_eq!(
+            items[0].published.as_deref(),
+            Some("Tue, 02 Jan 2024 00:00:00 GMT")
+        );
+    }
+
+    #[test]
+    fn test_parse_atom_feed() {
+        let items = parse_feed(ATOM).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].guid, "urn:uuid:1");
+        assert_eq!(items[0].link.as_deref(), Some("https://example.com/atom/1"));
+        assert_eq!(items[0].description.as_deref(), Some("Hello"));
+    }
+
+    #[test]
+    fn test_parse_invalid_feed() {
+        assert!(parse_feed("not xml").is_err());
+    }
+
+    #[test]
+    fn test_new_items_skips_seen_guids() {
+        let mut state = FeedState::default();
+
+        let (first_poll, _) = state.new_items(vec![item("a"), item("b")], None);
+        assert_eq!(first_poll, vec![item("a"), item("b")]);
+        state.remember(&first_poll);
+
+        let (second_poll, truncated) = state.new_items(vec![item("c"), item("a"), item("b")], None);
+        assert_eq!(second_poll, vec![item("c")]);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_new_items_deduplicates_within_a_poll_and_limits() {
+        let state = FeedState::default();
+
+        let (items, truncated) =
+            state.new_items(vec![item("a"), item("a"), item("b"), item("c")], Some(2));
+        assert_eq!(items, vec![item("a"), item("b")]);
+        assert!(truncated);
+
+        let (items, truncated) = state.new_items(vec![item("a"), item("b")], Some(2));
+        assert_eq!(items, vec![item("a"), item("b")]);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_record_fetch_keeps_cursor_when_truncated() {
+        let mut state = FeedState {
+            etag: Some("\"v1\"".to_string()),
+            ..Default::default()
+        };
+
+        let (items, truncated) = state.new_items(vec![item("a"), item("b")], Some(1));
+        state.record_fetch(&items, truncated, Some("\"v2\"".to_string()), None);
+        assert_eq!(state.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(state.seen_guids, vec!["a".to_string()]);
+
+        // The next poll refetches the feed and emits the rest
+        let (items, truncated) = state.new_items(vec![item("a"), item("b")], Some(1));
+        assert_eq!(items, vec![item("b")]);
+        state.record_fetch(&items, truncated, Some("\"v2\"".to_string()), None);
+        assert_eq!(state.etag.as_deref(), Some("\"v2\""));
+    }
+
+    #[test]
+    fn test_emit_remembers_only_forwarded_items() {
+        let mut state = FeedState {
+            etag: Some("\"v1\"".to_string()),
+            ..Default::default()
+        };
+        let items = vec![item("a"), item("b"), item("c")];
+
+        let mut forwarded = Vec::new();
+        let result = state.emit(&items, false, Some("\"v2\"".to_string()), None, |item| {
+            if item.guid == "b" {
+                return Err("Failed to publish".to_string());
+            }
+            forwarded.push(item.guid.clone());
+            Ok(())
+        });
+
+        assert_eq!(result, Err("Failed to publish".to_string()));
+        assert_eq!(forwarded, vec!["a".to_string()]);
+        assert_eq!(state.seen_guids, vec!["a".to_string()]);
+        assert_eq!(state.etag.as_deref(), Some("\"v1\""));
+
+        // The next poll emits the items that failed to be forwarded again
+        let (items, truncated) = state.new_items(items, None);
+        assert_eq!(items, vec![item("b"), item("c")]);
+        let result = state.emit(&items, truncated, Some("\"v2\"".to_string()), None, |_| {
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(state.seen_guids, vec!["a", "b", "c"]);
+        assert_eq!(state.etag.as_deref(), Some("\"v2\""));
+    }
+
+    #[test]
+    fn test_remember_keeps_most_recent_guids() {
+        let mut state = FeedState::default();
+        let items: Vec<FeedItem> = (0..MAX_SEEN_GUIDS + 5)
+            .map(|i| item(&i.to_string()))
+            .collect();
+
+        state.remember(&items);
+
+        assert_eq!(state.seen_guids.len(), MAX_SEEN_GUIDS);
+        assert_eq!(state.seen_guids.first().map(String::as_str), Some("5"));
+    }
+
+    #[test]
+    fn test_is_due() {
+        let mut state = FeedState::default();
+        assert!(state.is_due(1_000, 60_000));
+
+        state.last_poll_ms = Some(1_000);
+        assert!(!state.is_due(30_000, 60_000));
+        assert!(state.is_due(61_000, 60_000));
+    }
+}
@@ -0,0 +1,222 @@
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use bindings::{exports::wasmcloud::messaging, wasmcloud::messaging::types::BrokerMessage};
+use feed::FeedState;
+use nodes_common::UrlParts;
+use shared::{FromConfig, InRssReaderSettings};
+use wasmcloud_component::{error, info};
+
+mod feed;
+
+mod bindings {
+    use super::Component;
+    wit_bindgen::generate!({ generate_all });
+    export!(Component);
+}
+
+struct Component;
+
+const LOG_CONTEXT: &str = "in-rss-reader";
+
+/// Output of `out::run` for an item forwarded to the next step
+const FORWARDED: &str = "OK";
+
+/// Every message on the node's poll subject is a tick, published by the pipeline manager's poll
+/// scheduler; the feed is only fetched once `pollIntervalMs` has elapsed since the previous fetch.
+impl messaging::handler::Guest for Component {
+    fn handle_message(_msg: BrokerMessage) -> Result<(), String> {
+        let config = bindings::wasi::config::runtime::get("json").map_err(|e| {
+            error!(context: LOG_CONTEXT, "Failed to get config: {e:?}");
+            format!("Failed to get config: {e:?}")
+        })?;
+        let settings = InRssReaderSettings::from_config(config).map_err(|e| {
+            error!(context: LOG_CONTEXT, "Failed to parse config: {e}");
+            format!("Failed to parse config: {e}")
+        })?;
+        let state_key = bindings::wasi::config::runtime::get("state-key")
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| settings.feed_url.clone());
+
+        let bucket = bindings::wasi::keyvalue::store::open("")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {e:?}"))?;
+        let mut state: FeedState = bucket
+            .get(&state_key)
+            .map_err(|e| format!("Failed to read feed state: {e:?}"))?
+            .and_then(|value| serde_json::from_slice(&value).ok())
+            .unwrap_or_default();
+
+        let now = bindings::wasi::clocks::wall_clock::now();
+        let now_ms = now.seconds * 1_000 + u64::from(now.nanoseconds / 1_000_000);
+        if !state.is_due(now_ms, settings.poll_interval_ms) {
+            return Ok(());
+        }
+        state.last_poll_ms = Some(now_ms);
+
+        let emitted = match fetch_feed(&settings, &state)? {
+            FetchResult::NotModified => {
+                info!(context: LOG_CONTEXT, "Feed {} not modified", settings.feed_url);
+                Ok(())
+            }
+            FetchResult::Modified {
+                body,
+                etag,
+                last_modified,
+            } => {
+                let items = feed::parse_feed(&body)?;
+                let (new_items, truncated) = state.new_items(items, settings.max_items_per_poll);
+                info!(context: LOG_CONTEXT,
+                    "Fetched feed {}: {} new item(s)",
+                    settings.feed_url,
+                    new_items.len()
+                );
+
+                state.emit(&new_items, truncated, etag, last_modified, |item| {
+                    let payload = serde_json::to_string(item)
+                        .map_err(|e| format!("Failed to serialize feed item: {e}"))?;
+                    let received = bindings::pipestack::out::out::run(&payload);
+                    info!(context: LOG_CONTEXT, "Called out. Return value: {received}");
+                    if received != FORWARDED {
+                        return Err(format!(
+                            "Failed to forward feed item {}: {received}",
+                            item.guid
+                        ));
+                    }
+                    Ok(())
+                })
+            }
+        };
+
+        // The state is stored even when an item failed to be forwarded, so the items forwarded
+        // before it aren't emitted again
+        let value =
+            serde_json::to_vec(&state).map_err(|e| format!("Failed to serialize state: {e}"))?;
+        bucket
+            .set(&state_key, &value)
+            .map_err(|e| format!("Failed to store feed state: {e:?}"))?;
+
+        emitted.inspect_err(|e| error!(context: LOG_CONTEXT, "{e}"))
+    }
+}
+
+enum FetchResult {
+    NotModified,
+    Modified {
+        body: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+fn fetch_feed(settings: &InRssReaderSettings, state: &FeedState) -> Result<FetchResult, String> {
+    let fields = Fields::new();
+    if settings.conditional_requests.unwrap_or(true) {
+        if let Some(etag) = &state.etag {
+            fields
+                .set(&"If-None-Match".to_string(), &[etag.as_bytes().to_vec()])
+                .map_err(|e| format!("Failed to set If-None-Match header: {e}"))?;
+        }
+        if let Some(last_modified) = &state.last_modified {
+            fields
+                .set(
+                    &"If-Modified-Since".to_string(),
+                    &[last_modified.as_bytes().to_vec()],
+                )
+                .map_err(|e| format!("Failed to set If-Modified-Since header: {e}"))?;
+        }
+    }
+
+    let UrlParts {
+        https,
+        authority,
+        path_with_query,
+    } = nodes_common::split_url(&settings.feed_url);
+    let scheme = if https { Scheme::Https } else { Scheme::Http };
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&Method::Get).unwrap();
+    req.set_scheme(Some(&scheme)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(path_with_query.as_str()))
+        .unwrap();
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to fetch feed {}: {e}", settings.feed_url))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .ok_or("Feed response missing")?
+        .map_err(|_| "Feed response requested more than once")?
+        .map_err(|e| format!("Failed to fetch feed {}: {e}", settings.feed_url))?;
+
+    match response.status() {
+        304 => return Ok(FetchResult::NotModified),
+        200 => {}
+        status => {
+            return Err(format!(
+                "Fetching feed {} failed with status code {status}",
+                settings.feed_url
+            ));
+        }
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(&name.to_string())
+            .into_iter()
+            .next()
+            .map(|value| String::from_utf8_lossy(&value).to_string())
+    };
+    let etag = header("etag");
+    let last_modified = header("last-modified");
+
+    let response_body = response
+        .consume()
+        .map_err(|_| "Failed to get feed response body")?;
+    let input_stream = response_body
+        .stream()
+        .map_err(|_| "Failed to get feed response body stream")?;
+    let mut body = Vec::new();
+    while let Ok(chunk) = input_stream.blocking_read(64 * 1024) {
+        if chunk.is_empty() {
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(FetchResult::Modified {
+        body: String::from_utf8_lossy(&body).to_string(),
+        etag,
+        last_modified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_deserialization() {
+        let settings = InRssReaderSettings::from_config(Some(
+            r#"{"feedUrl":"https://example.com/feed.xml","pollIntervalMs":60000,"conditionalRequests":false,"maxItemsPerPoll":10}"#
+                .to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(settings.feed_url, "https://example.com/feed.xml");
+        assert_eq!(settings.poll_interval_ms, 60_000);
+        assert_eq!(settings.conditional_requests, Some(false));
+        assert_eq!(settings.max_items_per_poll, Some(10));
+    }
+
+    #[test]
+    fn test_settings_deserialization_defaults() {
+        let settings = InRssReaderSettings::from_config(Some(
+            r#"{"feedUrl":"https://example.com/feed.xml","pollIntervalMs":1000}"#.to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(settings.conditional_requests, None);
+        assert_eq!(settings.max_items_per_poll, None);
+        assert!(InRssReaderSettings::from_config(Some("{}".to_string())).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Requests of the Lambda Invoke API, see
+//! https://docs.aws.amazon.com/lambda/latest/api/API_Invoke.html
+
+use shared::{LambdaInvocationType, OutAwsLambdaSettings};
+
+/// Service name requests are signed for
+pub const SERVICE: &str = "lambda";
+
+/// URL invoking the function, whose name may be an ARN, at its qualifier if set
+pub fn invoke_url(settings: &OutAwsLambdaSettings) -> String {
+    let url = format!(
+        "https://lambda.{}.amazonaws.com/2015-03-31/functions/{}/invocations",
+        settings.region,
+        nodes_common::encode_path_segment(&settings.function_name)
+    );
+    match &settings.qualifier {
+        Some(qualifier) => format!(
+            "{url}?{}",
+            nodes_common::sigv4::encode_query(&[("Qualifier".to_string(), qualifier.clone())])
+        ),
+        None => url,
+    }
+}
+
+/// Value of the `X-Amz-Invocation-Type` header
+pub fn invocation_type(settings: &OutAwsLambdaSettings) -> &'static str {
+    match settings.invocation_type.unwrap_or_default() {
+        LambdaInvocationType::RequestResponse => "RequestResponse",
+        LambdaInvocationType::Event => "Event",
+    }
+}
+
+/// Outcome of an invocation from its response. `function_error` is the `X-Amz-Function-Error`
+/// header, set when the function was invoked but failed.
+pub fn outcome(status: u16, function_error: Option<&str>, body: &str) -> Result<(), String> {
+    match (status, function_error) {
+        (200..300, None) => Ok(()),
+        (200..300, Some(error)) => Err(format!("Error: The function failed ({error}): {body}")),
+        // Throttled or unavailable, the message can be sent again later
+        (429 | 500.., _) => Err(shared::transient_error(&format!(
+            "Lambda responded with status code {status}: {body}"
+        ))),
+        _ => Err(format!(
+            "Error: Lambda responded with status code {status}: {body}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::AwsCredentials;
+
+    fn settings(function_name: &str, qualifier: Option<&str>) -> OutAwsLambdaSettings {
+        OutAwsLambdaSettings {
+            function_name: function_name.to_string(),
+            region: "eu-west-1".to_string(),
+            invocation_type: None,
+            credentials: AwsCredentials {
+                access_key_id_secret: "aws-access-key-id".to_string(),
+                secret_access_key_secret: "aws-secret-access-key".to_string(),
+                session_token_secret: None,
+            },
+            qualifier: qualifier.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_invoke_request() {
+        let settings = settings(
+            "arn:aws:lambda:eu-west-1:123456789012:function:process-order",
+            Some("live"),
+        );
+
+        assert_eq!(
+            invoke_url(&settings),
+            "https://lambda.eu-west-1.amazonaws.com/2015-03-31/functions/arn%3Aaws%3Alambda%3Aeu-west-1%3A123456789012%3Afunction%3Aprocess-order/invocations?Qualifier=live"
+        );
+        assert_eq!(invocation_type(&settings), "RequestResponse");
+    }
+
+    #[test]
+    fn test_invoke_url_without_qualifier() {
+        assert_eq!(
+            invoke_url(&settings("process-order", None)),
+            "https://lambda.eu-west-1.amazonaws.com/2015-03-31/functions/process-order/invocations"
+        );
+    }
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(outcome(200, None, "{}"), Ok(()));
+        assert_eq!(outcome(202, None, ""), Ok(()));
+        assert_eq!(
+            outcome(200, Some("Unhandled"), r#"{"errorMessage":"boom"}"#),
+            Err(r#"Error: The function failed (Unhandled): {"errorMessage":"boom"}"#.to_string())
+        );
+        assert_eq!(
+            outcome(429, None, "Rate exceeded"),
+            Err(shared::transient_error(
+                "Lambda responded with status code 429: Rate exceeded"
+            ))
+        );
+        assert!(outcome(403, None, "").unwrap_err().starts_with("Error: "));
+    }
+}
@@ -0,0 +1,168 @@
+use bindings::exports::pipestack::out::out::Guest;
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use bindings::wasmcloud::secrets::reveal::SecretValue;
+use nodes_common::sigv4::{self, Credentials, Request, Scope};
+use shared::OutAwsLambdaSettings;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wasmcloud_component::{error, info};
+
+mod lambda;
+
+nodes_common::bindings!(Component);
+
+struct Component;
+
+const LOG_CONTEXT: &str = "out-aws-lambda";
+
+impl Guest for Component {
+    fn run(input: String) -> String {
+        let settings: OutAwsLambdaSettings =
+            match nodes_common::load_settings(bindings::wasi::config::runtime::get) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    error!(context: LOG_CONTEXT, "{e}");
+                    return e.to_string();
+                }
+            };
+
+        match invoke(&input, &settings) {
+            Ok(()) => {
+                info!(context: LOG_CONTEXT,
+                    "Invoked function {:?} ({})",
+                    settings.function_name,
+                    lambda::invocation_type(&settings)
+                );
+                "Done".into()
+            }
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to invoke Lambda function: {e}");
+                e
+            }
+        }
+    }
+}
+
+/// Invoke the function with the message as its payload, returning the error to report
+fn invoke(input: &str, settings: &OutAwsLambdaSettings) -> Result<(), String> {
+    let credentials = Credentials {
+        access_key_id: reveal(&settings.credentials.access_key_id_secret)?,
+        secret_access_key: reveal(&settings.credentials.secret_access_key_secret)?,
+        session_token: settings
+            .credentials
+            .session_token_secret
+            .as_deref()
+            .map(reveal)
+            .transpose()?,
+    };
+
+    let url = lambda::invoke_url(settings);
+    let mut headers = vec![
+        ("content-type".to_string(), "application/json".to_string()),
+        (
+            "x-amz-invocation-type".to_string(),
+            lambda::invocation_type(settings).to_string(),
+        ),
+    ];
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Error: Invalid system time: {e}"))?;
+    sigv4::sign_request(
+        &mut headers,
+        &Request {
+            method: "POST",
+            url: &url,
+            body: input.as_bytes(),
+        },
+        &credentials,
+        &Scope {
+            region: &settings.region,
+            service: lambda::SERVICE,
+        },
+        now.as_secs(),
+    )?;
+
+    let (status, function_error, body) =
+        send(&url, &headers, input).map_err(|e| shared::transient_error(&e))?;
+    lambda::outcome(status, function_error.as_deref(), &body)
+}
+
+/// Value of the secret `name`, e.g. the access key ID
+fn reveal(name: &str) -> Result<String, String> {
+    let secret = bindings::wasmcloud::secrets::store::get(name)
+        .map_err(|e| format!("Error: Failed to get secret {name:?}: {e:?}"))?;
+    match bindings::wasmcloud::secrets::reveal::reveal(&secret) {
+        SecretValue::String(value) => Ok(value),
+        SecretValue::Bytes(bytes) => {
+            String::from_utf8(bytes).map_err(|_| format!("Error: Secret {name:?} isn't UTF-8"))
+        }
+    }
+}
+
+/// Send the invocation, returning the response status code, `X-Amz-Function-Error` header and
+/// body. Errors are failures to get a response.
+fn send(
+    url: &str,
+    headers: &[(String, String)],
+    body: &str,
+) -> Result<(u16, Option<String>, String), String> {
+    let rest = url.strip_prefix("https://").unwrap_or(url);
+    let (authority, path_with_query) = rest
+        .find('/')
+        .map_or((rest, "/"), |index| rest.split_at(index));
+
+    let fields = Fields::new();
+    for (name, value) in headers {
+        fields
+            .set(name, &[value.as_bytes().to_vec()])
+            .map_err(|e| format!("Failed to set header {name}: {e}"))?;
+    }
+
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&Method::Post).unwrap();
+    req.set_scheme(Some(&Scheme::Https)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(path_with_query)).unwrap();
+
+    let request_body = req.body().unwrap();
+    let output_stream = request_body.write().unwrap();
+    output_stream
+        .blocking_write_and_flush(body.as_bytes())
+        .map_err(|e| format!("Failed to write request body: {e}"))?;
+    drop(output_stream);
+    bindings::wasi::http::types::OutgoingBody::finish(request_body, None)
+        .map_err(|e| format!("Failed to finish request body: {e}"))?;
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .expect("HTTP request response missing")
+        .expect("HTTP request response requested more than once")
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+
+    let status = response.status();
+    let function_error = response
+        .headers()
+        .get(&"x-amz-function-error".to_string())
+        .first()
+        .map(|value| String::from_utf8_lossy(value).to_string());
+    let response_body = response
+        .consume()
+        .map_err(|_| "Failed to get response body")?;
+    let input_stream = response_body
+        .stream()
+        .map_err(|_| "Failed to get response body stream")?;
+    let mut body = Vec::new();
+    while let Ok(chunk) = input_stream.blocking_read(64 * 1024) {
+        if chunk.is_empty() {
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((
+        status,
+        function_error,
+        String::from_utf8_lossy(&body).to_string(),
+    ))
+}
@@ -0,0 +1,163 @@
+use shared::{FileFormat, FileRotation};
+
+const DATE_PLACEHOLDER: &str = "{{date}}";
+
+/// Expand `{{date}}` in the path template to the UTC date of `now_ms`, e.g. `2024-01-31`
+pub fn render_path(template: &str, now_ms: u64) -> String {
+    template.replace(DATE_PLACEHOLDER, &format_date(now_ms))
+}
+
+/// Path the active file is moved to when it's rotated
+pub fn rotated_path(path: &str, now_ms: u64) -> String {
+    format!("{path}.{now_ms}")
+}
+
+/// Whether the active file must be rotated before appending `incoming_bytes` to it.
+///
+/// Size rotation trips when the write would push a non-empty file over `max_bytes`. Time rotation
+/// trips when the file was last written in an earlier `interval_ms` window than `now_ms`.
+pub fn should_rotate(
+    rotation: &FileRotation,
+    file_size: u64,
+    last_modified_ms: Option<u64>,
+    incoming_bytes: u64,
+    now_ms: u64,
+) -> bool {
+    if file_size == 0 {
+        return false;
+    }
+
+    let size_exceeded = rotation
+        .max_bytes
+        .is_some_and(|max_bytes| file_size.saturating_add(incoming_bytes) > max_bytes);
+    let interval_elapsed = match (rotation.interval_ms, last_modified_ms) {
+        (Some(interval_ms), Some(last_modified_ms)) if interval_ms > 0 => {
+            last_modified_ms / interval_ms != now_ms / interval_ms
+        }
+        _ => false,
+    };
+
+    size_exceeded || interval_elapsed
+}
+
+/// Render a message as a single line in the configured format
+pub fn format_line(input: &str, format: FileFormat) -> Result<String, String> {
+    match format {
+        FileFormat::Raw => Ok(format!("{input}\n")),
+        FileFormat::Jsonl => {
+            let value: serde_json::Value =
+                serde_json::from_str(input).map_err(|e| format!("Input is not valid JSON: {e}"))?;
+            Ok(format!("{value}\n"))
+        }
+    }
+}
+
+/// Format a unix timestamp in milliseconds as a `YYYY-MM-DD` UTC date
+fn format_date(epoch_ms: u64) -> String {
+    // Civil-from-days conversion, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (epoch_ms / 86_400_000) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-02-29T13:45:00Z
+    const LEAP_DAY_MS: u64 = 1_709_214_300_000;
+
+    #[test]
+    fn test_render_path_expands_date() {
+        assert_eq!(
+            render_path("logs/{{date}}/out.log", LEAP_DAY_MS),
+            "logs/2024-02-29/out.log"
+        );
+        assert_eq!(
+            render_path("{{date}}-{{date}}.log", 0),
+            "1970-01-01-1970-01-01.log"
+        );
+        assert_eq!(render_path("out.log", LEAP_DAY_MS), "out.log");
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(LEAP_DAY_MS), "2024-02-29");
+        // Last millisecond of 1999
+        assert_eq!(format_date(946_684_799_999), "1999-12-31");
+        assert_eq!(format_date(946_684_800_000), "2000-01-01");
+    }
+
+    #[test]
+    fn test_should_rotate_on_size() {
+        let rotation = FileRotation {
+            max_bytes: Some(100),
+            interval_ms: None,
+        };
+
+        assert!(!should_rotate(&rotation, 90, None, 10, 0));
+        assert!(should_rotate(&rotation, 90, None, 11, 0));
+        // A new file is never rotated, even when a single message exceeds the limit
+        assert!(!should_rotate(&rotation, 0, None, 500, 0));
+    }
+
+    #[test]
+    fn test_should_rotate_on_interval() {
+        let hour = 3_600_000;
+        let rotation = FileRotation {
+            max_bytes: None,
+            interval_ms: Some(hour),
+        };
+
+        assert!(!should_rotate(
+            &rotation,
+            10,
+            Some(hour + 1),
+            10,
+            2 * hour - 1
+        ));
+        assert!(should_rotate(
+            &rotation,
+            10,
+            Some(2 * hour - 1),
+            10,
+            2 * hour
+        ));
+        assert!(!should_rotate(&rotation, 10, None, 10, 2 * hour));
+    }
+
+    #[test]
+    fn test_should_not_rotate_without_thresholds() {
+        assert!(!should_rotate(
+            &FileRotation::default(),
+            u64::MAX,
+            Some(0),
+            1,
+            u64::MAX
+        ));
+    }
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(format_line("hello", FileFormat::Raw).unwrap(), "hello\n");
+        assert_eq!(
+            format_line("{\n  \"a\": 1\n}", FileFormat::Jsonl).unwrap(),
+            "{\"a\":1}\n"
+        );
+        assert!(format_line("not json", FileFormat::Jsonl).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use bindings::exports::pipestack::out::out::Guest;
+use bindings::wasi::filesystem::preopens;
+use bindings::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, ErrorCode, OpenFlags, PathFlags,
+};
+use shared::{FileFormat, FromConfig, OutFileSettings};
+use wasmcloud_component::{error, info};
+
+mod file;
+
+mod bindings {
+    use super::Component;
+    wit_bindgen::generate!({ generate_all });
+    export!(Component);
+}
+
+struct Component;
+
+const LOG_CONTEXT: &str = "out-file";
+
+impl Guest for Component {
+    fn run(input: String) -> String {
+        let config = match bindings::wasi::config::runtime::get("json") {
+            Ok(config) => config,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to get config: {e:?}");
+                return format!("Failed to get config: {e:?}");
+            }
+        };
+
+        let settings = match OutFileSettings::from_config(config) {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to parse config: {e}");
+                return format!("Failed to parse config: {e}");
+            }
+        };
+
+        match write_to_file(&input, &settings) {
+            Ok(path) => {
+                info!(context: LOG_CONTEXT, "Wrote message to {path}");
+                String::from("OK")
+            }
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to write message: {e}");
+                format!("Error: {e}")
+            }
+        }
+    }
+}
+
+fn write_to_file(input: &str, settings: &OutFileSettings) -> Result<String, String> {
+    let line = file::format_line(input, settings.format.unwrap_or(FileFormat::Raw))?;
+
+    let now = bindings::wasi::clocks::wall_clock::now();
+    let now_ms = now.seconds * 1_000 + u64::from(now.nanoseconds / 1_000_000);
+    let path = file::render_path(&settings.path_template, now_ms);
+
+    // Paths are resolved against the first directory the host preopened for the component
+    let (root, _) = preopens::get_directories()
+        .into_iter()
+        .next()
+        .ok_or("No preopened directory available to write to")?;
+    create_parent_directories(&root, &path)?;
+
+    if let Some(rotation) = &settings.rotation
+        && let Ok(stat) = root.stat_at(PathFlags::empty(), &path)
+    {
+        let last_modified_ms = stat
+            .data_modification_timestamp
+            .map(|t| t.seconds * 1_000 + u64::from(t.nanoseconds / 1_000_000));
+        if file::should_rotate(
+            rotation,
+            stat.size,
+            last_modified_ms,
+            line.len() as u64,
+            now_ms,
+        ) {
+            let rotated_path = file::rotated_path(&path, now_ms);
+            root.rename_at(&path, &root, &rotated_path)
+                .map_err(|e| format!("Failed to rotate {path} to {rotated_path}: {e:?}"))?;
+            info!(context: LOG_CONTEXT, "Rotated {path} to {rotated_path}");
+        }
+    }
+
+    let descriptor = root
+        .open_at(
+            PathFlags::empty(),
+            &path,
+            OpenFlags::CREATE,
+            DescriptorFlags::WRITE,
+        )
+        .map_err(|e| format!("Failed to open {path}: {e:?}"))?;
+    append(&descriptor, line.as_bytes()).map_err(|e| format!("Failed to write {path}: {e:?}"))?;
+
+    Ok(path)
+}
+
+fn create_parent_directories(root: &Descriptor, path: &str) -> Result<(), String> {
+    let Some((parent, _)) = path.rsplit_once('/') else {
+        return Ok(());
+    };
+
+    let mut directory = String::new();
+    for segment in parent.split('/').filter(|segment| !segment.is_empty()) {
+        if !directory.is_empty() {
+            directory.push('/');
+        }
+        directory.push_str(segment);
+        match root.create_directory_at(&directory) {
+            Ok(()) | Err(ErrorCode::Exist) => {}
+            Err(e) => return Err(format!("Failed to create directory {directory}: {e:?}")),
+        }
+    }
+    Ok(())
+}
+
+fn append(descriptor: &Descriptor, mut bytes: &[u8]) -> Result<(), ErrorCode> {
+    let mut offset = descriptor.stat()?.size;
+    while !bytes.is_empty() {
+        let written = descriptor.write(bytes, offset)?;
+        offset += written;
+        bytes = &bytes[written as usize..];
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use bindings::exports::pipestack::out::out::Guest;
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use nodes_common::UrlParts;
+use shared::{OutGraphqlMutationSettings, apply_authentication};
+use wasmcloud_component::{error, info};
+
+mod mutation;
+
+nodes_common::bindings!(Component);
+
+struct Component;
+
+const LOG_CONTEXT: &str = "out-graphql-mutation";
+
+impl Guest for Component {
+    fn run(input: String) -> String {
+        let settings: OutGraphqlMutationSettings =
+            match nodes_common::load_settings(bindings::wasi::config::runtime::get) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    error!(context: LOG_CONTEXT, "{e}");
+                    return e.to_string();
+                }
+            };
+
+        match execute(&input, &settings) {
+            Ok(()) => {
+                info!(context: LOG_CONTEXT, "Executed mutation against {}", settings.url);
+                "Done".into()
+            }
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to execute mutation: {e}");
+                e
+            }
+        }
+    }
+}
+
+/// Execute the mutation with the variables of the message, returning the error to report
+fn execute(input: &str, settings: &OutGraphqlMutationSettings) -> Result<(), String> {
+    let variables = mutation::variables(input, settings).map_err(|e| format!("Error: {e}"))?;
+    let body = mutation::request_body(settings, variables);
+
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    for header in settings.headers.iter().flatten() {
+        headers.push((header.key.clone(), header.value.clone()));
+    }
+    let url = match &settings.authentication {
+        Some(auth) => apply_authentication(&mut headers, &settings.url, auth)
+            .map_err(|e| format!("Error: {e}"))?,
+        None => settings.url.clone(),
+    };
+
+    let (status, response) =
+        send(&url, &headers, &body).map_err(|e| shared::transient_error(&e))?;
+    mutation::outcome(status, &response)
+}
+
+/// POST the mutation to the endpoint, returning the response status code and body. Errors are
+/// failures to get a response.
+fn send(url: &str, headers: &[(String, String)], body: &str) -> Result<(u16, String), String> {
+    let UrlParts {
+        https,
+        authority,
+        path_with_query,
+    } = nodes_common::split_url(url);
+    let scheme = if https { Scheme::Https } else { Scheme::Http };
+
+    let fields = Fields::new();
+    for (name, value) in headers {
+        fields
+            .set(name, &[value.as_bytes().to_vec()])
+            .map_err(|e| format!("Failed to set header {name}: {e}"))?;
+    }
+
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&Method::Post).unwrap();
+    req.set_scheme(Some(&scheme)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(&path_with_query)).unwrap();
+
+    let request_body = req.body().unwrap();
+    let output_stream = request_body.write().unwrap();
+    output_stream
+        .blocking_write_and_flush(body.as_bytes())
+        .map_err(|e| format!("Failed to write request body: {e}"))?;
+    drop(output_stream);
+    bindings::wasi::http::types::OutgoingBody::finish(request_body, None)
+        .map_err(|e| format!("Failed to finish request body: {e}"))?;
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .expect("HTTP request response missing")
+        .expect("HTTP request response requested more than once")
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+
+    let status = response.status();
+    let response_body = response
+        .consume()
+        .map_err(|_| "Failed to get response body")?;
+    let input_stream = response_body
+        .stream()
+        .map_err(|_| "Failed to get response body stream")?;
+    let mut body = Vec::new();
+    while let Ok(chunk) = input_stream.blocking_read(64 * 1024) {
+        if chunk.is_empty() {
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((status, String::from_utf8_lossy(&body).to_string()))
+}
@@ -0,0 +1,171 @@
+use serde_json::{Map, Value, json};
+use shared::{OutGraphqlMutationSettings, Template};
+
+/// Variables of the mutation for a message: the template with each string rendered with the
+/// message, or the message itself without one
+pub fn variables(input: &str, settings: &OutGraphqlMutationSettings) -> Result<Value, String> {
+    let message: Value = serde_json::from_str(input)
+        .map_err(|_| "Message is not JSON, so the mutation variables can't be read".to_string())?;
+    let Some(template) = &settings.variables_template else {
+        return match message {
+            Value::Object(_) => Ok(message),
+            _ => Err("Message is not a JSON object, so it can't be the mutation variables".into()),
+        };
+    };
+
+    let mut variables = Map::new();
+    for (name, value) in template {
+        variables.insert(name.clone(), fill(value, &message)?);
+    }
+    Ok(Value::Object(variables))
+}
+
+fn fill(template: &Value, message: &Value) -> Result<Value, String> {
+    match template {
+        Value::String(value) => Template::parse(value)
+            .and_then(|template| template.render_value(message))
+            .map_err(|e| e.to_string()),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| fill(value, message))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(values) => values
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), fill(value, message)?)))
+            .collect::<Result<_, String>>()
+            .map(Value::Object),
+        _ => Ok(template.clone()),
+    }
+}
+
+pub fn request_body(settings: &OutGraphqlMutationSettings, variables: Value) -> String {
+    json!({
+        "query": settings.mutation,
+        "variables": variables,
+    })
+    .to_string()
+}
+
+/// Outcome of the mutation from its response. GraphQL servers report failed mutations in the
+/// `errors` of the response, usually with a 200 status code.
+pub fn outcome(status: u16, body: &str) -> Result<(), String> {
+    if let Ok(response) = serde_json::from_str::<Value>(body)
+        && let Some(errors) = response.get("errors").and_then(Value::as_array)
+        && !errors.is_empty()
+    {
+        let messages: Vec<&str> = errors
+            .iter()
+            .map(|error| error.get("message").and_then(Value::as_str).unwrap_or("?"))
+            .collect();
+        return Err(format!("Error: GraphQL errors: {}", messages.join("; ")));
+    }
+
+    match status {
+        200..300 => Ok(()),
+        // The endpoint is rate limiting or unavailable, the message can be sent again later
+        429 | 500.. => Err(shared::transient_error(&format!(
+            "Endpoint responded with status code {status}"
+        ))),
+        _ => Err(format!(
+            "Error: Endpoint responded with status code {status}: {body}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn settings(template: Option<Value>) -> OutGraphqlMutationSettings {
+        OutGraphqlMutationSettings {
+            url: "https://api.example.com/graphql".to_string(),
+            mutation: "mutation Ship($input: ShipInput!) { ship(input: $input) { id } }"
+                .to_string(),
+            variables_template: template.map(|template| {
+                serde_json::from_value::<BTreeMap<String, Value>>(template).unwrap()
+            }),
+            headers: None,
+            authentication: None,
+        }
+    }
+
+    const ORDER: &str = r#"{"order": {"id": "o-42", "items": [{"sku": "A1"}], "express": true}}"#;
+
+    #[test]
+    fn test_variables_from_template() {
+        let settings = settings(Some(json!({
+            "input": {
+                "orderId": "{{/order/id}}",
+                "items": "{{/order/items}}",
+                "express": "{{/order/express}}",
+                "carrier": "ups",
+                "tags": ["{{/order/id}}", "pipestack"],
+                "reference": "order-{{/order/id | upper}}",
+                "note": "{{/order/note | default: null}}"
+            }
+        })));
+
+        assert_eq!(
+            variables(ORDER, &settings),
+            Ok(json!({
+                "input": {
+                    "orderId": "o-42",
+                    "items": [{"sku": "A1"}],
+                    "express": true,
+                    "carrier": "ups",
+                    "tags": ["o-42", "pipestack"],
+                    "reference": "order-O-42",
+                    "note": null
+                }
+            }))
+        );
+    }
+
+    #[test]
+    fn test_missing_variable_field() {
+        let settings = settings(Some(json!({"customer": "{{/order/customer}}"})));
+
+        assert_eq!(
+            variables(ORDER, &settings),
+            Err(r#"Template value "/order/customer" is missing from the message"#.to_string())
+        );
+        assert!(variables("plain text", &settings).is_err());
+    }
+
+    #[test]
+    fn test_message_is_variables_without_template() {
+        let settings = settings(None);
+
+        assert_eq!(variables(ORDER, &settings).unwrap()["order"]["id"], "o-42");
+        assert!(variables("[1, 2]", &settings).is_err());
+    }
+
+    #[test]
+    fn test_graphql_errors_fail_the_message() {
+        assert_eq!(outcome(200, r#"{"data": {"ship": {"id": "s-1"}}}"#), Ok(()));
+        assert_eq!(
+            outcome(
+                200,
+                r#"{"data": null, "errors": [{"message": "Order not found"}, {"message": "Invalid carrier"}]}"#
+            ),
+            Err("Error: GraphQL errors: Order not found; Invalid carrier".to_string())
+        );
+        assert_eq!(
+            outcome(400, r#"{"errors": [{"message": "Syntax Error"}]}"#),
+            Err("Error: GraphQL errors: Syntax Error".to_string())
+        );
+    }
+
+    #[test]
+    fn test_http_errors() {
+        assert!(shared::is_transient_error(
+            &outcome(503, "Service Unavailable").unwrap_err()
+        ));
+        assert_eq!(
+            outcome(401, "Unauthorized"),
+            Err("Error: Endpoint responded with status code 401: Unauthorized".to_string())
+        );
+    }
+}
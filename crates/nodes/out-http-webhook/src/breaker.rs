@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+/// State of a destination's circuit breaker. It's kept in keyvalue so every instance of the node
+/// shares it, hence the wall clock times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "state",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum State {
+    /// Requests go through. Counts consecutive failures.
+    Closed { failures: u32 },
+    /// Requests are short-circuited until the cooldown ends at `until_ms`
+    Open { until_ms: u64 },
+    /// The cooldown ended. The next request decides whether to close or reopen the breaker.
+    HalfOpen,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::Closed { failures: 0 }
+    }
+}
+
+/// Circuit breaker for a single destination
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown_ms: u64,
+    state: State,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown_ms: u64, state: State) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown_ms,
+            state,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Whether a request may be sent at `now_ms`, or how many milliseconds remain until it may
+    pub fn allow(&mut self, now_ms: u64) -> Result<(), u64> {
+        match self.state {
+            State::Open { until_ms } if now_ms < until_ms => Err(until_ms - now_ms),
+            State::Open { .. } => {
+                self.state = State::HalfOpen;
+                Ok(())
+            }
+            State::Closed { .. } | State::HalfOpen => Ok(()),
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = State::Closed { failures: 0 };
+    }
+
+    pub fn record_failure(&mut self, now_ms: u64) {
+        self.state = match self.state {
+            State::Closed { failures } if failures.saturating_add(1) < self.failure_threshold => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            _ => State::Open {
+                until_ms: now_ms.saturating_add(self.cooldown_ms),
+            },
+        };
+    }
+}
+
+/// Destination a webhook URL is sent to, i.e. its authority
+pub fn destination(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    without_scheme
+        .split(['/', '?'])
+        .next()
+        .unwrap_or(without_scheme)
+}
+
+/// Whether a response status means the destination is failing, as opposed to rejecting the
+/// request
+pub fn is_failure_status(status: u16) -> bool {
+    status >= 500 || status == 429
+}
+
+/// Keyvalue key of a destination's breaker state. Characters NATS keys don't allow, e.g. the `:`
+/// before a port, are replaced with `_`.
+pub fn state_key(destination: &str) -> String {
+    let destination: String = destination
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("circuit-breaker-{destination}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN_MS: u64 = 1_000;
+
+    #[test]
+    fn test_opens_after_threshold_failures() {
+        let mut breaker = CircuitBreaker::new(3, COOLDOWN_MS, State::default());
+
+        breaker.record_failure(0);
+        breaker.record_failure(0);
+        assert_eq!(breaker.state(), State::Closed { failures: 2 });
+        assert!(breaker.allow(0).is_ok());
+
+        breaker.record_failure(10);
+        assert_eq!(breaker.state(), State::Open { until_ms: 1_010 });
+        assert_eq!(breaker.allow(500), Err(510));
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let mut breaker = CircuitBreaker::new(2, COOLDOWN_MS, State::default());
+
+        breaker.record_failure(0);
+        breaker.record_success();
+        breaker.record_failure(0);
+        assert_eq!(breaker.state(), State::Closed { failures: 1 });
+    }
+
+    #[test]
+    fn test_half_open_success_closes() {
+        let mut breaker = CircuitBreaker::new(1, COOLDOWN_MS, State::default());
+        breaker.record_failure(0);
+
+        assert!(breaker.allow(COOLDOWN_MS).is_ok());
+        assert_eq!(breaker.state(), State::HalfOpen);
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), State::Closed { failures: 0 });
+    }
+
+    #[test]
+    fn test_half_open_failure_reopens() {
+        let mut breaker = CircuitBreaker::new(5, COOLDOWN_MS, State::default());
+        for _ in 0..5 {
+            breaker.record_failure(0);
+        }
+
+        assert!(breaker.allow(2 * COOLDOWN_MS).is_ok());
+        assert_eq!(breaker.state(), State::HalfOpen);
+
+        // A single failure while half-open reopens the breaker for another cooldown
+        breaker.record_failure(2 * COOLDOWN_MS);
+        assert_eq!(
+            breaker.state(),
+            State::Open {
+                until_ms: 3 * COOLDOWN_MS
+            }
+        );
+        assert!(breaker.allow(2 * COOLDOWN_MS + 1).is_err());
+    }
+
+    #[test]
+    fn test_destination() {
+        assert_eq!(
+            destination("https://hooks.example.com/a/b?c=d"),
+            "hooks.example.com"
+        );
+        assert_eq!(destination("http://localhost:8080"), "localhost:8080");
+        assert_eq!(destination("example.com/hook"), "example.com");
+    }
+
+    #[test]
+    fn test_is_failure_status() {
+        assert!(is_failure_status(503));
+        assert!(is_failure_status(429));
+        assert!(!is_failure_status(200));
+        assert!(!is_failure_status(404));
+    }
+
+    #[test]
+    fn test_cooldown_saturates() {
+        let mut breaker = CircuitBreaker::new(1, u64::MAX, State::default());
+
+        breaker.record_failure(10);
+        assert_eq!(breaker.state(), State::Open { until_ms: u64::MAX });
+    }
+
+    #[test]
+    fn test_state_round_trips_through_json() {
+        let state = State::Open { until_ms: 1_010 };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"state":"open","untilMs":1010}"#);
+        assert_eq!(serde_json::from_str::<State>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_state_key() {
+        assert_eq!(
+            state_key("hooks.example.com"),
+            "circuit-breaker-hooks.example.com"
+        );
+        assert_eq!(
+            state_key("localhost:8080"),
+            "circuit-breaker-localhost_8080"
+        );
+    }
+}
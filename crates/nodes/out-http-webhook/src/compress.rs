@@ -0,0 +1,47 @@
+use flate2::{Compression as Level, write::GzEncoder};
+use shared::Compression;
+use std::io::Write;
+
+/// Request body as sent with the given compression, and the `Content-Encoding` to send it with.
+/// Empty bodies are sent as is, as compressing them only adds bytes.
+pub fn encode(body: &[u8], compression: Compression) -> (Vec<u8>, Option<&'static str>) {
+    match compression {
+        Compression::Gzip if !body.is_empty() => (gzip(body), Some("gzip")),
+        _ => (body.to_vec(), None),
+    }
+}
+
+fn gzip(body: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+    encoder
+        .write_all(body)
+        .expect("Writing to a Vec doesn't fail");
+    encoder.finish().expect("Writing to a Vec doesn't fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_gzip_body_round_trips() {
+        let body = r#"{"data":{"id":7,"items":["a","b","c"]}}"#.repeat(100);
+        let (encoded, content_encoding) = encode(body.as_bytes(), Compression::Gzip);
+        assert_eq!(content_encoding, Some("gzip"));
+        assert!(encoded.len() < body.len());
+
+        let mut decoded = String::new();
+        GzDecoder::new(encoded.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, body);
+    }
+
+    #[test]
+    fn test_uncompressed_and_empty_bodies_are_sent_as_is() {
+        assert_eq!(encode(b"{}", Compression::None), (b"{}".to_vec(), None));
+        assert_eq!(encode(b"", Compression::Gzip), (Vec::new(), None));
+    }
+}
@@ -0,0 +1,53 @@
+//! Connection reuse. `wasi:http` 0.2.0 has no request option for keep-alive or connection pooling,
+//! and headers managing the connection are hop-by-hop, so the host rejects them. Whether
+//! connections to a destination are reused is up to the host's HTTP client. What the component can
+//! do is read each response to the end, which releases the connection for reuse instead of
+//! closing it.
+
+/// Headers managing the connection rather than the request, which components can't set
+const HOP_BY_HOP_HEADERS: [&str; 5] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Response bodies are read up to this size so the connection can be reused. A connection with a
+/// larger body left unread is closed, as reading it would cost more than opening a new one.
+pub const MAX_DRAINED_BYTES: usize = 64 * 1024;
+
+/// Whether a header manages the connection, e.g. a configured `Connection: keep-alive`
+pub fn is_hop_by_hop(name: &str) -> bool {
+    HOP_BY_HOP_HEADERS
+        .iter()
+        .any(|header| header.eq_ignore_ascii_case(name))
+}
+
+/// Whether to keep reading a response body after `read` bytes. The whole body is read when it's
+/// logged, otherwise only up to `MAX_DRAINED_BYTES`.
+pub fn keep_reading(read: usize, logged: bool) -> bool {
+    logged || read < MAX_DRAINED_BYTES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_headers_are_hop_by_hop() {
+        assert!(is_hop_by_hop("Connection"));
+        assert!(is_hop_by_hop("keep-alive"));
+        assert!(is_hop_by_hop("Transfer-Encoding"));
+        assert!(!is_hop_by_hop("Content-Type"));
+        assert!(!is_hop_by_hop("X-Connection-Id"));
+    }
+
+    #[test]
+    fn test_unlogged_bodies_are_drained_up_to_limit() {
+        assert!(keep_reading(0, false));
+        assert!(keep_reading(MAX_DRAINED_BYTES - 1, false));
+        assert!(!keep_reading(MAX_DRAINED_BYTES, false));
+        assert!(keep_reading(MAX_DRAINED_BYTES, true));
+    }
+}
@@ -1,13 +1,19 @@
 use bindings::exports::pipestack::out::out::Guest;
-use bindings::wasi::http::types::Fields;
-use shared::{FromConfig, OutHttpWebhookSettings};
+use bindings::wasi::http::types::{Fields, RequestOptions};
+use breaker::CircuitBreaker;
+use shared::{
+    FromConfig, OutHttpWebhookSettings, RESILIENCE_CONFIG_KEY, Resilience, apply_authentication,
+};
 use wasmcloud_component::{error, info};
 
-mod bindings {
-    use super::Component;
-    wit_bindgen::generate!({ generate_all });
-    export!(Component);
-}
+mod breaker;
+mod compress;
+mod connection;
+mod metrics;
+mod redact;
+mod sign;
+
+nodes_common::bindings!(Component);
 
 struct Component;
 
@@ -15,83 +21,212 @@ const LOG_CONTEXT: &str = "out-http-webhook";
 
 impl Guest for Component {
     fn run(input: String) -> String {
-        let config = match bindings::wasi::config::runtime::get("json") {
-            Ok(config) => config,
-            Err(e) => {
-                error!(context: LOG_CONTEXT, "Failed to get config: {e:?}");
-                return format!("Failed to get config: {e:?}");
-            }
-        };
+        let started_ns = bindings::wasi::clocks::monotonic_clock::now();
+        let output = send(input);
+        shared::record_outcome(
+            &Metrics,
+            elapsed_ms(started_ns),
+            metrics::error_kind(&output),
+        );
+        output
+    }
+}
+
+/// Reports to the linked metrics provider. Metrics are best effort, so failures, e.g. when no
+/// provider is linked, are ignored.
+struct Metrics;
+
+impl shared::MetricsRecorder for Metrics {
+    fn record_processed(&self, count: u64) {
+        let _ = bindings::pipestack::metrics::metrics::record_processed(count);
+    }
+
+    fn record_latency(&self, ms: u64) {
+        let _ = bindings::pipestack::metrics::metrics::record_latency(ms);
+    }
+
+    fn record_error(&self, kind: &str) {
+        let _ = bindings::pipestack::metrics::metrics::record_error(kind);
+    }
+}
 
-        let settings = match OutHttpWebhookSettings::from_config(config) {
+/// Milliseconds elapsed since the monotonic clock read `started_ns`
+fn elapsed_ms(started_ns: u64) -> u64 {
+    bindings::wasi::clocks::monotonic_clock::now().saturating_sub(started_ns) / 1_000_000
+}
+
+/// Send the input to the configured webhook, returning "Done" or the error to report
+fn send(input: String) -> String {
+    let settings: OutHttpWebhookSettings =
+        match nodes_common::load_settings(bindings::wasi::config::runtime::get) {
             Ok(settings) => settings,
             Err(e) => {
-                error!(context: LOG_CONTEXT, "Failed to parse config: {e}");
-                return format!("Failed to parse config: {e}");
+                error!(context: LOG_CONTEXT, "{e}");
+                return e.to_string();
             }
         };
 
-        match make_http_request(&input, &settings) {
-            Ok(response) => response,
-            Err(e) => format!("Error: {e}"),
+    let resilience = resilience();
+    let max_retries = resilience.retries.unwrap_or(0);
+    let destination = breaker::destination(&settings.url);
+    let mut retry = 0;
+
+    let outcome = loop {
+        if let Err(e) = check_breaker(&settings, destination) {
+            return e;
+        }
+
+        let outcome = make_http_request(&input, &settings, resilience.timeout_ms);
+        let failed = match &outcome {
+            Ok(status) => breaker::is_failure_status(*status),
+            Err(_) => true,
+        };
+        record_outcome(&settings, destination, failed);
+
+        if !failed || retry >= max_retries {
+            break outcome;
         }
+        retry += 1;
+        let delay_ms = resilience.delay_ms(retry);
+        info!(context: LOG_CONTEXT,
+            "Request to {destination} failed, retrying in {delay_ms}ms, retry {retry} of {max_retries}"
+        );
+        bindings::wasi::clocks::monotonic_clock::subscribe_duration(
+            delay_ms.saturating_mul(1_000_000),
+        )
+        .block();
+    };
+
+    match outcome {
+        Ok(status) if (200..300).contains(&status) => "Done".into(),
+        Ok(status) => format!("Error: HTTP request failed with status code {status}"),
+        Err(e) => format!("Error: {e}"),
     }
 }
 
-fn make_http_request(input: &str, settings: &OutHttpWebhookSettings) -> Result<String, String> {
-    // Create Fields with headers from settings
-    let fields = Fields::new();
-    if let Some(headers) = &settings.headers {
-        for header in headers {
-            fields
-                .set(&header.key, &[header.value.as_bytes().to_vec()])
-                .unwrap_or_else(|e| {
-                    error!(context: LOG_CONTEXT, "Failed to set header {}: {}", header.key, e);
-                });
+/// Resilience settings of the node. Defaults to a single attempt without a timeout.
+fn resilience() -> Resilience {
+    match bindings::wasi::config::runtime::get(RESILIENCE_CONFIG_KEY) {
+        Ok(None) => Resilience::default(),
+        Ok(config) => Resilience::from_config(config).unwrap_or_else(|e| {
+            error!(context: LOG_CONTEXT, "Failed to parse resilience config: {e}");
+            Resilience::default()
+        }),
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Failed to get resilience config: {e:?}");
+            Resilience::default()
         }
     }
+}
 
-    // Handle authentication
-    if let Some(auth) = &settings.authentication {
-        if let Some(config) = &auth.config {
-            match auth.auth_type.as_str() {
-                "api_key" => {
-                    if config.location == "header" {
-                        let auth_value = if config.prefix.is_empty() {
-                            config.value.clone()
-                        } else {
-                            format!("{} {}", config.prefix, config.value)
-                        };
-                        fields
-                            .set(&config.name, &[auth_value.as_bytes().to_vec()])
-                            .unwrap_or_else(|e| {
-                                error!(context: LOG_CONTEXT, "Failed to set auth header {}: {}", config.name, e);
-                            });
-                    }
-                    // Query string auth will be handled when constructing the URL
-                }
-                "bearer" => {
-                    let bearer_value = format!("Bearer {}", config.value);
-                    fields
-                        .set("Authorization", &[bearer_value.as_bytes().to_vec()])
-                        .unwrap_or_else(|e| {
-                            error!(context: LOG_CONTEXT, "Failed to set Bearer authorization header: {}", e);
-                        });
-                }
-                "basic" => {
-                    let basic_value = format!("Basic {}", config.value);
-                    fields
-                        .set("Authorization", &[basic_value.as_bytes().to_vec()])
-                        .unwrap_or_else(|e| {
-                            error!(context: LOG_CONTEXT, "Failed to set Basic authorization header: {}", e);
-                        });
-                }
-                _ => {
-                    error!(context: LOG_CONTEXT, "Unsupported authentication type: {}", auth.auth_type);
-                }
+/// Check the destination's circuit breaker, returning the transient error to report while it's
+/// open
+fn check_breaker(settings: &OutHttpWebhookSettings, destination: &str) -> Result<(), String> {
+    let Some(mut destination_breaker) = load_breaker(settings, destination) else {
+        return Ok(());
+    };
+    let allowed = destination_breaker.allow(now_ms());
+    if allowed.is_ok() {
+        // The cooldown may have ended, half-opening the breaker
+        store_breaker(destination, &destination_breaker);
+    }
+    allowed.map_err(|wait_ms| {
+        info!(context: LOG_CONTEXT, "Circuit breaker open for {destination}, not sending request");
+        shared::transient_error(&format!(
+            "Circuit breaker open for {destination}, retry in {wait_ms}ms"
+        ))
+    })
+}
+
+/// Record the outcome of a request in the destination's circuit breaker
+fn record_outcome(settings: &OutHttpWebhookSettings, destination: &str, failed: bool) {
+    let Some(mut destination_breaker) = load_breaker(settings, destination) else {
+        return;
+    };
+    if failed {
+        destination_breaker.record_failure(now_ms());
+        if matches!(destination_breaker.state(), breaker::State::Open { .. }) {
+            error!(context: LOG_CONTEXT, "Circuit breaker opened for {destination}");
+        }
+    } else {
+        destination_breaker.record_success();
+    }
+    store_breaker(destination, &destination_breaker);
+}
+
+/// The destination's circuit breaker, with the state every instance of the node shares. `None`
+/// when the node has no circuit breaker. A state that can't be read counts as closed, so keyvalue
+/// failures don't stop requests.
+fn load_breaker(settings: &OutHttpWebhookSettings, destination: &str) -> Option<CircuitBreaker> {
+    let circuit_breaker = settings.circuit_breaker.as_ref()?;
+    let state = bindings::wasi::keyvalue::store::open("")
+        .and_then(|bucket| bucket.get(&breaker::state_key(destination)))
+        .map_err(|e| {
+            error!(context: LOG_CONTEXT, "Failed to read circuit breaker of {destination}: {e:?}");
+        })
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_slice(&value).ok())
+        .unwrap_or_default();
+    Some(CircuitBreaker::new(
+        circuit_breaker.failure_threshold,
+        circuit_breaker.cooldown_ms,
+        state,
+    ))
+}
+
+fn store_breaker(destination: &str, destination_breaker: &CircuitBreaker) {
+    let value = serde_json::to_vec(&destination_breaker.state())
+        .expect("Failed to serialize circuit breaker state");
+    if let Err(e) = bindings::wasi::keyvalue::store::open("")
+        .and_then(|bucket| bucket.set(&breaker::state_key(destination), &value))
+    {
+        error!(context: LOG_CONTEXT, "Failed to store circuit breaker of {destination}: {e:?}");
+    }
+}
+
+/// Milliseconds since the Unix epoch. Breaker states are shared between instances, possibly on
+/// different hosts, so they can't use the monotonic clock.
+fn now_ms() -> u64 {
+    let now = bindings::wasi::clocks::wall_clock::now();
+    now.seconds
+        .saturating_mul(1_000)
+        .saturating_add(u64::from(now.nanoseconds / 1_000_000))
+}
+
+/// Request options applying `timeout_ms` to connecting and to waiting on the response. There is
+/// no option for keeping connections alive, see `connection`.
+fn request_options(timeout_ms: u64) -> RequestOptions {
+    let timeout_ns = Some(timeout_ms.saturating_mul(1_000_000));
+    let options = RequestOptions::new();
+    if options.set_connect_timeout(timeout_ns).is_err()
+        || options.set_first_byte_timeout(timeout_ns).is_err()
+        || options.set_between_bytes_timeout(timeout_ns).is_err()
+    {
+        error!(context: LOG_CONTEXT, "Failed to set request timeout of {timeout_ms}ms");
+    }
+    options
+}
+
+/// Send the request, returning the response status code. Errors are failures to get a response.
+fn make_http_request(
+    input: &str,
+    settings: &OutHttpWebhookSettings,
+    timeout_ms: Option<u64>,
+) -> Result<u16, String> {
+    // Create Fields with headers from settings. Every header that is set is also recorded so the
+    // request can be logged with sensitive values redacted.
+    let fields = Fields::new();
+    let mut headers: Vec<(String, String)> = Vec::new();
+    if let Some(settings_headers) = &settings.headers {
+        for header in settings_headers {
+            if connection::is_hop_by_hop(&header.key) {
+                info!(context: LOG_CONTEXT,
+                    "Not sending header {}, connections are managed by the host", header.key
+                );
+                continue;
             }
-        } else {
-            error!(context: LOG_CONTEXT, "Authentication config is missing for auth type: {}", auth.auth_type);
+            set_header(&fields, &mut headers, &header.key, &header.value);
         }
     }
 
@@ -131,43 +266,61 @@ fn make_http_request(input: &str, settings: &OutHttpWebhookSettings) -> Result<S
         )
     };
 
-    // Handle API key authentication in query string
-    if let Some(auth) = &settings.authentication
-        && auth.auth_type == "api_key"
-        && auth
-            .config
-            .as_ref()
-            .is_some_and(|config| config.location == "query")
-        && let Some(config) = &auth.config
-    {
-        let separator = if path_with_query.contains('?') {
-            "&"
-        } else {
-            "?"
-        };
-        path_with_query = format!(
-            "{}{}{}={}",
-            path_with_query, separator, config.name, config.value
-        );
+    // Authentication may add headers, and an API key query parameter to the path
+    if let Some(auth) = &settings.authentication {
+        let mut auth_headers = Vec::new();
+        match apply_authentication(&mut auth_headers, &path_with_query, auth) {
+            Ok(authenticated) => {
+                path_with_query = authenticated;
+                for (name, value) in &auth_headers {
+                    set_header(&fields, &mut headers, name, value);
+                }
+            }
+            Err(e) => error!(context: LOG_CONTEXT, "{e}"),
+        }
     }
 
-    // Set Content-Type header for methods that will have a body
-    if matches!(
+    // Build the body for methods that support it. It's built before the request so it can be
+    // signed, as headers can't be changed once the request exists.
+    let payload = if matches!(
         method,
         bindings::wasi::http::types::Method::Post
             | bindings::wasi::http::types::Method::Put
             | bindings::wasi::http::types::Method::Patch
     ) {
-        // Set Content-Type header from settings, defaulting to application/json
-        let content_type = settings
-            .content_type
-            .as_deref()
-            .unwrap_or("application/json");
-        fields
-            .set("Content-Type", &[content_type.as_bytes().to_vec()])
-            .unwrap_or_else(|e| {
-                error!("Failed to set Content-Type header: {}", e);
-            });
+        // Set Content-Type header from settings, defaulting to application/json, unless the
+        // server is left to infer it
+        if let Some(content_type) = settings.content_type_header() {
+            set_header(&fields, &mut headers, "Content-Type", content_type);
+        }
+
+        // Create JSON payload with the input as a JSON object
+        let data_value: serde_json::Value = match serde_json::from_str(input) {
+            Ok(json) => json,
+            Err(_) => serde_json::Value::String(input.to_string()),
+        };
+
+        let payload_obj = serde_json::json!({
+            "data": data_value
+        });
+
+        Some(payload_obj.to_string())
+    } else {
+        None
+    };
+
+    // The body is compressed before signing, so the signature covers the bytes that are sent
+    let (body_bytes, content_encoding) = compress::encode(
+        payload.as_deref().unwrap_or("").as_bytes(),
+        settings.compress.unwrap_or_default(),
+    );
+    if let Some(content_encoding) = content_encoding {
+        set_header(&fields, &mut headers, "Content-Encoding", content_encoding);
+    }
+
+    if let Some(signing) = &settings.sign {
+        let signature = sign::signature(signing, &body_bytes);
+        set_header(&fields, &mut headers, &signing.header_name, &signature);
     }
 
     let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
@@ -177,29 +330,25 @@ fn make_http_request(input: &str, settings: &OutHttpWebhookSettings) -> Result<S
     req.set_path_with_query(Some(path_with_query.as_str()))
         .unwrap();
 
-    // Add request body for methods that support it
-    if matches!(
-        method,
-        bindings::wasi::http::types::Method::Post
-            | bindings::wasi::http::types::Method::Put
-            | bindings::wasi::http::types::Method::Patch
-    ) {
+    let log_bodies = settings.log_bodies.unwrap_or(false);
+    // A header configured for API key authentication carries a secret under a custom name
+    let auth_header: Vec<&str> = settings
+        .authentication
+        .as_ref()
+        .filter(|auth| auth.auth_type == "api_key")
+        .and_then(|auth| auth.config.as_ref())
+        .filter(|config| config.location == "header")
+        .map(|config| config.name.as_str())
+        .into_iter()
+        .collect();
+
+    // Write the request body
+    if payload.is_some() {
         let body = req.body().unwrap();
         let output_stream = body.write().unwrap();
 
-        // Create JSON payload with the input as a JSON object
-        let data_value: serde_json::Value = match serde_json::from_str(input) {
-            Ok(json) => json,
-            Err(_) => serde_json::Value::String(input.to_string()),
-        };
-
-        let payload_obj = serde_json::json!({
-            "data": data_value
-        });
-
-        let payload = payload_obj.to_string();
         output_stream
-            .blocking_write_and_flush(payload.as_bytes())
+            .blocking_write_and_flush(&body_bytes)
             .unwrap_or_else(|e| {
                 error!(context: LOG_CONTEXT, "Failed to write request body: {}", e);
             });
@@ -207,49 +356,71 @@ fn make_http_request(input: &str, settings: &OutHttpWebhookSettings) -> Result<S
         drop(output_stream);
     }
 
+    info!(context: LOG_CONTEXT,
+        "{}",
+        redact::request_log_line(
+            &settings.method,
+            &settings.url,
+            &headers,
+            payload.as_deref(),
+            log_bodies,
+            &auth_header,
+        )
+    );
+
     // Perform the HTTP request
-    let _ = match bindings::wasi::http::outgoing_handler::handle(req, None) {
-        Ok(resp) => {
-            resp.subscribe().block();
-            let response = resp
-                .get()
-                .expect("HTTP request response missing")
-                .expect("HTTP request response requested more than once")
-                .expect("HTTP request failed");
-            if response.status() == 200 {
-                let response_body_stream = response
-                    .consume()
-                    .expect("failed to get incoming request body");
-                let input_stream = response_body_stream
-                    .stream()
-                    .expect("failed to get response body stream");
-
-                let mut body_content = Vec::new();
-                while let Ok(chunk) = input_stream.read(1024) {
-                    if chunk.is_empty() {
-                        break;
-                    }
-                    body_content.extend_from_slice(&chunk);
-                }
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, timeout_ms.map(request_options))
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .expect("HTTP request response missing")
+        .expect("HTTP request response requested more than once")
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+    let status = response.status();
+    // The body is read even when it isn't logged, so the connection can be reused
+    let logged = status == 200;
+    let body_content = read_body(response, logged);
+    if logged {
+        let body_string = String::from_utf8_lossy(&body_content);
+        info!(context: LOG_CONTEXT,
+            "Response status code: {}. Body: {}",
+            status,
+            redact::redact_body(&body_string, log_bodies)
+        );
+    } else {
+        info!(context: LOG_CONTEXT, "Response status code: {}", status);
+    }
 
-                let body_string = String::from_utf8_lossy(&body_content);
-                info!(context: LOG_CONTEXT,
-                    "Response status code: {}. Body: {}",
-                    response.status(),
-                    body_string
-                );
-                format!(
-                    "HTTP request succeeded with status code {}",
-                    response.status()
-                )
-            } else {
-                format!("HTTP request failed with status code {}", response.status())
-            }
+    Ok(status)
+}
+
+/// Read the response body, stopping early when it isn't logged. Failures to read are treated as
+/// the end of the body.
+fn read_body(response: bindings::wasi::http::types::IncomingResponse, logged: bool) -> Vec<u8> {
+    let mut body_content = Vec::new();
+    let Ok(response_body) = response.consume() else {
+        return body_content;
+    };
+    let Ok(input_stream) = response_body.stream() else {
+        return body_content;
+    };
+
+    while connection::keep_reading(body_content.len(), logged) {
+        match input_stream.blocking_read(1024) {
+            Ok(chunk) if !chunk.is_empty() => body_content.extend_from_slice(&chunk),
+            _ => break,
         }
+    }
+    body_content
+}
+
+/// Set a request header and record it for logging. Failures are logged without the value.
+fn set_header(fields: &Fields, headers: &mut Vec<(String, String)>, name: &str, value: &str) {
+    match fields.set(name, &[value.as_bytes().to_vec()]) {
+        Ok(()) => headers.push((name.to_string(), value.to_string())),
         Err(e) => {
-            format!("Got error when trying to fetch dog: {e}")
+            error!(context: LOG_CONTEXT, "Failed to set header {}: {}", name, e);
         }
-    };
-
-    Ok("Done".into())
+    }
 }
@@ -0,0 +1,69 @@
+/// Kind of error a webhook run reported, from its output. `None` when the request succeeded.
+pub fn error_kind(output: &str) -> Option<&'static str> {
+    if output == "Done" {
+        None
+    } else if shared::is_transient_error(output) {
+        Some("transient")
+    } else if output.starts_with("Error: HTTP request failed with status code") {
+        Some("http-status")
+    } else if output.starts_with("Error: ") {
+        Some("request")
+    } else {
+        Some("config")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{MetricsRecorder, record_outcome};
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Recorded(RefCell<Vec<String>>);
+
+    impl MetricsRecorder for Recorded {
+        fn record_processed(&self, count: u64) {
+            self.0.borrow_mut().push(format!("processed {count}"));
+        }
+
+        fn record_latency(&self, ms: u64) {
+            self.0.borrow_mut().push(format!("latency {ms}"));
+        }
+
+        fn record_error(&self, kind: &str) {
+            self.0.borrow_mut().push(format!("error {kind}"));
+        }
+    }
+
+    fn recorded(output: &str) -> Vec<String> {
+        let recorded = Recorded::default();
+        record_outcome(&recorded, 5, error_kind(output));
+        recorded.0.into_inner()
+    }
+
+    #[test]
+    fn test_success_records_processed() {
+        assert_eq!(recorded("Done"), ["latency 5", "processed 1"]);
+    }
+
+    #[test]
+    fn test_failures_record_errors() {
+        assert_eq!(
+            recorded("Error: HTTP request failed with status code 500"),
+            ["latency 5", "error http-status"]
+        );
+        assert_eq!(
+            recorded("Error: Failed to send HTTP request: timeout"),
+            ["latency 5", "error request"]
+        );
+        assert_eq!(
+            recorded(&shared::transient_error("Circuit breaker open")),
+            ["latency 5", "error transient"]
+        );
+        assert_eq!(
+            recorded("Failed to parse config: missing field `url`"),
+            ["latency 5", "error config"]
+        );
+    }
+}
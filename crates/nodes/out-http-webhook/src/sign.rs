@@ -0,0 +1,62 @@
+use hmac::{Hmac, Mac, digest::KeyInit};
+use sha2::{Sha256, Sha512};
+use shared::{SigningAlgorithm, WebhookSigning};
+
+/// Header value carrying the HMAC of `body`, e.g. `sha256=<hex digest>`
+pub fn signature(signing: &WebhookSigning, body: &[u8]) -> String {
+    let algorithm = signing.algorithm.unwrap_or_default();
+    let key = signing.secret.as_bytes();
+    let digest = match algorithm {
+        SigningAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(key, body),
+        SigningAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(key, body),
+    };
+
+    let name = match algorithm {
+        SigningAlgorithm::Sha256 => "sha256",
+        SigningAlgorithm::Sha512 => "sha512",
+    };
+    format!("{name}={}", hex::encode(digest))
+}
+
+fn hmac_digest<M: Mac + KeyInit>(key: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing(secret: &str, algorithm: Option<SigningAlgorithm>) -> WebhookSigning {
+        WebhookSigning {
+            header_name: "X-Hub-Signature-256".to_string(),
+            secret: secret.to_string(),
+            algorithm,
+        }
+    }
+
+    #[test]
+    fn test_signature_matches_known_hmac_sha256() {
+        // Example from GitHub's webhook signature documentation
+        assert_eq!(
+            signature(
+                &signing("It's a Secret to Everybody", None),
+                b"Hello, World!"
+            ),
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+        );
+    }
+
+    #[test]
+    fn test_signature_matches_known_hmac_sha512() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature(
+                &signing("Jefe", Some(SigningAlgorithm::Sha512)),
+                b"what do ya want for nothing?"
+            ),
+            "sha512=164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+}
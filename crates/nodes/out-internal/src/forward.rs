@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+/// What became of an output handed to out-internal
+#[derive(Debug, PartialEq, Eq)]
+pub enum Forwarded<E> {
+    /// Published to the subject of the next step
+    Published {
+        subject: String,
+    },
+    /// The node is the last of the pipeline, so there is nothing to publish to
+    NoNextStep,
+    Failed {
+        subject: String,
+        error: E,
+    },
+}
+
+/// Subject a node's output goes to, and the message to publish there. A result sent on a named
+/// output goes to that output's topic without its wrapping, anything else to the next step. A
+/// named output no node depends on has no topic.
+pub fn route<'a>(
+    next_step_topic: Option<&'a str>,
+    output_topics: &'a BTreeMap<String, String>,
+    input: String,
+) -> (Option<&'a str>, String) {
+    match shared::split_named_output(&input) {
+        Some((output, message)) => (output_topics.get(&output).map(String::as_str), message),
+        None => (next_step_topic, input),
+    }
+}
+
+/// Topics of the named outputs from their config, by output name. Unset config means no node
+/// depends on a named output.
+pub fn parse_output_topics(config: Option<&str>) -> Result<BTreeMap<String, String>, String> {
+    match config {
+        Some(config) => serde_json::from_str(config).map_err(|e| e.to_string()),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Publish `input` with its `metadata` to `next_step_topic`. An unset or blank topic means the
+/// node has no next step, and nothing is published.
+pub fn forward<E>(
+    next_step_topic: Option<&str>,
+    input: String,
+    metadata: &[(String, String)],
+    publish: impl FnOnce(&str, Vec<u8>, &[(String, String)]) -> Result<(), E>,
+) -> Forwarded<E> {
+    let Some(subject) = next_step_topic
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+    else {
+        return Forwarded::NoNextStep;
+    };
+
+    match publish(subject, input.into_bytes(), metadata) {
+        Ok(()) => Forwarded::Published {
+            subject: subject.to_string(),
+        },
+        Err(error) => Forwarded::Failed {
+            subject: subject.to_string(),
+            error,
+        },
+    }
+}
+
+/// What became of a request for the reply of the next step
+#[derive(Debug, PartialEq, Eq)]
+pub enum Requested {
+    /// The next step replied with `body`
+    Replied {
+        subject: String,
+        body: Vec<u8>,
+    },
+    /// The node is the last of the pipeline, so there is nothing to request
+    NoNextStep,
+    /// No reply came within the timeout
+    TimedOut {
+        subject: String,
+    },
+    Failed {
+        subject: String,
+        error: String,
+    },
+}
+
+impl Requested {
+    /// Output of the node for the request: the reply, or the error to report. A timeout is
+    /// reported as a transient error, so the message can be redelivered.
+    pub fn into_output(self) -> String {
+        match self {
+            Requested::Replied { body, .. } => String::from_utf8_lossy(&body).into_owned(),
+            Requested::NoNextStep => "OK".to_string(),
+            Requested::TimedOut { subject } => {
+                shared::transient_error(&format!("Timed out waiting for a reply from {subject}"))
+            }
+            Requested::Failed { subject, error } => {
+                format!("Failed to request a reply from {subject}: {error}")
+            }
+        }
+    }
+}
+
+/// Timeout of requests for the reply of the next step from its config, in milliseconds. Unset
+/// config means out-internal publishes without waiting for a reply.
+pub fn parse_request_timeout(config: Option<&str>) -> Result<Option<u32>, String> {
+    config
+        .map(|config| match config.trim().parse::<u32>() {
+            Ok(0) => Err("Request timeout must be greater than 0".to_string()),
+            Ok(timeout_ms) => Ok(timeout_ms),
+            Err(e) => Err(format!("Invalid request timeout {config:?}: {e}")),
+        })
+        .transpose()
+}
+
+/// Whether a failed request failed because no reply came in time
+pub fn is_timeout(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("timed out") || error.contains("timeout")
+}
+
+/// Send `input` to `next_step_topic` as a request and wait for its reply with `send`. The
+/// reply subject is generated by the messaging provider. An unset or blank topic means the node
+/// has no next step, and nothing is requested.
+pub fn request(
+    next_step_topic: Option<&str>,
+    input: String,
+    send: impl FnOnce(&str, Vec<u8>) -> Result<Vec<u8>, String>,
+) -> Requested {
+    let Some(subject) = next_step_topic
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+    else {
+        return Requested::NoNextStep;
+    };
+
+    let subject = subject.to_string();
+    match send(&subject, input.into_bytes()) {
+        Ok(body) => Requested::Replied { subject, body },
+        Err(error) if is_timeout(&error) => Requested::TimedOut { subject },
+        Err(error) => Requested::Failed { subject, error },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn must_not_publish(
+        subject: &str,
+        _body: Vec<u8>,
+        _metadata: &[(String, String)],
+    ) -> Result<(), String> {
+        panic!("Published to {subject:?} without a next step topic");
+    }
+
+    #[test]
+    fn test_no_publish_without_next_step_topic() {
+        assert_eq!(
+            forward(None, "output".to_string(), &[], must_not_publish),
+            Forwarded::NoNextStep
+        );
+        assert_eq!(
+            forward(Some(" "), "output".to_string(), &[], must_not_publish),
+            Forwarded::NoNextStep
+        );
+    }
+
+    #[test]
+    fn test_publishes_to_next_step_topic() {
+        let mut published = None;
+        let forwarded = forward(
+            Some("acme.orders.2"),
+            "output".to_string(),
+            &[],
+            |subject, body, _| {
+                published = Some((subject.to_string(), body));
+                Ok::<(), String>(())
+            },
+        );
+
+        assert_eq!(
+            forwarded,
+            Forwarded::Published {
+                subject: "acme.orders.2".to_string()
+            }
+        );
+        assert_eq!(
+            published,
+            Some(("acme.orders.2".to_string(), b"output".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_metadata_reaches_publish() {
+        let metadata = vec![
+            ("x-request-id".to_string(), "req-1".to_string()),
+            ("x-request-method".to_string(), "POST".to_string()),
+            ("x-tenant".to_string(), "acme".to_string()),
+        ];
+        let mut published = None;
+        forward(
+            Some("acme.orders.2"),
+            "raw,csv,body".to_string(),
+            &metadata,
+            |subject, body, metadata| {
+                published = Some((subject.to_string(), body, metadata.to_vec()));
+                Ok::<(), String>(())
+            },
+        );
+
+        // The body is published unchanged, with the metadata alongside it
+        assert_eq!(
+            published,
+            Some((
+                "acme.orders.2".to_string(),
+                b"raw,csv,body".to_vec(),
+                metadata
+            ))
+        );
+    }
+
+    #[test]
+    fn test_named_outputs_go_to_their_topics() {
+        let output_topics = parse_output_topics(Some(
+            r#"{"success":"acme.orders.step-3-in.out-success","error":"acme.orders.step-3-in.out-error"}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            route(
+                Some("acme.orders.step-3-in"),
+                &output_topics,
+                r#"{"$output":"error","data":{"code":7}}"#.to_string()
+            ),
+            (
+                Some("acme.orders.step-3-in.out-error"),
+                r#"{"code":7}"#.to_string()
+            )
+        );
+        assert_eq!(
+            route(
+                None,
+                &output_topics,
+                r#"{"$output":"success","data":"ok"}"#.to_string()
+            ),
+            (Some("acme.orders.step-3-in.out-success"), "ok".to_string())
+        );
+
+        // Nothing depends on this output
+        assert_eq!(
+            route(
+                Some("acme.orders.step-3-in"),
+                &output_topics,
+                r#"{"$output":"retry","data":"x"}"#.to_string()
+            ),
+            (None, "x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_output_goes_to_next_step() {
+        let output_topics = parse_output_topics(None).unwrap();
+        assert_eq!(
+            route(
+                Some("acme.orders.2"),
+                &output_topics,
+                r#"{"id":1}"#.to_string()
+            ),
+            (Some("acme.orders.2"), r#"{"id":1}"#.to_string())
+        );
+        assert!(parse_output_topics(Some("not json")).is_err());
+    }
+
+    #[test]
+    fn test_publish_failure_is_reported() {
+        let forwarded = forward(
+            Some("acme.orders.2"),
+            "output".to_string(),
+            &[],
+            |_, _, _| Err("no responders"),
+        );
+
+        assert_eq!(
+            forwarded,
+            Forwarded::Failed {
+                subject: "acme.orders.2".to_string(),
+                error: "no responders"
+            }
+        );
+    }
+
+    #[test]
+    fn test_request_timeout_config() {
+        assert_eq!(parse_request_timeout(None), Ok(None));
+        assert_eq!(parse_request_timeout(Some("5000")), Ok(Some(5000)));
+        assert!(parse_request_timeout(Some("0")).is_err());
+        assert!(parse_request_timeout(Some("soon")).is_err());
+    }
+
+    #[test]
+    fn test_reply_is_the_output() {
+        let mut requested = None;
+        let outcome = request(
+            Some("acme.orders.step-2-in"),
+            r#"{"id":1}"#.to_string(),
+            |subject, body| {
+                requested = Some((subject.to_string(), body));
+                Ok(br#"{"status":"paid"}"#.to_vec())
+            },
+        );
+
+        assert_eq!(
+            requested,
+            Some(("acme.orders.step-2-in".to_string(), br#"{"id":1}"#.to_vec()))
+        );
+        assert_eq!(outcome.into_output(), r#"{"status":"paid"}"#);
+    }
+
+    #[test]
+    fn test_request_timeout_is_transient() {
+        let outcome = request(Some("acme.orders.step-2-in"), "x".to_string(), |_, _| {
+            Err("request timed out".to_string())
+        });
+
+        assert_eq!(
+            outcome,
+            Requested::TimedOut {
+                subject: "acme.orders.step-2-in".to_string()
+            }
+        );
+        assert!(shared::is_transient_error(&outcome.into_output()));
+
+        let failed = request(Some("acme.orders.step-2-in"), "x".to_string(), |_, _| {
+            Err("no responders".to_string())
+        });
+        assert!(!shared::is_transient_error(&failed.into_output()));
+    }
+
+    #[test]
+    fn test_no_request_without_next_step_topic() {
+        let outcome = request(Some(" "), "x".to_string(), |subject, _| {
+            panic!("Requested {subject:?} without a next step topic")
+        });
+        assert_eq!(outcome.into_output(), "OK");
+    }
+}
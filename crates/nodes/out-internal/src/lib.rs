@@ -1,8 +1,11 @@
-use bindings::exports::pipestack::out::out::Guest;
+use bindings::exports::pipestack::out::{metadata, out::Guest};
 
 use bindings::wasmcloud::messaging::{consumer, types};
+use forward::{Forwarded, Requested};
 use wasmcloud_component::{error, trace};
 
+mod forward;
+
 mod bindings {
     use super::Component;
     wit_bindgen::generate!({ generate_all });
@@ -15,20 +18,101 @@ const LOG_CONTEXT: &str = "out-internal";
 
 impl Guest for Component {
     fn run(input: String) -> String {
-        let subject = bindings::wasi::config::runtime::get("next-step-topic")
-            .expect("Unable to fetch value")
-            .unwrap_or_else(|| "config value not set".to_string());
+        publish(input, &[])
+    }
+}
+
+impl metadata::Guest for Component {
+    fn run_with_metadata(input: String, metadata: Vec<(String, String)>) -> String {
+        publish(input, &metadata)
+    }
+}
+
+/// Publish a node's output to the subject it's routed to, returning "OK" or the error to report
+fn publish(input: String, metadata: &[(String, String)]) -> String {
+    let next_step_topic = match bindings::wasi::config::runtime::get("next-step-topic") {
+        Ok(topic) => topic,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Failed to get next-step-topic config: {e:?}");
+            return format!("Failed to get next-step-topic config: {e:?}");
+        }
+    };
+
+    let output_topics = match bindings::wasi::config::runtime::get(shared::OUTPUT_TOPICS_CONFIG_KEY)
+        .map_err(|e| format!("{e:?}"))
+        .and_then(|config| forward::parse_output_topics(config.as_deref()))
+    {
+        Ok(output_topics) => output_topics,
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "Failed to get output-topics config: {e}");
+            return format!("Failed to get output-topics config: {e}");
+        }
+    };
+
+    let request_timeout_ms =
+        match bindings::wasi::config::runtime::get(shared::REQUEST_TIMEOUT_CONFIG_KEY)
+            .map_err(|e| format!("{e:?}"))
+            .and_then(|config| forward::parse_request_timeout(config.as_deref()))
+        {
+            Ok(request_timeout_ms) => request_timeout_ms,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to get request-timeout-ms config: {e}");
+                return format!("Failed to get request-timeout-ms config: {e}");
+            }
+        };
 
-        if let Err(err) = consumer::publish(&types::BrokerMessage {
-            subject: subject.clone(),
+    let (topic, message) = forward::route(next_step_topic.as_deref(), &output_topics, input);
+    if let Some(timeout_ms) = request_timeout_ms {
+        return request(topic, message, timeout_ms);
+    }
+    let forwarded = forward::forward(topic, message, metadata, |subject, body, metadata| {
+        // Broker messages of wasmcloud:messaging 0.2.0 have no headers to carry the metadata
+        if !metadata.is_empty() {
+            trace!(context: LOG_CONTEXT,
+                "Publishing without {} metadata entries, the messaging link carries no headers",
+                metadata.len()
+            );
+        }
+        consumer::publish(&types::BrokerMessage {
+            subject: subject.to_string(),
             reply_to: None,
-            body: input.into_bytes(),
-        }) {
-            error!(context: LOG_CONTEXT, "Failed to publish message: {err:?}");
-        } else {
+            body,
+        })
+    });
+    match forwarded {
+        Forwarded::Published { subject } => {
             trace!(context: LOG_CONTEXT, "Successfully posted a message to subject: {subject:?}");
         }
+        Forwarded::NoNextStep => {
+            trace!(context: LOG_CONTEXT, "No topic configured for the output, nothing to publish");
+        }
+        Forwarded::Failed { subject, error } => {
+            error!(context: LOG_CONTEXT, "Failed to publish message to {subject:?}: {error:?}");
+        }
+    }
 
-        "OK".to_string()
+    "OK".to_string()
+}
+
+/// Request the reply of the next step for a node's output, returning the reply as the output. A
+/// webhook upstream waits for it to respond with the result of the pipeline.
+fn request(topic: Option<&str>, message: String, timeout_ms: u32) -> String {
+    let requested = forward::request(topic, message, |subject, body| {
+        consumer::request(subject, &body, timeout_ms).map(|reply| reply.body)
+    });
+    match &requested {
+        Requested::Replied { subject, body } => {
+            trace!(context: LOG_CONTEXT, "Received a reply of {} bytes from subject: {subject:?}", body.len());
+        }
+        Requested::NoNextStep => {
+            trace!(context: LOG_CONTEXT, "No topic configured for the output, nothing to request");
+        }
+        Requested::TimedOut { subject } => {
+            error!(context: LOG_CONTEXT, "No reply from {subject:?} within {timeout_ms}ms");
+        }
+        Requested::Failed { subject, error } => {
+            error!(context: LOG_CONTEXT, "Failed to request a reply from {subject:?}: {error}");
+        }
     }
+    requested.into_output()
 }
@@ -0,0 +1,125 @@
+use serde_json::{Map, Value};
+
+/// Logged in place of a message that isn't JSON, as no fields can be selected from it
+pub const NOT_JSON: &str = "<message is not JSON, no fields selected>";
+
+/// What's logged of the message: the selected fields when `fields` is set, otherwise the whole
+/// message
+pub fn logged_body(input: String, fields: Option<&[String]>) -> String {
+    match fields {
+        Some(pointers) => select_fields(&input, pointers),
+        None => input,
+    }
+}
+
+/// The fields of the message at the given JSON pointers, as a compact JSON object nesting each
+/// field under its path. Fields the message doesn't have are left out.
+pub fn select_fields(input: &str, pointers: &[String]) -> String {
+    let Ok(document) = serde_json::from_str::<Value>(input) else {
+        return NOT_JSON.to_string();
+    };
+
+    let mut selected = Map::new();
+    for pointer in pointers {
+        if let Some(value) = document.pointer(pointer) {
+            insert(&mut selected, &path(pointer), value.clone());
+        }
+    }
+    Value::Object(selected).to_string()
+}
+
+/// Unescaped reference tokens of a JSON pointer
+fn path(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+fn insert(object: &mut Map<String, Value>, path: &[String], value: Value) {
+    match path {
+        [] => {}
+        [key] => {
+            object.insert(key.clone(), value);
+        }
+        [key, rest @ ..] => {
+            let child = object
+                .entry(key.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(child) = child {
+                insert(child, rest, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointers(pointers: &[&str]) -> Vec<String> {
+        pointers.iter().map(|pointer| pointer.to_string()).collect()
+    }
+
+    #[test]
+    fn test_select_subset_of_fields() {
+        let input = r#"{
+            "id": 7,
+            "status": "paid",
+            "customer": {"id": "c-1", "email": "jane@example.com", "address": {"city": "Bern"}},
+            "items": [{"sku": "a"}, {"sku": "b"}]
+        }"#;
+
+        let selected = select_fields(
+            input,
+            &pointers(&[
+                "/id",
+                "/customer/id",
+                "/customer/address/city",
+                "/items/1/sku",
+            ]),
+        );
+        assert!(!selected.contains(char::is_whitespace));
+        assert_eq!(
+            serde_json::from_str::<Value>(&selected).unwrap(),
+            serde_json::json!({
+                "id": 7,
+                "customer": {"id": "c-1", "address": {"city": "Bern"}},
+                "items": {"1": {"sku": "b"}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_whole_body_is_logged_without_fields() {
+        let input = r#"{"id": 7, "status": "paid"}"#;
+        assert_eq!(logged_body(input.to_string(), None), input);
+        assert_eq!(
+            logged_body(input.to_string(), Some(&pointers(&["/status"]))),
+            r#"{"status":"paid"}"#
+        );
+    }
+
+    #[test]
+    fn test_missing_fields_are_left_out() {
+        assert_eq!(
+            select_fields(r#"{"id": 7}"#, &pointers(&["/id", "/missing/field"])),
+            r#"{"id":7}"#
+        );
+        assert_eq!(select_fields(r#"{"id": 7}"#, &pointers(&["/other"])), "{}");
+    }
+
+    #[test]
+    fn test_escaped_pointer_tokens() {
+        assert_eq!(
+            select_fields(r#"{"a/b": 1, "c~d": 2}"#, &pointers(&["/a~1b", "/c~0d"])),
+            r#"{"a/b":1,"c~d":2}"#
+        );
+    }
+
+    #[test]
+    fn test_message_that_is_not_json() {
+        assert_eq!(select_fields("plain text", &pointers(&["/id"])), NOT_JSON);
+    }
+}
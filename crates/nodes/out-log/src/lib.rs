@@ -1,11 +1,12 @@
 use bindings::exports::pipestack::out::out::Guest;
-use wasmcloud_component::info;
+use nodes_common::NodeError;
+use shared::OutLogSettings;
+use wasmcloud_component::{error, info};
 
-mod bindings {
-    use super::Component;
-    wit_bindgen::generate!({ generate_all });
-    export!(Component);
-}
+mod fields;
+mod truncate;
+
+nodes_common::bindings!(Component);
 
 struct Component;
 
@@ -13,7 +14,50 @@ const LOG_CONTEXT: &str = "out-internal";
 
 impl Guest for Component {
     fn run(input: String) -> String {
-        info!(context: LOG_CONTEXT, "{input}");
+        let started_ns = bindings::wasi::clocks::monotonic_clock::now();
+        let settings = settings();
+        let max_log_bytes = settings
+            .max_log_bytes
+            .unwrap_or(truncate::DEFAULT_MAX_LOG_BYTES);
+        let logged = fields::logged_body(input, settings.fields.as_deref());
+        info!(context: LOG_CONTEXT, "{}", truncate::log_line(&logged, max_log_bytes));
+        shared::record_outcome(&Metrics, elapsed_ms(started_ns), None);
         String::from("OK")
     }
 }
+
+/// Reports to the linked metrics provider. Metrics are best effort, so failures, e.g. when no
+/// provider is linked, are ignored.
+struct Metrics;
+
+impl shared::MetricsRecorder for Metrics {
+    fn record_processed(&self, count: u64) {
+        let _ = bindings::pipestack::metrics::metrics::record_processed(count);
+    }
+
+    fn record_latency(&self, ms: u64) {
+        let _ = bindings::pipestack::metrics::metrics::record_latency(ms);
+    }
+
+    fn record_error(&self, kind: &str) {
+        let _ = bindings::pipestack::metrics::metrics::record_error(kind);
+    }
+}
+
+/// Milliseconds elapsed since the monotonic clock read `started_ns`
+fn elapsed_ms(started_ns: u64) -> u64 {
+    bindings::wasi::clocks::monotonic_clock::now().saturating_sub(started_ns) / 1_000_000
+}
+
+/// The configured settings. Settings are optional, so missing or unreadable config falls back to
+/// the defaults.
+fn settings() -> OutLogSettings {
+    match nodes_common::load_settings(bindings::wasi::config::runtime::get) {
+        Ok(settings) => settings,
+        Err(NodeError::MissingConfig) => OutLogSettings::default(),
+        Err(e) => {
+            error!(context: LOG_CONTEXT, "{e}");
+            OutLogSettings::default()
+        }
+    }
+}
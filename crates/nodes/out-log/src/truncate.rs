@@ -0,0 +1,51 @@
+/// Cap on the logged message when no `maxLogBytes` is configured. Well under the line limits of
+/// common log backends such as Loki.
+pub const DEFAULT_MAX_LOG_BYTES: usize = 16 * 1024;
+
+/// The message as it should be logged, cut to at most `max_bytes` with a note of how much was
+/// dropped
+pub fn log_line(input: &str, max_bytes: usize) -> String {
+    if input.len() <= max_bytes {
+        return input.to_string();
+    }
+
+    let mut end = max_bytes;
+    while !input.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}...(truncated {} bytes)",
+        &input[..end],
+        input.len() - end
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_under_cap_input_is_logged_fully() {
+        assert_eq!(log_line("hello", 5), "hello");
+        assert_eq!(log_line("", 0), "");
+    }
+
+    #[test]
+    fn test_over_cap_input_is_truncated() {
+        let input = "a".repeat(DEFAULT_MAX_LOG_BYTES + 10);
+        let line = log_line(&input, DEFAULT_MAX_LOG_BYTES);
+
+        assert!(line.starts_with(&"a".repeat(DEFAULT_MAX_LOG_BYTES)));
+        assert!(line.ends_with("...(truncated 10 bytes)"));
+        assert_eq!(
+            line.len(),
+            DEFAULT_MAX_LOG_BYTES + "...(truncated 10 bytes)".len()
+        );
+    }
+
+    #[test]
+    fn test_truncation_keeps_whole_characters() {
+        // "é" is two bytes, so cutting at 3 bytes keeps one character
+        assert_eq!(log_line("ééé", 3), "é...(truncated 4 bytes)");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::loki::{Entry, request_limits};
+use shared::LokiBatchSettings;
+
+/// Entries held back across messages until a push request is full, the oldest entry has waited
+/// long enough, or the node is flushed
+#[derive(Default)]
+pub struct Buffer {
+    entries: Vec<Entry>,
+    bytes: u64,
+    oldest_ms: Option<u64>,
+}
+
+impl Buffer {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            bytes: 0,
+            oldest_ms: None,
+        }
+    }
+
+    pub fn add(&mut self, entries: Vec<Entry>, now_ms: u64) {
+        if entries.is_empty() {
+            return;
+        }
+        self.oldest_ms.get_or_insert(now_ms);
+        self.bytes += entries
+            .iter()
+            .map(|entry| entry.line.len() as u64)
+            .sum::<u64>();
+        self.entries.extend(entries);
+    }
+
+    /// Whether the buffered entries fill a push request, or the oldest has waited `maxWaitMs`
+    pub fn is_ready(&self, batch: &LokiBatchSettings, now_ms: u64) -> bool {
+        let Some(oldest_ms) = self.oldest_ms else {
+            return false;
+        };
+        let (max_entries, max_bytes) = request_limits(Some(batch));
+        self.entries.len() >= max_entries
+            || self.bytes >= max_bytes
+            || batch
+                .max_wait_ms
+                .is_some_and(|max_wait_ms| now_ms.saturating_sub(oldest_ms) >= max_wait_ms)
+    }
+
+    /// Take every buffered entry, including a partial batch
+    pub fn take(&mut self) -> Vec<Entry> {
+        self.bytes = 0;
+        self.oldest_ms = None;
+        std::mem::take(&mut self.entries)
+    }
+
+    /// Put back entries that failed to be pushed, ahead of the ones buffered since. They are
+    /// retried once the buffer is ready again.
+    pub fn restore(&mut self, entries: Vec<Entry>, now_ms: u64) {
+        let buffered = self.take();
+        self.add(entries, now_ms);
+        self.add(buffered, now_ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loki::push_bodies;
+    use std::collections::BTreeMap;
+
+    fn entry(line: &str) -> Entry {
+        Entry {
+            labels: BTreeMap::from([("app".to_string(), "pipestack".to_string())]),
+            timestamp_ns: 1_000_000,
+            line: line.to_string(),
+        }
+    }
+
+    fn batch(max_entries: u32, max_wait_ms: u64) -> LokiBatchSettings {
+        LokiBatchSettings {
+            max_entries: Some(max_entries),
+            max_bytes: None,
+            max_wait_ms: Some(max_wait_ms),
+        }
+    }
+
+    #[test]
+    fn test_ready_when_full_or_waited_long_enough() {
+        let batch = batch(3, 1_000);
+        let mut buffer = Buffer::new();
+        assert!(!buffer.is_ready(&batch, 0));
+
+        buffer.add(vec![entry("a"), entry("b")], 100);
+        assert!(!buffer.is_ready(&batch, 500));
+        assert!(buffer.is_ready(&batch, 1_100));
+
+        buffer.add(vec![entry("c")], 200);
+        assert!(buffer.is_ready(&batch, 200));
+    }
+
+    #[test]
+    fn test_partial_batch_is_flushed() {
+        let batch = batch(10, 60_000);
+        let mut buffer = Buffer::new();
+        buffer.add(vec![entry("a"), entry("b")], 0);
+        buffer.add(vec![entry("c")], 10);
+        assert!(!buffer.is_ready(&batch, 20));
+
+        // A flush takes the entries even though the batch isn't full
+        let flushed = buffer.take();
+        assert!(buffer.is_empty());
+        let bodies = push_bodies(&flushed, Some(&batch));
+        assert_eq!(bodies.len(), 1);
+        let body: serde_json::Value = serde_json::from_str(&bodies[0]).unwrap();
+        assert_eq!(body["streams"][0]["values"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_failed_entries_are_restored_first() {
+        let mut buffer = Buffer::new();
+        buffer.add(vec![entry("a")], 0);
+        let failed = buffer.take();
+        buffer.add(vec![entry("b")], 10);
+        buffer.restore(failed, 20);
+
+        let lines: Vec<String> = buffer.take().into_iter().map(|entry| entry.line).collect();
+        assert_eq!(lines, ["a", "b"]);
+    }
+}
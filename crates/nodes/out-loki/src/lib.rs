@@ -0,0 +1,183 @@
+use bindings::exports::pipestack::out::{flush, out::Guest};
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use buffer::Buffer;
+use loki::Entry;
+use nodes_common::UrlParts;
+use shared::{OutLokiSettings, apply_authentication};
+use std::sync::Mutex;
+use wasmcloud_component::{error, info};
+
+mod buffer;
+mod loki;
+
+nodes_common::bindings!(Component);
+
+struct Component;
+
+const LOG_CONTEXT: &str = "out-loki";
+
+/// Entries buffered across messages when `batch.maxWaitMs` is set
+static BUFFER: Mutex<Buffer> = Mutex::new(Buffer::new());
+
+impl Guest for Component {
+    fn run(input: String) -> String {
+        let settings = match settings() {
+            Ok(settings) => settings,
+            Err(e) => return e,
+        };
+        let now_ms = now_ms();
+        let entries = match loki::entries(&input, &settings, now_ms) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to read entries from message: {e}");
+                return format!("Error: {e}");
+            }
+        };
+
+        let Some(batch) = settings
+            .batch
+            .as_ref()
+            .filter(|batch| batch.max_wait_ms.is_some())
+        else {
+            return match push(&entries, &settings) {
+                Ok(()) => {
+                    info!(context: LOG_CONTEXT, "Pushed {} entries to Loki", entries.len());
+                    "Done".into()
+                }
+                Err(e) => {
+                    error!(context: LOG_CONTEXT, "Failed to push to Loki: {e}");
+                    e
+                }
+            };
+        };
+
+        let mut buffer = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+        buffer.add(entries, now_ms);
+        // The message's entries are buffered, so a failed push is retried with later messages or
+        // a flush instead of redelivering the message
+        if buffer.is_ready(batch, now_ms) {
+            let _ = push_buffered(&mut buffer, &settings, now_ms);
+        }
+        "Done".into()
+    }
+}
+
+impl flush::Guest for Component {
+    fn flush() -> String {
+        let settings = match settings() {
+            Ok(settings) => settings,
+            Err(e) => return e,
+        };
+        let mut buffer = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+        if buffer.is_empty() {
+            return "Done".into();
+        }
+        match push_buffered(&mut buffer, &settings, now_ms()) {
+            Ok(()) => "Done".into(),
+            Err(e) => e,
+        }
+    }
+}
+
+fn settings() -> Result<OutLokiSettings, String> {
+    nodes_common::load_settings(bindings::wasi::config::runtime::get).map_err(|e| {
+        error!(context: LOG_CONTEXT, "{e}");
+        e.to_string()
+    })
+}
+
+fn now_ms() -> u64 {
+    let now = bindings::wasi::clocks::wall_clock::now();
+    now.seconds * 1_000 + u64::from(now.nanoseconds / 1_000_000)
+}
+
+/// Push every buffered entry, putting them back in the buffer when the push fails
+fn push_buffered(
+    buffer: &mut Buffer,
+    settings: &OutLokiSettings,
+    now_ms: u64,
+) -> Result<(), String> {
+    let entries = buffer.take();
+    match push(&entries, settings) {
+        Ok(()) => {
+            info!(context: LOG_CONTEXT, "Pushed {} buffered entries to Loki", entries.len());
+            Ok(())
+        }
+        Err(e) => {
+            error!(context: LOG_CONTEXT,
+                "Failed to push {} buffered entries to Loki, keeping them buffered: {e}",
+                entries.len()
+            );
+            buffer.restore(entries, now_ms);
+            Err(e)
+        }
+    }
+}
+
+/// Push the entries, returning the error to report when a request fails
+fn push(entries: &[Entry], settings: &OutLokiSettings) -> Result<(), String> {
+    for body in loki::push_bodies(entries, settings.batch.as_ref()) {
+        let status = send(&body, settings).map_err(|e| shared::transient_error(&e))?;
+        match status {
+            200..300 => {}
+            // Loki is rate limiting or unavailable, the entries can be pushed again later
+            429 | 500.. => {
+                return Err(shared::transient_error(&format!(
+                    "Loki responded with status code {status}"
+                )));
+            }
+            _ => return Err(format!("Error: Loki responded with status code {status}")),
+        }
+    }
+    Ok(())
+}
+
+/// Send a push request, returning the response status code. Errors are failures to get a
+/// response.
+fn send(body: &str, settings: &OutLokiSettings) -> Result<u16, String> {
+    let url = loki::push_url(&settings.url);
+    let UrlParts {
+        https,
+        authority,
+        path_with_query: path,
+    } = nodes_common::split_url(&url);
+    let scheme = if https { Scheme::Https } else { Scheme::Http };
+
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    let path_with_query = match &settings.auth {
+        Some(auth) => apply_authentication(&mut headers, &path, auth)?,
+        None => path,
+    };
+
+    let fields = Fields::new();
+    for (name, value) in &headers {
+        fields
+            .set(name, &[value.as_bytes().to_vec()])
+            .map_err(|e| format!("Failed to set header {name}: {e}"))?;
+    }
+
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&Method::Post).unwrap();
+    req.set_scheme(Some(&scheme)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(&path_with_query)).unwrap();
+
+    let request_body = req.body().unwrap();
+    let output_stream = request_body.write().unwrap();
+    output_stream
+        .blocking_write_and_flush(body.as_bytes())
+        .map_err(|e| format!("Failed to write request body: {e}"))?;
+    drop(output_stream);
+    bindings::wasi::http::types::OutgoingBody::finish(request_body, None)
+        .map_err(|e| format!("Failed to finish request body: {e}"))?;
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .expect("HTTP request response missing")
+        .expect("HTTP request response requested more than once")
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+    Ok(response.status())
+}
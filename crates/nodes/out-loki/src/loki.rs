@@ -0,0 +1,305 @@
+use serde_json::{Value, json};
+use shared::{LokiBatchSettings, OutLokiSettings, Template, TemplateError};
+use std::collections::BTreeMap;
+
+/// Path of Loki's push API, relative to the configured base URL
+pub const PUSH_PATH: &str = "/loki/api/v1/push";
+
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+const DEFAULT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// A log line and the labels of the stream it's added to
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub labels: BTreeMap<String, String>,
+    pub timestamp_ns: u128,
+    pub line: String,
+}
+
+/// URL entries are pushed to
+pub fn push_url(base_url: &str) -> String {
+    format!("{}{PUSH_PATH}", base_url.trim_end_matches('/'))
+}
+
+/// Entries of a message: one per element of a JSON array, otherwise the message itself
+pub fn entries(input: &str, settings: &OutLokiSettings, now_ms: u64) -> Result<Vec<Entry>, String> {
+    match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                let line = match item {
+                    Value::String(line) => line.clone(),
+                    other => other.to_string(),
+                };
+                entry(line, Some(item), settings, now_ms)
+            })
+            .collect(),
+        Ok(value) => Ok(vec![entry(
+            input.to_string(),
+            Some(&value),
+            settings,
+            now_ms,
+        )?]),
+        Err(_) => Ok(vec![entry(input.to_string(), None, settings, now_ms)?]),
+    }
+}
+
+fn entry(
+    line: String,
+    value: Option<&Value>,
+    settings: &OutLokiSettings,
+    now_ms: u64,
+) -> Result<Entry, String> {
+    // Labels read from a field the message doesn't have are left out, like Loki does with
+    // empty label values
+    let mut labels = BTreeMap::new();
+    for (name, value_template) in &settings.labels {
+        let template = Template::parse(value_template).map_err(|e| e.to_string())?;
+        match template.render(value.unwrap_or(&Value::Null)) {
+            Ok(label_value) => {
+                labels.insert(name.clone(), label_value);
+            }
+            Err(TemplateError::MissingValue(_)) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    if labels.is_empty() {
+        return Err("Entry has no labels, none of the label fields are in the message".into());
+    }
+
+    let timestamp_ms = match &settings.timestamp_field {
+        Some(field) => value
+            .and_then(|v| v.pointer(field))
+            .and_then(as_millis)
+            .ok_or_else(|| {
+                format!("Timestamp field {field} is missing or not a number of milliseconds")
+            })?,
+        None => now_ms,
+    };
+
+    Ok(Entry {
+        labels,
+        timestamp_ns: u128::from(timestamp_ms) * 1_000_000,
+        line,
+    })
+}
+
+fn as_millis(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(number) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Entries and log line bytes a single push request holds at most
+pub fn request_limits(batch: Option<&LokiBatchSettings>) -> (usize, u64) {
+    let max_entries = batch
+        .and_then(|batch| batch.max_entries)
+        .map_or(DEFAULT_MAX_ENTRIES, |max| max as usize);
+    let max_bytes = batch
+        .and_then(|batch| batch.max_bytes)
+        .unwrap_or(DEFAULT_MAX_BYTES);
+    (max_entries, max_bytes)
+}
+
+/// Bodies of the push requests sending `entries`, with the entries of each request grouped into
+/// a stream per label set
+pub fn push_bodies(entries: &[Entry], batch: Option<&LokiBatchSettings>) -> Vec<String> {
+    let (max_entries, max_bytes) = request_limits(batch);
+
+    let mut bodies = Vec::new();
+    let mut start = 0;
+    while start < entries.len() {
+        // Every request holds at least one entry, even one above the size limit
+        let mut end = start + 1;
+        let mut bytes = entries[start].line.len() as u64;
+        while end < entries.len() && end - start < max_entries {
+            bytes += entries[end].line.len() as u64;
+            if bytes > max_bytes {
+                break;
+            }
+            end += 1;
+        }
+        bodies.push(push_body(&entries[start..end]));
+        start = end;
+    }
+    bodies
+}
+
+fn push_body(entries: &[Entry]) -> String {
+    let mut streams: BTreeMap<&BTreeMap<String, String>, Vec<[String; 2]>> = BTreeMap::new();
+    for entry in entries {
+        streams
+            .entry(&entry.labels)
+            .or_default()
+            .push([entry.timestamp_ns.to_string(), entry.line.clone()]);
+    }
+
+    let streams: Vec<Value> = streams
+        .into_iter()
+        .map(|(labels, values)| json!({ "stream": labels, "values": values }))
+        .collect();
+    json!({ "streams": streams }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(labels: &[(&str, &str)]) -> OutLokiSettings {
+        OutLokiSettings {
+            url: "https://logs.example.com".to_string(),
+            labels: labels
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            auth: None,
+            timestamp_field: None,
+            batch: None,
+        }
+    }
+
+    fn labels(labels: &[(&str, &str)]) -> BTreeMap<String, String> {
+        labels
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_push_url() {
+        assert_eq!(
+            push_url("https://logs.example.com/"),
+            "https://logs.example.com/loki/api/v1/push"
+        );
+    }
+
+    #[test]
+    fn test_entries_of_json_array_and_plain_text() {
+        let settings = settings(&[
+            ("app", "pipestack"),
+            ("level", "{{/level}}"),
+            (
+                "job",
+                "{{/job | default: \"worker\"}}-{{/shard | default: 0}}",
+            ),
+        ]);
+
+        let entries = entries(
+            r#"[{"level":"info","msg":"a"},{"msg":"b"},"plain"]"#,
+            &settings,
+            1_700_000_000_000,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    labels: labels(&[("app", "pipestack"), ("job", "worker-0"), ("level", "info")]),
+                    timestamp_ns: 1_700_000_000_000_000_000,
+                    line: r#"{"level":"info","msg":"a"}"#.to_string(),
+                },
+                Entry {
+                    labels: labels(&[("app", "pipestack"), ("job", "worker-0")]),
+                    timestamp_ns: 1_700_000_000_000_000_000,
+                    line: r#"{"msg":"b"}"#.to_string(),
+                },
+                Entry {
+                    labels: labels(&[("app", "pipestack"), ("job", "worker-0")]),
+                    timestamp_ns: 1_700_000_000_000_000_000,
+                    line: "plain".to_string(),
+                },
+            ]
+        );
+
+        let entries = super::entries("not json", &settings, 5).unwrap();
+        assert_eq!(entries[0].line, "not json");
+        assert_eq!(
+            entries[0].labels,
+            labels(&[("app", "pipestack"), ("job", "worker-0")])
+        );
+    }
+
+    #[test]
+    fn test_timestamp_field() {
+        let mut settings = settings(&[("app", "pipestack")]);
+        settings.timestamp_field = Some("/ts".to_string());
+
+        let entries = entries(r#"{"ts": 1500, "msg": "a"}"#, &settings, 0).unwrap();
+        assert_eq!(entries[0].timestamp_ns, 1_500_000_000);
+
+        let err = super::entries(r#"{"msg": "a"}"#, &settings, 0).unwrap_err();
+        assert_eq!(
+            err,
+            "Timestamp field /ts is missing or not a number of milliseconds"
+        );
+    }
+
+    #[test]
+    fn test_entry_without_labels_is_rejected() {
+        let settings = settings(&[("level", "{{/level}}")]);
+        assert!(entries(r#"{"msg": "a"}"#, &settings, 0).is_err());
+    }
+
+    #[test]
+    fn test_push_body_groups_streams_by_label_set() {
+        let settings = settings(&[("level", "{{/level}}")]);
+        let entries = entries(
+            r#"[{"level":"info","n":1},{"level":"error","n":2},{"level":"info","n":3}]"#,
+            &settings,
+            2,
+        )
+        .unwrap();
+
+        let bodies = push_bodies(&entries, None);
+        assert_eq!(bodies.len(), 1);
+        let body: Value = serde_json::from_str(&bodies[0]).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "streams": [
+                    {
+                        "stream": { "level": "error" },
+                        "values": [["2000000", r#"{"level":"error","n":2}"#]]
+                    },
+                    {
+                        "stream": { "level": "info" },
+                        "values": [
+                            ["2000000", r#"{"level":"info","n":1}"#],
+                            ["2000000", r#"{"level":"info","n":3}"#]
+                        ]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_push_bodies_split_by_batch_limits() {
+        let settings = settings(&[("app", "pipestack")]);
+        let entries = entries(r#"["aaaa","bbbb","cccc","dddd","eeee"]"#, &settings, 0).unwrap();
+
+        let by_entries = LokiBatchSettings {
+            max_entries: Some(2),
+            max_bytes: None,
+            max_wait_ms: None,
+        };
+        assert_eq!(push_bodies(&entries, Some(&by_entries)).len(), 3);
+
+        let by_bytes = LokiBatchSettings {
+            max_entries: None,
+            max_bytes: Some(9),
+            max_wait_ms: None,
+        };
+        assert_eq!(push_bodies(&entries, Some(&by_bytes)).len(), 3);
+
+        // An entry above the size limit is still sent, on its own
+        let tiny = LokiBatchSettings {
+            max_entries: None,
+            max_bytes: Some(1),
+            max_wait_ms: None,
+        };
+        assert_eq!(push_bodies(&entries, Some(&tiny)).len(), 5);
+    }
+}
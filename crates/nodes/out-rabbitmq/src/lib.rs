@@ -0,0 +1,162 @@
+use bindings::exports::pipestack::out::out::Guest;
+use bindings::wasi::http::types::{Fields, Method, Scheme};
+use nodes_common::UrlParts;
+use shared::{OutRabbitmqSettings, apply_authentication};
+use std::sync::atomic::{AtomicBool, Ordering};
+use wasmcloud_component::{error, info};
+
+mod rabbitmq;
+
+nodes_common::bindings!(Component);
+
+struct Component;
+
+const LOG_CONTEXT: &str = "out-rabbitmq";
+
+/// Whether this component instance declared the exchange, so it's not declared for every message.
+/// wasmCloud can run a message in a fresh instance, which declares the exchange again; that's
+/// harmless as declaring is idempotent.
+static DECLARED: AtomicBool = AtomicBool::new(false);
+
+impl Guest for Component {
+    fn run(input: String) -> String {
+        let settings: OutRabbitmqSettings =
+            match nodes_common::load_settings(bindings::wasi::config::runtime::get) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    error!(context: LOG_CONTEXT, "{e}");
+                    return e.to_string();
+                }
+            };
+
+        match publish(&input, &settings) {
+            Ok(routing_key) => {
+                info!(context: LOG_CONTEXT,
+                    "Published message to exchange {:?} with routing key {routing_key:?}",
+                    settings.exchange
+                );
+                "Done".into()
+            }
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to publish to RabbitMQ: {e}");
+                e
+            }
+        }
+    }
+}
+
+/// Publish the message, returning its routing key or the error to report
+fn publish(input: &str, settings: &OutRabbitmqSettings) -> Result<String, String> {
+    if let Some(declare) = &settings.declare
+        && !DECLARED.load(Ordering::Relaxed)
+    {
+        let (status, _) = send(
+            Method::Put,
+            &rabbitmq::exchange_url(settings),
+            &rabbitmq::declare_body(declare),
+            settings,
+        )
+        .map_err(|e| shared::transient_error(&e))?;
+        check_status("declaring the exchange", status)?;
+        DECLARED.store(true, Ordering::Relaxed);
+    }
+
+    let routing_key = rabbitmq::routing_key(input, settings).map_err(|e| format!("Error: {e}"))?;
+    let (status, body) = send(
+        Method::Post,
+        &rabbitmq::publish_url(settings),
+        &rabbitmq::publish_body(input, &routing_key, settings),
+        settings,
+    )
+    .map_err(|e| shared::transient_error(&e))?;
+    check_status("publishing", status)?;
+
+    if !rabbitmq::was_routed(&body) {
+        return Err(format!(
+            "Error: No queue is bound to receive routing key {routing_key:?}"
+        ));
+    }
+    Ok(routing_key)
+}
+
+fn check_status(action: &str, status: u16) -> Result<(), String> {
+    match status {
+        200..300 => Ok(()),
+        // RabbitMQ is rate limiting or unavailable, the message can be published again later
+        429 | 500.. => Err(shared::transient_error(&format!(
+            "RabbitMQ responded to {action} with status code {status}"
+        ))),
+        _ => Err(format!(
+            "Error: RabbitMQ responded to {action} with status code {status}"
+        )),
+    }
+}
+
+/// Send a request to the management API, returning the response status code and body. Errors
+/// are failures to get a response.
+fn send(
+    method: Method,
+    url: &str,
+    body: &str,
+    settings: &OutRabbitmqSettings,
+) -> Result<(u16, String), String> {
+    let UrlParts {
+        https,
+        authority,
+        path_with_query: path,
+    } = nodes_common::split_url(url);
+    let scheme = if https { Scheme::Https } else { Scheme::Http };
+
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    let path_with_query = match &settings.auth {
+        Some(auth) => apply_authentication(&mut headers, &path, auth)?,
+        None => path,
+    };
+
+    let fields = Fields::new();
+    for (name, value) in &headers {
+        fields
+            .set(name, &[value.as_bytes().to_vec()])
+            .map_err(|e| format!("Failed to set header {name}: {e}"))?;
+    }
+
+    let req = bindings::wasi::http::outgoing_handler::OutgoingRequest::new(fields);
+    req.set_method(&method).unwrap();
+    req.set_scheme(Some(&scheme)).unwrap();
+    req.set_authority(Some(authority)).unwrap();
+    req.set_path_with_query(Some(&path_with_query)).unwrap();
+
+    let request_body = req.body().unwrap();
+    let output_stream = request_body.write().unwrap();
+    output_stream
+        .blocking_write_and_flush(body.as_bytes())
+        .map_err(|e| format!("Failed to write request body: {e}"))?;
+    drop(output_stream);
+    bindings::wasi::http::types::OutgoingBody::finish(request_body, None)
+        .map_err(|e| format!("Failed to finish request body: {e}"))?;
+
+    let resp = bindings::wasi::http::outgoing_handler::handle(req, None)
+        .map_err(|e| format!("Failed to send HTTP request: {e}"))?;
+    resp.subscribe().block();
+    let response = resp
+        .get()
+        .expect("HTTP request response missing")
+        .expect("HTTP request response requested more than once")
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+
+    let status = response.status();
+    let response_body = response
+        .consume()
+        .map_err(|_| "Failed to get response body")?;
+    let input_stream = response_body
+        .stream()
+        .map_err(|_| "Failed to get response body stream")?;
+    let mut body = Vec::new();
+    while let Ok(chunk) = input_stream.blocking_read(64 * 1024) {
+        if chunk.is_empty() {
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((status, String::from_utf8_lossy(&body).to_string()))
+}
@@ -0,0 +1,182 @@
+use serde_json::{Value, json};
+use shared::{OutRabbitmqSettings, RabbitmqDeclareSettings, RabbitmqDeliveryMode, Template};
+
+const DEFAULT_VHOST: &str = "/";
+const DEFAULT_EXCHANGE_TYPE: &str = "direct";
+
+/// Name the management API gives the default exchange, whose actual name is empty
+const DEFAULT_EXCHANGE: &str = "amq.default";
+
+/// Routing key of a message: the configured key, with its `{{/pointer}}` placeholders
+/// rendered from the message
+pub fn routing_key(input: &str, settings: &OutRabbitmqSettings) -> Result<String, String> {
+    let template = Template::parse(&settings.routing_key).map_err(|e| e.to_string())?;
+    if template.is_literal() {
+        return Ok(settings.routing_key.clone());
+    }
+
+    let value: Value = serde_json::from_str(input)
+        .map_err(|_| "Message is not JSON, so its routing key can't be rendered".to_string())?;
+    template.render(&value).map_err(|e| e.to_string())
+}
+
+/// URL of the configured exchange in the management API
+pub fn exchange_url(settings: &OutRabbitmqSettings) -> String {
+    let exchange = match settings.exchange.as_str() {
+        "" => DEFAULT_EXCHANGE,
+        exchange => exchange,
+    };
+    format!(
+        "{}/api/exchanges/{}/{}",
+        settings.url.trim_end_matches('/'),
+        nodes_common::encode_path_segment(settings.vhost.as_deref().unwrap_or(DEFAULT_VHOST)),
+        nodes_common::encode_path_segment(exchange)
+    )
+}
+
+/// URL messages are published to
+pub fn publish_url(settings: &OutRabbitmqSettings) -> String {
+    format!("{}/publish", exchange_url(settings))
+}
+
+/// Body of a publish request for the message
+pub fn publish_body(input: &str, routing_key: &str, settings: &OutRabbitmqSettings) -> String {
+    let delivery_mode = match settings.delivery_mode.unwrap_or_default() {
+        RabbitmqDeliveryMode::Transient => 1,
+        RabbitmqDeliveryMode::Persistent => 2,
+    };
+    json!({
+        "properties": { "delivery_mode": delivery_mode },
+        "routing_key": routing_key,
+        "payload": input,
+        "payload_encoding": "string",
+    })
+    .to_string()
+}
+
+/// Body of a request declaring the exchange
+pub fn declare_body(declare: &RabbitmqDeclareSettings) -> String {
+    json!({
+        "type": declare.exchange_type.as_deref().unwrap_or(DEFAULT_EXCHANGE_TYPE),
+        "durable": declare.durable.unwrap_or(true),
+        "auto_delete": declare.auto_delete.unwrap_or(false),
+        "internal": false,
+        "arguments": {},
+    })
+    .to_string()
+}
+
+/// Whether the response to a publish request reports the message reached a queue. RabbitMQ drops
+/// a message no queue is bound to receive.
+pub fn was_routed(response_body: &str) -> bool {
+    serde_json::from_str::<Value>(response_body)
+        .ok()
+        .and_then(|response| response.get("routed").and_then(Value::as_bool))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(routing_key: &str) -> OutRabbitmqSettings {
+        OutRabbitmqSettings {
+            url: "https://rabbitmq.example.com:15672/".to_string(),
+            vhost: None,
+            exchange: "orders".to_string(),
+            routing_key: routing_key.to_string(),
+            delivery_mode: None,
+            auth: None,
+            declare: None,
+        }
+    }
+
+    #[test]
+    fn test_static_routing_key() {
+        let settings = settings("orders.created");
+        assert_eq!(
+            routing_key(r#"{"region":"eu"}"#, &settings),
+            Ok("orders.created".to_string())
+        );
+        // A static key doesn't need the message to be JSON
+        assert_eq!(
+            routing_key("plain text", &settings),
+            Ok("orders.created".to_string())
+        );
+    }
+
+    #[test]
+    fn test_routing_key_from_field() {
+        let settings = settings("{{/order/region}}");
+        assert_eq!(
+            routing_key(r#"{"order":{"region":"eu-west"}}"#, &settings),
+            Ok("eu-west".to_string())
+        );
+        assert_eq!(
+            routing_key(r#"{"order":{"region":42}}"#, &settings),
+            Ok("42".to_string())
+        );
+        assert_eq!(
+            routing_key(r#"{"order":{}}"#, &settings),
+            Err(r#"Template value "/order/region" is missing from the message"#.to_string())
+        );
+        assert!(routing_key("plain text", &settings).is_err());
+    }
+
+    #[test]
+    fn test_routing_key_template() {
+        let settings = settings(r#"orders.{{/order/region | default: "global"}}"#);
+        assert_eq!(
+            routing_key(r#"{"order":{"region":"eu-west"}}"#, &settings),
+            Ok("orders.eu-west".to_string())
+        );
+        assert_eq!(
+            routing_key(r#"{"order":{}}"#, &settings),
+            Ok("orders.global".to_string())
+        );
+    }
+
+    #[test]
+    fn test_urls() {
+        let mut settings = settings("orders");
+        assert_eq!(
+            publish_url(&settings),
+            "https://rabbitmq.example.com:15672/api/exchanges/%2F/orders/publish"
+        );
+
+        settings.vhost = Some("shop".to_string());
+        settings.exchange = String::new();
+        assert_eq!(
+            exchange_url(&settings),
+            "https://rabbitmq.example.com:15672/api/exchanges/shop/amq.default"
+        );
+    }
+
+    #[test]
+    fn test_publish_body() {
+        let mut settings = settings("orders");
+        let body: Value =
+            serde_json::from_str(&publish_body(r#"{"id":1}"#, "orders", &settings)).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "properties": { "delivery_mode": 2 },
+                "routing_key": "orders",
+                "payload": "{\"id\":1}",
+                "payload_encoding": "string",
+            })
+        );
+
+        settings.delivery_mode = Some(RabbitmqDeliveryMode::Transient);
+        let body: Value =
+            serde_json::from_str(&publish_body("hello", "orders", &settings)).unwrap();
+        assert_eq!(body["properties"]["delivery_mode"], 1);
+    }
+
+    #[test]
+    fn test_was_routed() {
+        assert!(was_routed(r#"{"routed":true}"#));
+        assert!(!was_routed(r#"{"routed":false}"#));
+        assert!(!was_routed("Not Found"));
+    }
+}
@@ -0,0 +1,64 @@
+use bindings::exports::pipestack::customer::customer::{Guest, RunError};
+use bindings::wrpc::rpc;
+use shared::{FromConfig, ProcessorFilterSettings, text_input};
+use wasmcloud_component::{error, info};
+
+mod predicate;
+
+mod bindings {
+    use super::Component;
+    wit_bindgen::generate!({ generate_all });
+    export!(Component);
+}
+
+struct Component;
+
+const LOG_CONTEXT: &str = "processor-filter";
+
+impl Guest for Component {
+    fn run(input: String) -> Result<Result<String, RunError>, rpc::error::Error> {
+        let config = match bindings::wasi::config::runtime::get("json") {
+            Ok(config) => config,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to get config: {e:?}");
+                return Ok(Err(RunError::AppError(format!(
+                    "Failed to get config: {e:?}"
+                ))));
+            }
+        };
+
+        let settings = match ProcessorFilterSettings::from_config(config) {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to parse config: {e}");
+                return Ok(Err(RunError::AppError(format!(
+                    "Failed to parse config: {e}"
+                ))));
+            }
+        };
+
+        match predicate::filter(&input, &settings) {
+            Ok(output) => {
+                if output.is_empty() {
+                    info!(context: LOG_CONTEXT, "Message dropped by predicate {}", settings.predicate);
+                }
+                Ok(Ok(output))
+            }
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to filter input: {e}");
+                Ok(Err(RunError::AppError(e)))
+            }
+        }
+    }
+
+    fn run_bytes(input: Vec<u8>) -> Result<Result<Vec<u8>, RunError>, rpc::error::Error> {
+        let input = match text_input(input) {
+            Ok(input) => input,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "{e}");
+                return Ok(Err(RunError::AppError(e)));
+            }
+        };
+        Self::run(input).map(|output| output.map(String::into_bytes))
+    }
+}
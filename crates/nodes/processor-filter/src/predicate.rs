@@ -0,0 +1,207 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+use shared::ProcessorFilterSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// A predicate of the form `<json-pointer> [<operator> <json-value>]`
+#[derive(Debug, PartialEq)]
+pub struct Predicate {
+    pointer: String,
+    comparison: Option<(Operator, Value)>,
+}
+
+impl Predicate {
+    pub fn parse(predicate: &str) -> Result<Self, String> {
+        let predicate = predicate.trim();
+        let (pointer, rest) = match predicate.split_once(char::is_whitespace) {
+            Some((pointer, rest)) => (pointer, rest.trim_start()),
+            None => (predicate, ""),
+        };
+
+        if !pointer.starts_with('/') {
+            return Err(format!(
+                "Predicate must start with a JSON pointer such as `/field`, got {predicate:?}"
+            ));
+        }
+
+        if rest.is_empty() {
+            return Ok(Self {
+                pointer: pointer.to_string(),
+                comparison: None,
+            });
+        }
+
+        let (operator, value) = rest
+            .split_once(char::is_whitespace)
+            .map(|(operator, value)| (operator, value.trim()))
+            .unwrap_or((rest, ""));
+        let operator = match operator {
+            "==" => Operator::Eq,
+            "!=" => Operator::Ne,
+            ">" => Operator::Gt,
+            ">=" => Operator::Ge,
+            "<" => Operator::Lt,
+            "<=" => Operator::Le,
+            operator => return Err(format!("Unknown predicate operator {operator:?}")),
+        };
+        if value.is_empty() {
+            return Err(format!("Missing value to compare {pointer} with"));
+        }
+        let value: Value = serde_json::from_str(value)
+            .map_err(|e| format!("Predicate value {value:?} is not valid JSON: {e}"))?;
+
+        Ok(Self {
+            pointer: pointer.to_string(),
+            comparison: Some((operator, value)),
+        })
+    }
+
+    /// Whether the given document matches the predicate
+    pub fn matches(&self, document: &Value) -> bool {
+        let actual = document.pointer(&self.pointer);
+
+        let Some((operator, expected)) = &self.comparison else {
+            return !matches!(actual, None | Some(Value::Null) | Some(Value::Bool(false)));
+        };
+
+        match operator {
+            Operator::Eq => actual == Some(expected),
+            Operator::Ne => actual != Some(expected),
+            _ => {
+                let Some(ordering) = actual.and_then(|actual| compare(actual, expected)) else {
+                    return false;
+                };
+                match operator {
+                    Operator::Gt => ordering == Ordering::Greater,
+                    Operator::Ge => ordering != Ordering::Less,
+                    Operator::Lt => ordering == Ordering::Less,
+                    Operator::Le => ordering != Ordering::Greater,
+                    Operator::Eq | Operator::Ne => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+/// Pass the input through when it matches the configured predicate. A dropped message yields an
+/// empty output, which in-internal treats as nothing to forward.
+pub fn filter(input: &str, settings: &ProcessorFilterSettings) -> Result<String, String> {
+    let predicate = Predicate::parse(&settings.predicate)
+        .map_err(|e| format!("Invalid predicate configuration: {e}"))?;
+    let document: Value =
+        serde_json::from_str(input).map_err(|e| format!("Input is not valid JSON: {e}"))?;
+
+    if predicate.matches(&document) {
+        Ok(input.to_string())
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// Order numbers and strings; values of any other or of differing types can't be ordered
+fn compare(actual: &Value, expected: &Value) -> Option<Ordering> {
+    match (actual, expected) {
+        (Value::Number(actual), Value::Number(expected)) => {
+            actual.as_f64()?.partial_cmp(&expected.as_f64()?)
+        }
+        (Value::String(actual), Value::String(expected)) => Some(actual.cmp(expected)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn matches(predicate: &str, document: Value) -> bool {
+        Predicate::parse(predicate).unwrap().matches(&document)
+    }
+
+    #[test]
+    fn test_equality() {
+        assert!(matches(
+            r#"/status == "active""#,
+            json!({"status": "active"})
+        ));
+        assert!(!matches(
+            r#"/status == "active""#,
+            json!({"status": "closed"})
+        ));
+        assert!(!matches(r#"/status == "active""#, json!({})));
+        assert!(matches(r#"/status != "active""#, json!({})));
+        assert!(matches(
+            "/user/tags/0 == \"vip\"",
+            json!({"user": {"tags": ["vip"]}})
+        ));
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(matches("/amount >= 100", json!({"amount": 100})));
+        assert!(matches("/amount > 99.5", json!({"amount": 100})));
+        assert!(!matches("/amount < 100", json!({"amount": 100})));
+        assert!(matches("/name <= \"m\"", json!({"name": "alice"})));
+        // Values of different types are never ordered
+        assert!(!matches("/amount > 1", json!({"amount": "100"})));
+        assert!(!matches("/amount > 1", json!({})));
+    }
+
+    #[test]
+    fn test_bare_pointer() {
+        assert!(matches("/flag", json!({"flag": true})));
+        assert!(matches("/flag", json!({"flag": 0})));
+        assert!(!matches("/flag", json!({"flag": false})));
+        assert!(!matches("/flag", json!({"flag": null})));
+        assert!(!matches("/flag", json!({})));
+    }
+
+    fn settings(predicate: &str) -> ProcessorFilterSettings {
+        ProcessorFilterSettings {
+            predicate: predicate.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_filter_passes_matching_message() {
+        let input = r#"{"status": "active", "id": 1}"#;
+        assert_eq!(
+            filter(input, &settings(r#"/status == "active""#)).unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn test_filter_drops_other_messages() {
+        let input = r#"{"status": "closed", "id": 1}"#;
+        assert_eq!(
+            filter(input, &settings(r#"/status == "active""#)).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_filter_rejects_malformed_predicate() {
+        let err = filter(r#"{"status": "active"}"#, &settings("status = active")).unwrap_err();
+        assert!(err.starts_with("Invalid predicate configuration"));
+    }
+
+    #[test]
+    fn test_malformed_predicates() {
+        assert!(Predicate::parse("").is_err());
+        assert!(Predicate::parse("status == 1").is_err());
+        assert!(Predicate::parse("/status ~= 1").is_err());
+        assert!(Predicate::parse("/status ==").is_err());
+        assert!(Predicate::parse("/status == active").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use serde_json::{Map, Value};
+use shared::{CsvOptions, Format, ProcessorFormatSettings};
+
+/// Convert the input from the configured source format to the target format
+pub fn convert(input: &str, settings: &ProcessorFormatSettings) -> Result<String, String> {
+    let default_options = CsvOptions::default();
+    let options = settings.csv_options.as_ref().unwrap_or(&default_options);
+
+    match (settings.from, settings.to) {
+        (Format::Json, Format::Json) | (Format::Csv, Format::Csv) => Ok(input.to_string()),
+        (Format::Json, Format::Csv) => json_to_csv(input, options),
+        (Format::Csv, Format::Json) => csv_to_json(input, options),
+    }
+}
+
+fn delimiter(options: &CsvOptions) -> Result<u8, String> {
+    let delimiter = options.delimiter.unwrap_or(',');
+    u8::try_from(delimiter)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or_else(|| format!("CSV delimiter must be a single ASCII character, got {delimiter:?}"))
+}
+
+/// Convert a JSON array of objects (or a single object) to CSV. The header is the union of the
+/// keys of all records, in the order they are first seen.
+fn json_to_csv(input: &str, options: &CsvOptions) -> Result<String, String> {
+    let value: Value =
+        serde_json::from_str(input).map_err(|e| format!("Input is not valid JSON: {e}"))?;
+    let records = match value {
+        Value::Array(records) => records,
+        Value::Object(_) => vec![value],
+        _ => return Err("JSON input must be an object or an array of objects".to_string()),
+    };
+
+    let mut columns: Vec<String> = Vec::new();
+    for record in &records {
+        let Value::Object(fields) = record else {
+            return Err("JSON input must be an object or an array of objects".to_string());
+        };
+        for key in fields.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter(options)?)
+        .from_writer(Vec::new());
+    let write_error = |e: csv::Error| format!("Failed to write CSV: {e}");
+
+    if options.has_headers.unwrap_or(true) {
+        writer.write_record(&columns).map_err(write_error)?;
+    }
+    for record in &records {
+        let row = columns.iter().map(|column| match record.get(column) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(value)) => value.clone(),
+            // Nested arrays and objects are kept as JSON text
+            Some(value) => value.to_string(),
+        });
+        writer.write_record(row).map_err(write_error)?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to write CSV: {e}"))?;
+    String::from_utf8(bytes).map_err(|e| format!("CSV output is not valid UTF-8: {e}"))
+}
+
+/// Convert CSV to a JSON array. With headers every row becomes an object keyed by column name,
+/// without headers every row becomes an array of values.
+fn csv_to_json(input: &str, options: &CsvOptions) -> Result<String, String> {
+    let has_headers = options.has_headers.unwrap_or(true);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter(options)?)
+        .has_headers(has_headers)
+        .from_reader(input.as_bytes());
+    let read_error = |e: csv::Error| format!("Input is not valid CSV: {e}");
+
+    let headers = if has_headers {
+        Some(reader.headers().map_err(read_error)?.clone())
+    } else {
+        None
+    };
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(read_error)?;
+        let record = match &headers {
+            Some(headers) => Value::Object(
+                headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(column, field)| (column.to_string(), field_value(field)))
+                    .collect::<Map<String, Value>>(),
+            ),
+            None => Value::Array(row.iter().map(field_value).collect()),
+        };
+        records.push(record);
+    }
+
+    serde_json::to_string(&records).map_err(|e| format!("Failed to serialize JSON: {e}"))
+}
+
+/// Infer the JSON type of a CSV field: empty fields become null, booleans and numbers are parsed
+/// and everything else is kept as a string.
+fn field_value(field: &str) -> Value {
+    if field.is_empty() {
+        return Value::Null;
+    }
+    match field {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match serde_json::from_str::<serde_json::Number>(field) {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(field.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings(
+        from: Format,
+        to: Format,
+        csv_options: Option<CsvOptions>,
+    ) -> ProcessorFormatSettings {
+        ProcessorFormatSettings {
+            from,
+            to,
+            csv_options,
+        }
+    }
+
+    #[test]
+    fn test_json_array_to_csv_and_back() {
+        let records = json!([
+            {"active": true, "age": 34, "name": "Ada"},
+            {"active": false, "age": 29, "name": "Grace, Jr."}
+        ]);
+
+        let csv = convert(
+            &records.to_string(),
+            &settings(Format::Json, Format::Csv, None),
+        )
+        .unwrap();
+        assert_eq!(
+            csv,
+            "active,age,name\ntrue,34,Ada\nfalse,29,\"Grace, Jr.\"\n"
+        );
+
+        let json = convert(&csv, &settings(Format::Csv, Format::Json, None)).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), records);
+    }
+
+    #[test]
+    fn test_json_to_csv_fills_missing_and_nested_fields() {
+        let records = json!([
+            {"id": 1, "tags": ["a", "b"]},
+            {"extra": "x", "id": 2, "note": null}
+        ]);
+
+        let csv = convert(
+            &records.to_string(),
+            &settings(Format::Json, Format::Csv, None),
+        )
+        .unwrap();
+        assert_eq!(
+            csv,
+            "id,tags,extra,note\n1,\"[\"\"a\"\",\"\"b\"\"]\",,\n2,,x,\n"
+        );
+    }
+
+    #[test]
+    fn test_json_object_to_csv_with_options() {
+        let options = CsvOptions {
+            delimiter: Some(';'),
+            has_headers: Some(false),
+        };
+
+        let csv = convert(
+            r#"{"a": "x", "b": "y"}"#,
+            &settings(Format::Json, Format::Csv, Some(options)),
+        )
+        .unwrap();
+        assert_eq!(csv, "x;y\n");
+    }
+
+    #[test]
+    fn test_csv_without_headers_to_json() {
+        let options = CsvOptions {
+            delimiter: Some('\t'),
+            has_headers: Some(false),
+        };
+
+        let json = convert(
+            "1\tone\n2\t\n",
+            &settings(Format::Csv, Format::Json, Some(options)),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&json).unwrap(),
+            json!([[1, "one"], [2, null]])
+        );
+    }
+
+    #[test]
+    fn test_same_format_passes_input_through() {
+        let input = "not even json";
+        assert_eq!(
+            convert(input, &settings(Format::Json, Format::Json, None)).unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        assert!(convert("not json", &settings(Format::Json, Format::Csv, None)).is_err());
+        assert!(convert("[1, 2]", &settings(Format::Json, Format::Csv, None)).is_err());
+        assert!(convert("a,b\n1,2,3\n", &settings(Format::Csv, Format::Json, None)).is_err());
+
+        let options = CsvOptions {
+            delimiter: Some('→'),
+            has_headers: None,
+        };
+        assert!(convert("{}", &settings(Format::Json, Format::Csv, Some(options))).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use bindings::exports::pipestack::customer::customer::{Guest, RunError};
+use bindings::wrpc::rpc;
+use shared::{FromConfig, ProcessorFormatSettings, text_input};
+use wasmcloud_component::{error, info};
+
+mod convert;
+
+mod bindings {
+    use super::Component;
+    wit_bindgen::generate!({ generate_all });
+    export!(Component);
+}
+
+struct Component;
+
+const LOG_CONTEXT: &str = "processor-format";
+
+impl Guest for Component {
+    fn run(input: String) -> Result<Result<String, RunError>, rpc::error::Error> {
+        let config = match bindings::wasi::config::runtime::get("json") {
+            Ok(config) => config,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to get config: {e:?}");
+                return Ok(Err(RunError::AppError(format!(
+                    "Failed to get config: {e:?}"
+                ))));
+            }
+        };
+
+        let settings = match ProcessorFormatSettings::from_config(config) {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to parse config: {e}");
+                return Ok(Err(RunError::AppError(format!(
+                    "Failed to parse config: {e}"
+                ))));
+            }
+        };
+
+        match convert::convert(&input, &settings) {
+            Ok(output) => {
+                info!(context: LOG_CONTEXT, "Converted {:?} to {:?}", settings.from, settings.to);
+                Ok(Ok(output))
+            }
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "Failed to convert input: {e}");
+                Ok(Err(RunError::AppError(e)))
+            }
+        }
+    }
+
+    fn run_bytes(input: Vec<u8>) -> Result<Result<Vec<u8>, RunError>, rpc::error::Error> {
+        let input = match text_input(input) {
+            Ok(input) => input,
+            Err(e) => {
+                error!(context: LOG_CONTEXT, "{e}");
+                return Ok(Err(RunError::AppError(e)));
+            }
+        };
+        Self::run(input).map(|output| output.map(String::into_bytes))
+    }
+}
@@ -1,8 +1,180 @@
-use schemars::generate::SchemaSettings;
+use std::path::Path;
+
+use pipeline_manager::{config::AppConfig, config_converter::convert_pipeline};
+use schemars::{Schema, generate::SchemaSettings};
 use shared::Pipeline;
 
-fn main() {
+/// Stable URI tooling can reference the pipeline schema by
+const SCHEMA_ID: &str = "https://github.com/pipestack/platform/schemas/pipeline.schema.json";
+const SCHEMA_TITLE: &str = "Pipestack pipeline";
+
+/// Workspace pipelines are converted for when `--workspace` isn't given
+const DEFAULT_WORKSPACE: &str = "default";
+
+const USAGE: &str = "Usage: pipeline [--emit-wadm <pipeline.yaml|json> --config <config.toml> [--workspace <slug>]]";
+
+fn pipeline_schema() -> Schema {
     let generator = SchemaSettings::draft07().into_generator();
-    let schema = generator.into_root_schema_for::<Pipeline>();
-    println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+    let mut schema = generator.into_root_schema_for::<Pipeline>();
+    schema.insert("$id".to_string(), SCHEMA_ID.into());
+    schema.insert("title".to_string(), SCHEMA_TITLE.into());
+    schema
+}
+
+/// The wadm manifest of a pipeline file, as YAML. Pipeline files may be YAML or JSON.
+fn emit_wadm(pipeline_path: &Path, config_path: &Path, workspace: &str) -> Result<String, String> {
+    let app_config = AppConfig::for_conversion(config_path)
+        .map_err(|e| format!("Failed to read config {}: {e}", config_path.display()))?;
+    let pipeline = std::fs::read_to_string(pipeline_path)
+        .map_err(|e| format!("Failed to read pipeline {}: {e}", pipeline_path.display()))?;
+    let pipeline: Pipeline = serde_yaml::from_str(&pipeline)
+        .map_err(|e| format!("Failed to parse pipeline {}: {e}", pipeline_path.display()))?;
+
+    let wadm = convert_pipeline(&pipeline, &workspace.to_string(), &app_config)
+        .map_err(|e| format!("Invalid pipeline: {e}"))?;
+    serde_yaml::to_string(&wadm).map_err(|e| format!("Failed to serialize wadm manifest: {e}"))
+}
+
+/// Arguments of `--emit-wadm` as `(pipeline, config, workspace)`
+fn emit_wadm_args(args: &[String]) -> Option<(&Path, &Path, &str)> {
+    let (mut pipeline, mut config, mut workspace) = (None, None, DEFAULT_WORKSPACE);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next()?;
+        match arg.as_str() {
+            "--emit-wadm" => pipeline = Some(Path::new(value)),
+            "--config" => config = Some(Path::new(value)),
+            "--workspace" => workspace = value,
+            _ => return None,
+        }
+    }
+    Some((pipeline?, config?, workspace))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&pipeline_schema()).unwrap()
+        );
+        return;
+    }
+
+    let Some((pipeline, config, workspace)) = emit_wadm_args(&args) else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+    match emit_wadm(pipeline, config, workspace) {
+        Ok(wadm) => print!("{wadm}"),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_emit_wadm_converts_fixture() {
+        let wadm = emit_wadm(
+            &fixture("pipeline.yaml"),
+            &fixture("config.toml"),
+            DEFAULT_WORKSPACE,
+        )
+        .unwrap();
+
+        let application: pipeline_manager::builders::WadmApplication =
+            serde_yaml::from_str(&wadm).expect("Output should be a wadm manifest");
+        assert_eq!(application.metadata.name, "default-orders");
+    }
+
+    #[test]
+    fn test_emit_wadm_rejects_invalid_pipeline() {
+        let dir = std::env::temp_dir().join("pipeline-emit-wadm-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let pipeline = dir.join("cyclic.yaml");
+        let contents = std::fs::read_to_string(fixture("pipeline.yaml")).unwrap();
+        std::fs::write(
+            &pipeline,
+            contents.replace("      - webhook", "      - log"),
+        )
+        .unwrap();
+
+        let err = emit_wadm(&pipeline, &fixture("config.toml"), DEFAULT_WORKSPACE).unwrap_err();
+        assert!(err.starts_with("Invalid pipeline: "), "{err}");
+    }
+
+    #[test]
+    fn test_emit_wadm_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let parsed = args(&["--emit-wadm", "p.yaml", "--config", "c.toml"]);
+        assert_eq!(
+            emit_wadm_args(&parsed),
+            Some((Path::new("p.yaml"), Path::new("c.toml"), DEFAULT_WORKSPACE))
+        );
+        let parsed = args(&[
+            "--config",
+            "c.toml",
+            "--emit-wadm",
+            "p.yaml",
+            "--workspace",
+            "acme",
+        ]);
+        assert_eq!(
+            emit_wadm_args(&parsed),
+            Some((Path::new("p.yaml"), Path::new("c.toml"), "acme"))
+        );
+        assert_eq!(emit_wadm_args(&args(&["--emit-wadm", "p.yaml"])), None);
+        assert_eq!(emit_wadm_args(&args(&["--emit-wadm"])), None);
+        assert_eq!(emit_wadm_args(&args(&["--verbose", "x"])), None);
+    }
+
+    #[test]
+    fn test_schema_has_id_and_title() {
+        let schema = pipeline_schema().to_value();
+
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["$id"], SCHEMA_ID);
+        assert_eq!(schema["title"], SCHEMA_TITLE);
+        assert!(
+            schema["properties"]["deadLetter"]["description"]
+                .as_str()
+                .is_some_and(|description| !description.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_every_node_settings_variant_has_a_title() {
+        let schema = pipeline_schema().to_value();
+        let variants = schema["definitions"]["PipelineNodeSettings"]["oneOf"]
+            .as_array()
+            .expect("node settings should be a oneOf of node types");
+
+        for variant in variants {
+            let node_type = &variant["properties"]["type"];
+            assert!(
+                variant["title"].as_str().is_some(),
+                "{node_type} has no title"
+            );
+        }
+        let title = |node_type: &str| {
+            variants
+                .iter()
+                .find(|v| v["properties"]["type"]["const"] == node_type)
+                .map(|v| v["title"].clone())
+        };
+        assert_eq!(title("in-http-webhook").unwrap(), "HTTP webhook source");
+        assert_eq!(title("out-log").unwrap(), "Log sink");
+    }
 }
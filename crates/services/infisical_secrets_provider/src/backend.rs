@@ -3,13 +3,14 @@ use async_nats::{HeaderMap, Message, Subscriber};
 use bytes::Bytes;
 use futures_util::stream::StreamExt;
 use nkeys::XKey;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
 use uuid::Uuid;
 
 use crate::config::AppConfig;
 use crate::encryption::EncryptionHandler;
 use crate::infisical_client::InfisicalClientWrapper;
 use crate::jwt::JwtValidator;
+use crate::request_span::{self, Outcome};
 use crate::types::{SecretRequest, SecretResponse};
 
 /// The main Infisical secrets backend implementation
@@ -154,19 +155,28 @@ impl InfisicalSecretsBackend {
             let request_id = Uuid::new_v4().to_string();
             debug!("Processing get request: {}. Message: {:?}", request_id, msg);
 
-            if let Err(e) = self.process_get_request(&msg, &request_id).await {
-                error!("Error processing get request {}: {}", request_id, e);
-
-                // Try to send error response if possible
-                if let Err(send_error) = self
-                    .send_error_response(&msg, &format!("Internal error: {}", e))
-                    .await
-                {
-                    error!(
-                        "Failed to send error response for request {}: {}",
-                        request_id, send_error
-                    );
+            let span = request_span::get_request_span(&request_id);
+            let result =
+                request_span::traced(span.clone(), self.process_get_request(&msg, &request_id))
+                    .await;
+
+            if let Err(e) = result {
+                async {
+                    error!("Error processing get request {}: {}", request_id, e);
+
+                    // Try to send error response if possible
+                    if let Err(send_error) = self
+                        .send_error_response(&msg, &format!("Internal error: {}", e))
+                        .await
+                    {
+                        error!(
+                            "Failed to send error response for request {}: {}",
+                            request_id, send_error
+                        );
+                    }
                 }
+                .instrument(span)
+                .await;
             }
         }
 
@@ -191,8 +201,8 @@ impl InfisicalSecretsBackend {
         Ok(())
     }
 
-    /// Processes a get secret request
-    async fn process_get_request(&self, msg: &Message, request_id: &str) -> Result<()> {
+    /// Processes a get secret request, returning how it was answered
+    async fn process_get_request(&self, msg: &Message, request_id: &str) -> Result<Outcome> {
         // Extract host xkey from headers
         let host_xkey = self
             .extract_host_xkey(&msg.headers)
@@ -204,7 +214,12 @@ impl InfisicalSecretsBackend {
         debug!("Decrypting payload: {:?}", &msg.payload);
         let decrypted_payload = self
             .encryption_handler
-            .decrypt_payload(&msg.payload, &host_xkey)
+            .decrypt_payload_within(
+                &msg.payload,
+                &host_xkey,
+                self.config.backend.max_ciphertext_bytes,
+                self.config.backend.max_plaintext_bytes,
+            )
             .map_err(|e| {
                 error!("Decryption error: {}", e);
                 e
@@ -220,6 +235,7 @@ impl InfisicalSecretsBackend {
         // Parse the secret request
         let secret_request: SecretRequest = serde_json::from_slice(&decrypted_payload)
             .context("Failed to parse secret request JSON")?;
+        request_span::record_key(&secret_request.key);
 
         info!(
             "Request {}: Processing secret request for '{}'",
@@ -238,7 +254,8 @@ impl InfisicalSecretsBackend {
                 jwt_validation.errors().join(", ")
             );
             warn!("Request {}: {}", request_id, error_msg);
-            return self.send_error_response(msg, &error_msg).await;
+            self.send_error_response(msg, &error_msg).await?;
+            return Ok(Outcome::Rejected);
         }
 
         debug!(
@@ -260,15 +277,15 @@ impl InfisicalSecretsBackend {
                     .await?;
 
                 debug!("Request {}: Sent successful response", request_id);
+                Ok(Outcome::Served)
             }
             Err(e) => {
                 let error_msg = format!("Failed to fetch secret from Infisical: {}", e);
                 warn!("Request {}: {}", request_id, error_msg);
-                return self.send_error_response(msg, &error_msg).await;
+                self.send_error_response(msg, &error_msg).await?;
+                Ok(Outcome::Failed)
             }
         }
-
-        Ok(())
     }
 
     /// Processes a server xkey request
@@ -398,16 +415,20 @@ mod tests {
                 base_url: "https://app.infisical.com".to_string(),
                 project_id: "test_project_id".to_string(),
                 environment: "test".to_string(),
+                expand_secret_references: true,
             },
             nats: crate::config::NatsConfig {
                 jwt: None,
                 nkey: None,
                 url: "nats://localhost:4222".to_string(),
                 subject_prefix: "wasmcloud.secrets".to_string(),
+                get_subject: None,
+                xkey_subject: None,
             },
             backend: crate::config::BackendConfig {
                 name: "infisical".to_string(),
                 api_version: "v1alpha1".to_string(),
+                ..Default::default()
             },
         }
     }
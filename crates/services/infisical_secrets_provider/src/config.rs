@@ -16,6 +16,8 @@ pub struct InfisicalConfig {
     pub base_url: String,
     pub project_id: String,
     pub environment: String,
+    /// Resolve `${...}` references to other secrets before returning a secret's value
+    pub expand_secret_references: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -24,12 +26,20 @@ pub struct NatsConfig {
     pub nkey: Option<String>,
     pub url: String,
     pub subject_prefix: String,
+    /// Subject of the get operation, used verbatim instead of the one composed from the prefix
+    pub get_subject: Option<String>,
+    /// Subject of the server_xkey operation, used verbatim instead of the composed one
+    pub xkey_subject: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BackendConfig {
     pub name: String,
     pub api_version: String,
+    /// Largest encrypted request payload accepted, checked before decrypting it
+    pub max_ciphertext_bytes: usize,
+    /// Largest decrypted request payload accepted, checked before parsing it
+    pub max_plaintext_bytes: usize,
 }
 
 impl Default for InfisicalConfig {
@@ -40,6 +50,7 @@ impl Default for InfisicalConfig {
             base_url: "https://app.infisical.com".to_string(),
             project_id: String::new(),
             environment: "prod".to_string(),
+            expand_secret_references: true,
         }
     }
 }
@@ -51,6 +62,8 @@ impl Default for NatsConfig {
             nkey: None,
             url: "nats://localhost:4222".to_string(),
             subject_prefix: "wasmcloud.secrets".to_string(),
+            get_subject: None,
+            xkey_subject: None,
         }
     }
 }
@@ -60,6 +73,8 @@ impl Default for BackendConfig {
         Self {
             name: "infisical".to_string(),
             api_version: "v1alpha1".to_string(),
+            max_ciphertext_bytes: 64 * 1024,
+            max_plaintext_bytes: 64 * 1024,
         }
     }
 }
@@ -98,22 +113,36 @@ impl AppConfig {
             return Err(anyhow::anyhow!("Backend name cannot be empty"));
         }
 
+        if self.backend.max_ciphertext_bytes == 0 || self.backend.max_plaintext_bytes == 0 {
+            return Err(anyhow::anyhow!(
+                "Backend payload size limits cannot be zero"
+            ));
+        }
+
         Ok(())
     }
 
     /// Returns the NATS subject for the get operation
     pub fn get_subject(&self) -> String {
-        format!(
-            "{}.{}.{}.get",
-            self.nats.subject_prefix, self.backend.api_version, self.backend.name
-        )
+        match &self.nats.get_subject {
+            Some(subject) => subject.clone(),
+            None => self.composed_subject("get"),
+        }
     }
 
     /// Returns the NATS subject for the server_xkey operation
     pub fn server_xkey_subject(&self) -> String {
+        match &self.nats.xkey_subject {
+            Some(subject) => subject.clone(),
+            None => self.composed_subject("server_xkey"),
+        }
+    }
+
+    /// `{prefix}.{api_version}.{name}.{operation}`, the subject wasmCloud hosts use by default
+    fn composed_subject(&self, operation: &str) -> String {
         format!(
-            "{}.{}.{}.server_xkey",
-            self.nats.subject_prefix, self.backend.api_version, self.backend.name
+            "{}.{}.{}.{}",
+            self.nats.subject_prefix, self.backend.api_version, self.backend.name, operation
         )
     }
 }
@@ -135,6 +164,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_subject_overrides_are_used_verbatim() {
+        let mut config = AppConfig::default();
+        config.nats.get_subject = Some("acme.secrets.get".to_string());
+        assert_eq!(config.get_subject(), "acme.secrets.get");
+        // Subjects without an override are still composed
+        assert_eq!(
+            config.server_xkey_subject(),
+            "wasmcloud.secrets.v1alpha1.infisical.server_xkey"
+        );
+
+        config.nats.xkey_subject = Some("acme.secrets.xkey".to_string());
+        assert_eq!(config.server_xkey_subject(), "acme.secrets.xkey");
+    }
+
+    #[test]
+    fn test_subject_overrides_default_to_unset() {
+        let defaults = Config::try_from(&AppConfig::default()).unwrap();
+        let config: AppConfig = Config::builder()
+            .add_source(defaults)
+            .set_override("nats.xkey_subject", "acme.secrets.xkey")
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        assert_eq!(config.nats.get_subject, None);
+        assert_eq!(
+            config.get_subject(),
+            "wasmcloud.secrets.v1alpha1.infisical.get"
+        );
+        assert_eq!(config.server_xkey_subject(), "acme.secrets.xkey");
+    }
+
     #[test]
     fn test_validation() {
         let mut config = AppConfig::default();
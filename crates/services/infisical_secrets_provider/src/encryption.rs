@@ -81,6 +81,36 @@ impl EncryptionHandler {
         Ok(decrypted)
     }
 
+    /// Decrypts a payload like `decrypt_payload`, rejecting a payload of over
+    /// `max_ciphertext_bytes` before decrypting it and one decrypting to over
+    /// `max_plaintext_bytes` before it's parsed
+    pub fn decrypt_payload_within(
+        &self,
+        encrypted_payload: &[u8],
+        host_public_key: &str,
+        max_ciphertext_bytes: usize,
+        max_plaintext_bytes: usize,
+    ) -> Result<Vec<u8>> {
+        if encrypted_payload.len() > max_ciphertext_bytes {
+            return Err(anyhow::anyhow!(
+                "Encrypted payload of {} bytes exceeds the limit of {} bytes",
+                encrypted_payload.len(),
+                max_ciphertext_bytes
+            ));
+        }
+
+        let decrypted = self.decrypt_payload(encrypted_payload, host_public_key)?;
+        if decrypted.len() > max_plaintext_bytes {
+            return Err(anyhow::anyhow!(
+                "Decrypted payload of {} bytes exceeds the limit of {} bytes",
+                decrypted.len(),
+                max_plaintext_bytes
+            ));
+        }
+
+        Ok(decrypted)
+    }
+
     /// Encrypts a payload using XKey encryption with the provided host public key
     #[cfg(test)]
     pub fn encrypt_payload(&self, payload: &[u8], host_public_key: &str) -> Result<Vec<u8>> {
@@ -329,4 +359,45 @@ mod tests {
 
         assert!(decrypt_result.is_err());
     }
+
+    #[test]
+    fn test_oversized_payloads_are_rejected() {
+        let server_handler = EncryptionHandler::new();
+        let client_handler = EncryptionHandler::new();
+        let server_public_key = server_handler.public_key();
+
+        let encrypted = server_handler
+            .encrypt_payload(&[b'a'; 2048], &client_handler.public_key())
+            .expect("Failed to encrypt");
+
+        let too_large = client_handler
+            .decrypt_payload_within(&encrypted, &server_public_key, 1024, 4096)
+            .unwrap_err();
+        assert!(too_large.to_string().starts_with("Encrypted payload of"));
+
+        // Within the ciphertext limit, but decrypting to more than the plaintext limit
+        let too_large = client_handler
+            .decrypt_payload_within(&encrypted, &server_public_key, 4096, 1024)
+            .unwrap_err();
+        assert_eq!(
+            too_large.to_string(),
+            "Decrypted payload of 2048 bytes exceeds the limit of 1024 bytes"
+        );
+    }
+
+    #[test]
+    fn test_payloads_within_limits_are_decrypted() {
+        let server_handler = EncryptionHandler::new();
+        let client_handler = EncryptionHandler::new();
+        let test_data = br#"{"key":"db_password","context":{}}"#;
+
+        let encrypted = server_handler
+            .encrypt_payload(test_data, &client_handler.public_key())
+            .expect("Failed to encrypt");
+        let decrypted = client_handler
+            .decrypt_payload_within(&encrypted, &server_handler.public_key(), 1024, 1024)
+            .expect("Failed to decrypt");
+
+        assert_eq!(test_data, decrypted.as_slice());
+    }
 }
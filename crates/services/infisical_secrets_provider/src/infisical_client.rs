@@ -10,6 +10,38 @@ use crate::config::InfisicalConfig;
 use crate::types::Context as SecretContext;
 use crate::types::{Secret, SecretRequest};
 
+/// Folder secrets are read from
+const SECRETS_PATH: &str = "/nats/workspaces/default"; // Could be made configurable in the future
+
+/// What a secret is requested from Infisical with
+#[derive(Debug, PartialEq, Eq)]
+struct SecretQuery<'a> {
+    key: &'a str,
+    project_id: &'a str,
+    environment: &'a str,
+    path: &'a str,
+    expand_secret_references: bool,
+}
+
+impl<'a> SecretQuery<'a> {
+    fn new(config: &'a InfisicalConfig, request: &'a SecretRequest) -> Self {
+        Self {
+            key: &request.key,
+            project_id: &config.project_id,
+            environment: &config.environment,
+            path: SECRETS_PATH,
+            expand_secret_references: config.expand_secret_references,
+        }
+    }
+
+    fn build(&self) -> GetSecretRequest {
+        GetSecretRequest::builder(self.key, self.project_id, self.environment)
+            .path(self.path)
+            .expand_secret_references(self.expand_secret_references)
+            .build()
+    }
+}
+
 /// Wrapper around the Infisical client that handles authentication and secret retrieval
 pub struct InfisicalClientWrapper {
     client: Arc<RwLock<Client>>,
@@ -53,14 +85,7 @@ impl InfisicalClientWrapper {
 
         let client = self.client.read().await;
 
-        let infisical_request = GetSecretRequest::builder(
-            &request.key,
-            &self.config.project_id,
-            &self.config.environment,
-        )
-        .path("/nats/workspaces/default") // Default path - could be made configurable in the future
-        .expand_secret_references(true)
-        .build();
+        let infisical_request = SecretQuery::new(&self.config, request).build();
 
         match client.secrets().get(infisical_request).await {
             Ok(infisical_secret) => {
@@ -163,6 +188,7 @@ mod tests {
             base_url: "https://app.infisical.com".to_string(),
             project_id: "test_project_id".to_string(),
             environment: "test".to_string(),
+            expand_secret_references: true,
         }
     }
 
@@ -177,6 +203,52 @@ mod tests {
         assert_eq!(expected_project_id, "test_project_id");
     }
 
+    fn create_test_request() -> SecretRequest {
+        SecretRequest {
+            key: "test_secret".to_string(),
+            field: None,
+            version: None,
+            context: SecretContext {
+                entity_jwt: "test.entity.jwt".to_string(),
+                host_jwt: "test.host.jwt".to_string(),
+                application: crate::types::Application {
+                    name: "test-app".to_string(),
+                    policy: "{}".to_string(),
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_secret_query_expands_references() {
+        let config = create_test_config();
+        let request = create_test_request();
+
+        assert_eq!(
+            SecretQuery::new(&config, &request),
+            SecretQuery {
+                key: "test_secret",
+                project_id: "test_project_id",
+                environment: "test",
+                path: SECRETS_PATH,
+                expand_secret_references: true,
+            }
+        );
+        // Expansion is on unless disabled in config
+        assert!(SecretQuery::new(&InfisicalConfig::default(), &request).expand_secret_references);
+    }
+
+    #[test]
+    fn test_secret_query_expansion_can_be_disabled() {
+        let config = InfisicalConfig {
+            expand_secret_references: false,
+            ..create_test_config()
+        };
+        let request = create_test_request();
+
+        assert!(!SecretQuery::new(&config, &request).expand_secret_references);
+    }
+
     #[tokio::test]
     async fn test_secret_request_creation() {
         let request = SecretRequest {
@@ -3,6 +3,9 @@ use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_NO_PAD, Engine};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+/// Longest base64url JWT payload decoded. Component and provider claims are a few hundred bytes.
+const MAX_PAYLOAD_LEN: usize = 64 * 1024;
+
 /// JWT claims structure for wasmCloud components and providers
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct JwtClaims {
@@ -175,6 +178,13 @@ impl JwtValidator {
 
         // Decode the payload (second part)
         let payload_b64 = parts[1];
+        if payload_b64.len() > MAX_PAYLOAD_LEN {
+            return Err(anyhow::anyhow!(
+                "JWT payload is {} bytes, which exceeds the maximum of {}",
+                payload_b64.len(),
+                MAX_PAYLOAD_LEN
+            ));
+        }
         let payload_bytes = BASE64_NO_PAD
             .decode(payload_b64)
             .context("Failed to decode JWT payload")?;
@@ -294,6 +304,16 @@ mod tests {
         assert!(result.errors()[0].contains("Invalid JWT format"));
     }
 
+    #[test]
+    fn test_oversized_payload() {
+        let validator = JwtValidator::default();
+        let token = format!("header.{}.signature", "a".repeat(MAX_PAYLOAD_LEN + 1));
+        let result = validator.validate_token(&token).unwrap();
+
+        assert!(!result.is_valid());
+        assert!(result.errors()[0].contains("exceeds the maximum"));
+    }
+
     #[test]
     fn test_expired_token() {
         let validator = JwtValidator::new(true, 10);
@@ -3,6 +3,7 @@ mod config;
 mod encryption;
 mod infisical_client;
 mod jwt;
+mod request_span;
 mod types;
 
 use anyhow::Result;
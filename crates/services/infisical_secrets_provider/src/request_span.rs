@@ -0,0 +1,146 @@
+//! Tracing spans around the requests the backend handles, so every log line of a request carries
+//! its id and key, and the outcome and duration of each request are recorded.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Instant;
+use tracing::{field, info, info_span, Instrument, Span};
+
+/// How a get request was answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The secret was sent to the host
+    Served,
+    /// The request's JWT was invalid
+    Rejected,
+    /// The secret couldn't be fetched from Infisical
+    Failed,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Served => "served",
+            Self::Rejected => "rejected",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Outcome recorded for a request that failed with an error, e.g. an undecryptable payload
+const ERROR_OUTCOME: &str = "error";
+
+/// Creates the span a get request is handled in. The key is recorded once the request is
+/// decrypted, the outcome and duration once it's handled.
+pub fn get_request_span(request_id: &str) -> Span {
+    info_span!(
+        "get_request",
+        request_id,
+        key = field::Empty,
+        outcome = field::Empty,
+        duration_ms = field::Empty
+    )
+}
+
+/// Records the key the request handled in the current span is for
+pub fn record_key(key: &str) {
+    Span::current().record("key", key);
+}
+
+/// Runs a request in `span`, recording its outcome and duration
+pub async fn traced<F>(span: Span, request: F) -> Result<Outcome>
+where
+    F: Future<Output = Result<Outcome>>,
+{
+    let started = Instant::now();
+    let result = request.instrument(span.clone()).await;
+
+    let outcome = match &result {
+        Ok(outcome) => outcome.as_str(),
+        Err(_) => ERROR_OUTCOME,
+    };
+    span.record("outcome", outcome);
+    span.record(
+        "duration_ms",
+        u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+    );
+    span.in_scope(|| info!("Finished get request: {}", outcome));
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Collects the fields recorded on spans, by field name
+    #[derive(Clone, Default)]
+    struct SpanFields(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Visit for SpanFields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for SpanFields {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut self.clone());
+        }
+
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_span_fields_are_populated() {
+        let fields = SpanFields::default();
+        let subscriber = tracing_subscriber::registry().with(fields.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let result = traced(get_request_span("request-1"), async {
+            record_key("db_password");
+            Ok(Outcome::Rejected)
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), Outcome::Rejected);
+        let fields = fields.0.lock().unwrap();
+        assert_eq!(fields["request_id"], "request-1");
+        assert_eq!(fields["key"], "db_password");
+        assert_eq!(fields["outcome"], "rejected");
+        assert!(fields["duration_ms"].parse::<u64>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_recorded_as_outcome() {
+        let fields = SpanFields::default();
+        let subscriber = tracing_subscriber::registry().with(fields.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let result = traced(get_request_span("request-2"), async {
+            Err(anyhow::anyhow!("Failed to decrypt request payload"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(fields.0.lock().unwrap()["outcome"], "error");
+    }
+}
@@ -1,12 +1,23 @@
+use std::collections::HashMap;
+
 use config::{Config, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct DatabaseConfig {
     pub url: String,
     pub notification_channel: String,
 }
 
+impl std::fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("url", &shared::redact_connection_string(&self.url))
+            .field("notification_channel", &self.notification_channel)
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RailwayConfig {
     pub environment_id: String,
@@ -15,6 +26,9 @@ pub struct RailwayConfig {
     pub api_url: String,
     pub default_template_repo: String,
     pub default_branch: String,
+    pub deploy_poll_interval_ms: u64,
+    pub deploy_poll_max_interval_ms: u64,
+    pub deploy_timeout_secs: u64,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -33,6 +47,43 @@ pub struct NatsConfig {
     pub operator_seed: String,
     pub pipestack_account_seed: String,
     pub url: String,
+    /// NATS servers the wasmCloud hosts of workspaces connect to, as reachable from the hosts
+    pub host_urls: Vec<String>,
+    /// Permission overrides for workspace users, keyed by workspace slug
+    #[serde(default)]
+    pub workspace_permissions: HashMap<String, NatsUserPermissions>,
+}
+
+/// How custom permissions combine with the default workspace user permissions
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionMode {
+    /// Add the custom subjects to the defaults
+    #[default]
+    Merge,
+    /// Use only the custom subjects
+    Replace,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct SubjectPermissions {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Custom permissions of a workspace's NATS user. New credentials are verified by echoing a
+/// probe on a subject the user may both publish and subscribe to, so permissions that allow no
+/// such subject leave the credentials verified by connecting only.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct NatsUserPermissions {
+    #[serde(default)]
+    pub mode: PermissionMode,
+    #[serde(default)]
+    pub publish: SubjectPermissions,
+    #[serde(default)]
+    pub subscribe: SubjectPermissions,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -86,6 +137,9 @@ impl Default for RailwayConfig {
             api_url: "https://backboard.railway.app/graphql/v2".to_string(),
             default_template_repo: "pipestack/wasmcloud-infra".to_string(),
             default_branch: "main".to_string(),
+            deploy_poll_interval_ms: 2000,
+            deploy_poll_max_interval_ms: 15000,
+            deploy_timeout_secs: 450,
         }
     }
 }
@@ -102,6 +156,8 @@ impl Default for NatsConfig {
                 .unwrap_or_default(),
             url: std::env::var("NATS_SERVER_URL")
                 .unwrap_or_else(|_| "nats://localhost:4222".to_string()),
+            host_urls: vec!["${{nats.RAILWAY_PRIVATE_DOMAIN}}".to_string()],
+            workspace_permissions: HashMap::new(),
         }
     }
 }
@@ -158,6 +214,19 @@ impl AppConfig {
             ));
         }
 
+        if self.railway.deploy_poll_interval_ms == 0 {
+            return Err(ConfigError::Message(
+                "Railway deploy poll interval must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.railway.deploy_poll_max_interval_ms < self.railway.deploy_poll_interval_ms {
+            return Err(ConfigError::Message(
+                "Railway deploy poll max interval cannot be less than the poll interval"
+                    .to_string(),
+            ));
+        }
+
         if self.nats.operator_seed.is_empty() {
             return Err(ConfigError::Message(
                 "NATS operator seed cannot be empty".to_string(),
@@ -170,6 +239,12 @@ impl AppConfig {
             ));
         }
 
+        if self.nats.host_urls.is_empty() {
+            return Err(ConfigError::Message(
+                "NATS host URLs cannot be empty".to_string(),
+            ));
+        }
+
         if self.infisical.client_id.is_empty() {
             return Err(ConfigError::Message(
                 "Infisical client ID cannot be empty".to_string(),
@@ -210,6 +285,9 @@ mod tests {
                 api_url: "https://api.railway.app".to_string(),
                 default_template_repo: "https://github.com/test/repo".to_string(),
                 default_branch: "main".to_string(),
+                deploy_poll_interval_ms: 2000,
+                deploy_poll_max_interval_ms: 15000,
+                deploy_timeout_secs: 450,
             },
             service: ServiceConfig::default(),
             nats: NatsConfig {
@@ -220,6 +298,8 @@ mod tests {
                 operator_seed: "test_operator_seed".to_string(),
                 pipestack_account_seed: "pipestack_account_seed".to_string(),
                 url: "nats://localhost:4222".to_string(),
+                host_urls: vec!["nats://localhost:4222".to_string()],
+                workspace_permissions: HashMap::new(),
             },
             infisical: InfisicalConfig {
                 client_id: "test_client_id".to_string(),
@@ -240,6 +320,11 @@ mod tests {
         app_config.database.url = "postgresql://test".to_string();
         app_config.railway.token = "".to_string();
         assert!(app_config.validate().is_err());
+
+        // Reset railway token and test a max poll interval below the base interval
+        app_config.railway.token = "test_token".to_string();
+        app_config.railway.deploy_poll_max_interval_ms = 1000;
+        assert!(app_config.validate().is_err());
     }
 
     #[test]
@@ -269,6 +354,9 @@ mod tests {
             "pipestack/wasmcloud-infra"
         );
         assert_eq!(railway_config.default_branch, "main");
+        assert_eq!(railway_config.deploy_poll_interval_ms, 2000);
+        assert_eq!(railway_config.deploy_poll_max_interval_ms, 15000);
+        assert_eq!(railway_config.deploy_timeout_secs, 450);
     }
 
     #[test]
@@ -0,0 +1,108 @@
+//! Everything a wasmCloud host of a workspace needs to connect to NATS: the user it connects as,
+//! the account and operator it belongs to, and the servers to connect to. The credentials are
+//! stored in Infisical as one secret per field and handed to the host as env variables.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Keys of the Infisical secrets the credentials are stored as
+pub const SECRET_KEYS: [&str; 7] = [
+    "account_nkey",
+    "account_jwt",
+    "user_nkey",
+    "user_jwt",
+    "user_seed",
+    "operator_public_key",
+    "nats_urls",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NatsCredentials {
+    pub account_nkey: String,
+    pub account_jwt: String,
+    pub user_nkey: String,
+    pub user_jwt: String,
+    pub user_seed: String,
+    /// Public key of the operator that signed the account
+    #[serde(default)]
+    pub operator_public_key: String,
+    /// NATS servers the host connects to, as reachable from the host
+    #[serde(default)]
+    pub nats_urls: Vec<String>,
+}
+
+impl NatsCredentials {
+    /// The credentials as `(secret key, value)`, in the order of `SECRET_KEYS`. The NATS URLs
+    /// are stored comma separated.
+    pub fn secrets(&self) -> Vec<(&'static str, String)> {
+        let values = [
+            self.account_nkey.clone(),
+            self.account_jwt.clone(),
+            self.user_nkey.clone(),
+            self.user_jwt.clone(),
+            self.user_seed.clone(),
+            self.operator_public_key.clone(),
+            self.nats_urls.join(","),
+        ];
+        SECRET_KEYS.into_iter().zip(values).collect()
+    }
+
+    /// Env variables connecting a wasmCloud host with the credentials. The control interface and
+    /// RPC connect to the first NATS server.
+    pub fn host_env_variables(&self) -> HashMap<String, String> {
+        let mut env_variables = HashMap::new();
+        if let Some(url) = self.nats_urls.first() {
+            env_variables.insert("WASMCLOUD_CTL_HOST".to_string(), url.clone());
+            env_variables.insert("WASMCLOUD_RPC_HOST".to_string(), url.clone());
+        }
+        env_variables.insert("WASMCLOUD_NATS_JWT".to_string(), self.user_jwt.clone());
+        env_variables.insert("WASMCLOUD_NATS_SEED".to_string(), self.user_seed.clone());
+        env_variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials() -> NatsCredentials {
+        NatsCredentials {
+            account_nkey: "ATEST".to_string(),
+            account_jwt: "account-jwt".to_string(),
+            user_nkey: "UTEST".to_string(),
+            user_jwt: "user-jwt".to_string(),
+            user_seed: "SUTEST".to_string(),
+            operator_public_key: "OTEST".to_string(),
+            nats_urls: vec![
+                "nats://nats-a:4222".to_string(),
+                "nats://nats-b:4222".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_credentials_round_trip() {
+        let credentials = credentials();
+
+        let json = serde_json::to_string(&credentials).unwrap();
+        assert_eq!(
+            serde_json::from_str::<NatsCredentials>(&json).unwrap(),
+            credentials
+        );
+
+        let secrets: HashMap<&str, String> = credentials.secrets().into_iter().collect();
+        assert_eq!(
+            secrets["nats_urls"],
+            "nats://nats-a:4222,nats://nats-b:4222"
+        );
+    }
+
+    #[test]
+    fn test_host_env_variables() {
+        let env_variables = credentials().host_env_variables();
+        assert_eq!(env_variables["WASMCLOUD_CTL_HOST"], "nats://nats-a:4222");
+        assert_eq!(env_variables["WASMCLOUD_RPC_HOST"], "nats://nats-a:4222");
+        assert_eq!(env_variables["WASMCLOUD_NATS_JWT"], "user-jwt");
+        assert_eq!(env_variables["WASMCLOUD_NATS_SEED"], "SUTEST");
+    }
+}
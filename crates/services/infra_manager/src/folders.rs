@@ -0,0 +1,196 @@
+//! Creating nested Infisical folders one level at a time. Infra manager instances provisioning
+//! the same workspace concurrently create the same levels, so a level created by another
+//! instance counts as created, and a level whose creation raced another is retried.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Attempts at creating a single folder level before giving up on the folder
+pub const MAX_LEVEL_ATTEMPTS: u32 = 3;
+
+/// Delay before retrying a level, multiplied by the number of attempts made
+pub const LEVEL_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Failure to create a folder level
+#[derive(Debug, PartialEq, Eq)]
+pub enum FolderError {
+    /// May succeed when retried, e.g. the request raced another instance creating the folder
+    Transient(String),
+    Fatal(String),
+}
+
+impl std::fmt::Display for FolderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transient(message) | Self::Fatal(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Every level of `folder_path` from the top, as `(name, full path)`, e.g. `("nats", "/nats")`
+/// then `("workspaces", "/nats/workspaces")`
+pub fn folder_levels(folder_path: &str) -> Vec<(String, String)> {
+    let mut full_path = String::new();
+    folder_path
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            full_path = format!("{full_path}/{name}");
+            (name.to_string(), full_path.clone())
+        })
+        .collect()
+}
+
+/// Outcome of a folder creation request from its response. A folder that already exists, e.g.
+/// as another instance created it first, counts as created.
+pub fn classify_response(status: u16, body: &str) -> Result<(), FolderError> {
+    let body_lower = body.to_lowercase();
+    match status {
+        200..300 | 409 => Ok(()),
+        _ if body_lower.contains("already exists")
+            || body_lower.contains("duplicate")
+            || body_lower.contains("exists") =>
+        {
+            Ok(())
+        }
+        429 | 500.. => Err(FolderError::Transient(format!(
+            "API request failed: HTTP {status} - {body}"
+        ))),
+        _ => Err(FolderError::Fatal(format!(
+            "API request failed: HTTP {status} - {body}"
+        ))),
+    }
+}
+
+/// Create each level in order with `create_level(name, full path)`. A level failing with a
+/// transient error is attempted up to `MAX_LEVEL_ATTEMPTS` times, and the next level is created
+/// once it succeeds.
+pub async fn create_levels<F, Fut>(
+    levels: &[(String, String)],
+    retry_delay: Duration,
+    mut create_level: F,
+) -> Result<()>
+where
+    F: FnMut(&str, &str) -> Fut,
+    Fut: Future<Output = Result<(), FolderError>>,
+{
+    for (name, full_path) in levels {
+        let mut attempt = 1;
+        loop {
+            match create_level(name, full_path).await {
+                Ok(()) => {
+                    debug!("Ensured folder exists: {}", full_path);
+                    break;
+                }
+                Err(FolderError::Transient(e)) if attempt < MAX_LEVEL_ATTEMPTS => {
+                    warn!(
+                        "Creating folder '{}' failed on attempt {}, retrying: {}",
+                        full_path, attempt, e
+                    );
+                    tokio::time::sleep(retry_delay * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "Failed to create folder structure at '{}': {}",
+                        full_path,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_folder_levels() {
+        assert_eq!(
+            folder_levels("/nats/workspaces/acme"),
+            [
+                ("nats".to_string(), "/nats".to_string()),
+                ("workspaces".to_string(), "/nats/workspaces".to_string()),
+                ("acme".to_string(), "/nats/workspaces/acme".to_string()),
+            ]
+        );
+        assert_eq!(folder_levels("nats//workspaces/").len(), 2);
+        assert!(folder_levels("/").is_empty());
+    }
+
+    #[test]
+    fn test_existing_folders_count_as_created() {
+        assert_eq!(classify_response(200, ""), Ok(()));
+        assert_eq!(classify_response(409, ""), Ok(()));
+        assert_eq!(
+            classify_response(
+                400,
+                r#"{"message":"Folder with name 'nats' already exists"}"#
+            ),
+            Ok(())
+        );
+        assert!(matches!(
+            classify_response(503, "Service unavailable"),
+            Err(FolderError::Transient(_))
+        ));
+        assert!(matches!(
+            classify_response(401, "Unauthorized"),
+            Err(FolderError::Fatal(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_conflict_at_intermediate_level_is_handled() {
+        let levels = folder_levels("/nats/workspaces/acme");
+        let attempts = Mutex::new(Vec::new());
+
+        // Another instance is creating /nats/workspaces: the first request races it, the retry
+        // finds the folder it created
+        let result = create_levels(&levels, Duration::ZERO, |_, full_path| {
+            let mut attempts = attempts.lock().unwrap();
+            let previous = attempts.iter().filter(|path| *path == full_path).count();
+            attempts.push(full_path.to_string());
+            let outcome = match (full_path, previous) {
+                ("/nats/workspaces", 0) => classify_response(500, "could not serialize access"),
+                ("/nats/workspaces", _) => classify_response(409, "Folder already exists"),
+                _ => classify_response(200, ""),
+            };
+            async move { outcome }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            attempts.into_inner().unwrap(),
+            [
+                "/nats",
+                "/nats/workspaces",
+                "/nats/workspaces",
+                "/nats/workspaces/acme"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persistent_failures_stop_folder_creation() {
+        let levels = folder_levels("/nats/workspaces");
+        let mut attempts = 0;
+        let result = create_levels(&levels, Duration::ZERO, |_, _| {
+            attempts += 1;
+            async { Err(FolderError::Transient("HTTP 503".to_string())) }
+        })
+        .await;
+
+        assert_eq!(attempts, MAX_LEVEL_ATTEMPTS);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to create folder structure at '/nats': HTTP 503"
+        );
+    }
+}
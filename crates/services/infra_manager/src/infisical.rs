@@ -7,7 +7,9 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use crate::config::InfisicalConfig;
-use crate::nats::NatsCredentials;
+use crate::credentials::{self, NatsCredentials};
+use crate::folders::{self, FolderError};
+use crate::secrets;
 
 /// Wrapper around the Infisical client that handles authentication and secret operations
 pub struct InfisicalClient {
@@ -79,60 +81,29 @@ impl InfisicalClient {
             return Ok(());
         }
 
-        // Split the path into components to create nested folders
-        let path_components: Vec<&str> = folder_path.trim_start_matches('/').split('/').collect();
-        let filtered_components: Vec<&str> = path_components
-            .into_iter()
-            .filter(|c| !c.is_empty())
-            .collect();
-
-        if filtered_components.is_empty() {
+        let levels = folders::folder_levels(folder_path);
+        if levels.is_empty() {
             debug!("Root path provided, no folder creation needed");
             return Ok(());
         }
 
-        // Create each folder level progressively
-        let mut current_path = String::from("/");
-
-        for component in &filtered_components {
-            // Validate folder name component
-            if component.len() > 255 {
-                return Err(anyhow::anyhow!(
-                    "Folder name '{}' exceeds maximum length of 255 characters",
-                    component
-                ));
-            }
-
-            // Update current path
-            if current_path == "/" {
-                current_path = format!("/{}", component);
-            } else {
-                current_path = format!("{}/{}", current_path, component);
-            }
-
-            // Try to create the folder at this level
-            match self.create_single_folder(component, &current_path).await {
-                Ok(_) => {
-                    debug!("Successfully created folder: {}", current_path);
-                }
-                Err(e)
-                    if e.to_string().contains("already exists")
-                        || e.to_string().contains("duplicate")
-                        || e.to_string().contains("409") =>
-                {
-                    debug!("Folder already exists: {}", current_path);
-                    // Continue with next folder level
-                }
-                Err(e) => {
-                    error!("Failed to create folder '{}': {}", current_path, e);
-                    return Err(e.context(format!(
-                        "Failed to create folder structure at '{}'",
-                        current_path
-                    )));
-                }
-            }
+        // Validate folder names
+        if let Some((name, _)) = levels.iter().find(|(name, _)| name.len() > 255) {
+            return Err(anyhow::anyhow!(
+                "Folder name '{}' exceeds maximum length of 255 characters",
+                name
+            ));
         }
 
+        // Create each folder level progressively. Levels created concurrently by another
+        // instance count as created, and levels whose creation raced another are retried.
+        folders::create_levels(&levels, folders::LEVEL_RETRY_DELAY, |name, full_path| {
+            let (name, full_path) = (name.to_string(), full_path.to_string());
+            async move { self.create_single_folder(&name, &full_path).await }
+        })
+        .await
+        .inspect_err(|e| error!("Failed to create folder structure '{}': {}", folder_path, e))?;
+
         info!(
             "Successfully ensured folder structure exists: {}",
             folder_path
@@ -153,8 +124,13 @@ impl InfisicalClient {
     /// # Returns
     ///
     /// * `Ok(())` - If the folder was created successfully or already exists
-    /// * `Err(anyhow::Error)` - If folder creation fails due to API or network errors
-    async fn create_single_folder(&self, folder_name: &str, full_path: &str) -> Result<()> {
+    /// * `Err(FolderError::Transient)` - If the request failed but may succeed when retried
+    /// * `Err(FolderError::Fatal)` - If folder creation fails for any other reason
+    async fn create_single_folder(
+        &self,
+        folder_name: &str,
+        full_path: &str,
+    ) -> Result<(), FolderError> {
         // Get parent directory path
         let parent_path = if full_path == format!("/{}", folder_name) {
             "/"
@@ -166,7 +142,7 @@ impl InfisicalClient {
         let http_client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(30))
             .build()
-            .context("Failed to create HTTP client")?;
+            .map_err(|e| FolderError::Fatal(format!("Failed to create HTTP client: {e}")))?;
 
         let url = format!(
             "{}/api/v1/folders",
@@ -176,10 +152,11 @@ impl InfisicalClient {
         // Note: We need to get the access token from the authenticated client
         // This is a limitation - the current infisical crate doesn't expose the token
         // For now, we'll need to make a separate auth request
-        let token = self
-            .get_access_token()
-            .await
-            .context("Failed to obtain access token for folder creation")?;
+        let token = self.get_access_token().await.map_err(|e| {
+            FolderError::Fatal(format!(
+                "Failed to obtain access token for folder creation: {e}"
+            ))
+        })?;
 
         let payload = json!({
             "workspaceId": self.config.project_id,
@@ -203,44 +180,26 @@ impl InfisicalClient {
             .json(&payload)
             .send()
             .await
-            .context("Failed to send folder creation request to Infisical API")?;
+            .map_err(|e| {
+                FolderError::Transient(format!(
+                    "Failed to send folder creation request to Infisical API: {e}"
+                ))
+            })?;
 
         let status = response.status();
-
-        if status.is_success() {
-            debug!("Successfully created folder '{}' via REST API", folder_name);
-            Ok(())
-        } else if status.as_u16() == 409 {
-            debug!("Folder '{}' already exists (409 Conflict)", folder_name);
-            Ok(())
+        let error_text = if status.is_success() {
+            String::new()
         } else {
-            let error_text = response
+            response
                 .text()
                 .await
-                .unwrap_or_else(|_| "Unable to read error response".to_string());
+                .unwrap_or_else(|_| "Unable to read error response".to_string())
+        };
 
-            // Check for common "already exists" patterns in different response formats
-            if error_text.to_lowercase().contains("already exists")
-                || error_text.to_lowercase().contains("duplicate")
-                || error_text.to_lowercase().contains("exists")
-            {
-                debug!(
-                    "Folder '{}' already exists (detected from error message)",
-                    folder_name
-                );
-                Ok(())
-            } else {
-                error!(
-                    "Failed to create folder '{}': HTTP {} - {}",
-                    folder_name, status, error_text
-                );
-                Err(anyhow::anyhow!(
-                    "API request failed: HTTP {} - {}",
-                    status,
-                    error_text
-                ))
-            }
-        }
+        // Folders that already exist, e.g. created by a concurrent instance, count as created
+        folders::classify_response(status.as_u16(), &error_text).inspect_err(|e| {
+            error!("Failed to create folder '{}': {}", folder_name, e);
+        })
     }
 
     /// Get access token by making a separate authentication request
@@ -370,45 +329,39 @@ impl InfisicalClient {
         }
 
         // Store each credential component as a separate secret
-        let secrets = vec![
-            ("account_nkey", &credentials.account_nkey),
-            ("account_jwt", &credentials.account_jwt),
-            ("user_nkey", &credentials.user_nkey),
-            ("user_jwt", &credentials.user_jwt),
-            ("user_seed", &credentials.user_seed),
-        ];
-
-        for (key, value) in secrets {
+        for (key, value) in credentials.secrets() {
             let secret_key = String::from(key);
 
-            let create_request = CreateSecretRequest::builder(
-                &secret_key,
-                value,
-                &self.config.project_id,
-                &self.config.environment,
-            )
-            .path(&base_path)
-            .build();
-
-            match client.secrets().create(create_request).await {
-                Ok(_) => {
-                    debug!(
-                        "Successfully stored secret '{}' for workspace '{}'",
-                        secret_key, workspace_slug
-                    );
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to store secret '{}' for workspace '{}': {}",
-                        secret_key, workspace_slug, e
-                    );
-                    return Err(anyhow::anyhow!(
-                        "Failed to store secret '{}': {}",
-                        secret_key,
-                        e
-                    ));
-                }
-            }
+            // Transient Infisical failures are retried with backoff, so a single unavailable
+            // response doesn't fail the whole provisioning
+            secrets::store_with_retry(&secret_key, secrets::STORE_RETRY_DELAY, || async {
+                let create_request = CreateSecretRequest::builder(
+                    &secret_key,
+                    &value,
+                    &self.config.project_id,
+                    &self.config.environment,
+                )
+                .path(&base_path)
+                .build();
+
+                client
+                    .secrets()
+                    .create(create_request)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| secrets::classify_error(&e.to_string()))
+            })
+            .await
+            .inspect_err(|e| {
+                error!(
+                    "Failed to store secret '{}' for workspace '{}': {}",
+                    secret_key, workspace_slug, e
+                )
+            })?;
+            debug!(
+                "Successfully stored secret '{}' for workspace '{}'",
+                secret_key, workspace_slug
+            );
         }
 
         info!(
@@ -432,16 +385,9 @@ impl InfisicalClient {
         let base_path = format!("/nats/workspaces/{}", workspace_slug);
 
         // Retrieve each credential component
-        let secret_keys = [
-            "account_nkey",
-            "account_jwt",
-            "user_nkey",
-            "user_jwt",
-            "user_seed",
-        ];
         let mut secrets = std::collections::HashMap::new();
 
-        for key in &secret_keys {
+        for key in &credentials::SECRET_KEYS {
             let secret_key = format!("nats_{}", key);
             let get_request = GetSecretRequest::builder(
                 &secret_key,
@@ -499,6 +445,19 @@ impl InfisicalClient {
                 .get("user_seed")
                 .ok_or_else(|| anyhow::anyhow!("Missing user_seed"))?
                 .clone(),
+            operator_public_key: secrets
+                .get("operator_public_key")
+                .ok_or_else(|| anyhow::anyhow!("Missing operator_public_key"))?
+                .clone(),
+            // Stored comma separated, see `NatsCredentials::secrets`
+            nats_urls: secrets
+                .get("nats_urls")
+                .ok_or_else(|| anyhow::anyhow!("Missing nats_urls"))?
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect(),
         };
 
         info!(
@@ -582,6 +541,8 @@ mod tests {
             user_nkey: "UTEST123456789ABCDEF".to_string(),
             user_jwt: "eyJ0eXAiOiJKV1QiLCJhbGciOiJFZDI1NTE5LW5rZXkifQ.test.user.jwt".to_string(),
             user_seed: "SUTEST123456789ABCDEFGHIJKLMNOP".to_string(),
+            operator_public_key: "OTEST123456789ABCDEF".to_string(),
+            nats_urls: vec!["nats://localhost:4222".to_string()],
         }
     }
 
@@ -1,11 +1,17 @@
 mod config;
+mod credentials;
 mod database;
+mod folders;
 mod infisical;
 mod nats;
+mod provision;
 mod railway;
+mod railway_client;
+mod secrets;
 
 use anyhow::{Context, Result};
 use config::AppConfig;
+use futures_util::stream::StreamExt;
 use infisical::InfisicalClient;
 use nats::NatsManager;
 use serde::Deserialize;
@@ -20,6 +26,7 @@ struct WorkspaceNotification {
 struct InfraManager {
     app_config: AppConfig,
     pool: PgPool,
+    nats_client: async_nats::Client,
     nats_manager: NatsManager,
     infisical_client: InfisicalClient,
 }
@@ -74,7 +81,7 @@ impl InfraManager {
         let nats_manager = NatsManager::new(
             app_config.nats.operator_seed.clone(),
             app_config.nats.pipestack_account_seed.clone(),
-            nats_client,
+            nats_client.clone(),
             nats_client_sys,
         )?;
 
@@ -84,6 +91,7 @@ impl InfraManager {
         Ok(Self {
             app_config,
             pool,
+            nats_client,
             nats_manager,
             infisical_client,
         })
@@ -110,50 +118,8 @@ impl InfraManager {
                 Ok(workspace) => {
                     info!("Processing new workspace: {:?}", workspace);
 
-                    // Create NATS account and credentials for the workspace
-                    let nats_credentials = match self
-                        .nats_manager
-                        .create_workspace_credentials(&workspace.slug, &self.pool)
-                        .await
-                    {
-                        Ok(credentials) => {
-                            info!("Created NATS credentials for workspace: {}", workspace.slug);
-
-                            // Store credentials in Infisical
-                            if let Err(e) = self
-                                .infisical_client
-                                .store_nats_credentials(&workspace.slug, &credentials)
-                                .await
-                            {
-                                error!(
-                                    "Failed to store NATS credentials in Infisical for workspace {}: {}",
-                                    workspace.slug, e
-                                );
-                            } else {
-                                info!(
-                                    "NATS credentials stored in Infisical for workspace: {}",
-                                    workspace.slug
-                                );
-                            }
-
-                            info!(
-                                "NATS credentials processing completed for workspace: {}",
-                                workspace.slug
-                            );
-                            Some(credentials)
-                        }
-                        Err(e) => {
-                            error!(
-                                "Failed to create NATS credentials for workspace {}: {}",
-                                workspace.slug, e
-                            );
-                            None
-                        }
-                    };
-
-                    if let Some(credentials) = nats_credentials {
-                        railway::try_to_create_service(&self.app_config, workspace, &credentials)
-                            .await;
+                    if let Err(e) = self.provision_workspace(workspace).await {
+                        error!("Failed to provision workspace: {}", e);
                     }
                 }
                 Err(e) => {
@@ -162,6 +128,130 @@ impl InfraManager {
             }
         }
     }
+
+    async fn listen_for_provision_requests(&self) -> Result<()> {
+        let mut subscriber = self
+            .nats_client
+            .subscribe(provision::PROVISION_SUBJECT)
+            .await
+            .context("Failed to subscribe to provision subject")?;
+
+        info!(
+            "Started listening for provision requests on subject: {}",
+            provision::PROVISION_SUBJECT
+        );
+
+        while let Some(message) = subscriber.next().await {
+            let response = provision::handle_provision_request(&message.payload, |workspace| {
+                self.provision_workspace(workspace)
+            })
+            .await;
+            self.reply(message.reply, &response).await?;
+        }
+
+        anyhow::bail!("Provision request subscription closed")
+    }
+
+    async fn listen_for_deprovision_requests(&self) -> Result<()> {
+        let mut subscriber = self
+            .nats_client
+            .subscribe(provision::DEPROVISION_SUBJECT)
+            .await
+            .context("Failed to subscribe to deprovision subject")?;
+
+        info!(
+            "Started listening for deprovision requests on subject: {}",
+            provision::DEPROVISION_SUBJECT
+        );
+
+        while let Some(message) = subscriber.next().await {
+            let response = provision::handle_deprovision_request(&message.payload, |workspace| {
+                self.deprovision_workspace(workspace)
+            })
+            .await;
+            self.reply(message.reply, &response).await?;
+        }
+
+        anyhow::bail!("Deprovision request subscription closed")
+    }
+
+    /// Answer a request with `response` if it asked for a reply
+    async fn reply(
+        &self,
+        reply: Option<async_nats::Subject>,
+        response: &provision::ProvisionResponse,
+    ) -> Result<()> {
+        if let Some(reply) = reply {
+            let payload = serde_json::to_vec(response)?;
+            if let Err(e) = self.nats_client.publish(reply, payload.into()).await {
+                error!("Failed to reply to request: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Create NATS credentials, store them in Infisical and create the Railway service for a workspace
+    async fn provision_workspace(&self, workspace: WorkspaceNotification) -> Result<()> {
+        // Create NATS account and credentials for the workspace
+        let credentials = self
+            .nats_manager
+            .create_workspace_credentials(
+                &workspace.slug,
+                &self.pool,
+                self.app_config
+                    .nats
+                    .workspace_permissions
+                    .get(&workspace.slug)
+                    .cloned(),
+                self.app_config.nats.host_urls.clone(),
+            )
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to create NATS credentials for workspace {}",
+                    workspace.slug
+                )
+            })?;
+        info!("Created NATS credentials for workspace: {}", workspace.slug);
+
+        // Make sure the credentials actually work before handing them out
+        self.nats_manager
+            .verify_credentials(&self.app_config.nats.url, &workspace.slug, &credentials)
+            .await?;
+
+        // Store credentials in Infisical. Credentials that aren't stored are lost, so the service
+        // isn't created with them.
+        self.infisical_client
+            .store_nats_credentials(&workspace.slug, &credentials)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to store NATS credentials in Infisical for workspace {}",
+                    workspace.slug
+                )
+            })?;
+        info!(
+            "NATS credentials stored in Infisical for workspace: {}",
+            workspace.slug
+        );
+
+        railway::try_to_create_service(&self.app_config, workspace, &credentials).await
+    }
+
+    /// Remove the imports of a deleted workspace from the pipestack_account, so it stops growing
+    /// with every workspace ever provisioned. The workspace's own account, credentials and
+    /// Railway service are left in place.
+    async fn deprovision_workspace(&self, workspace: WorkspaceNotification) -> Result<()> {
+        self.nats_manager
+            .remove_workspace_import(&workspace.slug)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to remove the imports of workspace {}",
+                    workspace.slug
+                )
+            })
+    }
 }
 
 #[tokio::main]
@@ -187,7 +277,13 @@ async fn main() -> Result<()> {
     }
 
     info!("Infrastructure Manager service started successfully");
-    infra_manager.listen_for_notifications().await?;
+    // Any listener stopping stops the service rather than leaving it half working, and the
+    // restart policy brings them all back
+    tokio::try_join!(
+        infra_manager.listen_for_notifications(),
+        infra_manager.listen_for_provision_requests(),
+        infra_manager.listen_for_deprovision_requests()
+    )?;
 
     Ok(())
 }
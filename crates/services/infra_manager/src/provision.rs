@@ -0,0 +1,200 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use tracing::{error, info};
+
+use crate::WorkspaceNotification;
+
+/// NATS subject on which operators can request a workspace to be (re)provisioned on demand
+pub const PROVISION_SUBJECT: &str = "provision.workspace";
+
+/// NATS subject on which operators request a deleted workspace to be deprovisioned, taking the
+/// same payload as [`PROVISION_SUBJECT`]
+pub const DEPROVISION_SUBJECT: &str = "deprovision.workspace";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProvisionResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+/// Longest workspace slug accepted for provisioning, the same limit pipeline_manager deploys with
+pub const MAX_SLUG_LEN: usize = 63;
+
+/// Parse a provisioning request payload, e.g. `{"slug": "my-workspace"}`. The slug must match
+/// `^[a-z0-9][a-z0-9-]*$` and be at most [`MAX_SLUG_LEN`] long, as it ends up in NATS subjects
+/// and permissions, where `.`, `*` or `>` would reach into other workspaces.
+pub fn parse_provision_request(payload: &[u8]) -> Result<WorkspaceNotification> {
+    let workspace: WorkspaceNotification =
+        serde_json::from_slice(payload).context("Invalid provision request payload")?;
+
+    let slug = &workspace.slug;
+    let valid = slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-');
+    if slug.is_empty() || !valid {
+        anyhow::bail!(
+            "Invalid workspace slug '{slug}': must be lowercase letters, digits and dashes, not starting with a dash"
+        );
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        anyhow::bail!("Invalid workspace slug '{slug}': must be at most {MAX_SLUG_LEN} characters");
+    }
+
+    Ok(workspace)
+}
+
+/// Parse a provisioning request and dispatch it to the given provisioning function
+pub async fn handle_provision_request<F, Fut>(payload: &[u8], provision: F) -> ProvisionResponse
+where
+    F: FnOnce(WorkspaceNotification) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    handle_request("provision", payload, provision).await
+}
+
+/// Parse a deprovisioning request and dispatch it to the given deprovisioning function
+pub async fn handle_deprovision_request<F, Fut>(payload: &[u8], deprovision: F) -> ProvisionResponse
+where
+    F: FnOnce(WorkspaceNotification) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    handle_request("deprovision", payload, deprovision).await
+}
+
+/// Parse a request to `action` a workspace and dispatch it to `handle`
+async fn handle_request<F, Fut>(action: &str, payload: &[u8], handle: F) -> ProvisionResponse
+where
+    F: FnOnce(WorkspaceNotification) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let workspace = match parse_provision_request(payload) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            error!("Failed to parse {} request: {:#}", action, e);
+            return ProvisionResponse {
+                success: false,
+                message: format!("{e:#}"),
+            };
+        }
+    };
+
+    let slug = workspace.slug.clone();
+    info!("Received {} request for workspace: {}", action, slug);
+
+    match handle(workspace).await {
+        Ok(()) => ProvisionResponse {
+            success: true,
+            message: format!("Workspace {slug} {action}ed"),
+        },
+        Err(e) => {
+            error!("Failed to {} workspace {}: {:#}", action, slug, e);
+            ProvisionResponse {
+                success: false,
+                message: format!("Failed to {action} workspace {slug}: {e:#}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_parse_provision_request_valid() {
+        let workspace = parse_provision_request(br#"{"slug": "acme"}"#).unwrap();
+        assert_eq!(workspace.slug, "acme");
+    }
+
+    #[test]
+    fn test_parse_provision_request_invalid() {
+        assert!(parse_provision_request(b"not json").is_err());
+        assert!(parse_provision_request(br#"{"name": "acme"}"#).is_err());
+        assert!(parse_provision_request(br#"{"slug": "  "}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_provision_request_rejects_unsafe_slugs() {
+        for slug in [">", "*", "a.b", "acme.>", "Acme", "-acme", "acme corp", ""] {
+            let payload = serde_json::json!({ "slug": slug }).to_string();
+            assert!(
+                parse_provision_request(payload.as_bytes()).is_err(),
+                "slug {slug:?} should be rejected"
+            );
+        }
+
+        let longest = serde_json::json!({ "slug": "a".repeat(MAX_SLUG_LEN) }).to_string();
+        assert!(parse_provision_request(longest.as_bytes()).is_ok());
+        let too_long = serde_json::json!({ "slug": "a".repeat(MAX_SLUG_LEN + 1) }).to_string();
+        assert!(parse_provision_request(too_long.as_bytes()).is_err());
+        assert!(parse_provision_request(br#"{"slug": "acme-2"}"#).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handle_provision_request_dispatches_slug() {
+        let provisioned = Mutex::new(Vec::new());
+
+        let response = handle_provision_request(br#"{"slug": "acme"}"#, |workspace| {
+            provisioned.lock().unwrap().push(workspace.slug);
+            async { Ok(()) }
+        })
+        .await;
+
+        assert!(response.success);
+        assert_eq!(*provisioned.lock().unwrap(), vec!["acme".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_handle_provision_request_reports_failure() {
+        let response = handle_provision_request(br#"{"slug": "acme"}"#, |_| async {
+            Err(anyhow::anyhow!("railway unavailable"))
+        })
+        .await;
+
+        assert!(!response.success);
+        assert!(response.message.contains("railway unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_deprovision_request_dispatches_slug() {
+        let deprovisioned = Mutex::new(Vec::new());
+
+        let response = handle_deprovision_request(br#"{"slug": "acme"}"#, |workspace| {
+            deprovisioned.lock().unwrap().push(workspace.slug);
+            async { Ok(()) }
+        })
+        .await;
+
+        assert_eq!(
+            response,
+            ProvisionResponse {
+                success: true,
+                message: "Workspace acme deprovisioned".to_string(),
+            }
+        );
+        assert_eq!(*deprovisioned.lock().unwrap(), vec!["acme".to_string()]);
+
+        let response = handle_deprovision_request(br#"{"slug": ">"}"#, |_| async {
+            panic!("An invalid slug should not be deprovisioned")
+        })
+        .await;
+        assert!(!response.success);
+    }
+
+    #[tokio::test]
+    async fn test_handle_provision_request_does_not_dispatch_invalid_payload() {
+        let mut called = false;
+
+        let response = handle_provision_request(b"{}", |_| {
+            called = true;
+            async { Ok(()) }
+        })
+        .await;
+
+        assert!(!response.success);
+        assert!(!called);
+    }
+}
@@ -1,117 +1,33 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
 use anyhow::Result;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tracing::{error, info, warn};
 
-use crate::{WorkspaceNotification, config::AppConfig, nats::NatsCredentials};
-
-#[derive(Debug, Serialize)]
-struct RailwayServiceSource {
-    repo: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayResponse {
-    data: Option<RailwayData>,
-    errors: Option<Vec<RailwayError>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayData {
-    #[serde(rename = "serviceCreate")]
-    service_create: Option<RailwayService>,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayService {
-    id: String,
-    name: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayError {
-    message: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayDomainCreateResponse {
-    data: Option<RailwayDomainCreateData>,
-    errors: Option<Vec<RailwayError>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayDomainCreateData {
-    #[serde(rename = "serviceDomainCreate")]
-    service_domain_create: Option<RailwayDomain>,
-}
-
-#[derive(Debug, Deserialize)]
-struct RailwayDomain {
-    id: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct DeploymentListResponse {
-    data: Option<DeploymentListData>,
-    errors: Option<Vec<RailwayError>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct DeploymentListData {
-    deployments: DeploymentEdges,
-}
-
-#[derive(Debug, Deserialize)]
-struct DeploymentEdges {
-    edges: Vec<DeploymentEdge>,
-}
-
-#[derive(Debug, Deserialize, Clone)]
-struct DeploymentEdge {
-    node: DeploymentNode,
-}
-
-#[derive(Debug, Deserialize, Clone)]
-struct DeploymentNode {
-    #[serde(rename = "createdAt")]
-    created_at: String,
-    status: String,
-}
+use crate::railway_client::{
+    DeploymentNode, RailwayClient, ServiceCreateInput, ServiceInstanceUpdateInput, ServiceSource,
+};
+use crate::{WorkspaceNotification, config::AppConfig, credentials::NatsCredentials};
 
-#[derive(Debug, Serialize)]
-struct ServiceInstanceUpdateInput {
-    builder: String,
-    #[serde(rename = "railwayConfigFile")]
-    railway_config_file: String,
-    region: String,
-    #[serde(rename = "rootDirectory")]
-    root_directory: String,
-}
+const SERVICE_TARGET_PORT: u16 = 8000;
 
-#[derive(Debug, Serialize)]
-struct RailwayServiceInput {
-    branch: String,
-    #[serde(rename = "environmentId")]
-    environment_id: String,
-    name: String,
-    #[serde(rename = "projectId")]
-    project_id: String,
-    source: RailwayServiceSource,
-    variables: std::collections::HashMap<String, String>,
-}
+/// Most recent deployments fetched when polling. The redeploy is always among the newest.
+const DEPLOYMENT_LIST_LIMIT: u32 = 10;
 
 pub async fn try_to_create_service(
     app_config: &AppConfig,
     workspace: WorkspaceNotification,
     nats_credentials: &NatsCredentials,
-) {
+) -> Result<()> {
     // Try to create Railway service with retries
+    let client = RailwayClient::new(app_config.railway.clone());
     let mut retry_count = 0;
     let mut success = false;
 
     while retry_count < app_config.service.max_retries && !success {
-        match create_railway_service(app_config, &workspace, nats_credentials).await {
+        match create_railway_service(&client, app_config, &workspace, nats_credentials).await {
             Ok(_) => {
                 success = true;
                 info!(
@@ -139,47 +55,34 @@ pub async fn try_to_create_service(
     }
 
     if !success {
-        error!(
+        anyhow::bail!(
             "Failed to create Railway service for workspace {} after {} attempts",
-            workspace.slug, app_config.service.max_retries
+            workspace.slug,
+            app_config.service.max_retries
         );
     }
+
+    Ok(())
 }
 
 async fn create_railway_service(
+    client: &RailwayClient,
     app_config: &AppConfig,
     workspace: &WorkspaceNotification,
     nats_credentials: &NatsCredentials,
 ) -> Result<()> {
     let service_name = format!("{}-{}", app_config.service.name_prefix, &workspace.slug);
 
-    let mutation = r#"
-            mutation ServiceCreate($input: ServiceCreateInput!) {
-                serviceCreate(input: $input) {
-                    id
-                    name
-                }
-            }
-        "#;
-
-    let mut env_variables = std::collections::HashMap::new();
+    let mut env_variables = HashMap::new();
     env_variables.insert(
             "RUST_LOG".to_string(),
             "debug,hyper=info,async_nats=info,oci_client=info,cranelift_codegen=warn,opentelemetry-http=warn".to_string(),
         );
-    env_variables.insert(
-        "WASMCLOUD_CTL_HOST".to_string(),
-        "${{nats.RAILWAY_PRIVATE_DOMAIN}}".to_string(),
-    );
     env_variables.insert("WASMCLOUD_LOG_LEVEL".to_string(), "debug".to_string());
     env_variables.insert(
         "WASMCLOUD_OCI_ALLOWED_INSECURE".to_string(),
         "${{registry.RAILWAY_PRIVATE_DOMAIN}}:5000".to_string(),
     );
-    env_variables.insert(
-        "WASMCLOUD_RPC_HOST".to_string(),
-        "${{nats.RAILWAY_PRIVATE_DOMAIN}}".to_string(),
-    );
     env_variables.insert(
         "WASMCLOUD_OBSERVABILITY_ENABLED".to_string(),
         "true".to_string(),
@@ -190,143 +93,74 @@ async fn create_railway_service(
     );
     env_variables.insert("WASMCLOUD_LATTICE".to_string(), workspace.slug.clone());
     env_variables.insert("WASMCLOUD_JS_DOMAIN".to_string(), "pipestack".to_string());
+    env_variables.extend(nats_credentials.host_env_variables());
+
+    let input = ServiceCreateInput {
+        branch: app_config.railway.default_branch.clone(),
+        environment_id: app_config.railway.environment_id.clone(),
+        name: service_name.clone(),
+        project_id: app_config.railway.project_id.clone(),
+        source: ServiceSource {
+            repo: app_config.railway.default_template_repo.clone(),
+        },
+        variables: env_variables,
+    };
 
-    env_variables.insert(
-        "WASMCLOUD_NATS_JWT".to_string(),
-        nats_credentials.user_jwt.clone(),
-    );
-    env_variables.insert(
-        "WASMCLOUD_NATS_SEED".to_string(),
-        nats_credentials.user_seed.clone(),
+    info!(
+        "Creating Railway service: {}. Input: {:?}",
+        service_name, input
     );
 
-    let variables = json!({
-        "input": RailwayServiceInput {
-            branch: app_config.railway.default_branch.clone(),
-            environment_id: app_config.railway.environment_id.clone(),
-            name: service_name.clone(),
-            project_id: app_config.railway.project_id.clone(),
-            source: RailwayServiceSource {
-                repo: app_config.railway.default_template_repo.clone(),
-            },
-            variables: env_variables,
-        }
-    });
-
+    let service = client.create_service(input).await?;
     info!(
-        "Creating Railway service: {}. GraphQL query variables: {:?}",
-        service_name, variables
+        "Successfully created Railway service: {} (ID: {})",
+        service.name, service.id
     );
 
-    let response_text =
-        make_railway_graphql_request(app_config, mutation, variables, "service creation").await?;
-
-    let railway_response: RailwayResponse = serde_json::from_str(&response_text)?;
-
-    if let Some(errors) = railway_response.errors {
-        for error in errors {
-            error!("Railway API error: {}", error.message);
-        }
-        return Err(anyhow::anyhow!("Railway API returned errors"));
-    }
-
-    if let Some(data) = railway_response.data {
-        if let Some(service) = data.service_create {
-            info!(
-                "Successfully created Railway service: {} (ID: {})",
-                service.name, service.id
-            );
-
-            // Update the service instance configuration
-            update_service_instance(app_config, &service.id).await?;
-
-            // Create a domain for the service
-            create_service_domain(app_config, &service.id, &workspace.slug).await?;
-
-            // Redeploy the service instance
-            redeploy_service_instance(app_config, &service.id).await?;
+    // Update the service instance configuration
+    update_service_instance(client, &service.id).await?;
+
+    // Create a domain for the service
+    create_service_domain(client, &service.id, &workspace.slug).await?;
+
+    // Remember existing deployments so only the redeploy is awaited. Without them an earlier
+    // deployment could be taken for the redeploy, so failing to list them fails the setup.
+    let previous_deployments: HashSet<String> = client
+        .list_deployments(&service.id, DEPLOYMENT_LIST_LIMIT)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list deployments before redeploy: {e}"))?
+        .into_iter()
+        .map(|d| d.id)
+        .collect();
+
+    // Redeploy the service instance
+    info!("Redeploying Railway service instance: {}", service.id);
+    client.redeploy(&service.id).await?;
+    info!(
+        "Successfully redeployed Railway service instance: {}",
+        service.id
+    );
 
-            // Wait for deployment to succeed
-            wait_for_deployment_success(app_config, &service.id).await?;
+    // Wait for deployment to succeed
+    wait_for_deployment_success(client, &service.id, &previous_deployments).await?;
 
-            // Notify pipeline manager about the new deployment
-            notify_pipeline_manager(&workspace.slug).await?;
-        } else {
-            warn!("Railway service creation succeeded but no service data returned");
-        }
-    } else {
-        warn!("Railway service creation response contained no data");
-    }
+    // Notify pipeline manager about the new deployment
+    notify_pipeline_manager(&workspace.slug).await?;
 
     Ok(())
 }
 
-async fn make_railway_graphql_request(
-    app_config: &AppConfig,
-    mutation: &str,
-    variables: serde_json::Value,
-    operation_name: &str,
-) -> Result<String> {
-    let request_body = json!({
-        "query": mutation,
-        "variables": variables
-    });
-
-    info!("Making Railway GraphQL request: {}", operation_name);
-
-    let response = Client::new()
-        .post(&app_config.railway.api_url)
-        .header(
-            "Authorization",
-            format!("Bearer {}", app_config.railway.token),
-        )
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unable to read response body".to_string());
-        return Err(anyhow::anyhow!(
-            "Railway {} failed with status {}: {}",
-            operation_name,
-            status,
-            body
-        ));
-    }
-
-    let response_text = response.text().await?;
-    info!("{} response: {}", operation_name, response_text);
-
-    Ok(response_text)
-}
-
-async fn update_service_instance(app_config: &AppConfig, service_id: &str) -> Result<()> {
-    let mutation = r#"
-        mutation ServiceInstanceUpdate($serviceId: String!, $environmentId: String, $input: ServiceInstanceUpdateInput!) {
-            serviceInstanceUpdate(serviceId: $serviceId, environmentId: $environmentId, input: $input)
-        }
-    "#;
-
-    let variables = json!({
-        "serviceId": service_id,
-        "environmentId": app_config.railway.environment_id,
-        "input": ServiceInstanceUpdateInput {
-            builder: "NIXPACKS".to_string(),
-            railway_config_file: "./services/wasmcloud/railway.json".to_string(),
-            region: "us-east4-eqdc4a".to_string(),
-            root_directory: "/services/wasmcloud".to_string(),
-        }
-    });
+async fn update_service_instance(client: &RailwayClient, service_id: &str) -> Result<()> {
+    let input = ServiceInstanceUpdateInput {
+        builder: "NIXPACKS".to_string(),
+        railway_config_file: "./services/wasmcloud/railway.json".to_string(),
+        region: "us-east4-eqdc4a".to_string(),
+        root_directory: "/services/wasmcloud".to_string(),
+    };
 
     info!("Updating Railway service instance: {}", service_id);
 
-    make_railway_graphql_request(app_config, mutation, variables, "service instance update")
-        .await?;
+    client.update_service_instance(service_id, input).await?;
 
     info!(
         "Successfully updated Railway service instance: {}",
@@ -336,96 +170,30 @@ async fn update_service_instance(app_config: &AppConfig, service_id: &str) -> Re
 }
 
 async fn create_service_domain(
-    app_config: &AppConfig,
+    client: &RailwayClient,
     service_id: &str,
     workspace_slug: &str,
 ) -> Result<()> {
-    let mutation = r#"
-        mutation ServiceDomainCreate($input: ServiceDomainCreateInput!) {
-            serviceDomainCreate(input: $input) {
-                id
-            }
-        }
-    "#;
-
-    let variables = json!({
-        "input": {
-            "environmentId": app_config.railway.environment_id,
-            "serviceId": service_id,
-            "targetPort": 8000
-        }
-    });
-
     info!("Creating domain for Railway service: {}", service_id);
 
-    let response_text =
-        make_railway_graphql_request(app_config, mutation, variables, "service domain create")
-            .await?;
-
-    let domain_response: RailwayDomainCreateResponse = serde_json::from_str(&response_text)?;
-
-    if let Some(errors) = domain_response.errors {
-        for error in errors {
-            error!("Railway domain creation error: {}", error.message);
-        }
-        return Err(anyhow::anyhow!(
-            "Railway domain creation API returned errors"
-        ));
-    }
-
-    if let Some(data) = domain_response.data {
-        if let Some(domain) = data.service_domain_create {
-            info!(
-                "Successfully created domain for Railway service: {} (Domain ID: {})",
-                service_id, domain.id
-            );
-
-            // Update the domain with a better name
-            update_service_domain(app_config, &domain.id, service_id, workspace_slug).await?;
-        } else {
-            return Err(anyhow::anyhow!(
-                "Domain creation succeeded but no domain data returned"
-            ));
-        }
-    } else {
-        return Err(anyhow::anyhow!(
-            "Domain creation response contained no data"
-        ));
-    }
-
-    Ok(())
-}
+    let domain_id = client
+        .create_domain(service_id, SERVICE_TARGET_PORT)
+        .await?;
+    info!(
+        "Successfully created domain for Railway service: {} (Domain ID: {})",
+        service_id, domain_id
+    );
 
-async fn update_service_domain(
-    app_config: &AppConfig,
-    domain_id: &str,
-    service_id: &str,
-    workspace_slug: &str,
-) -> Result<()> {
+    // Update the domain with a better name
     let domain_name = format!("pipestack-{workspace_slug}.up.railway.app");
-
-    let mutation = r#"
-        mutation serviceDomainUpdate($input: ServiceDomainUpdateInput!) {
-            serviceDomainUpdate(input: $input)
-        }
-    "#;
-
-    let variables = json!({
-        "input": {
-            "domain": domain_name,
-            "environmentId": app_config.railway.environment_id,
-            "serviceDomainId": domain_id,
-            "serviceId": service_id,
-            "targetPort": 8000
-        }
-    });
-
     info!(
         "Updating domain for Railway service: {} with domain name: {}",
         service_id, domain_name
     );
 
-    make_railway_graphql_request(app_config, mutation, variables, "service domain update").await?;
+    client
+        .update_domain(&domain_id, service_id, &domain_name, SERVICE_TARGET_PORT)
+        .await?;
 
     info!(
         "Successfully updated domain for Railway service: {} to {}",
@@ -434,128 +202,117 @@ async fn update_service_domain(
     Ok(())
 }
 
-async fn redeploy_service_instance(app_config: &AppConfig, service_id: &str) -> Result<()> {
-    let mutation = r#"
-        mutation serviceInstanceRedeploy($serviceId: String!, $environmentId: String!) {
-            serviceInstanceRedeploy(serviceId: $serviceId, environmentId: $environmentId)
-        }
-    "#;
-
-    let variables = json!({
-        "serviceId": service_id,
-        "environmentId": app_config.railway.environment_id
-    });
+#[derive(Debug, PartialEq)]
+enum DeploymentStatus {
+    Succeeded,
+    Failed,
+    Pending,
+}
 
-    info!("Redeploying Railway service instance: {}", service_id);
+impl DeploymentStatus {
+    fn classify(status: &str) -> Self {
+        match status {
+            "SUCCESS" => DeploymentStatus::Succeeded,
+            "FAILED" | "CRASHED" | "REMOVED" => DeploymentStatus::Failed,
+            _ => DeploymentStatus::Pending,
+        }
+    }
+}
 
-    make_railway_graphql_request(app_config, mutation, variables, "service instance redeploy")
-        .await?;
+/// Returns the most recent deployment that is not in `previous`.
+fn latest_new_deployment<'a>(
+    deployments: &'a [DeploymentNode],
+    previous: &HashSet<String>,
+) -> Option<&'a DeploymentNode> {
+    deployments
+        .iter()
+        .filter(|node| !previous.contains(&node.id))
+        .max_by(|a, b| a.created_at.cmp(&b.created_at))
+}
 
-    info!(
-        "Successfully redeployed Railway service instance: {}",
-        service_id
-    );
-    Ok(())
+/// Exponential backoff capped at `max_ms`, with the upper half of the delay
+/// scaled by `jitter` (expected in `0.0..1.0`).
+fn poll_delay(attempt: u32, base_ms: u64, max_ms: u64, jitter: f64) -> Duration {
+    let backoff = base_ms
+        .saturating_mul(1u64.checked_shl(attempt).unwrap_or(u64::MAX))
+        .min(max_ms);
+    let half = backoff / 2;
+    Duration::from_millis(half + ((backoff - half) as f64 * jitter) as u64)
 }
 
-async fn wait_for_deployment_success(app_config: &AppConfig, service_id: &str) -> Result<()> {
-    let query = r#"
-        query GetDeployments($input: DeploymentListInput!) {
-            deployments(input: $input) {
-                edges {
-                    node {
-                        createdAt
-                        status
-                    }
-                }
-            }
-        }
-    "#;
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    f64::from(nanos % 1000) / 1000.0
+}
 
-    let max_attempts = 90;
-    let sleep_duration = tokio::time::Duration::from_secs(5);
-    let mut attempts = 0;
+async fn wait_for_deployment_success(
+    client: &RailwayClient,
+    service_id: &str,
+    previous_deployments: &HashSet<String>,
+) -> Result<()> {
+    let railway = client.config();
+    let deadline = Instant::now() + Duration::from_secs(railway.deploy_timeout_secs);
+    let mut attempt = 0;
 
     info!("Checking deployment status for service: {}", service_id);
 
     loop {
-        attempts += 1;
-
-        if attempts > max_attempts {
-            return Err(anyhow::anyhow!(
-                "Deployment did not succeed within the timeout period"
-            ));
-        }
-
-        let variables = json!({
-            "input": {
-                "environmentId": app_config.railway.environment_id,
-                "serviceId": service_id.to_string(),
-                "includeDeleted": false
-            }
-        });
-
-        match make_railway_graphql_request(app_config, query, variables, "deployment status check")
+        match client
+            .list_deployments(service_id, DEPLOYMENT_LIST_LIMIT)
             .await
         {
-            Ok(response_text) => {
-                let deployment_response: DeploymentListResponse =
-                    match serde_json::from_str(&response_text) {
-                        Ok(response) => response,
-                        Err(e) => {
-                            warn!("Failed to parse deployment status response: {}", e);
-                            tokio::time::sleep(sleep_duration).await;
-                            continue;
-                        }
-                    };
-
-                if let Some(errors) = deployment_response.errors {
-                    for error in errors {
-                        error!("Railway deployment status error: {}", error.message);
+            Ok(deployments) => match latest_new_deployment(&deployments, previous_deployments) {
+                Some(deployment) => match DeploymentStatus::classify(&deployment.status) {
+                    DeploymentStatus::Succeeded => {
+                        info!(
+                            "Deployment succeeded! Status: {}, Created: {}",
+                            deployment.status, deployment.created_at
+                        );
+                        return Ok(());
                     }
-                    tokio::time::sleep(sleep_duration).await;
-                    continue;
-                }
-
-                if let Some(data) = deployment_response.data {
-                    let deployments = &data.deployments.edges;
-
-                    if deployments.len() >= 2 {
-                        // Sort deployments by createdAt (most recent first)
-                        let mut sorted_deployments = deployments.to_vec();
-                        sorted_deployments
-                            .sort_by(|a, b| b.node.created_at.cmp(&a.node.created_at));
-
-                        let most_recent = &sorted_deployments[0].node;
-
-                        if most_recent.status == "SUCCESS" {
-                            info!(
-                                "Deployment succeeded! Status: {}, Created: {}",
-                                most_recent.status, most_recent.created_at
-                            );
-                            return Ok(());
-                        } else {
-                            info!(
-                                "Most recent deployment status: {} (waiting for SUCCESS)",
-                                most_recent.status
-                            );
-                        }
-                    } else {
+                    DeploymentStatus::Failed => {
+                        return Err(anyhow::anyhow!(
+                            "Deployment {} finished with status {}",
+                            deployment.id,
+                            deployment.status
+                        ));
+                    }
+                    DeploymentStatus::Pending => {
                         info!(
-                            "Waiting for deployments to appear (found: {})",
-                            deployments.len()
+                            "Most recent deployment status: {} (waiting for SUCCESS)",
+                            deployment.status
                         );
                     }
-                } else {
-                    warn!("Deployment status response contained no data");
+                },
+                None => {
+                    info!(
+                        "Waiting for the redeploy to appear (found: {})",
+                        deployments.len()
+                    );
                 }
-            }
+            },
             Err(e) => {
                 warn!("Failed to check deployment status: {}", e);
             }
         }
 
-        tokio::time::sleep(sleep_duration).await;
+        let delay = poll_delay(
+            attempt,
+            railway.deploy_poll_interval_ms,
+            railway.deploy_poll_max_interval_ms,
+            jitter(),
+        );
+        if Instant::now() + delay > deadline {
+            return Err(anyhow::anyhow!(
+                "Deployment did not succeed within the timeout period"
+            ));
+        }
+
+        attempt += 1;
+        tokio::time::sleep(delay).await;
     }
 }
 
@@ -601,3 +358,103 @@ async fn notify_pipeline_manager(workspace_slug: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(id: &str, created_at: &str, status: &str) -> DeploymentNode {
+        DeploymentNode {
+            id: id.to_string(),
+            created_at: created_at.to_string(),
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_classify_deployment_status() {
+        assert_eq!(
+            DeploymentStatus::classify("SUCCESS"),
+            DeploymentStatus::Succeeded
+        );
+        assert_eq!(
+            DeploymentStatus::classify("FAILED"),
+            DeploymentStatus::Failed
+        );
+        assert_eq!(
+            DeploymentStatus::classify("CRASHED"),
+            DeploymentStatus::Failed
+        );
+        assert_eq!(
+            DeploymentStatus::classify("REMOVED"),
+            DeploymentStatus::Failed
+        );
+        assert_eq!(
+            DeploymentStatus::classify("BUILDING"),
+            DeploymentStatus::Pending
+        );
+        assert_eq!(
+            DeploymentStatus::classify("DEPLOYING"),
+            DeploymentStatus::Pending
+        );
+        assert_eq!(
+            DeploymentStatus::classify("QUEUED"),
+            DeploymentStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_latest_new_deployment_with_single_deployment() {
+        let deployments = vec![deployment("d1", "2025-01-01T00:00:00.000Z", "BUILDING")];
+        let latest = latest_new_deployment(&deployments, &HashSet::new()).unwrap();
+        assert_eq!(latest.id, "d1");
+    }
+
+    #[test]
+    fn test_latest_new_deployment_ignores_previous() {
+        let deployments = vec![
+            deployment("d1", "2025-01-01T00:00:00.000Z", "FAILED"),
+            deployment("d3", "2025-01-01T00:02:00.000Z", "SUCCESS"),
+            deployment("d2", "2025-01-01T00:01:00.000Z", "BUILDING"),
+        ];
+        let previous = HashSet::from(["d1".to_string()]);
+        assert_eq!(
+            latest_new_deployment(&deployments, &previous).unwrap().id,
+            "d3"
+        );
+
+        let previous = HashSet::from(["d1".to_string(), "d2".to_string(), "d3".to_string()]);
+        assert!(latest_new_deployment(&deployments, &previous).is_none());
+    }
+
+    #[test]
+    fn test_latest_new_deployment_picks_most_recent() {
+        let deployments = vec![
+            deployment("d2", "2025-03-01T10:15:00.000Z", "REMOVED"),
+            deployment("d4", "2025-03-02T08:00:00.000Z", "DEPLOYING"),
+            deployment("d1", "2024-12-31T23:59:59.000Z", "SUCCESS"),
+            deployment("d3", "2025-03-02T07:59:59.999Z", "FAILED"),
+        ];
+        let latest = latest_new_deployment(&deployments, &HashSet::new()).unwrap();
+        assert_eq!(latest.id, "d4");
+        assert_eq!(
+            DeploymentStatus::classify(&latest.status),
+            DeploymentStatus::Pending
+        );
+
+        assert!(latest_new_deployment(&[], &HashSet::new()).is_none());
+    }
+
+    #[test]
+    fn test_poll_delay_backs_off_with_jitter() {
+        assert_eq!(poll_delay(0, 2000, 15000, 0.0), Duration::from_millis(1000));
+        assert_eq!(poll_delay(0, 2000, 15000, 0.5), Duration::from_millis(1500));
+        assert_eq!(poll_delay(1, 2000, 15000, 0.0), Duration::from_millis(2000));
+        assert_eq!(poll_delay(3, 2000, 15000, 0.0), Duration::from_millis(7500));
+        assert_eq!(
+            poll_delay(64, 2000, 15000, 0.0),
+            Duration::from_millis(7500)
+        );
+        assert!(poll_delay(10, 2000, 15000, 0.5) <= Duration::from_millis(15000));
+    }
+}
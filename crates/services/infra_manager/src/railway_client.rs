@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{error, info};
+
+use crate::config::RailwayConfig;
+
+/// Errors returned by the Railway GraphQL API
+#[derive(Debug)]
+pub enum RailwayError {
+    /// The request could not be sent or its body could not be read
+    Request {
+        operation: String,
+        source: reqwest::Error,
+    },
+    /// Railway responded with a non-success HTTP status
+    Http {
+        operation: String,
+        status: u16,
+        body: String,
+    },
+    /// The response contained a GraphQL `errors` array
+    Api {
+        operation: String,
+        messages: Vec<String>,
+    },
+    /// The response could not be parsed
+    Parse {
+        operation: String,
+        source: serde_json::Error,
+    },
+    /// The response contained neither data nor errors
+    MissingData { operation: String },
+}
+
+impl fmt::Display for RailwayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RailwayError::Request { operation, source } => {
+                write!(f, "Railway {operation} request failed: {source}")
+            }
+            RailwayError::Http {
+                operation,
+                status,
+                body,
+            } => write!(f, "Railway {operation} failed with status {status}: {body}"),
+            RailwayError::Api {
+                operation,
+                messages,
+            } => write!(
+                f,
+                "Railway {operation} returned errors: {}",
+                messages.join("; ")
+            ),
+            RailwayError::Parse { operation, source } => {
+                write!(f, "Failed to parse Railway {operation} response: {source}")
+            }
+            RailwayError::MissingData { operation } => {
+                write!(f, "Railway {operation} response contained no data")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RailwayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RailwayError::Request { source, .. } => Some(source),
+            RailwayError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphqlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+/// Parses a GraphQL response body, mapping the `errors` array to [`RailwayError::Api`]
+fn parse_response<T: DeserializeOwned>(operation: &str, body: &str) -> Result<T, RailwayError> {
+    let response: GraphqlResponse<T> =
+        serde_json::from_str(body).map_err(|source| RailwayError::Parse {
+            operation: operation.to_string(),
+            source,
+        })?;
+
+    if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+        for error in &errors {
+            error!("Railway {} error: {}", operation, error.message);
+        }
+        return Err(RailwayError::Api {
+            operation: operation.to_string(),
+            messages: errors.into_iter().map(|error| error.message).collect(),
+        });
+    }
+
+    response.data.ok_or_else(|| RailwayError::MissingData {
+        operation: operation.to_string(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RailwayService {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeploymentNode {
+    pub id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceSource {
+    pub repo: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceInstanceUpdateInput {
+    pub builder: String,
+    #[serde(rename = "railwayConfigFile")]
+    pub railway_config_file: String,
+    pub region: String,
+    #[serde(rename = "rootDirectory")]
+    pub root_directory: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceCreateInput {
+    pub branch: String,
+    #[serde(rename = "environmentId")]
+    pub environment_id: String,
+    pub name: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub source: ServiceSource,
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceCreateData {
+    #[serde(rename = "serviceCreate")]
+    service_create: RailwayService,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceDomainCreateData {
+    #[serde(rename = "serviceDomainCreate")]
+    service_domain_create: ServiceDomain,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceDomain {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentListData {
+    deployments: DeploymentEdges,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentEdges {
+    #[serde(default)]
+    edges: Vec<DeploymentEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentEdge {
+    node: DeploymentNode,
+}
+
+/// Typed wrapper around the Railway GraphQL API
+#[derive(Clone)]
+pub struct RailwayClient {
+    http: Client,
+    config: RailwayConfig,
+}
+
+impl RailwayClient {
+    pub fn new(config: RailwayConfig) -> Self {
+        Self {
+            http: Client::new(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &RailwayConfig {
+        &self.config
+    }
+
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, RailwayError> {
+        let request_error = |source| RailwayError::Request {
+            operation: operation.to_string(),
+            source,
+        };
+
+        info!("Making Railway GraphQL request: {}", operation);
+
+        let response = self
+            .http
+            .post(&self.config.api_url)
+            .header("Authorization", format!("Bearer {}", self.config.token))
+            .header("Content-Type", "application/json")
+            .json(&json!({
+                "query": query,
+                "variables": variables
+            }))
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unable to read response body".to_string());
+            return Err(RailwayError::Http {
+                operation: operation.to_string(),
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let body = response.text().await.map_err(request_error)?;
+        info!("{} response: {}", operation, body);
+
+        parse_response(operation, &body)
+    }
+
+    pub async fn create_service(
+        &self,
+        input: ServiceCreateInput,
+    ) -> Result<RailwayService, RailwayError> {
+        let mutation = r#"
+            mutation ServiceCreate($input: ServiceCreateInput!) {
+                serviceCreate(input: $input) {
+                    id
+                    name
+                }
+            }
+        "#;
+
+        let data: ServiceCreateData = self
+            .execute("service creation", mutation, json!({ "input": input }))
+            .await?;
+        Ok(data.service_create)
+    }
+
+    pub async fn update_service_instance(
+        &self,
+        service_id: &str,
+        input: ServiceInstanceUpdateInput,
+    ) -> Result<(), RailwayError> {
+        let mutation = r#"
+            mutation ServiceInstanceUpdate($serviceId: String!, $environmentId: String, $input: ServiceInstanceUpdateInput!) {
+                serviceInstanceUpdate(serviceId: $serviceId, environmentId: $environmentId, input: $input)
+            }
+        "#;
+
+        let variables = json!({
+            "serviceId": service_id,
+            "environmentId": self.config.environment_id,
+            "input": input
+        });
+
+        self.execute::<serde_json::Value>("service instance update", mutation, variables)
+            .await?;
+        Ok(())
+    }
+
+    /// Creates a domain for the service and returns its ID
+    pub async fn create_domain(
+        &self,
+        service_id: &str,
+        target_port: u16,
+    ) -> Result<String, RailwayError> {
+        let mutation = r#"
+            mutation ServiceDomainCreate($input: ServiceDomainCreateInput!) {
+                serviceDomainCreate(input: $input) {
+                    id
+                }
+            }
+        "#;
+
+        let variables = json!({
+            "input": {
+                "environmentId": self.config.environment_id,
+                "serviceId": service_id,
+                "targetPort": target_port
+            }
+        });
+
+        let data: ServiceDomainCreateData = self
+            .execute("service domain create", mutation, variables)
+            .await?;
+        Ok(data.service_domain_create.id)
+    }
+
+    pub async fn update_domain(
+        &self,
+        domain_id: &str,
+        service_id: &str,
+        domain: &str,
+        target_port: u16,
+    ) -> Result<(), RailwayError> {
+        let mutation = r#"
+            mutation serviceDomainUpdate($input: ServiceDomainUpdateInput!) {
+                serviceDomainUpdate(input: $input)
+            }
+        "#;
+
+        let variables = json!({
+            "input": {
+                "domain": domain,
+                "environmentId": self.config.environment_id,
+                "serviceDomainId": domain_id,
+                "serviceId": service_id,
+                "targetPort": target_port
+            }
+        });
+
+        self.execute::<serde_json::Value>("service domain update", mutation, variables)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn redeploy(&self, service_id: &str) -> Result<(), RailwayError> {
+        let mutation = r#"
+            mutation serviceInstanceRedeploy($serviceId: String!, $environmentId: String!) {
+                serviceInstanceRedeploy(serviceId: $serviceId, environmentId: $environmentId)
+            }
+        "#;
+
+        let variables = json!({
+            "serviceId": service_id,
+            "environmentId": self.config.environment_id
+        });
+
+        self.execute::<serde_json::Value>("service instance redeploy", mutation, variables)
+            .await?;
+        Ok(())
+    }
+
+    /// Lists at most `limit` deployments of the service, most recent first
+    pub async fn list_deployments(
+        &self,
+        service_id: &str,
+        limit: u32,
+    ) -> Result<Vec<DeploymentNode>, RailwayError> {
+        let query = r#"
+            query GetDeployments($input: DeploymentListInput!, $first: Int) {
+                deployments(input: $input, first: $first) {
+                    edges {
+                        node {
+                            id
+                            createdAt
+                            status
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let variables = json!({
+            "input": {
+                "environmentId": self.config.environment_id,
+                "serviceId": service_id,
+                "includeDeleted": false
+            },
+            "first": limit
+        });
+
+        let data: DeploymentListData = self
+            .execute("deployment status check", query, variables)
+            .await?;
+        Ok(data
+            .deployments
+            .edges
+            .into_iter()
+            .map(|edge| edge.node)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_graphql_error_response() {
+        let body = r#"{
+            "data": null,
+            "errors": [
+                {"message": "Not Authorized", "path": ["serviceCreate"]},
+                {"message": "Project not found"}
+            ]
+        }"#;
+
+        let error = parse_response::<ServiceCreateData>("service creation", body).unwrap_err();
+        match &error {
+            RailwayError::Api {
+                operation,
+                messages,
+            } => {
+                assert_eq!(operation, "service creation");
+                assert_eq!(messages, &["Not Authorized", "Project not found"]);
+            }
+            other => panic!("expected an API error, got {other:?}"),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Railway service creation returned errors: Not Authorized; Project not found"
+        );
+    }
+
+    #[test]
+    fn test_parse_successful_response() {
+        let body = r#"{"data": {"serviceCreate": {"id": "svc-1", "name": "wasmcloud-acme"}}}"#;
+        let data = parse_response::<ServiceCreateData>("service creation", body).unwrap();
+        assert_eq!(data.service_create.id, "svc-1");
+        assert_eq!(data.service_create.name, "wasmcloud-acme");
+    }
+
+    #[test]
+    fn test_parse_response_without_data() {
+        let error = parse_response::<ServiceCreateData>("service creation", r#"{"data": null}"#)
+            .unwrap_err();
+        assert!(matches!(error, RailwayError::MissingData { .. }));
+
+        let error =
+            parse_response::<ServiceCreateData>("service creation", "not json").unwrap_err();
+        assert!(matches!(error, RailwayError::Parse { .. }));
+    }
+
+    #[test]
+    fn test_parse_empty_deployment_list() {
+        let body = r#"{"data": {"deployments": {"edges": []}}}"#;
+        let data = parse_response::<DeploymentListData>("deployment status check", body).unwrap();
+        assert!(data.deployments.edges.is_empty());
+
+        let body = r#"{"data": {"deployments": {}}}"#;
+        let data = parse_response::<DeploymentListData>("deployment status check", body).unwrap();
+        assert!(data.deployments.edges.is_empty());
+    }
+}
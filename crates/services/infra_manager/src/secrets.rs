@@ -0,0 +1,170 @@
+//! Storing secrets in Infisical with retries. A transient failure, e.g. Infisical answering 503
+//! or 429 while under load, is retried with exponential backoff instead of failing the whole
+//! provisioning. Auth and validation errors won't succeed on retry, so they fail right away.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Attempts at storing a single secret before giving up on it
+pub const MAX_STORE_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry of a secret, doubled on each further retry
+pub const STORE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Failure to store a secret
+#[derive(Debug, PartialEq, Eq)]
+pub enum StoreError {
+    /// May succeed when retried, e.g. Infisical was unavailable or rate limited the request
+    Transient(String),
+    Fatal(String),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transient(message) | Self::Fatal(message) => f.write_str(message),
+        }
+    }
+}
+
+/// HTTP status an Infisical client error reports, the first three-digit number in it that is
+/// one, e.g. 503 in "HTTP error 503: Service Unavailable"
+fn status_code(message: &str) -> Option<u16> {
+    message
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| digits.len() == 3)
+        .filter_map(|digits| digits.parse().ok())
+        .find(|status| (100..600).contains(status))
+}
+
+/// Outcome of a failed store from the client's error. Server errors, rate limiting and errors
+/// without a status, e.g. a dropped connection, are transient. Any other client error, such as
+/// failed authentication or a rejected secret, is fatal.
+pub fn classify_error(message: &str) -> StoreError {
+    match status_code(message) {
+        Some(429) => StoreError::Transient(message.to_string()),
+        Some(400..500) => StoreError::Fatal(message.to_string()),
+        _ => StoreError::Transient(message.to_string()),
+    }
+}
+
+/// Delay before retrying after `attempt` failed attempts
+pub fn retry_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+}
+
+/// Store the secret `key` with `store`, attempting it up to `MAX_STORE_ATTEMPTS` times while it
+/// fails with a transient error
+pub async fn store_with_retry<F, Fut>(key: &str, base_delay: Duration, mut store: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), StoreError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match store().await {
+            Ok(()) => {
+                debug!("Stored secret '{}' on attempt {}", key, attempt);
+                return Ok(());
+            }
+            Err(StoreError::Transient(e)) if attempt < MAX_STORE_ATTEMPTS => {
+                let delay = retry_delay(base_delay, attempt);
+                warn!(
+                    "Storing secret '{}' failed on attempt {}, retrying in {:?}: {}",
+                    key, attempt, delay, e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!("Failed to store secret '{}': {}", key, e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error() {
+        for message in [
+            "HTTP error 503: Service Unavailable",
+            "Request failed with status 429 Too Many Requests",
+            "error sending request: connection reset by peer",
+        ] {
+            assert!(
+                matches!(classify_error(message), StoreError::Transient(_)),
+                "{message}"
+            );
+        }
+        for message in [
+            "HTTP error 401: Unauthorized",
+            "HTTP error 403: Forbidden",
+            "HTTP error 400: secret key is invalid",
+        ] {
+            assert!(
+                matches!(classify_error(message), StoreError::Fatal(_)),
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let base = Duration::from_millis(500);
+        assert_eq!(retry_delay(base, 1), Duration::from_millis(500));
+        assert_eq!(retry_delay(base, 2), Duration::from_secs(1));
+        assert_eq!(retry_delay(base, 3), Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_then_stored_succeeds() {
+        let mut attempts = 0;
+        let result = store_with_retry("user_jwt", Duration::ZERO, || {
+            attempts += 1;
+            let outcome = if attempts == 1 {
+                Err(classify_error("HTTP error 503: Service Unavailable"))
+            } else {
+                Ok(())
+            };
+            async move { outcome }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_auth_errors_are_not_retried() {
+        let mut attempts = 0;
+        let result = store_with_retry("user_jwt", Duration::ZERO, || {
+            attempts += 1;
+            async { Err(classify_error("HTTP error 401: Unauthorized")) }
+        })
+        .await;
+
+        assert_eq!(attempts, 1);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to store secret 'user_jwt': HTTP error 401: Unauthorized"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persistent_failures_give_up() {
+        let mut attempts = 0;
+        let result = store_with_retry("user_jwt", Duration::ZERO, || {
+            attempts += 1;
+            async { Err(StoreError::Transient("HTTP error 502".to_string())) }
+        })
+        .await;
+
+        assert_eq!(attempts, MAX_STORE_ATTEMPTS);
+        assert!(result.is_err());
+    }
+}
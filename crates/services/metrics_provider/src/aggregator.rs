@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Upper bounds, in milliseconds, of the latency histogram buckets
+const LATENCY_BUCKETS_MS: [u64; 11] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket, non-cumulative. The last entry counts those above every bound.
+    buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    sum: u64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: u64) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.count += 1;
+    }
+}
+
+#[derive(Default)]
+struct State {
+    processed: BTreeMap<String, u64>,
+    errors: BTreeMap<(String, String), u64>,
+    latency: BTreeMap<String, Histogram>,
+}
+
+/// Metrics recorded by nodes, labelled by the component that recorded them
+#[derive(Default)]
+pub struct Aggregator {
+    state: Mutex<State>,
+}
+
+impl Aggregator {
+    pub fn record_processed(&self, component: &str, count: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state.processed.entry(component.to_string()).or_default() += count;
+    }
+
+    pub fn record_latency(&self, component: &str, ms: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .latency
+            .entry(component.to_string())
+            .or_default()
+            .observe(ms);
+    }
+
+    pub fn record_error(&self, component: &str, kind: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state
+            .errors
+            .entry((component.to_string(), kind.to_string()))
+            .or_default() += 1;
+    }
+
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+
+        out.push_str("# HELP pipestack_messages_processed_total Messages handled successfully\n");
+        out.push_str("# TYPE pipestack_messages_processed_total counter\n");
+        for (component, count) in &state.processed {
+            let _ = writeln!(
+                out,
+                "pipestack_messages_processed_total{{component=\"{}\"}} {count}",
+                escape(component)
+            );
+        }
+
+        out.push_str("# HELP pipestack_errors_total Messages that failed, by error kind\n");
+        out.push_str("# TYPE pipestack_errors_total counter\n");
+        for ((component, kind), count) in &state.errors {
+            let _ = writeln!(
+                out,
+                "pipestack_errors_total{{component=\"{}\",kind=\"{}\"}} {count}",
+                escape(component),
+                escape(kind)
+            );
+        }
+
+        out.push_str("# HELP pipestack_latency_milliseconds Time taken to handle a message\n");
+        out.push_str("# TYPE pipestack_latency_milliseconds histogram\n");
+        for (component, histogram) in &state.latency {
+            let component = escape(component);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "pipestack_latency_milliseconds_bucket{{component=\"{component}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "pipestack_latency_milliseconds_bucket{{component=\"{component}\",le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "pipestack_latency_milliseconds_sum{{component=\"{component}\"}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "pipestack_latency_milliseconds_count{{component=\"{component}\"}} {}",
+                histogram.count
+            );
+        }
+
+        out
+    }
+}
+
+/// Escape a Prometheus label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters() {
+        let aggregator = Aggregator::default();
+        aggregator.record_processed("out-log", 1);
+        aggregator.record_processed("out-log", 2);
+        aggregator.record_error("webhook", "http-status");
+        aggregator.record_error("webhook", "http-status");
+        aggregator.record_error("webhook", "request");
+
+        let rendered = aggregator.render();
+        assert!(rendered.contains("pipestack_messages_processed_total{component=\"out-log\"} 3\n"));
+        assert!(
+            rendered
+                .contains("pipestack_errors_total{component=\"webhook\",kind=\"http-status\"} 2\n")
+        );
+        assert!(
+            rendered.contains("pipestack_errors_total{component=\"webhook\",kind=\"request\"} 1\n")
+        );
+    }
+
+    #[test]
+    fn test_render_latency_histogram() {
+        let aggregator = Aggregator::default();
+        for ms in [3, 7, 7, 20000] {
+            aggregator.record_latency("in-internal", ms);
+        }
+
+        let rendered = aggregator.render();
+        let bucket = |le: &str| {
+            format!(
+                "pipestack_latency_milliseconds_bucket{{component=\"in-internal\",le=\"{le}\"}}"
+            )
+        };
+        assert!(rendered.contains(&format!("{} 1\n", bucket("5"))));
+        assert!(rendered.contains(&format!("{} 3\n", bucket("10"))));
+        assert!(rendered.contains(&format!("{} 3\n", bucket("10000"))));
+        assert!(rendered.contains(&format!("{} 4\n", bucket("+Inf"))));
+        assert!(
+            rendered
+                .contains("pipestack_latency_milliseconds_sum{component=\"in-internal\"} 20017\n")
+        );
+        assert!(
+            rendered
+                .contains("pipestack_latency_milliseconds_count{component=\"in-internal\"} 4\n")
+        );
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let aggregator = Aggregator::default();
+        aggregator.record_error("node", "bad \"kind\"\n");
+
+        assert!(aggregator.render().contains("kind=\"bad \\\"kind\\\"\\n\""));
+    }
+}
@@ -0,0 +1,9 @@
+mod aggregator;
+mod provider;
+
+use provider::MetricsProvider;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    MetricsProvider::run().await
+}
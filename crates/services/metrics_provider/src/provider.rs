@@ -0,0 +1,108 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use axum::{Router, extract::State, routing::get};
+use tracing::{error, info};
+use wasmcloud_provider_sdk::{
+    Context, Provider, get_connection, run_provider, serve_provider_exports,
+};
+
+use crate::aggregator::Aggregator;
+
+mod bindings {
+    wit_bindgen_wrpc::generate!({ generate_all });
+}
+
+use bindings::exports::pipestack::metrics::metrics::Handler;
+use bindings::wrpc::rpc::error::Error;
+
+/// Address the Prometheus scrape endpoint listens on, unless `METRICS_ADDR` is set
+const DEFAULT_METRICS_ADDR: &str = "0.0.0.0:9464";
+
+/// Label of metrics recorded without a known source component
+const UNKNOWN_COMPONENT: &str = "unknown";
+
+/// Capability provider aggregating the metrics nodes record, exposed for Prometheus to scrape
+#[derive(Clone, Default)]
+pub struct MetricsProvider {
+    aggregator: Arc<Aggregator>,
+}
+
+impl MetricsProvider {
+    pub async fn run() -> anyhow::Result<()> {
+        let provider = Self::default();
+
+        let addr: SocketAddr = std::env::var("METRICS_ADDR")
+            .unwrap_or_else(|_| DEFAULT_METRICS_ADDR.to_string())
+            .parse()
+            .context("Invalid METRICS_ADDR")?;
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind the metrics endpoint to {addr}"))?;
+        let app = Router::new()
+            .route("/metrics", get(render_metrics))
+            .with_state(provider.aggregator.clone());
+        info!("Serving Prometheus metrics on http://{addr}/metrics");
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Metrics endpoint failed: {e}");
+            }
+        });
+
+        let shutdown = run_provider(provider.clone(), "metrics-provider")
+            .await
+            .context("Failed to run provider")?;
+        let connection = get_connection();
+        let wrpc = connection
+            .get_wrpc_client(connection.provider_key())
+            .await
+            .context("Failed to get wRPC client")?;
+        serve_provider_exports(&wrpc, provider, shutdown, bindings::serve)
+            .await
+            .context("Failed to serve provider exports")
+    }
+}
+
+async fn render_metrics(State(aggregator): State<Arc<Aggregator>>) -> String {
+    aggregator.render()
+}
+
+/// The component that invoked the provider, used to label its metrics
+fn component(ctx: &Option<Context>) -> &str {
+    ctx.as_ref()
+        .and_then(|ctx| ctx.component.as_deref())
+        .unwrap_or(UNKNOWN_COMPONENT)
+}
+
+impl Handler<Option<Context>> for MetricsProvider {
+    async fn record_processed(
+        &self,
+        ctx: Option<Context>,
+        count: u64,
+    ) -> anyhow::Result<Result<(), Error>> {
+        self.aggregator.record_processed(component(&ctx), count);
+        Ok(Ok(()))
+    }
+
+    async fn record_latency(
+        &self,
+        ctx: Option<Context>,
+        ms: u64,
+    ) -> anyhow::Result<Result<(), Error>> {
+        self.aggregator.record_latency(component(&ctx), ms);
+        Ok(Ok(()))
+    }
+
+    async fn record_error(
+        &self,
+        ctx: Option<Context>,
+        kind: String,
+    ) -> anyhow::Result<Result<(), Error>> {
+        self.aggregator.record_error(component(&ctx), &kind);
+        Ok(Ok(()))
+    }
+}
+
+/// Links carry no configuration, so the defaults of [`Provider`] suffice
+impl Provider for MetricsProvider {}
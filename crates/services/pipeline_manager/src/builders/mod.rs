@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
-use shared::{Pipeline, PipelineNode};
-use std::collections::{BTreeMap, HashMap};
+use shared::{Pipeline, PipelineNode, PipelineNodeSettings, output_subject};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::config::AppConfig;
 
@@ -60,6 +60,8 @@ pub struct ApplicationRef {
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Config {
     pub name: String,
+    /// Empty when referencing a config declared elsewhere by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub properties: BTreeMap<String, serde_yaml::Value>,
 }
 
@@ -102,6 +104,10 @@ pub struct LinkSource {
     pub config: Option<Vec<Config>>,
 }
 
+/// Instances of the in-internal and out-internal components of a node when neither the node nor
+/// the pipeline sets `helper_instances`
+pub const DEFAULT_HELPER_INSTANCES: u32 = 10_000;
+
 /// Context passed to component builders
 pub struct BuildContext<'a> {
     pub pipeline: &'a Pipeline,
@@ -125,18 +131,77 @@ impl<'a> BuildContext<'a> {
         }
     }
 
+    /// Id the component named `component_name` is deployed under
+    pub fn component_id(&self, component_name: &str) -> String {
+        component_id(self.workspace_slug, &self.pipeline.name, component_name)
+    }
+
+    /// Instances of the in-internal and out-internal components of `step`, independent of the
+    /// instances of the node itself
+    pub fn helper_instances(&self, step: &PipelineNode) -> u32 {
+        step.helper_instances
+            .or(self.pipeline.helper_instances)
+            .unwrap_or(DEFAULT_HELPER_INSTANCES)
+    }
+
+    /// Topic of the node that consumes `current_step`'s default output. A node with several
+    /// dependencies has a single in-topic, so every one of them publishes to the same subject.
     pub fn find_next_step_topic(&self, current_step: &str) -> Option<String> {
         self.pipeline
             .nodes
             .iter()
             .find(|s| {
                 s.depends_on
-                    .as_ref()
-                    .is_some_and(|deps| deps.contains(&current_step.to_string()))
+                    .iter()
+                    .flatten()
+                    .any(|dep| dep.node() == current_step && dep.output().is_none())
             })
             .and_then(|s| self.step_topics.get(&s.id))
             .cloned()
     }
+
+    /// Subjects `current_step` publishes its named outputs on, by output name. Like the default
+    /// output, each output goes to the topic of the first node consuming it.
+    pub fn find_output_topics(&self, current_step: &str) -> BTreeMap<String, String> {
+        let mut output_topics = BTreeMap::new();
+        for step in &self.pipeline.nodes {
+            let Some(topic) = self.step_topics.get(&step.id) else {
+                continue;
+            };
+            for dep in step.depends_on.iter().flatten() {
+                if let Some(output) = dep.output().filter(|_| dep.node() == current_step) {
+                    output_topics
+                        .entry(output.to_string())
+                        .or_insert_with(|| output_subject(topic, output));
+                }
+            }
+        }
+        output_topics
+    }
+
+    /// How long `step` waits for the reply of the nodes it publishes to, when a webhook at or
+    /// upstream of it responds with the result of the pipeline. The longest `replyTimeoutMs` of
+    /// those webhooks when several are.
+    pub fn reply_timeout_ms(&self, step: &PipelineNode) -> Option<u32> {
+        let mut seen = HashSet::new();
+        let mut pending = vec![step];
+        let mut timeout_ms = None;
+        while let Some(node) = pending.pop() {
+            if !seen.insert(node.id.as_str()) {
+                continue;
+            }
+            if let Some(PipelineNodeSettings::InHttpWebhook(settings)) = &node.settings {
+                timeout_ms = timeout_ms.max(settings.reply_timeout_ms);
+            }
+            pending.extend(
+                node.depends_on
+                    .iter()
+                    .flatten()
+                    .filter_map(|dep| self.pipeline.nodes.iter().find(|n| n.id == dep.node())),
+            );
+        }
+        timeout_ms
+    }
 }
 
 /// Trait for building pipeline components
@@ -156,6 +221,8 @@ pub enum ProviderType {
     HttpServer,
     HttpClient,
     NatsMessaging,
+    KeyvalueNats,
+    Metrics,
 }
 
 /// Trait for building provider components
@@ -168,6 +235,49 @@ pub trait ProviderBuilder {
     ) -> Result<Component, Box<dyn std::error::Error>>;
 }
 
+/// Deterministic link name derived from the link's source, target and interface.
+///
+/// wadm treats unnamed links from the same component on the same interface as one link,
+/// so every generated link gets a name to keep fan-out links from clobbering each other.
+pub fn link_name(source: &str, target: &str, interface: &str) -> String {
+    format!("{source}-to-{target}-{interface}-link")
+}
+
+/// Id a component is deployed under, `{workspace}_{pipeline}-{component}`. Underscores in the
+/// workspace slug and pipeline name are replaced with dashes, so the `_` separating them can't be
+/// confused with one in either name.
+pub fn component_id(workspace_slug: &str, pipeline_name: &str, component_name: &str) -> String {
+    format!(
+        "{}_{}-{component_name}",
+        workspace_slug.replace('_', "-"),
+        pipeline_name.replace('_', "-")
+    )
+}
+
+/// Subject a node publishes the messages it failed to process on
+pub fn dead_letter_subject(workspace_slug: &str, pipeline_name: &str, step_id: &str) -> String {
+    format!("pipestack.{workspace_slug}.{pipeline_name}.{step_id}.dlq")
+}
+
+/// Id of the node a dead-letter subject belongs to, or `None` if the subject isn't a dead-letter
+/// subject of the pipeline
+pub fn dead_letter_step<'a>(
+    workspace_slug: &str,
+    pipeline_name: &str,
+    subject: &'a str,
+) -> Option<&'a str> {
+    subject
+        .strip_prefix(&format!("pipestack.{workspace_slug}.{pipeline_name}."))?
+        .strip_suffix(".dlq")
+        .filter(|step_id| !step_id.is_empty())
+}
+
+/// Subject dead letters of a node are replayed on. Only the node's in-internal component subscribes
+/// to it, unlike its step topic, which the other nodes at the same depth share.
+pub fn replay_subject(workspace_slug: &str, pipeline_name: &str, step_id: &str) -> String {
+    format!("pipestack.{workspace_slug}.{pipeline_name}.{step_id}.replay")
+}
+
 /// Helper function to convert settings to config properties
 fn settings_to_config_properties<T: serde::Serialize>(
     settings: &T,
@@ -0,0 +1,102 @@
+use crate::builders::{
+    BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
+    Trait, TraitProperties, link_name, nodes::NODE_IN_GRAPHQL_POLLER_NAME,
+    nodes::NODE_IN_GRAPHQL_POLLER_VERSION, nodes::keyvalue_link, nodes::out_internal_component,
+    settings_to_config_properties,
+};
+use shared::{PipelineNode, PipelineNodeSettings};
+
+pub struct InGraphqlPollerBuilder;
+
+impl ComponentBuilder for InGraphqlPollerBuilder {
+    fn build_components(
+        &self,
+        step: &PipelineNode,
+        context: &BuildContext,
+    ) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
+        let mut components = Vec::new();
+
+        let config = match &step.settings {
+            Some(PipelineNodeSettings::InGraphqlPoller(settings)) => {
+                let mut properties = settings_to_config_properties(settings);
+                // Key under which the node keeps its cursor and last poll time in the keyvalue bucket
+                properties.insert(
+                    "state-key".to_string(),
+                    serde_yaml::Value::String(format!(
+                        "{}-{}-{}",
+                        context.workspace_slug, context.pipeline.name, step.id
+                    )),
+                );
+                vec![Config {
+                    name: format!("{}-config-v{}", step.id, context.pipeline.version),
+                    properties,
+                }]
+            }
+            _ => {
+                return Err(
+                    format!("Node {} is missing its GraphQL poller settings", step.id).into(),
+                );
+            }
+        };
+
+        // Add in-graphql-poller component
+        components.push(Component {
+            name: step.id.clone(),
+            component_type: "component".to_string(),
+            properties: Properties::WithImage {
+                id: Some(context.component_id(&step.id)),
+                image: format!(
+                    "{}/nodes/{NODE_IN_GRAPHQL_POLLER_NAME}:{NODE_IN_GRAPHQL_POLLER_VERSION}",
+                    context.app_config.registry.url
+                ),
+                config: Some(config),
+            },
+            traits: vec![
+                Trait {
+                    trait_type: "spreadscaler".to_string(),
+                    properties: TraitProperties::Spreadscaler {
+                        instances: step.instances.unwrap_or(1),
+                    },
+                },
+                Trait {
+                    trait_type: "link".to_string(),
+                    properties: TraitProperties::Link(LinkProperties {
+                        name: Some(link_name(
+                            &step.id,
+                            &format!("out-internal-for-{}", step.id),
+                            "out",
+                        )),
+                        source: None,
+                        target: LinkTarget {
+                            name: format!("out-internal-for-{}", step.id),
+                            config: None,
+                        },
+                        namespace: "pipestack".to_string(),
+                        package: "out".to_string(),
+                        interfaces: vec!["out".to_string()],
+                    }),
+                },
+                Trait {
+                    trait_type: "link".to_string(),
+                    properties: TraitProperties::Link(LinkProperties {
+                        name: Some(link_name(&step.id, "httpclient", "outgoing-handler")),
+                        source: None,
+                        target: LinkTarget {
+                            name: "httpclient".to_string(),
+                            config: None,
+                        },
+                        namespace: "wasi".to_string(),
+                        package: "http".to_string(),
+                        interfaces: vec!["outgoing-handler".to_string()],
+                    }),
+                },
+                keyvalue_link(&step.id, context),
+            ],
+        });
+
+        // Add corresponding out-internal component
+        components.extend(out_internal_component(step, context));
+
+        Ok(components)
+    }
+}
@@ -1,7 +1,7 @@
 use crate::builders::{
     BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
-    Trait, TraitProperties, nodes::NODE_IN_HTTP_NAME, nodes::NODE_IN_HTTP_VERSION,
-    nodes::NODE_OUT_INTERNAL_NAME, nodes::NODE_OUT_INTERNAL_VERSION, settings_to_config_properties,
+    Trait, TraitProperties, link_name, nodes::NODE_IN_HTTP_NAME, nodes::NODE_IN_HTTP_VERSION,
+    nodes::out_internal_component, settings_to_config_properties,
 };
 use shared::{PipelineNode, PipelineNodeSettings};
 
@@ -15,15 +15,16 @@ impl ComponentBuilder for InHttpWebhookBuilder {
     ) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
         let mut components = Vec::new();
 
+        // Request metadata, which always carries the request id, is passed to out-internal
+        // through its `metadata` interface
+        let interfaces = vec!["out".to_string(), "metadata".to_string()];
+
         // Add in-http component
         components.push(Component {
             name: step.id.clone(),
             component_type: "component".to_string(),
             properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
+                id: Some(context.component_id(&step.id)),
                 image: format!(
                     "{}/nodes/{NODE_IN_HTTP_NAME}:{NODE_IN_HTTP_VERSION}",
                     context.app_config.registry.url
@@ -46,7 +47,11 @@ impl ComponentBuilder for InHttpWebhookBuilder {
                 Trait {
                     trait_type: "link".to_string(),
                     properties: TraitProperties::Link(LinkProperties {
-                        name: None,
+                        name: Some(link_name(
+                            &step.id,
+                            &format!("out-internal-for-{}", step.id),
+                            "out",
+                        )),
                         source: None,
                         target: LinkTarget {
                             name: format!("out-internal-for-{}", step.id),
@@ -54,65 +59,14 @@ impl ComponentBuilder for InHttpWebhookBuilder {
                         },
                         namespace: "pipestack".to_string(),
                         package: "out".to_string(),
-                        interfaces: vec!["out".to_string()],
+                        interfaces,
                     }),
                 },
             ],
         });
 
         // Add corresponding out-internal component
-        let next_topic = context.find_next_step_topic(&step.id).unwrap_or_default();
-
-        if !next_topic.is_empty() {
-            components.push(Component {
-                name: format!("out-internal-for-{}", step.id),
-                component_type: "component".to_string(),
-                properties: Properties::WithImage {
-                    id: Some(format!(
-                        "{}_{}-out-internal-for-{}",
-                        context.workspace_slug, context.pipeline.name, step.id
-                    )),
-                    image: format!(
-                        "{}/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}",
-                        context.app_config.registry.url
-                    ),
-                    config: Some(vec![Config {
-                        name: format!(
-                            "out-internal-for-{}-config-v{}",
-                            step.id, context.pipeline.version
-                        ),
-                        properties: {
-                            let mut props = std::collections::BTreeMap::new();
-                            props.insert(
-                                "next-step-topic".to_string(),
-                                serde_yaml::Value::String(next_topic.clone()),
-                            );
-                            props
-                        },
-                    }]),
-                },
-                traits: vec![
-                    Trait {
-                        trait_type: "spreadscaler".to_string(),
-                        properties: TraitProperties::Spreadscaler { instances: 10_000 },
-                    },
-                    Trait {
-                        trait_type: "link".to_string(),
-                        properties: TraitProperties::Link(LinkProperties {
-                            name: None,
-                            source: None,
-                            target: LinkTarget {
-                                name: "messaging-nats".to_string(),
-                                config: None,
-                            },
-                            namespace: "wasmcloud".to_string(),
-                            package: "messaging".to_string(),
-                            interfaces: vec!["consumer".to_string()],
-                        }),
-                    },
-                ],
-            });
-        }
+        components.extend(out_internal_component(step, context));
 
         Ok(components)
     }
@@ -1,3 +1,37 @@
+pub mod graphql_poller;
 pub mod http_webhook;
+pub mod rabbitmq;
+pub mod rss_reader;
 
+pub use graphql_poller::InGraphqlPollerBuilder;
 pub use http_webhook::InHttpWebhookBuilder;
+pub use rabbitmq::InRabbitmqBuilder;
+pub use rss_reader::InRssReaderBuilder;
+
+use shared::{Pipeline, PipelineNodeType};
+
+/// Whether nodes of this type are sources that fetch messages when a message arrives on their
+/// poll subject
+pub fn is_polled(node_type: &PipelineNodeType) -> bool {
+    matches!(
+        node_type,
+        PipelineNodeType::InRssReader
+            | PipelineNodeType::InRabbitmq
+            | PipelineNodeType::InGraphqlPoller
+    )
+}
+
+/// Subject a polling source is subscribed to; every message published on it triggers a poll
+pub fn poll_subject(workspace_slug: &str, pipeline_name: &str, step_id: &str) -> String {
+    format!("pipestack.{workspace_slug}.{pipeline_name}.{step_id}.poll")
+}
+
+/// Poll subjects of every polling source of a pipeline, which the poll scheduler publishes ticks to
+pub fn poll_subjects(pipeline: &Pipeline, workspace_slug: &str) -> Vec<String> {
+    pipeline
+        .nodes
+        .iter()
+        .filter(|step| is_polled(&step.step_type))
+        .map(|step| poll_subject(workspace_slug, &pipeline.name, &step.id))
+        .collect()
+}
@@ -0,0 +1,85 @@
+use crate::builders::{
+    BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
+    Trait, TraitProperties, link_name, nodes::NODE_IN_RABBITMQ_NAME,
+    nodes::NODE_IN_RABBITMQ_VERSION, nodes::out_internal_component, settings_to_config_properties,
+};
+use shared::{PipelineNode, PipelineNodeSettings};
+
+pub struct InRabbitmqBuilder;
+
+impl ComponentBuilder for InRabbitmqBuilder {
+    fn build_components(
+        &self,
+        step: &PipelineNode,
+        context: &BuildContext,
+    ) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
+        let mut components = Vec::new();
+
+        let config = match &step.settings {
+            Some(PipelineNodeSettings::InRabbitmq(settings)) => vec![Config {
+                name: format!("{}-config-v{}", step.id, context.pipeline.version),
+                properties: settings_to_config_properties(settings),
+            }],
+            _ => return Err(format!("Node {} is missing its RabbitMQ settings", step.id).into()),
+        };
+
+        // Add in-rabbitmq component
+        components.push(Component {
+            name: step.id.clone(),
+            component_type: "component".to_string(),
+            properties: Properties::WithImage {
+                id: Some(context.component_id(&step.id)),
+                image: format!(
+                    "{}/nodes/{NODE_IN_RABBITMQ_NAME}:{NODE_IN_RABBITMQ_VERSION}",
+                    context.app_config.registry.url
+                ),
+                config: Some(config),
+            },
+            traits: vec![
+                Trait {
+                    trait_type: "spreadscaler".to_string(),
+                    properties: TraitProperties::Spreadscaler {
+                        instances: step.instances.unwrap_or(1),
+                    },
+                },
+                Trait {
+                    trait_type: "link".to_string(),
+                    properties: TraitProperties::Link(LinkProperties {
+                        name: Some(link_name(
+                            &step.id,
+                            &format!("out-internal-for-{}", step.id),
+                            "out",
+                        )),
+                        source: None,
+                        target: LinkTarget {
+                            name: format!("out-internal-for-{}", step.id),
+                            config: None,
+                        },
+                        namespace: "pipestack".to_string(),
+                        package: "out".to_string(),
+                        interfaces: vec!["out".to_string()],
+                    }),
+                },
+                Trait {
+                    trait_type: "link".to_string(),
+                    properties: TraitProperties::Link(LinkProperties {
+                        name: Some(link_name(&step.id, "httpclient", "outgoing-handler")),
+                        source: None,
+                        target: LinkTarget {
+                            name: "httpclient".to_string(),
+                            config: None,
+                        },
+                        namespace: "wasi".to_string(),
+                        package: "http".to_string(),
+                        interfaces: vec!["outgoing-handler".to_string()],
+                    }),
+                },
+            ],
+        });
+
+        // Add corresponding out-internal component
+        components.extend(out_internal_component(step, context));
+
+        Ok(components)
+    }
+}
@@ -3,13 +3,286 @@ pub mod out;
 pub mod processor;
 pub mod registry;
 
+use shared::{
+    OUTPUT_TOPICS_CONFIG_KEY, PROCESSOR_INPUT_CONFIG_KEY, PipelineNode, PipelineNodeSettings,
+    PipelineNodeType, ProcessorInput, REQUEST_TIMEOUT_CONFIG_KEY, RESILIENCE_CONFIG_KEY,
+};
+use std::collections::BTreeMap;
+
+use crate::builders::{
+    BuildContext, Component, Config, LinkProperties, LinkTarget, Properties, Trait,
+    TraitProperties, link_name,
+};
+
+pub const NODE_IN_GRAPHQL_POLLER_NAME: &str = "in_graphql_poller_s.wasm";
+pub const NODE_IN_GRAPHQL_POLLER_VERSION: &str = "0.1.0";
 pub const NODE_IN_HTTP_NAME: &str = "in_http_s.wasm";
-pub const NODE_IN_HTTP_VERSION: &str = "0.1.7";
+pub const NODE_IN_HTTP_VERSION: &str = "0.1.8";
+pub const NODE_IN_RABBITMQ_NAME: &str = "in_rabbitmq_s.wasm";
+pub const NODE_IN_RABBITMQ_VERSION: &str = "0.1.0";
+pub const NODE_IN_RSS_READER_NAME: &str = "in_rss_reader_s.wasm";
+pub const NODE_IN_RSS_READER_VERSION: &str = "0.1.0";
 pub const NODE_IN_INTERNAL_NAME: &str = "in_internal_s.wasm";
-pub const NODE_IN_INTERNAL_VERSION: &str = "0.1.8";
+pub const NODE_IN_INTERNAL_VERSION: &str = "0.1.13";
+pub const NODE_OUT_AWS_LAMBDA_NAME: &str = "out_aws_lambda_s.wasm";
+pub const NODE_OUT_AWS_LAMBDA_VERSION: &str = "0.1.0";
+pub const NODE_OUT_FILE_NAME: &str = "out_file_s.wasm";
+pub const NODE_OUT_FILE_VERSION: &str = "0.1.0";
+pub const NODE_OUT_GOOGLE_BIGQUERY_NAME: &str = "out_google_bigquery_s.wasm";
+pub const NODE_OUT_GOOGLE_BIGQUERY_VERSION: &str = "0.1.0";
+pub const NODE_OUT_GRAPHQL_MUTATION_NAME: &str = "out_graphql_mutation_s.wasm";
+pub const NODE_OUT_GRAPHQL_MUTATION_VERSION: &str = "0.1.0";
 pub const NODE_OUT_HTTP_WEBHOOK_NAME: &str = "out_http_webhook_s.wasm";
-pub const NODE_OUT_HTTP_WEBHOOK_VERSION: &str = "0.1.7";
+pub const NODE_OUT_HTTP_WEBHOOK_VERSION: &str = "0.1.8";
 pub const NODE_OUT_INTERNAL_NAME: &str = "out_internal_s.wasm";
 pub const NODE_OUT_INTERNAL_VERSION: &str = "0.1.7";
 pub const NODE_OUT_LOG_NAME: &str = "out_log_s.wasm";
 pub const NODE_OUT_LOG_VERSION: &str = "0.1.9";
+pub const NODE_OUT_LOKI_NAME: &str = "out_loki_s.wasm";
+pub const NODE_OUT_LOKI_VERSION: &str = "0.1.1";
+pub const NODE_OUT_MONGODB_NAME: &str = "out_mongodb_s.wasm";
+pub const NODE_OUT_MONGODB_VERSION: &str = "0.1.0";
+pub const NODE_OUT_RABBITMQ_NAME: &str = "out_rabbitmq_s.wasm";
+pub const NODE_OUT_RABBITMQ_VERSION: &str = "0.1.0";
+pub const NODE_OUT_SNOWFLAKE_NAME: &str = "out_snowflake_s.wasm";
+pub const NODE_OUT_SNOWFLAKE_VERSION: &str = "0.1.0";
+pub const NODE_PROCESSOR_FORMAT_NAME: &str = "processor_format_s.wasm";
+pub const NODE_PROCESSOR_FORMAT_VERSION: &str = "0.1.1";
+pub const NODE_PROCESSOR_FILTER_NAME: &str = "processor_filter_s.wasm";
+pub const NODE_PROCESSOR_FILTER_VERSION: &str = "0.1.1";
+
+/// Config passing the resilience settings of a node to one of its components. `None` when the
+/// node has none.
+pub fn resilience_config(step: &PipelineNode, context: &BuildContext) -> Option<Config> {
+    let resilience = step.resilience.as_ref()?;
+    let json = serde_json::to_string(resilience).expect("Failed to serialize resilience settings");
+    Some(Config {
+        name: format!("{}-resilience-v{}", step.id, context.pipeline.version),
+        properties: BTreeMap::from([(
+            RESILIENCE_CONFIG_KEY.to_string(),
+            serde_yaml::Value::String(json),
+        )]),
+    })
+}
+
+/// Config telling a processor's in-internal component how to hand it messages. Built-in
+/// processors take bytes, custom ones only when they say they implement `run-bytes`.
+fn processor_input_config(step: &PipelineNode, context: &BuildContext) -> Config {
+    let bytes_input = match &step.settings {
+        Some(PipelineNodeSettings::ProcessorWasm(settings)) => {
+            settings.bytes_input.unwrap_or(false)
+        }
+        _ => step.step_type != PipelineNodeType::ProcessorWasm,
+    };
+    let input = if bytes_input {
+        ProcessorInput::Bytes
+    } else {
+        ProcessorInput::Text
+    };
+    Config {
+        name: format!("{}-processor-input-v{}", step.id, context.pipeline.version),
+        properties: BTreeMap::from([(
+            PROCESSOR_INPUT_CONFIG_KEY.to_string(),
+            serde_yaml::Value::String(input.as_str().to_string()),
+        )]),
+    }
+}
+
+/// Build the in-internal component feeding a processor node: it calls the processor through
+/// the `customer` interface and hands the result to the processor's out-internal component.
+/// When a webhook upstream waits for the result, it also publishes the reply to requests.
+pub fn processor_in_internal_component(step: &PipelineNode, context: &BuildContext) -> Component {
+    let mut component = Component {
+        name: format!("in-internal-for-{}", step.id),
+        component_type: "component".to_string(),
+        properties: Properties::WithImage {
+            id: Some(context.component_id(&format!("in-internal-for-{}", step.id))),
+            image: format!(
+                "{}/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}",
+                context.app_config.registry.url
+            ),
+            config: Some(
+                std::iter::once(processor_input_config(step, context))
+                    .chain(resilience_config(step, context))
+                    .collect(),
+            ),
+        },
+        traits: vec![
+            Trait {
+                trait_type: "spreadscaler".to_string(),
+                properties: TraitProperties::Spreadscaler {
+                    instances: context.helper_instances(step),
+                },
+            },
+            metrics_link(&format!("in-internal-for-{}", step.id)),
+            Trait {
+                trait_type: "link".to_string(),
+                properties: TraitProperties::Link(LinkProperties {
+                    name: Some(link_name(
+                        &format!("in-internal-for-{}", step.id),
+                        &step.id,
+                        "customer",
+                    )),
+                    source: None,
+                    target: LinkTarget {
+                        name: step.id.clone(),
+                        config: None,
+                    },
+                    namespace: "pipestack".to_string(),
+                    package: "customer".to_string(),
+                    interfaces: vec!["customer".to_string()],
+                }),
+            },
+            Trait {
+                trait_type: "link".to_string(),
+                properties: TraitProperties::Link(LinkProperties {
+                    name: Some(link_name(
+                        &format!("in-internal-for-{}", step.id),
+                        &format!("out-internal-for-{}", step.id),
+                        "out",
+                    )),
+                    source: None,
+                    target: LinkTarget {
+                        name: format!("out-internal-for-{}", step.id),
+                        config: None,
+                    },
+                    namespace: "pipestack".to_string(),
+                    package: "out".to_string(),
+                    interfaces: vec!["out".to_string()],
+                }),
+            },
+        ],
+    };
+    if context.reply_timeout_ms(step).is_some() {
+        component.traits.push(messaging_consumer_link(&format!(
+            "in-internal-for-{}",
+            step.id
+        )));
+    }
+    component
+}
+
+/// Build the out-internal component publishing a node's output to the next step's topic, and
+/// its named outputs to the subjects of the nodes depending on them. Returns `None` when no step
+/// depends on the node.
+pub fn out_internal_component(step: &PipelineNode, context: &BuildContext) -> Option<Component> {
+    let next_topic = context.find_next_step_topic(&step.id).unwrap_or_default();
+    let output_topics = context.find_output_topics(&step.id);
+
+    if next_topic.is_empty() && output_topics.is_empty() {
+        return None;
+    }
+
+    Some(Component {
+        name: format!("out-internal-for-{}", step.id),
+        component_type: "component".to_string(),
+        properties: Properties::WithImage {
+            id: Some(context.component_id(&format!("out-internal-for-{}", step.id))),
+            image: format!(
+                "{}/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}",
+                context.app_config.registry.url
+            ),
+            config: Some(vec![Config {
+                name: format!(
+                    "out-internal-for-{}-config-v{}",
+                    step.id, context.pipeline.version
+                ),
+                properties: {
+                    let mut props = BTreeMap::new();
+                    props.insert(
+                        "next-step-topic".to_string(),
+                        serde_yaml::Value::String(next_topic.clone()),
+                    );
+                    if !output_topics.is_empty() {
+                        props.insert(
+                            OUTPUT_TOPICS_CONFIG_KEY.to_string(),
+                            serde_yaml::Value::String(
+                                serde_json::to_string(&output_topics)
+                                    .expect("Failed to serialize output topics"),
+                            ),
+                        );
+                    }
+                    if let Some(timeout_ms) = context.reply_timeout_ms(step) {
+                        props.insert(
+                            REQUEST_TIMEOUT_CONFIG_KEY.to_string(),
+                            serde_yaml::Value::String(timeout_ms.to_string()),
+                        );
+                    }
+                    props
+                },
+            }]),
+        },
+        traits: vec![
+            Trait {
+                trait_type: "spreadscaler".to_string(),
+                properties: TraitProperties::Spreadscaler {
+                    instances: context.helper_instances(step),
+                },
+            },
+            messaging_consumer_link(&format!("out-internal-for-{}", step.id)),
+        ],
+    })
+}
+
+/// Link letting `component` record metrics through the metrics provider
+pub fn metrics_link(component: &str) -> Trait {
+    Trait {
+        trait_type: "link".to_string(),
+        properties: TraitProperties::Link(LinkProperties {
+            name: Some(link_name(component, "metrics", "metrics")),
+            source: None,
+            target: LinkTarget {
+                name: "metrics".to_string(),
+                config: None,
+            },
+            namespace: "pipestack".to_string(),
+            package: "metrics".to_string(),
+            interfaces: vec!["metrics".to_string()],
+        }),
+    }
+}
+
+/// Link letting `component` publish through the NATS messaging provider
+pub fn messaging_consumer_link(component: &str) -> Trait {
+    Trait {
+        trait_type: "link".to_string(),
+        properties: TraitProperties::Link(LinkProperties {
+            name: Some(link_name(component, "messaging-nats", "consumer")),
+            source: None,
+            target: LinkTarget {
+                name: "messaging-nats".to_string(),
+                config: None,
+            },
+            namespace: "wasmcloud".to_string(),
+            package: "messaging".to_string(),
+            interfaces: vec!["consumer".to_string()],
+        }),
+    }
+}
+
+/// Link letting `component` keep state in the workspace's bucket of the NATS keyvalue provider
+pub fn keyvalue_link(component: &str, context: &BuildContext) -> Trait {
+    Trait {
+        trait_type: "link".to_string(),
+        properties: TraitProperties::Link(LinkProperties {
+            name: Some(link_name(component, "keyvalue-nats", "store")),
+            source: None,
+            target: LinkTarget {
+                name: "keyvalue-nats".to_string(),
+                config: Some(vec![Config {
+                    name: format!(
+                        "{}-{}-keyvalue-config",
+                        context.workspace_slug, context.pipeline.name
+                    ),
+                    properties: BTreeMap::from([(
+                        "bucket".to_string(),
+                        serde_yaml::Value::String(format!("pipestack-{}", context.workspace_slug)),
+                    )]),
+                }]),
+            },
+            namespace: "wasi".to_string(),
+            package: "keyvalue".to_string(),
+            interfaces: vec!["store".to_string()],
+        }),
+    }
+}
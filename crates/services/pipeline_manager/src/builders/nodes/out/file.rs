@@ -0,0 +1,107 @@
+use crate::builders::{
+    BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
+    Trait, TraitProperties, link_name, nodes::NODE_IN_INTERNAL_NAME,
+    nodes::NODE_IN_INTERNAL_VERSION, nodes::NODE_OUT_FILE_NAME, nodes::NODE_OUT_FILE_VERSION,
+    nodes::metrics_link, nodes::resilience_config, settings_to_config_properties,
+};
+use shared::{PipelineNode, PipelineNodeSettings};
+
+pub struct OutFileBuilder;
+
+impl ComponentBuilder for OutFileBuilder {
+    fn build_components(
+        &self,
+        step: &PipelineNode,
+        context: &BuildContext,
+    ) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
+        let mut components = Vec::new();
+
+        // Add in-internal component for out-file
+        components.push(Component {
+            name: format!("in-internal-for-{}", step.id),
+            component_type: "component".to_string(),
+            properties: Properties::WithImage {
+                id: Some(context.component_id(&format!("in-internal-for-{}", step.id))),
+                image: format!(
+                    "{}/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}",
+                    context.app_config.registry.url
+                ),
+                config: resilience_config(step, context).map(|config| vec![config]),
+            },
+            traits: vec![
+                Trait {
+                    trait_type: "spreadscaler".to_string(),
+                    properties: TraitProperties::Spreadscaler {
+                        instances: context.helper_instances(step),
+                    },
+                },
+                metrics_link(&format!("in-internal-for-{}", step.id)),
+                Trait {
+                    trait_type: "link".to_string(),
+                    properties: TraitProperties::Link(LinkProperties {
+                        name: Some(link_name(
+                            &format!("in-internal-for-{}", step.id),
+                            "messaging-nats",
+                            "consumer",
+                        )),
+                        source: None,
+                        target: LinkTarget {
+                            name: "messaging-nats".to_string(),
+                            config: None,
+                        },
+                        namespace: "wasmcloud".to_string(),
+                        package: "messaging".to_string(),
+                        interfaces: vec!["consumer".to_string()],
+                    }),
+                },
+                Trait {
+                    trait_type: "link".to_string(),
+                    properties: TraitProperties::Link(LinkProperties {
+                        name: Some(link_name(
+                            &format!("in-internal-for-{}", step.id),
+                            &step.id,
+                            "out",
+                        )),
+                        source: None,
+                        target: LinkTarget {
+                            name: step.id.clone(),
+                            config: None,
+                        },
+                        namespace: "pipestack".to_string(),
+                        package: "out".to_string(),
+                        interfaces: vec!["out".to_string()],
+                    }),
+                },
+            ],
+        });
+
+        // Add the out-file component itself
+        components.push(Component {
+            name: step.id.clone(),
+            component_type: "component".to_string(),
+            properties: Properties::WithImage {
+                id: Some(context.component_id(&step.id)),
+                image: format!(
+                    "{}/nodes/{NODE_OUT_FILE_NAME}:{NODE_OUT_FILE_VERSION}",
+                    context.app_config.registry.url
+                ),
+                config: step.settings.as_ref().map(|s| match s {
+                    PipelineNodeSettings::OutFile(settings) => vec![Config {
+                        name: format!("{}-config-v{}", step.id, context.pipeline.version),
+                        properties: settings_to_config_properties(settings),
+                    }],
+                    _ => vec![],
+                }),
+            },
+            // Instances append to the same files, so a single writer is the default
+            traits: vec![Trait {
+                trait_type: "spreadscaler".to_string(),
+                properties: TraitProperties::Spreadscaler {
+                    instances: step.instances.unwrap_or(1),
+                },
+            }],
+        });
+
+        Ok(components)
+    }
+}
@@ -1,8 +1,9 @@
 use crate::builders::{
     BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
-    Trait, TraitProperties, nodes::NODE_IN_INTERNAL_NAME, nodes::NODE_IN_INTERNAL_VERSION,
-    nodes::NODE_OUT_HTTP_WEBHOOK_NAME, nodes::NODE_OUT_HTTP_WEBHOOK_VERSION,
-    settings_to_config_properties,
+    Trait, TraitProperties, link_name, nodes::NODE_IN_INTERNAL_NAME,
+    nodes::NODE_IN_INTERNAL_VERSION, nodes::NODE_OUT_HTTP_WEBHOOK_NAME,
+    nodes::NODE_OUT_HTTP_WEBHOOK_VERSION, nodes::keyvalue_link, nodes::metrics_link,
+    nodes::resilience_config, settings_to_config_properties,
 };
 use shared::{PipelineNode, PipelineNodeSettings};
 
@@ -16,15 +17,23 @@ impl ComponentBuilder for OutHttpWebhookBuilder {
     ) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
         let mut components = Vec::new();
 
+        let mut config = match &step.settings {
+            Some(PipelineNodeSettings::OutHttpWebhook(settings)) => vec![Config {
+                name: format!("{}-config-v{}", step.id, context.pipeline.version),
+                properties: settings_to_config_properties(settings),
+            }],
+            _ => vec![],
+        };
+        // Retries and request timeouts are applied by the webhook itself, which checks its circuit
+        // breaker between attempts. Its in-internal component doesn't redeliver on top of them.
+        config.extend(resilience_config(step, context));
+
         // Add in-internal component for out-http-webhook
         components.push(Component {
             name: format!("in-internal-for-{}", step.id),
             component_type: "component".to_string(),
             properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-in-internal-for-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
+                id: Some(context.component_id(&format!("in-internal-for-{}", step.id))),
                 image: format!(
                     "{}/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}",
                     context.app_config.registry.url
@@ -34,12 +43,19 @@ impl ComponentBuilder for OutHttpWebhookBuilder {
             traits: vec![
                 Trait {
                     trait_type: "spreadscaler".to_string(),
-                    properties: TraitProperties::Spreadscaler { instances: 10_000 },
+                    properties: TraitProperties::Spreadscaler {
+                        instances: context.helper_instances(step),
+                    },
                 },
+                metrics_link(&format!("in-internal-for-{}", step.id)),
                 Trait {
                     trait_type: "link".to_string(),
                     properties: TraitProperties::Link(LinkProperties {
-                        name: None,
+                        name: Some(link_name(
+                            &format!("in-internal-for-{}", step.id),
+                            "messaging-nats",
+                            "consumer",
+                        )),
                         source: None,
                         target: LinkTarget {
                             name: "messaging-nats".to_string(),
@@ -53,7 +69,11 @@ impl ComponentBuilder for OutHttpWebhookBuilder {
                 Trait {
                     trait_type: "link".to_string(),
                     properties: TraitProperties::Link(LinkProperties {
-                        name: None,
+                        name: Some(link_name(
+                            &format!("in-internal-for-{}", step.id),
+                            &step.id,
+                            "out",
+                        )),
                         source: None,
                         target: LinkTarget {
                             name: step.id.clone(),
@@ -72,21 +92,12 @@ impl ComponentBuilder for OutHttpWebhookBuilder {
             name: step.id.clone(),
             component_type: "component".to_string(),
             properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
+                id: Some(context.component_id(&step.id)),
                 image: format!(
                     "{}/nodes/{NODE_OUT_HTTP_WEBHOOK_NAME}:{NODE_OUT_HTTP_WEBHOOK_VERSION}",
                     context.app_config.registry.url
                 ),
-                config: step.settings.as_ref().map(|s| match s {
-                    PipelineNodeSettings::OutHttpWebhook(settings) => vec![Config {
-                        name: format!("{}-config-v{}", step.id, context.pipeline.version),
-                        properties: settings_to_config_properties(settings),
-                    }],
-                    _ => vec![],
-                }),
+                config: (!config.is_empty()).then_some(config),
             },
             traits: vec![
                 Trait {
@@ -95,10 +106,13 @@ impl ComponentBuilder for OutHttpWebhookBuilder {
                         instances: step.instances.unwrap_or(10_000),
                     },
                 },
+                metrics_link(&step.id),
+                // Circuit breaker state is kept in keyvalue, shared by every instance
+                keyvalue_link(&step.id, context),
                 Trait {
                     trait_type: "link".to_string(),
                     properties: TraitProperties::Link(LinkProperties {
-                        name: None,
+                        name: Some(link_name(&step.id, "httpclient", "outgoing-handler")),
                         source: None,
                         target: LinkTarget {
                             name: "httpclient".to_string(),
@@ -1,9 +1,10 @@
 use crate::builders::{
-    BuildContext, Component, ComponentBuilder, LinkProperties, LinkTarget, Properties, Trait,
-    TraitProperties, nodes::NODE_IN_INTERNAL_NAME, nodes::NODE_IN_INTERNAL_VERSION,
-    nodes::NODE_OUT_LOG_NAME, nodes::NODE_OUT_LOG_VERSION,
+    BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
+    Trait, TraitProperties, link_name, nodes::NODE_IN_INTERNAL_NAME,
+    nodes::NODE_IN_INTERNAL_VERSION, nodes::NODE_OUT_LOG_NAME, nodes::NODE_OUT_LOG_VERSION,
+    nodes::metrics_link, nodes::resilience_config, settings_to_config_properties,
 };
-use shared::PipelineNode;
+use shared::{PipelineNode, PipelineNodeSettings};
 
 pub struct OutLogBuilder;
 
@@ -20,25 +21,29 @@ impl ComponentBuilder for OutLogBuilder {
             name: format!("in-internal-for-{}", step.id),
             component_type: "component".to_string(),
             properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-in-internal-for-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
+                id: Some(context.component_id(&format!("in-internal-for-{}", step.id))),
                 image: format!(
                     "{}/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}",
                     context.app_config.registry.url
                 ),
-                config: None,
+                config: resilience_config(step, context).map(|config| vec![config]),
             },
             traits: vec![
                 Trait {
                     trait_type: "spreadscaler".to_string(),
-                    properties: TraitProperties::Spreadscaler { instances: 10_000 },
+                    properties: TraitProperties::Spreadscaler {
+                        instances: context.helper_instances(step),
+                    },
                 },
+                metrics_link(&format!("in-internal-for-{}", step.id)),
                 Trait {
                     trait_type: "link".to_string(),
                     properties: TraitProperties::Link(LinkProperties {
-                        name: None,
+                        name: Some(link_name(
+                            &format!("in-internal-for-{}", step.id),
+                            "messaging-nats",
+                            "consumer",
+                        )),
                         source: None,
                         target: LinkTarget {
                             name: "messaging-nats".to_string(),
@@ -52,7 +57,11 @@ impl ComponentBuilder for OutLogBuilder {
                 Trait {
                     trait_type: "link".to_string(),
                     properties: TraitProperties::Link(LinkProperties {
-                        name: None,
+                        name: Some(link_name(
+                            &format!("in-internal-for-{}", step.id),
+                            &step.id,
+                            "out",
+                        )),
                         source: None,
                         target: LinkTarget {
                             name: step.id.clone(),
@@ -71,22 +80,28 @@ impl ComponentBuilder for OutLogBuilder {
             name: step.id.clone(),
             component_type: "component".to_string(),
             properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
+                id: Some(context.component_id(&step.id)),
                 image: format!(
                     "{}/nodes/{NODE_OUT_LOG_NAME}:{NODE_OUT_LOG_VERSION}",
                     context.app_config.registry.url
                 ),
-                config: None,
+                config: step.settings.as_ref().map(|s| match s {
+                    PipelineNodeSettings::OutLog(settings) => vec![Config {
+                        name: format!("{}-config-v{}", step.id, context.pipeline.version),
+                        properties: settings_to_config_properties(settings),
+                    }],
+                    _ => vec![],
+                }),
             },
-            traits: vec![Trait {
-                trait_type: "spreadscaler".to_string(),
-                properties: TraitProperties::Spreadscaler {
-                    instances: step.instances.unwrap_or(10_000),
+            traits: vec![
+                Trait {
+                    trait_type: "spreadscaler".to_string(),
+                    properties: TraitProperties::Spreadscaler {
+                        instances: step.instances.unwrap_or(10_000),
+                    },
                 },
-            }],
+                metrics_link(&step.id),
+            ],
         });
 
         Ok(components)
@@ -1,5 +1,21 @@
+pub mod aws_lambda;
+pub mod file;
+pub mod google_bigquery;
+pub mod graphql_mutation;
 pub mod http_webhook;
 pub mod log;
+pub mod loki;
+pub mod mongodb;
+pub mod rabbitmq;
+pub mod snowflake;
 
+pub use aws_lambda::OutAwsLambdaBuilder;
+pub use file::OutFileBuilder;
+pub use google_bigquery::OutGoogleBigqueryBuilder;
+pub use graphql_mutation::OutGraphqlMutationBuilder;
 pub use http_webhook::OutHttpWebhookBuilder;
 pub use log::OutLogBuilder;
+pub use loki::OutLokiBuilder;
+pub use mongodb::OutMongodbBuilder;
+pub use rabbitmq::OutRabbitmqBuilder;
+pub use snowflake::OutSnowflakeBuilder;
@@ -0,0 +1,59 @@
+use crate::builders::{
+    BuildContext, Component, ComponentBuilder, Config, Properties, Trait, TraitProperties,
+    nodes::NODE_PROCESSOR_FILTER_NAME, nodes::NODE_PROCESSOR_FILTER_VERSION,
+    nodes::out_internal_component, nodes::processor_in_internal_component,
+    settings_to_config_properties,
+};
+use shared::{PipelineNode, PipelineNodeSettings};
+
+pub struct ProcessorFilterBuilder;
+
+impl ComponentBuilder for ProcessorFilterBuilder {
+    fn build_components(
+        &self,
+        step: &PipelineNode,
+        context: &BuildContext,
+    ) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
+        let settings = match &step.settings {
+            Some(PipelineNodeSettings::ProcessorFilter(settings)) => settings,
+            _ => {
+                return Err(
+                    format!("Node {} is missing processor-filter settings", step.id).into(),
+                );
+            }
+        };
+
+        let mut components = Vec::new();
+
+        // Add in-internal component for processor
+        components.push(processor_in_internal_component(step, context));
+
+        // Add the built-in filter processor component itself
+        components.push(Component {
+            name: step.id.clone(),
+            component_type: "component".to_string(),
+            properties: Properties::WithImage {
+                id: Some(context.component_id(&step.id)),
+                image: format!(
+                    "{}/nodes/{NODE_PROCESSOR_FILTER_NAME}:{NODE_PROCESSOR_FILTER_VERSION}",
+                    context.app_config.registry.url
+                ),
+                config: Some(vec![Config {
+                    name: format!("{}-config-v{}", step.id, context.pipeline.version),
+                    properties: settings_to_config_properties(settings),
+                }]),
+            },
+            traits: vec![Trait {
+                trait_type: "spreadscaler".to_string(),
+                properties: TraitProperties::Spreadscaler {
+                    instances: step.instances.unwrap_or(10_000),
+                },
+            }],
+        });
+
+        // Add corresponding out-internal component
+        components.extend(out_internal_component(step, context));
+
+        Ok(components)
+    }
+}
@@ -1,3 +1,7 @@
+pub mod filter;
+pub mod format;
 pub mod wasm;
 
+pub use filter::ProcessorFilterBuilder;
+pub use format::ProcessorFormatBuilder;
 pub use wasm::ProcessorWasmBuilder;
@@ -1,12 +1,93 @@
 use crate::builders::{
-    BuildContext, Component, ComponentBuilder, Config, LinkProperties, LinkTarget, Properties,
-    Trait, TraitProperties, nodes::NODE_IN_INTERNAL_NAME, nodes::NODE_IN_INTERNAL_VERSION,
-    nodes::NODE_OUT_INTERNAL_NAME, nodes::NODE_OUT_INTERNAL_VERSION,
+    BuildContext, Component, ComponentBuilder, Properties, Trait, TraitProperties,
+    nodes::out_internal_component, nodes::processor_in_internal_component,
 };
-use shared::PipelineNode;
+use sha2::{Digest, Sha256};
+use shared::{PipelineNode, PipelineNodeSettings};
 
 pub struct ProcessorWasmBuilder;
 
+/// Whether a processor-wasm `source` is an already-published OCI reference, e.g.
+/// `ghcr.io/acme/processor:1.0.0` or `registry.example.com/processor@sha256:...`
+pub fn is_oci_reference(source: &str) -> bool {
+    let source = source.trim();
+    if source.is_empty() || source.contains(char::is_whitespace) {
+        return false;
+    }
+
+    match source.rsplit_once('/') {
+        Some((registry_and_path, name)) => {
+            !registry_and_path.is_empty() && (name.contains(':') || name.contains('@'))
+        }
+        None => false,
+    }
+}
+
+/// The OCI reference of the processor's image when the node points at a published one
+pub fn published_source(step: &PipelineNode) -> Option<&str> {
+    match &step.settings {
+        Some(PipelineNodeSettings::ProcessorWasm(settings))
+            if is_oci_reference(&settings.source) =>
+        {
+            Some(settings.source.trim())
+        }
+        _ => None,
+    }
+}
+
+/// The `sha256:` digest the processor's component is pinned to, if any
+pub fn pinned_digest(step: &PipelineNode) -> Option<&str> {
+    match &step.settings {
+        Some(PipelineNodeSettings::ProcessorWasm(settings)) => settings.digest.as_deref(),
+        _ => None,
+    }
+}
+
+/// `sha256:` digest of a component's content
+pub fn content_digest(wasm: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(wasm))
+}
+
+/// Check a fetched component against the digest it's pinned to. Unpinned components pass.
+pub fn verify_digest(pinned: Option<&str>, wasm: &[u8]) -> Result<(), String> {
+    let Some(pinned) = pinned else {
+        return Ok(());
+    };
+    let actual = content_digest(wasm);
+    if actual != pinned {
+        return Err(format!(
+            "Component digest {actual} does not match the pinned digest {pinned}"
+        ));
+    }
+    Ok(())
+}
+
+/// Check a pinned published reference is pinned to the same digest. Published images are pulled
+/// by the host rather than fetched here, so the reference itself must carry the pin.
+pub fn check_published_digest(step: &PipelineNode) -> Result<(), String> {
+    match (published_source(step), pinned_digest(step)) {
+        (Some(source), Some(digest)) if !source.ends_with(&format!("@{digest}")) => Err(format!(
+            "Node {} pins digest {digest}, its source {source} must reference it as {}@{digest}",
+            step.id,
+            source.split('@').next().unwrap_or(source)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Effective number of processor instances.
+///
+/// `PipelineNode.instances` is the scaling knob shared by every node type, so when it is set it
+/// takes precedence. `ProcessorWasmSettings.instances` acts as the processor's own default and
+/// is used otherwise, falling back to 10_000 when neither is present.
+pub fn processor_instances(step: &PipelineNode) -> u32 {
+    match (step.instances, &step.settings) {
+        (Some(instances), _) => instances,
+        (None, Some(PipelineNodeSettings::ProcessorWasm(settings))) => settings.instances,
+        (None, _) => 10_000,
+    }
+}
+
 impl ComponentBuilder for ProcessorWasmBuilder {
     fn build_components(
         &self,
@@ -16,136 +97,38 @@ impl ComponentBuilder for ProcessorWasmBuilder {
         let mut components = Vec::new();
 
         // Add in-internal component for processor
-        components.push(Component {
-            name: format!("in-internal-for-{}", step.id),
-            component_type: "component".to_string(),
-            properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-in-internal-for-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
-                image: format!(
-                    "{}/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}",
-                    context.app_config.registry.url
-                ),
-                config: None,
-            },
-            traits: vec![
-                Trait {
-                    trait_type: "spreadscaler".to_string(),
-                    properties: TraitProperties::Spreadscaler { instances: 10_000 },
-                },
-                Trait {
-                    trait_type: "link".to_string(),
-                    properties: TraitProperties::Link(LinkProperties {
-                        name: None,
-                        source: None,
-                        target: LinkTarget {
-                            name: step.id.clone(),
-                            config: None,
-                        },
-                        namespace: "pipestack".to_string(),
-                        package: "customer".to_string(),
-                        interfaces: vec!["customer".to_string()],
-                    }),
-                },
-                Trait {
-                    trait_type: "link".to_string(),
-                    properties: TraitProperties::Link(LinkProperties {
-                        name: None,
-                        source: None,
-                        target: LinkTarget {
-                            name: format!("out-internal-for-{}", step.id),
-                            config: None,
-                        },
-                        namespace: "pipestack".to_string(),
-                        package: "out".to_string(),
-                        interfaces: vec!["out".to_string()],
-                    }),
-                },
-            ],
-        });
+        components.push(processor_in_internal_component(step, context));
 
         // Add the processor component itself
         components.push(Component {
             name: step.id.clone(),
             component_type: "component".to_string(),
             properties: Properties::WithImage {
-                id: Some(format!(
-                    "{}_{}-{}",
-                    context.workspace_slug, context.pipeline.name, step.id
-                )),
-                image: format!(
-                    "{}/{}/pipeline/{}/{}/builder/components/nodes/processor/wasm/{}:1.0.0",
-                    context.app_config.registry.internal_url,
-                    context.workspace_slug,
-                    context.pipeline.name,
-                    context.pipeline.version,
-                    step.id
-                ),
+                id: Some(context.component_id(&step.id)),
+                // Use the published image as-is, otherwise the one pushed from the builder upload
+                image: match published_source(step) {
+                    Some(source) => source.to_string(),
+                    None => format!(
+                        "{}/{}/pipeline/{}/{}/builder/components/nodes/processor/wasm/{}:1.0.0",
+                        context.app_config.registry.internal_url,
+                        context.workspace_slug,
+                        context.pipeline.name,
+                        context.pipeline.version,
+                        step.id
+                    ),
+                },
                 config: None,
             },
             traits: vec![Trait {
                 trait_type: "spreadscaler".to_string(),
                 properties: TraitProperties::Spreadscaler {
-                    instances: step.instances.unwrap_or(10_000),
+                    instances: processor_instances(step),
                 },
             }],
         });
 
-        // Add out-internal component for processor
-        let next_topic = context.find_next_step_topic(&step.id).unwrap_or_default();
-
-        if !next_topic.is_empty() {
-            components.push(Component {
-                name: format!("out-internal-for-{}", step.id),
-                component_type: "component".to_string(),
-                properties: Properties::WithImage {
-                    id: Some(format!(
-                        "{}_{}-out-internal-for-{}",
-                        context.workspace_slug, context.pipeline.name, step.id
-                    )),
-                    image: format!(
-                        "{}/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}",
-                        context.app_config.registry.url
-                    ),
-                    config: Some(vec![Config {
-                        name: format!(
-                            "out-internal-for-{}-config-v{}",
-                            step.id, context.pipeline.version
-                        ),
-                        properties: {
-                            let mut props = std::collections::BTreeMap::new();
-                            props.insert(
-                                "next-step-topic".to_string(),
-                                serde_yaml::Value::String(next_topic.clone()),
-                            );
-                            props
-                        },
-                    }]),
-                },
-                traits: vec![
-                    Trait {
-                        trait_type: "spreadscaler".to_string(),
-                        properties: TraitProperties::Spreadscaler { instances: 10_000 },
-                    },
-                    Trait {
-                        trait_type: "link".to_string(),
-                        properties: TraitProperties::Link(LinkProperties {
-                            name: None,
-                            source: None,
-                            target: LinkTarget {
-                                name: "messaging-nats".to_string(),
-                                config: None,
-                            },
-                            namespace: "wasmcloud".to_string(),
-                            package: "messaging".to_string(),
-                            interfaces: vec!["consumer".to_string()],
-                        }),
-                    },
-                ],
-            });
-        }
+        // Add corresponding out-internal component
+        components.extend(out_internal_component(step, context));
 
         Ok(components)
     }
@@ -2,34 +2,79 @@ use shared::PipelineNodeType;
 
 use crate::builders::{
     ComponentBuilder,
-    nodes::r#in::InHttpWebhookBuilder,
-    nodes::out::{OutHttpWebhookBuilder, OutLogBuilder},
-    nodes::processor::ProcessorWasmBuilder,
+    nodes::r#in::{
+        InGraphqlPollerBuilder, InHttpWebhookBuilder, InRabbitmqBuilder, InRssReaderBuilder,
+    },
+    nodes::out::{
+        OutAwsLambdaBuilder, OutFileBuilder, OutGoogleBigqueryBuilder, OutGraphqlMutationBuilder,
+        OutHttpWebhookBuilder, OutLogBuilder, OutLokiBuilder, OutMongodbBuilder,
+        OutRabbitmqBuilder, OutSnowflakeBuilder,
+    },
+    nodes::processor::{ProcessorFilterBuilder, ProcessorFormatBuilder, ProcessorWasmBuilder},
 };
 
 pub struct ComponentBuilderRegistry {
     in_http_webhook: InHttpWebhookBuilder,
+    in_rss_reader: InRssReaderBuilder,
+    in_rabbitmq: InRabbitmqBuilder,
+    in_graphql_poller: InGraphqlPollerBuilder,
     processor_wasm: ProcessorWasmBuilder,
+    processor_format: ProcessorFormatBuilder,
+    processor_filter: ProcessorFilterBuilder,
     out_log: OutLogBuilder,
     out_http_webhook: OutHttpWebhookBuilder,
+    out_file: OutFileBuilder,
+    out_mongodb: OutMongodbBuilder,
+    out_loki: OutLokiBuilder,
+    out_rabbitmq: OutRabbitmqBuilder,
+    out_snowflake: OutSnowflakeBuilder,
+    out_google_bigquery: OutGoogleBigqueryBuilder,
+    out_aws_lambda: OutAwsLambdaBuilder,
+    out_graphql_mutation: OutGraphqlMutationBuilder,
 }
 
 impl ComponentBuilderRegistry {
     pub fn new() -> Self {
         Self {
             in_http_webhook: InHttpWebhookBuilder,
+            in_rss_reader: InRssReaderBuilder,
+            in_rabbitmq: InRabbitmqBuilder,
+            in_graphql_poller: InGraphqlPollerBuilder,
             processor_wasm: ProcessorWasmBuilder,
+            processor_format: ProcessorFormatBuilder,
+            processor_filter: ProcessorFilterBuilder,
             out_log: OutLogBuilder,
             out_http_webhook: OutHttpWebhookBuilder,
+            out_file: OutFileBuilder,
+            out_mongodb: OutMongodbBuilder,
+            out_loki: OutLokiBuilder,
+            out_rabbitmq: OutRabbitmqBuilder,
+            out_snowflake: OutSnowflakeBuilder,
+            out_google_bigquery: OutGoogleBigqueryBuilder,
+            out_aws_lambda: OutAwsLambdaBuilder,
+            out_graphql_mutation: OutGraphqlMutationBuilder,
         }
     }
 
     pub fn get_builder(&self, node_type: &PipelineNodeType) -> Option<&dyn ComponentBuilder> {
         match node_type {
             PipelineNodeType::InHttpWebhook => Some(&self.in_http_webhook),
+            PipelineNodeType::InRssReader => Some(&self.in_rss_reader),
+            PipelineNodeType::InRabbitmq => Some(&self.in_rabbitmq),
+            PipelineNodeType::InGraphqlPoller => Some(&self.in_graphql_poller),
             PipelineNodeType::ProcessorWasm => Some(&self.processor_wasm),
+            PipelineNodeType::ProcessorFormat => Some(&self.processor_format),
+            PipelineNodeType::ProcessorFilter => Some(&self.processor_filter),
             PipelineNodeType::OutLog => Some(&self.out_log),
             PipelineNodeType::OutHttpWebhook => Some(&self.out_http_webhook),
+            PipelineNodeType::OutFile => Some(&self.out_file),
+            PipelineNodeType::OutMongodb => Some(&self.out_mongodb),
+            PipelineNodeType::OutLoki => Some(&self.out_loki),
+            PipelineNodeType::OutRabbitmq => Some(&self.out_rabbitmq),
+            PipelineNodeType::OutSnowflake => Some(&self.out_snowflake),
+            PipelineNodeType::OutGoogleBigquery => Some(&self.out_google_bigquery),
+            PipelineNodeType::OutAwsLambda => Some(&self.out_aws_lambda),
+            PipelineNodeType::OutGraphqlMutation => Some(&self.out_graphql_mutation),
             _ => None,
         }
     }
@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use crate::builders::{Component, Config, Properties, ProviderBuilder, Trait, TraitProperties};
+use crate::config::AppConfig;
+
+pub struct KeyvalueNatsProviderBuilder;
+
+impl ProviderBuilder for KeyvalueNatsProviderBuilder {
+    fn build_component(
+        &self,
+        workspace_slug: &str,
+        app_config: &AppConfig,
+    ) -> Result<Component, Box<dyn std::error::Error>> {
+        Ok(Component {
+            name: "keyvalue-nats".to_string(),
+            component_type: "capability".to_string(),
+            properties: Properties::WithImage {
+                id: None,
+                image: "ghcr.io/wasmcloud/keyvalue-nats:0.3.1".to_string(),
+                config: Some(vec![Config {
+                    name: format!("{workspace_slug}-keyvalue-nats-config"),
+                    properties: {
+                        let mut props = BTreeMap::new();
+                        props.insert(
+                            "cluster_uri".to_string(),
+                            serde_yaml::Value::String(app_config.nats.joined_cluster_uris()),
+                        );
+                        if let Some(jwt) = &app_config.nats.jwt {
+                            props.insert(
+                                "client_jwt".to_string(),
+                                serde_yaml::Value::String(jwt.clone()),
+                            );
+                        }
+                        if let Some(seed) = &app_config.nats.nkey {
+                            props.insert(
+                                "client_seed".to_string(),
+                                serde_yaml::Value::String(seed.clone()),
+                            );
+                        }
+                        props
+                    },
+                }]),
+            },
+            traits: vec![Trait {
+                trait_type: "spreadscaler".to_string(),
+                properties: TraitProperties::Spreadscaler { instances: 1 },
+            }],
+        })
+    }
+}
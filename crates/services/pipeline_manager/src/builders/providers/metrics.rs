@@ -0,0 +1,34 @@
+use crate::builders::{Component, Properties, ProviderBuilder, Trait, TraitProperties};
+use crate::config::AppConfig;
+
+pub const METRICS_PROVIDER_NAME: &str = "metrics_provider";
+pub const METRICS_PROVIDER_VERSION: &str = "0.1.0";
+
+/// The Prometheus metrics provider of `crates/services/metrics_provider`, pushed to the same
+/// registry as the nodes
+pub struct MetricsProviderBuilder;
+
+impl ProviderBuilder for MetricsProviderBuilder {
+    fn build_component(
+        &self,
+        _workspace_slug: &str,
+        app_config: &AppConfig,
+    ) -> Result<Component, Box<dyn std::error::Error>> {
+        Ok(Component {
+            name: "metrics".to_string(),
+            component_type: "capability".to_string(),
+            properties: Properties::WithImage {
+                id: None,
+                image: format!(
+                    "{}/providers/{METRICS_PROVIDER_NAME}:{METRICS_PROVIDER_VERSION}",
+                    app_config.registry.url
+                ),
+                config: None,
+            },
+            traits: vec![Trait {
+                trait_type: "spreadscaler".to_string(),
+                properties: TraitProperties::Spreadscaler { instances: 1 },
+            }],
+        })
+    }
+}
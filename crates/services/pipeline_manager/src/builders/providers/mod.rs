@@ -1,9 +1,36 @@
 pub mod http_client;
 pub mod http_server;
+pub mod keyvalue_nats;
+pub mod metrics;
 pub mod nats_messaging;
 pub mod registry;
 
 pub use http_client::HttpClientProviderBuilder;
 pub use http_server::HttpServerProviderBuilder;
+pub use keyvalue_nats::KeyvalueNatsProviderBuilder;
+pub use metrics::MetricsProviderBuilder;
 pub use nats_messaging::NatsMessagingProviderBuilder;
 pub use registry::ProviderBuilderRegistry;
+
+/// Capability a pipeline links to, provided by the shared `{workspace}-providers` application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capability {
+    HttpServer,
+    HttpClient,
+    KeyvalueNats,
+    MessagingNats,
+    Metrics,
+}
+
+impl Capability {
+    /// Name of the provider component exposing the capability
+    pub fn component_name(self) -> &'static str {
+        match self {
+            Capability::HttpServer => "httpserver",
+            Capability::HttpClient => "httpclient",
+            Capability::KeyvalueNats => "keyvalue-nats",
+            Capability::MessagingNats => "messaging-nats",
+            Capability::Metrics => "metrics",
+        }
+    }
+}
@@ -23,7 +23,7 @@ impl ProviderBuilder for NatsMessagingProviderBuilder {
                         let mut props = BTreeMap::new();
                         props.insert(
                             "cluster_uris".to_string(),
-                            serde_yaml::Value::String(app_config.nats.cluster_uris.to_string()),
+                            serde_yaml::Value::String(app_config.nats.joined_cluster_uris()),
                         );
                         if let Some(jwt) = &app_config.nats.jwt {
                             props.insert(
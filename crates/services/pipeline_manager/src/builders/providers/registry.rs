@@ -2,13 +2,16 @@ use crate::builders::ProviderBuilder;
 #[cfg(test)]
 use crate::builders::ProviderType;
 use crate::builders::providers::{
-    HttpClientProviderBuilder, HttpServerProviderBuilder, NatsMessagingProviderBuilder,
+    HttpClientProviderBuilder, HttpServerProviderBuilder, KeyvalueNatsProviderBuilder,
+    MetricsProviderBuilder, NatsMessagingProviderBuilder,
 };
 
 pub struct ProviderBuilderRegistry {
     http_server: HttpServerProviderBuilder,
     http_client: HttpClientProviderBuilder,
     nats_messaging: NatsMessagingProviderBuilder,
+    keyvalue_nats: KeyvalueNatsProviderBuilder,
+    metrics: MetricsProviderBuilder,
 }
 
 impl ProviderBuilderRegistry {
@@ -17,6 +20,8 @@ impl ProviderBuilderRegistry {
             http_server: HttpServerProviderBuilder,
             http_client: HttpClientProviderBuilder,
             nats_messaging: NatsMessagingProviderBuilder,
+            keyvalue_nats: KeyvalueNatsProviderBuilder,
+            metrics: MetricsProviderBuilder,
         }
     }
 
@@ -26,6 +31,8 @@ impl ProviderBuilderRegistry {
             ProviderType::HttpServer => Some(&self.http_server),
             ProviderType::HttpClient => Some(&self.http_client),
             ProviderType::NatsMessaging => Some(&self.nats_messaging),
+            ProviderType::KeyvalueNats => Some(&self.keyvalue_nats),
+            ProviderType::Metrics => Some(&self.metrics),
         }
     }
 
@@ -34,6 +41,8 @@ impl ProviderBuilderRegistry {
             &self.http_server as &dyn ProviderBuilder,
             &self.http_client as &dyn ProviderBuilder,
             &self.nats_messaging as &dyn ProviderBuilder,
+            &self.keyvalue_nats as &dyn ProviderBuilder,
+            &self.metrics as &dyn ProviderBuilder,
         ]
     }
 }
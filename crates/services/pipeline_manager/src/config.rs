@@ -1,11 +1,20 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
 use config::{Config, ConfigError, Environment, File};
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, de::Error};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
 }
 
+impl std::fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("url", &shared::redact_connection_string(&self.url))
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Cloudflare {
     pub account_id: String,
@@ -16,15 +25,173 @@ pub struct Cloudflare {
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Nats {
-    pub cluster_uris: String,
+    /// NATS servers to connect to, as a list or a comma-separated string
+    #[serde(deserialize_with = "deserialize_cluster_uris")]
+    pub cluster_uris: Vec<String>,
     pub jwt: Option<String>,
     pub nkey: Option<String>,
+    /// User dead letters are replayed as. It should only be allowed to publish to replay subjects,
+    /// `pipestack.*.*.*.replay`. Replaying is disabled without it; with it, dead letters are kept
+    /// in the dead-letter stream to be replayed from.
+    #[serde(default)]
+    pub replay: Option<NatsUser>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct NatsUser {
+    pub jwt: String,
+    pub nkey: String,
+}
+
+impl std::fmt::Debug for NatsUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NatsUser")
+            .field("jwt", &self.jwt)
+            .field("nkey", &"***")
+            .finish()
+    }
+}
+
+impl Nats {
+    /// Cluster URIs as the comma-separated list NATS clients and the messaging provider accept
+    pub fn joined_cluster_uris(&self) -> String {
+        self.cluster_uris.join(",")
+    }
+}
+
+fn deserialize_cluster_uris<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ClusterUris {
+        List(Vec<String>),
+        CommaSeparated(String),
+    }
+
+    let uris = match ClusterUris::deserialize(deserializer)? {
+        ClusterUris::List(uris) => uris,
+        ClusterUris::CommaSeparated(uris) => uris.split(',').map(str::to_string).collect(),
+    };
+    let uris: Vec<String> = uris
+        .iter()
+        .map(|uri| uri.trim())
+        .filter(|uri| !uri.is_empty())
+        .map(str::to_string)
+        .collect();
+    if uris.is_empty() {
+        return Err(D::Error::custom(
+            "cluster_uris must list at least one NATS server",
+        ));
+    }
+    Ok(uris)
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Registry {
     pub internal_url: String,
     pub url: String,
+    /// Credentials components are pushed with, anonymous by default
+    #[serde(default)]
+    pub auth: RegistryAuth,
+}
+
+/// How to authenticate with the registry components are pushed to
+#[derive(Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RegistryAuth {
+    #[default]
+    Anonymous,
+    Basic {
+        username: String,
+        password: String,
+    },
+    /// A token the registry accepts as is, e.g. an identity token
+    Bearer {
+        token: String,
+    },
+}
+
+/// User a bearer token is pushed as. wash only pushes with basic auth, and registries taking
+/// identity tokens accept them as the password of this user.
+const BEARER_TOKEN_USER: &str = "<token>";
+
+impl RegistryAuth {
+    /// Value of the `Authorization` header of requests to the registry API, if any
+    pub fn authorization_header(&self) -> Option<String> {
+        match self {
+            Self::Anonymous => None,
+            Self::Basic { username, password } => Some(format!(
+                "Basic {}",
+                BASE64_STANDARD.encode(format!("{username}:{password}"))
+            )),
+            Self::Bearer { token } => Some(format!("Bearer {token}")),
+        }
+    }
+
+    /// User and password to push with, if any
+    pub fn push_credentials(&self) -> Option<(String, String)> {
+        match self {
+            Self::Anonymous => None,
+            Self::Basic { username, password } => Some((username.clone(), password.clone())),
+            Self::Bearer { token } => Some((BEARER_TOKEN_USER.to_string(), token.clone())),
+        }
+    }
+}
+
+impl std::fmt::Debug for RegistryAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Anonymous => f.write_str("Anonymous"),
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+            Self::Bearer { .. } => f.debug_struct("Bearer").field("token", &"***").finish(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Pipelines with more nodes than this are rejected before being converted
+    pub max_nodes: usize,
+    /// Deploy request bodies larger than this are rejected before being parsed
+    pub max_body_bytes: usize,
+    /// Deploy requests a workspace regains per minute. 0 disables deploy rate limiting.
+    pub deploys_per_minute: u32,
+    /// Deploy requests a workspace can make in a row before being limited
+    pub deploy_burst: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 250,
+            max_body_bytes: 2 * 1024 * 1024,
+            deploys_per_minute: 6,
+            deploy_burst: 5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Scheduler {
+    /// How often a tick is published to the poll subject of every deployed polling source. Sources
+    /// only fetch once their own poll interval has elapsed, so this bounds how late a poll can be.
+    /// 0 disables the poll scheduler.
+    pub poll_tick_ms: u64,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self {
+            poll_tick_ms: 1_000,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -33,6 +200,14 @@ pub struct AppConfig {
     pub nats: Nats,
     pub registry: Registry,
     pub database: DatabaseConfig,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub scheduler: Scheduler,
+    /// Prefix of every webhook route, e.g. `/hooks/{workspace}` to serve a pipeline's webhooks
+    /// under `/hooks/{workspace}/{pipeline}/...`. `{workspace}` is replaced with the workspace slug.
+    #[serde(default)]
+    pub route_prefix: String,
 }
 
 impl AppConfig {
@@ -45,4 +220,107 @@ impl AppConfig {
         tracing::debug!("Loaded app config: {:?}", app_config);
         Ok(app_config)
     }
+
+    /// Config for converting pipelines offline, read from a file in the `.env.example.toml`
+    /// format. Only the `nats`, `registry` and `limits` sections are needed: nothing is connected
+    /// to, so the database and Cloudflare settings default to empty values.
+    #[allow(dead_code)] // Only used by the offline conversion of the pipeline schema crate
+    pub fn for_conversion(path: &std::path::Path) -> Result<Self, ConfigError> {
+        Config::builder()
+            .set_default("database.url", "")?
+            .set_default("cloudflare.account_id", "")?
+            .set_default("cloudflare.r2_access_key_id", "")?
+            .set_default("cloudflare.r2_secret_access_key", "")?
+            .set_default("cloudflare.r2_bucket", "")?
+            .add_source(File::from(path))
+            .build()?
+            .try_deserialize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nats(cluster_uris: serde_json::Value) -> Result<Nats, serde_json::Error> {
+        serde_json::from_value(serde_json::json!({ "cluster_uris": cluster_uris }))
+    }
+
+    #[test]
+    fn test_cluster_uris_accepts_list_or_comma_separated_string() {
+        let expected = vec![
+            "nats://nats-a:4222".to_string(),
+            "nats://nats-b:4222".to_string(),
+        ];
+
+        let from_list = nats(serde_json::json!([
+            "nats://nats-a:4222",
+            "nats://nats-b:4222"
+        ]));
+        assert_eq!(from_list.unwrap().cluster_uris, expected);
+
+        let from_string = nats(serde_json::json!("nats://nats-a:4222, nats://nats-b:4222"));
+        let from_string = from_string.unwrap();
+        assert_eq!(from_string.cluster_uris, expected);
+        assert_eq!(
+            from_string.joined_cluster_uris(),
+            "nats://nats-a:4222,nats://nats-b:4222"
+        );
+    }
+
+    #[test]
+    fn test_cluster_uris_must_not_be_empty() {
+        assert!(nats(serde_json::json!("")).is_err());
+        assert!(nats(serde_json::json!([" "])).is_err());
+    }
+
+    #[test]
+    fn test_registry_auth_header() {
+        let auth =
+            |value: serde_json::Value| -> RegistryAuth { serde_json::from_value(value).unwrap() };
+
+        let anonymous = auth(serde_json::json!({ "type": "anonymous" }));
+        assert_eq!(anonymous, RegistryAuth::default());
+        assert_eq!(anonymous.authorization_header(), None);
+        assert_eq!(anonymous.push_credentials(), None);
+
+        let basic = auth(serde_json::json!({
+            "type": "basic",
+            "username": "pipestack",
+            "password": "s3cret"
+        }));
+        assert_eq!(
+            basic.authorization_header().as_deref(),
+            Some("Basic cGlwZXN0YWNrOnMzY3JldA==")
+        );
+        assert_eq!(
+            basic.push_credentials(),
+            Some(("pipestack".to_string(), "s3cret".to_string()))
+        );
+        assert_eq!(
+            format!("{basic:?}"),
+            r#"Basic { username: "pipestack", password: "***" }"#
+        );
+
+        let bearer = auth(serde_json::json!({ "type": "bearer", "token": "t0ken" }));
+        assert_eq!(
+            bearer.authorization_header().as_deref(),
+            Some("Bearer t0ken")
+        );
+        assert_eq!(
+            bearer.push_credentials(),
+            Some(("<token>".to_string(), "t0ken".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_database_url_is_redacted_in_debug_output() {
+        let database = DatabaseConfig {
+            url: "postgres://pipestack:s3cret@db:5432/pipestack".to_string(),
+        };
+        assert_eq!(
+            format!("{database:?}"),
+            r#"DatabaseConfig { url: "postgres://pipestack:***@db:5432/pipestack" }"#
+        );
+    }
 }
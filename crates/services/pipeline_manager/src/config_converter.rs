@@ -1,20 +1,42 @@
-use shared::{Pipeline, PipelineNodeSettings, PipelineNodeType};
-use std::collections::{BTreeMap, HashMap};
+use serde::Serialize;
+use shared::{
+    DEAD_LETTER_SUBJECT_CONFIG_KEY, NodeCategory, Pipeline, PipelineNode, PipelineNodeSettings,
+    PipelineNodeType, flush_subject, is_valid_output_name, output_subject,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use crate::builders::{
     ApplicationRef, BuildContext, Component, Config, LinkProperties, LinkSource, LinkTarget,
-    Metadata, Properties, Spec, Trait, TraitProperties, WadmApplication,
-    nodes::registry::ComponentBuilderRegistry, providers::ProviderBuilderRegistry,
+    Metadata, Properties, Spec, Trait, TraitProperties, WadmApplication, dead_letter_step,
+    dead_letter_subject,
+    nodes::r#in::{is_polled, poll_subject},
+    nodes::messaging_consumer_link,
+    nodes::processor::wasm::check_published_digest,
+    nodes::registry::ComponentBuilderRegistry,
+    providers::{Capability, ProviderBuilderRegistry},
+    replay_subject,
 };
 use crate::config::AppConfig;
+use crate::deployment::DeployError;
 
 pub fn convert_pipeline(
     pipeline: &Pipeline,
     workspace_slug: &String,
     app_config: &AppConfig,
+) -> Result<WadmApplication, DeployError> {
+    validate_pipeline(pipeline, app_config)?;
+    build_application(pipeline, workspace_slug, app_config)
+        .map_err(|e| DeployError::Conversion(e.to_string()))
+}
+
+/// The wadm manifest of a validated pipeline
+fn build_application(
+    pipeline: &Pipeline,
+    workspace_slug: &String,
+    app_config: &AppConfig,
 ) -> Result<WadmApplication, Box<dyn std::error::Error>> {
     let mut components = Vec::new();
-    let step_topics = determine_step_topics(pipeline, workspace_slug);
+    let step_topics = determine_step_topics(pipeline, workspace_slug)?;
 
     // Create build context
     let context = BuildContext::new(pipeline, workspace_slug, app_config, &step_topics);
@@ -24,26 +46,35 @@ pub fn convert_pipeline(
 
     // Process each step using the appropriate builder
     for step in &pipeline.nodes {
-        if let Some(builder) = registry.get_builder(&step.step_type) {
-            let step_components = builder.build_components(step, &context)?;
-            components.extend(step_components);
-        } else {
-            // Handle unsupported step types
-            eprintln!("Unsupported step type: {:?}", step.step_type);
-        }
+        let builder = registry.get_builder(&step.step_type).ok_or_else(|| {
+            format!(
+                "Node {} has type {:?}, which can't be deployed yet",
+                step.id, step.step_type
+            )
+        })?;
+        components.extend(builder.build_components(step, &context)?);
+    }
+    if let Some(dead_letter) = &pipeline.dead_letter {
+        add_dead_letter_subjects(&mut components, pipeline, workspace_slug, dead_letter);
     }
+    check_unique_component_ids(&components)?;
 
-    // Add capabilities (httpserver, httpclient and messaging-nats)
-    // HTTP Server capability
-    let http_steps: Vec<_> = pipeline
-        .nodes
-        .iter()
-        .filter(|s| matches!(s.step_type, PipelineNodeType::InHttpWebhook))
-        .collect();
+    // Add the capabilities the nodes require, all exposed by the workspace's providers
+    let capabilities = pipeline_capabilities(pipeline);
+    check_providers_expose(
+        &capabilities,
+        &create_providers_wadm(workspace_slug, app_config),
+    )?;
 
-    if !http_steps.is_empty() {
+    // HTTP Server capability
+    if capabilities.contains(&Capability::HttpServer) {
         let mut http_traits = Vec::new();
+        let route_prefix = route_prefix(app_config, workspace_slug);
 
+        let http_steps = pipeline
+            .nodes
+            .iter()
+            .filter(|s| matches!(s.step_type, PipelineNodeType::InHttpWebhook));
         for http_step in http_steps {
             // Extract path from settings, or use empty string as default
             let path = match &http_step.settings {
@@ -66,14 +97,12 @@ pub fn convert_pipeline(
                             ),
                             properties: {
                                 let mut props = BTreeMap::new();
-                                let final_path = if path.is_empty() {
-                                    format!("/{}", pipeline.name)
-                                } else {
-                                    format!("/{}/{}", pipeline.name, path)
-                                };
                                 props.insert(
                                     "path".to_string(),
-                                    serde_yaml::Value::String(final_path),
+                                    serde_yaml::Value::String(format!(
+                                        "{route_prefix}{}",
+                                        webhook_route(pipeline, &path)
+                                    )),
                                 );
                                 props
                             },
@@ -90,45 +119,77 @@ pub fn convert_pipeline(
             });
         }
 
-        components.push(Component {
-            name: "httpserver".to_string(),
-            component_type: "capability".to_string(),
-            properties: Properties::WithApplication {
-                application: ApplicationRef {
-                    name: format!("{workspace_slug}-providers"),
-                    component: "httpserver".to_string(),
-                },
-            },
-            traits: http_traits,
-        });
+        components.push(capability_component(
+            Capability::HttpServer,
+            workspace_slug,
+            http_traits,
+        ));
     }
 
-    // HTTP Client capability
-    if pipeline
-        .nodes
-        .iter()
-        .any(|s| matches!(s.step_type, PipelineNodeType::OutHttpWebhook))
-    {
-        components.push(Component {
-            name: "httpclient".to_string(),
-            component_type: "capability".to_string(),
-            properties: Properties::WithApplication {
-                application: ApplicationRef {
-                    name: format!("{workspace_slug}-providers"),
-                    component: "httpclient".to_string(),
-                },
-            },
-            traits: vec![],
-        });
+    // HTTP Client, key-value and metrics capabilities, linked to from the nodes' components
+    for capability in [
+        Capability::HttpClient,
+        Capability::KeyvalueNats,
+        Capability::Metrics,
+    ] {
+        if capabilities.contains(&capability) {
+            components.push(capability_component(capability, workspace_slug, vec![]));
+        }
     }
 
-    // NATS messaging capability
+    // NATS messaging capability. Every link references a single config holding the cluster URIs.
     let mut nats_traits = vec![];
+    let nats_cluster_config = format!("messaging-nats-cluster-config-v{}", pipeline.version);
 
-    // Add messaging-nats links
-    let mut subscription_counter = 1;
+    // Subscribe pollers to their poll subject
     for step in &pipeline.nodes {
-        if matches!(step.step_type, PipelineNodeType::ProcessorWasm)
+        if is_polled(&step.step_type) {
+            nats_traits.push(Trait {
+                trait_type: "link".to_string(),
+                properties: TraitProperties::Link(LinkProperties {
+                    name: Some(format!(
+                        "messaging-nats-to-{}-{}-link",
+                        workspace_slug, step.id
+                    )),
+                    source: Some(LinkSource {
+                        config: Some(vec![
+                            Config {
+                                name: format!("{}-poll-config-v{}", step.id, pipeline.version),
+                                properties: {
+                                    let mut props = BTreeMap::new();
+                                    props.insert(
+                                        "subscriptions".to_string(),
+                                        serde_yaml::Value::String(poll_subject(
+                                            workspace_slug,
+                                            &pipeline.name,
+                                            &step.id,
+                                        )),
+                                    );
+                                    props
+                                },
+                            },
+                            config_ref(&nats_cluster_config),
+                        ]),
+                    }),
+                    target: LinkTarget {
+                        name: step.id.clone(),
+                        config: None,
+                    },
+                    namespace: "wasmcloud".to_string(),
+                    package: "messaging".to_string(),
+                    interfaces: vec!["handler".to_string()],
+                }),
+            });
+        }
+    }
+
+    // Add messaging-nats links. Subscription configs are numbered in node id order so that the
+    // order of the nodes in the pipeline doesn't change their names.
+    let mut steps_by_id: Vec<_> = pipeline.nodes.iter().collect();
+    steps_by_id.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut subscription_counter = 1;
+    for step in &steps_by_id {
+        if step.step_type.category() == NodeCategory::Processor
             && let Some(topic) = step_topics.get(&step.id)
         {
             nats_traits.push(Trait {
@@ -139,26 +200,28 @@ pub fn convert_pipeline(
                         workspace_slug, step.id
                     )),
                     source: Some(LinkSource {
-                        config: Some(vec![Config {
-                            name: format!(
-                                "subscription-{subscription_counter}-config-v{}",
-                                pipeline.version
-                            ),
-                            properties: {
-                                let mut props = BTreeMap::new();
-                                props.insert(
-                                    "subscriptions".to_string(),
-                                    serde_yaml::Value::String(topic.clone()),
-                                );
-                                props.insert(
-                                    "cluster_uris".to_string(),
-                                    serde_yaml::Value::String(
-                                        app_config.nats.cluster_uris.to_string(),
-                                    ),
-                                );
-                                props
+                        config: Some(vec![
+                            Config {
+                                name: format!(
+                                    "subscription-{subscription_counter}-config-v{}",
+                                    pipeline.version
+                                ),
+                                properties: {
+                                    let mut props = BTreeMap::new();
+                                    props.insert(
+                                        "subscriptions".to_string(),
+                                        serde_yaml::Value::String(subscriptions(
+                                            pipeline,
+                                            workspace_slug,
+                                            step,
+                                            topic,
+                                        )),
+                                    );
+                                    props
+                                },
                             },
-                        }]),
+                            config_ref(&nats_cluster_config),
+                        ]),
                     }),
                     target: LinkTarget {
                         name: format!("in-internal-for-{}", step.id),
@@ -173,11 +236,9 @@ pub fn convert_pipeline(
         }
     }
 
-    for step in &pipeline.nodes {
-        if matches!(
-            step.step_type,
-            PipelineNodeType::OutLog | PipelineNodeType::OutHttpWebhook
-        ) && let Some(topic) = step_topics.get(&step.id)
+    for step in &steps_by_id {
+        if is_deployable_sink(&step.step_type, &registry)
+            && let Some(topic) = step_topics.get(&step.id)
         {
             nats_traits.push(Trait {
                 trait_type: "link".to_string(),
@@ -187,26 +248,28 @@ pub fn convert_pipeline(
                         workspace_slug, step.id
                     )),
                     source: Some(LinkSource {
-                        config: Some(vec![Config {
-                            name: format!(
-                                "subscription-{subscription_counter}-config-v{}",
-                                pipeline.version
-                            ),
-                            properties: {
-                                let mut props = BTreeMap::new();
-                                props.insert(
-                                    "subscriptions".to_string(),
-                                    serde_yaml::Value::String(topic.clone()),
-                                );
-                                props.insert(
-                                    "cluster_uris".to_string(),
-                                    serde_yaml::Value::String(
-                                        app_config.nats.cluster_uris.to_string(),
-                                    ),
-                                );
-                                props
+                        config: Some(vec![
+                            Config {
+                                name: format!(
+                                    "subscription-{subscription_counter}-config-v{}",
+                                    pipeline.version
+                                ),
+                                properties: {
+                                    let mut props = BTreeMap::new();
+                                    props.insert(
+                                        "subscriptions".to_string(),
+                                        serde_yaml::Value::String(subscriptions(
+                                            pipeline,
+                                            workspace_slug,
+                                            step,
+                                            topic,
+                                        )),
+                                    );
+                                    props
+                                },
                             },
-                        }]),
+                            config_ref(&nats_cluster_config),
+                        ]),
                     }),
                     target: LinkTarget {
                         name: format!("in-internal-for-{}", step.id),
@@ -221,26 +284,82 @@ pub fn convert_pipeline(
         }
     }
 
-    components.push(Component {
-        name: "messaging-nats".to_string(),
-        component_type: "capability".to_string(),
-        properties: Properties::WithApplication {
-            application: ApplicationRef {
-                name: format!("{workspace_slug}-providers"),
-                component: "messaging-nats".to_string(),
-            },
+    // Route every other node's dead-letter subject to the pipeline's dead-letter sink
+    if let Some(dead_letter) = &pipeline.dead_letter {
+        for step in steps_by_id.iter().filter(|step| &step.id != dead_letter) {
+            nats_traits.push(Trait {
+                trait_type: "link".to_string(),
+                properties: TraitProperties::Link(LinkProperties {
+                    name: Some(format!(
+                        "messaging-nats-to-{}-dead-letter-for-{}-link",
+                        workspace_slug, step.id
+                    )),
+                    source: Some(LinkSource {
+                        config: Some(vec![
+                            Config {
+                                name: format!(
+                                    "{}-dead-letter-config-v{}",
+                                    step.id, pipeline.version
+                                ),
+                                properties: {
+                                    let mut props = BTreeMap::new();
+                                    props.insert(
+                                        "subscriptions".to_string(),
+                                        serde_yaml::Value::String(dead_letter_subject(
+                                            workspace_slug,
+                                            &pipeline.name,
+                                            &step.id,
+                                        )),
+                                    );
+                                    props
+                                },
+                            },
+                            config_ref(&nats_cluster_config),
+                        ]),
+                    }),
+                    target: LinkTarget {
+                        name: format!("in-internal-for-{dead_letter}"),
+                        config: None,
+                    },
+                    namespace: "wasmcloud".to_string(),
+                    package: "messaging".to_string(),
+                    interfaces: vec!["handler".to_string()],
+                }),
+            });
+        }
+    }
+
+    nats_traits.sort_by(|a, b| trait_sort_key(a).cmp(&trait_sort_key(b)));
+    declare_shared_config(
+        &mut nats_traits,
+        Config {
+            name: nats_cluster_config,
+            properties: BTreeMap::from([(
+                "cluster_uris".to_string(),
+                serde_yaml::Value::String(app_config.nats.joined_cluster_uris()),
+            )]),
         },
-        traits: nats_traits,
-    });
+    );
+
+    if capabilities.contains(&Capability::MessagingNats) {
+        components.push(capability_component(
+            Capability::MessagingNats,
+            workspace_slug,
+            nats_traits,
+        ));
+    }
+
+    sort_components(&mut components);
 
     Ok(WadmApplication {
         api_version: "core.oam.dev/v1beta1".to_string(),
         kind: "Application".to_string(),
         metadata: Metadata {
-            name: format!("{}-{}", workspace_slug, pipeline.name,),
+            name: application_name(workspace_slug, &pipeline.name),
             annotations: {
                 let mut annotations = BTreeMap::new();
                 annotations.insert("version".to_string(), pipeline.version.clone());
+                annotations.extend(node_annotations(pipeline));
                 annotations
             },
         },
@@ -248,156 +367,751 @@ pub fn convert_pipeline(
     })
 }
 
-fn determine_step_topics(pipeline: &Pipeline, workspace_slug: &String) -> HashMap<String, String> {
-    let mut step_topics = HashMap::new();
+/// Reference to a config declared elsewhere in the manifest
+fn config_ref(name: &str) -> Config {
+    Config {
+        name: name.to_string(),
+        properties: BTreeMap::new(),
+    }
+}
 
-    // Generate topic names for inter-step communication based on dependency depth
-    // Build a map of node names to their dependency depth
-    let mut node_depths = HashMap::new();
+/// Declare a config on the first link referencing it. wadm creates the config from that
+/// declaration, and the other links reference it by name.
+fn declare_shared_config(traits: &mut [Trait], config: Config) {
+    let referencing = traits.iter_mut().find_map(|t| match &mut t.properties {
+        TraitProperties::Link(LinkProperties {
+            source: Some(LinkSource {
+                config: Some(configs),
+            }),
+            ..
+        }) => configs.iter_mut().find(|c| c.name == config.name),
+        _ => None,
+    });
+    if let Some(reference) = referencing {
+        *reference = config;
+    }
+}
 
-    // Find root nodes (no dependencies)
-    for step in &pipeline.nodes {
-        if step.depends_on.is_none() || step.depends_on.as_ref().unwrap().is_empty() {
-            node_depths.insert(step.id.clone(), 1);
+/// Reject components deployed under the same id, e.g. a node whose id matches the name of a
+/// component built for another node
+fn check_unique_component_ids(components: &[Component]) -> Result<(), String> {
+    let mut ids: HashMap<&str, &str> = HashMap::new();
+    for component in components {
+        let Properties::WithImage { id: Some(id), .. } = &component.properties else {
+            continue;
+        };
+        if let Some(other) = ids.insert(id, &component.name) {
+            return Err(format!(
+                "Components {other} and {} would both be deployed with the id {id}",
+                component.name
+            ));
         }
     }
+    Ok(())
+}
 
-    // Calculate depths for dependent nodes
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for step in &pipeline.nodes {
-            if let Some(depends_on) = &step.depends_on
-                && !depends_on.is_empty()
-                && !node_depths.contains_key(&step.id)
-            {
-                // Check if all dependencies have been processed
-                let mut max_depth = 0;
-                let mut all_deps_processed = true;
-                for dep in depends_on {
-                    if let Some(&depth) = node_depths.get(dep) {
-                        max_depth = max_depth.max(depth);
-                    } else {
-                        all_deps_processed = false;
-                        break;
-                    }
-                }
-                if all_deps_processed {
-                    node_depths.insert(step.id.clone(), max_depth + 1);
-                    changed = true;
-                }
-            }
+/// Name of the wadm application a pipeline is deployed as
+pub fn application_name(workspace_slug: &str, pipeline_name: &str) -> String {
+    format!("{workspace_slug}-{pipeline_name}")
+}
+
+/// Name of the pipeline a wadm application was deployed from, or `None` if the application
+/// isn't named after a pipeline of the workspace
+pub fn pipeline_name<'a>(workspace_slug: &str, application_name: &'a str) -> Option<&'a str> {
+    application_name
+        .strip_prefix(workspace_slug)?
+        .strip_prefix('-')
+        .filter(|name| !name.is_empty())
+}
+
+/// Subjects the in-internal component of a node subscribes to: its step topic for the default
+/// outputs of its dependencies, a subject per named output it depends on, and its replay subject.
+/// Sinks that can buffer messages also take flush requests.
+fn subscriptions(
+    pipeline: &Pipeline,
+    workspace_slug: &str,
+    step: &PipelineNode,
+    topic: &str,
+) -> String {
+    let mut subjects: Vec<String> = Vec::new();
+    for dep in step.depends_on.iter().flatten() {
+        let subject = match dep.output() {
+            Some(output) => output_subject(topic, output),
+            None => topic.to_string(),
+        };
+        if !subjects.contains(&subject) {
+            subjects.push(subject);
         }
     }
+    if step.step_type == PipelineNodeType::OutLoki {
+        subjects.push(flush_subject(topic));
+    }
+    subjects.push(replay_subject(workspace_slug, &pipeline.name, &step.id));
+    subjects.join(",")
+}
 
-    // Generate topics for nodes that have dependencies
-    for step in &pipeline.nodes {
-        if let Some(depends_on) = &step.depends_on
-            && !depends_on.is_empty()
-            && let Some(&depth) = node_depths.get(&step.id)
-        {
-            let topic = format!(
-                "pipestack.{}.{}.step-{}-in",
-                workspace_slug, pipeline.name, depth
-            );
-            step_topics.insert(step.id.clone(), topic);
+/// Capabilities the components of a node of this type link to. Every node's components exchange
+/// messages over NATS.
+pub fn required_capabilities(step_type: &PipelineNodeType) -> &'static [Capability] {
+    match step_type {
+        PipelineNodeType::InHttpWebhook => &[Capability::HttpServer, Capability::MessagingNats],
+        PipelineNodeType::InRssReader | PipelineNodeType::InGraphqlPoller => &[
+            Capability::HttpClient,
+            Capability::KeyvalueNats,
+            Capability::MessagingNats,
+        ],
+        PipelineNodeType::InRabbitmq => &[Capability::HttpClient, Capability::MessagingNats],
+        // The webhook keeps its circuit breakers in keyvalue
+        PipelineNodeType::OutHttpWebhook => &[
+            Capability::HttpClient,
+            Capability::KeyvalueNats,
+            Capability::MessagingNats,
+            Capability::Metrics,
+        ],
+        // Processors and sinks record metrics from their in-internal component
+        PipelineNodeType::OutAwsLambda
+        | PipelineNodeType::OutGraphqlMutation
+        | PipelineNodeType::OutLoki
+        | PipelineNodeType::OutRabbitmq => &[
+            Capability::HttpClient,
+            Capability::MessagingNats,
+            Capability::Metrics,
+        ],
+        _ => &[Capability::MessagingNats, Capability::Metrics],
+    }
+}
+
+/// Capabilities required by the nodes of a pipeline
+pub fn pipeline_capabilities(pipeline: &Pipeline) -> BTreeSet<Capability> {
+    pipeline
+        .nodes
+        .iter()
+        .flat_map(|step| required_capabilities(&step.step_type))
+        .copied()
+        .collect()
+}
+
+/// Check that the providers application exposes every capability a pipeline requires, as a
+/// capability it doesn't expose leaves the pipeline's links dangling
+fn check_providers_expose(
+    capabilities: &BTreeSet<Capability>,
+    providers: &WadmApplication,
+) -> Result<(), String> {
+    let missing: Vec<_> = capabilities
+        .iter()
+        .map(|capability| capability.component_name())
+        .filter(|name| {
+            !providers
+                .spec
+                .components
+                .iter()
+                .any(|c| c.name == *name && c.component_type == "capability")
+        })
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "The {} application doesn't expose the capabilities the pipeline requires: {}",
+        providers.metadata.name,
+        missing.join(", ")
+    ))
+}
+
+/// Pass every node but the dead-letter sink its dead-letter subject. The node's in-internal
+/// component publishes the messages the node gives up on there.
+fn add_dead_letter_subjects(
+    components: &mut [Component],
+    pipeline: &Pipeline,
+    workspace_slug: &str,
+    dead_letter: &str,
+) {
+    for step in pipeline.nodes.iter().filter(|step| step.id != dead_letter) {
+        let name = format!("in-internal-for-{}", step.id);
+        let Some(component) = components.iter_mut().find(|c| c.name == name) else {
+            continue;
+        };
+        if let Properties::WithImage { config, .. } = &mut component.properties {
+            config.get_or_insert_with(Vec::new).push(Config {
+                name: format!("{name}-dead-letter-v{}", pipeline.version),
+                properties: BTreeMap::from([(
+                    DEAD_LETTER_SUBJECT_CONFIG_KEY.to_string(),
+                    serde_yaml::Value::String(dead_letter_subject(
+                        workspace_slug,
+                        &pipeline.name,
+                        &step.id,
+                    )),
+                )]),
+            });
+        }
+        let consumer = messaging_consumer_link(&name);
+        if !component.traits.contains(&consumer) {
+            component.traits.push(consumer);
         }
     }
-    step_topics
 }
 
-pub fn create_providers_wadm(workspace_slug: &str, app_config: &AppConfig) -> WadmApplication {
-    let mut annotations = BTreeMap::new();
-    annotations.insert(
-        "experimental.wasmcloud.dev/shared".to_string(),
-        "true".to_string(),
-    );
-    annotations.insert(
-        "description".to_string(),
-        format!("Shared providers for the {workspace_slug} workspace"),
-    );
-    annotations.insert("version".to_string(), "0.8.0".to_string());
+/// Component referencing a capability of the workspace's providers application
+fn capability_component(
+    capability: Capability,
+    workspace_slug: &str,
+    traits: Vec<Trait>,
+) -> Component {
+    Component {
+        name: capability.component_name().to_string(),
+        component_type: "capability".to_string(),
+        properties: Properties::WithApplication {
+            application: ApplicationRef {
+                name: format!("{workspace_slug}-providers"),
+                component: capability.component_name().to_string(),
+            },
+        },
+        traits,
+    }
+}
 
-    let mut components = Vec::new();
+/// Prefix of the annotations holding the label of each node, followed by the node id
+pub const NODE_LABEL_ANNOTATION_PREFIX: &str = "pipestack.node/";
 
-    // Create provider registry
-    let registry = ProviderBuilderRegistry::new();
+/// Annotations mapping the id of each node to its label in the editor. wadm components have no
+/// annotations of their own, and every component of a node is named after its id.
+fn node_annotations(pipeline: &Pipeline) -> impl Iterator<Item = (String, String)> + '_ {
+    pipeline.nodes.iter().map(|step| {
+        (
+            format!("{NODE_LABEL_ANNOTATION_PREFIX}{}", step.id),
+            step.label.clone(),
+        )
+    })
+}
 
-    // Build all provider components using the registry
-    for provider_builder in registry.get_all_providers() {
-        match provider_builder.build_component(workspace_slug, app_config) {
-            Ok(component) => components.push(component),
-            Err(e) => {
-                eprintln!("Failed to build provider component: {}", e);
-            }
+/// Whether the node type is a sink there is a builder for. Sink types without one are listed
+/// ahead of being implemented.
+fn is_deployable_sink(step_type: &PipelineNodeType, registry: &ComponentBuilderRegistry) -> bool {
+    step_type.category() == NodeCategory::Sink && registry.get_builder(step_type).is_some()
+}
+
+/// Sort components by name, and their traits with the spreadscaler first followed by links in
+/// name order, so the manifest is the same regardless of the order of the pipeline's nodes
+fn sort_components(components: &mut [Component]) {
+    components.sort_by(|a, b| a.name.cmp(&b.name));
+    for component in components.iter_mut() {
+        component
+            .traits
+            .sort_by(|a, b| trait_sort_key(a).cmp(&trait_sort_key(b)));
+    }
+}
+
+fn trait_sort_key(t: &Trait) -> (u8, &str) {
+    match &t.properties {
+        TraitProperties::Spreadscaler { .. } => (0, ""),
+        TraitProperties::Link(link) => (1, link.name.as_deref().unwrap_or(&link.target.name)),
+    }
+}
+
+/// The configured route prefix for a workspace's webhooks, with a leading and no trailing slash,
+/// or empty when none is configured
+fn route_prefix(app_config: &AppConfig, workspace_slug: &str) -> String {
+    let prefix = app_config
+        .route_prefix
+        .replace("{workspace}", workspace_slug);
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("/{prefix}")
+    }
+}
+
+/// Route httpserver serves a webhook node's `path` on, without the route prefix. Every webhook of
+/// a pipeline shares the prefix, so it doesn't matter when comparing their routes. Leading and
+/// trailing slashes of the path are dropped, so `orders` and `/orders/` share a route.
+fn webhook_route(pipeline: &Pipeline, path: &str) -> String {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        format!("/{}", pipeline.name)
+    } else {
+        format!("/{}/{}", pipeline.name, path)
+    }
+}
+
+/// How much a problem found validating a pipeline matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The pipeline deploys, but likely doesn't do what was meant
+    Warning,
+    /// The pipeline can't be deployed
+    Error,
+}
+
+/// Problem found validating a pipeline, with the node and settings field it's about when it's
+/// about one
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Problem {
+    #[serde(rename = "nodeId", skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl Problem {
+    fn error(node_id: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            node_id: node_id.map(str::to_string),
+            field: None,
+            message: message.into(),
+            severity: Severity::Error,
         }
     }
 
-    WadmApplication {
-        api_version: "core.oam.dev/v1beta1".to_string(),
-        kind: "Application".to_string(),
-        metadata: Metadata {
-            name: format!("{workspace_slug}-providers"),
-            annotations,
-        },
-        spec: Spec { components },
+    fn warning(node_id: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(Some(node_id), message)
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::builders::nodes::{
-        NODE_IN_HTTP_NAME, NODE_IN_HTTP_VERSION, NODE_IN_INTERNAL_NAME, NODE_IN_INTERNAL_VERSION,
-        NODE_OUT_INTERNAL_NAME, NODE_OUT_INTERNAL_VERSION, NODE_OUT_LOG_NAME, NODE_OUT_LOG_VERSION,
-    };
+pub fn validate_pipeline(pipeline: &Pipeline, app_config: &AppConfig) -> Result<(), DeployError> {
+    match dry_validate(pipeline, app_config)
+        .into_iter()
+        .find(|problem| problem.severity == Severity::Error)
+    {
+        Some(problem) => Err(DeployError::Validation(problem.message)),
+        None => Ok(()),
+    }
+}
 
-    #[test]
-    fn test_convert_pipeline_in_processor_out() {
-        let input_yaml = r#"
-name: mine
-version: 1
-nodes:
-  - id: in-http-webhook_17
-    label: in-http-webhook_17
-    type: in-http-webhook
-    position:
-      x: 300
-      'y': 180
-    settings:
-      type: in-http-webhook
-      settings:
-        method: GET
-        path: 'in-http-webhook_17'
-  - id: processor-wasm_18
-    label: processor-wasm_18
-    type: processor-wasm
-    position:
-      x: 548
-      'y': 69
-    source: localhost:5000/nodes/data-processor:0.0.1
-    instances: 10000
-    depends_on:
-      - in-http-webhook_17
-  - id: out-log_19
-    label: out-log_19
-    type: out-log
-    position:
-      x: 660
-      'y': 180
-    depends_on:
-      - processor-wasm_18
-"#;
+/// Every problem of a pipeline, found without converting or deploying it, so editors can report
+/// them as the pipeline is edited. Deploying fails on the first error.
+pub fn dry_validate(pipeline: &Pipeline, app_config: &AppConfig) -> Vec<Problem> {
+    let mut problems = Vec::new();
 
-        let expected_yaml = format!(
-            r#"apiVersion: core.oam.dev/v1beta1
-kind: Application
-metadata:
-  name: default-mine
-  annotations:
-    version: '1'
+    if pipeline.nodes.len() > app_config.limits.max_nodes {
+        problems.push(Problem::error(
+            None,
+            format!(
+                "Pipeline has {} nodes, which exceeds the maximum of {}",
+                pipeline.nodes.len(),
+                app_config.limits.max_nodes
+            ),
+        ));
+    }
+
+    if let Err(e) = pipeline.validate_names() {
+        problems.push(Problem::error(None, e));
+    }
+
+    for step in &pipeline.nodes {
+        if let Err(e) = step.validate_settings_type() {
+            problems.push(Problem {
+                field: Some("settings".to_string()),
+                ..Problem::error(Some(&step.id), e)
+            });
+        }
+        if let Some(Err(e)) = step.settings.as_ref().map(PipelineNodeSettings::validate) {
+            problems.push(Problem {
+                field: Some(e.field.to_string()),
+                ..Problem::error(
+                    Some(&step.id),
+                    format!("Invalid settings for node {}: {e}", step.id),
+                )
+            });
+        }
+        if let Err(e) = check_published_digest(step) {
+            problems.push(Problem {
+                field: Some("digest".to_string()),
+                ..Problem::error(Some(&step.id), e)
+            });
+        }
+    }
+
+    // A node with several dependencies subscribes once to its step topic, which every dependency
+    // publishes to. Listing a dependency twice would not add a second input.
+    for step in &pipeline.nodes {
+        let mut seen = HashSet::new();
+        if let Some(dep) = step
+            .depends_on
+            .iter()
+            .flatten()
+            .find(|dep| !seen.insert(*dep))
+        {
+            problems.push(Problem::error(
+                Some(&step.id),
+                format!("Node {} depends on {dep} more than once", step.id),
+            ));
+        }
+    }
+
+    // Only processors choose what to send on which output
+    for step in &pipeline.nodes {
+        for dep in step.depends_on.iter().flatten() {
+            let Some(output) = dep.output() else {
+                continue;
+            };
+            if !is_valid_output_name(output) {
+                problems.push(Problem::error(
+                    Some(&step.id),
+                    format!(
+                        "Node {} depends on an output named {output:?}, output names must be lowercase letters, digits, dashes and underscores",
+                        step.id
+                    ),
+                ));
+                continue;
+            }
+            let is_processor = pipeline
+                .nodes
+                .iter()
+                .find(|node| node.id == dep.node())
+                .is_some_and(|node| node.step_type.category() == NodeCategory::Processor);
+            if !is_processor {
+                problems.push(Problem::error(
+                    Some(&step.id),
+                    format!(
+                        "Node {} depends on {dep}, but only processors have named outputs",
+                        step.id
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut routes: HashMap<String, &str> = HashMap::new();
+    for step in &pipeline.nodes {
+        if step.step_type != PipelineNodeType::InHttpWebhook {
+            continue;
+        }
+        let path = match &step.settings {
+            Some(PipelineNodeSettings::InHttpWebhook(settings)) => settings.path.as_str(),
+            _ => "",
+        };
+        let route = webhook_route(pipeline, path);
+        if let Some(other) = routes.insert(route.clone(), &step.id) {
+            problems.push(Problem {
+                field: Some("path".to_string()),
+                ..Problem::error(
+                    Some(&step.id),
+                    format!(
+                        "Webhook nodes {other} and {} both serve the path {route}",
+                        step.id
+                    ),
+                )
+            });
+        }
+    }
+
+    if let Some(dead_letter) = &pipeline.dead_letter {
+        match pipeline.nodes.iter().find(|step| &step.id == dead_letter) {
+            None => problems.push(Problem::error(
+                None,
+                format!("Dead-letter sink {dead_letter} is not a node of the pipeline"),
+            )),
+            Some(sink)
+                if !is_deployable_sink(&sink.step_type, &ComponentBuilderRegistry::new()) =>
+            {
+                problems.push(Problem::error(
+                    Some(dead_letter),
+                    format!(
+                        "Dead-letter sink {dead_letter} must be a sink node, got {:?}",
+                        sink.step_type
+                    ),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    if let Err(e) = pipeline.topologically_sorted_nodes() {
+        problems.push(Problem::error(None, e.to_string()));
+    }
+
+    // The output of a source or processor nothing depends on goes nowhere
+    for step in &pipeline.nodes {
+        let consumed = pipeline
+            .nodes
+            .iter()
+            .flat_map(|node| node.depends_on.iter().flatten())
+            .any(|dep| dep.node() == step.id);
+        if step.step_type.category() != NodeCategory::Sink && !consumed {
+            problems.push(Problem::warning(
+                &step.id,
+                format!("No node depends on node {}, its output is dropped", step.id),
+            ));
+        }
+    }
+
+    problems
+}
+
+/// NATS subjects a node's components use once deployed
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct NodeSubjects {
+    /// Subjects the node receives messages on
+    #[serde(rename = "in")]
+    pub subscriptions: Vec<String>,
+    /// Subjects the node publishes to: the next step's topic, the subjects of its named outputs
+    /// and its dead-letter subject
+    #[serde(rename = "out")]
+    pub publishes: Vec<String>,
+}
+
+/// Subjects and routes a pipeline uses once deployed, for debugging how messages are routed
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct PipelineSubjects {
+    /// Subjects of each node, by node id
+    pub nodes: BTreeMap<String, NodeSubjects>,
+    /// Route httpserver serves each webhook node on, by node id
+    pub routes: BTreeMap<String, String>,
+}
+
+/// Subjects and routes of a valid pipeline, as `convert_pipeline` wires them
+pub fn preview_subjects(
+    pipeline: &Pipeline,
+    workspace_slug: &String,
+    app_config: &AppConfig,
+) -> Result<PipelineSubjects, DeployError> {
+    validate_pipeline(pipeline, app_config)?;
+    let step_topics = determine_step_topics(pipeline, workspace_slug)
+        .map_err(|e| DeployError::Conversion(e.to_string()))?;
+    let context = BuildContext::new(pipeline, workspace_slug, app_config, &step_topics);
+    let registry = ComponentBuilderRegistry::new();
+
+    let mut nodes: BTreeMap<String, NodeSubjects> = BTreeMap::new();
+    let mut routes = BTreeMap::new();
+    let route_prefix = route_prefix(app_config, workspace_slug);
+    for step in &pipeline.nodes {
+        let subjects = nodes.entry(step.id.clone()).or_default();
+        let subscribes = step.step_type.category() == NodeCategory::Processor
+            || is_deployable_sink(&step.step_type, &registry);
+        if let Some(topic) = step_topics.get(&step.id).filter(|_| subscribes) {
+            subjects.subscriptions.extend(
+                subscriptions(pipeline, workspace_slug, step, topic)
+                    .split(',')
+                    .map(str::to_string),
+            );
+        }
+        match step.step_type {
+            node_type if is_polled(&node_type) => {
+                subjects
+                    .subscriptions
+                    .push(poll_subject(workspace_slug, &pipeline.name, &step.id))
+            }
+            PipelineNodeType::InHttpWebhook => {
+                let path = match &step.settings {
+                    Some(PipelineNodeSettings::InHttpWebhook(settings)) => settings.path.as_str(),
+                    _ => "",
+                };
+                routes.insert(
+                    step.id.clone(),
+                    format!("{}{}", route_prefix, webhook_route(pipeline, path)),
+                );
+            }
+            _ => {}
+        }
+        subjects
+            .publishes
+            .extend(context.find_next_step_topic(&step.id));
+        subjects
+            .publishes
+            .extend(context.find_output_topics(&step.id).into_values());
+    }
+
+    // Every other node dead-letters the messages it fails to process to the dead-letter sink
+    if let Some(dead_letter) = &pipeline.dead_letter {
+        for step in pipeline.nodes.iter().filter(|step| &step.id != dead_letter) {
+            let subject = dead_letter_subject(workspace_slug, &pipeline.name, &step.id);
+            if let Some(subjects) = nodes.get_mut(&step.id) {
+                subjects.publishes.push(subject.clone());
+            }
+            if let Some(sink) = nodes.get_mut(dead_letter) {
+                sink.subscriptions.push(subject);
+            }
+        }
+    }
+
+    Ok(PipelineSubjects { nodes, routes })
+}
+
+/// Subject a message dead-lettered on `dlq_subject` is replayed to: the replay subject of the node
+/// that failed to process it, which only that node's in-internal component subscribes to
+pub fn replay_target(
+    pipeline: &Pipeline,
+    workspace_slug: &String,
+    dlq_subject: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let step_id =
+        dead_letter_step(workspace_slug, &pipeline.name, dlq_subject).ok_or_else(|| {
+            format!(
+                "{dlq_subject} is not a dead-letter subject of pipeline {}",
+                pipeline.name
+            )
+        })?;
+    let step = pipeline
+        .nodes
+        .iter()
+        .find(|step| step.id == step_id)
+        .ok_or_else(|| format!("Node {step_id} is not a node of the pipeline"))?;
+    let registry = ComponentBuilderRegistry::new();
+    let subscribes = step.step_type.category() == NodeCategory::Processor
+        || is_deployable_sink(&step.step_type, &registry);
+    if !subscribes || !determine_step_topics(pipeline, workspace_slug)?.contains_key(step_id) {
+        return Err(format!(
+            "Node {step_id} receives no messages from other nodes, there is nothing to replay to"
+        )
+        .into());
+    }
+    Ok(replay_subject(workspace_slug, &pipeline.name, step_id))
+}
+
+fn determine_step_topics(
+    pipeline: &Pipeline,
+    workspace_slug: &String,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let mut step_topics = HashMap::new();
+
+    // Generate topic names for inter-step communication based on dependency depth. Sorting
+    // guarantees every dependency's depth is known before the nodes depending on it.
+    let mut node_depths: HashMap<&str, usize> = HashMap::new();
+    for step in pipeline.topologically_sorted_nodes()? {
+        let depends_on = step.depends_on.as_deref().unwrap_or_default();
+        let depth = depends_on
+            .iter()
+            .map(|dep| node_depths[dep.node()])
+            .max()
+            .map_or(1, |max_depth| max_depth + 1);
+        node_depths.insert(&step.id, depth);
+
+        // Only nodes that have dependencies receive messages on a step topic
+        if !depends_on.is_empty() {
+            let topic = format!(
+                "pipestack.{}.{}.step-{}-in",
+                workspace_slug, pipeline.name, depth
+            );
+            step_topics.insert(step.id.clone(), topic);
+        }
+    }
+    Ok(step_topics)
+}
+
+pub fn create_providers_wadm(workspace_slug: &str, app_config: &AppConfig) -> WadmApplication {
+    let mut annotations = BTreeMap::new();
+    annotations.insert(
+        "experimental.wasmcloud.dev/shared".to_string(),
+        "true".to_string(),
+    );
+    annotations.insert(
+        "description".to_string(),
+        format!("Shared providers for the {workspace_slug} workspace"),
+    );
+    annotations.insert("version".to_string(), "0.9.0".to_string());
+
+    let mut components = Vec::new();
+
+    // Create provider registry
+    let registry = ProviderBuilderRegistry::new();
+
+    // Build all provider components using the registry
+    for provider_builder in registry.get_all_providers() {
+        match provider_builder.build_component(workspace_slug, app_config) {
+            Ok(component) => components.push(component),
+            Err(e) => {
+                eprintln!("Failed to build provider component: {}", e);
+            }
+        }
+    }
+
+    WadmApplication {
+        api_version: "core.oam.dev/v1beta1".to_string(),
+        kind: "Application".to_string(),
+        metadata: Metadata {
+            name: format!("{workspace_slug}-providers"),
+            annotations,
+        },
+        spec: Spec { components },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::nodes::{
+        NODE_IN_HTTP_NAME, NODE_IN_HTTP_VERSION, NODE_IN_INTERNAL_NAME, NODE_IN_INTERNAL_VERSION,
+        NODE_OUT_INTERNAL_NAME, NODE_OUT_INTERNAL_VERSION, NODE_OUT_LOG_NAME, NODE_OUT_LOG_VERSION,
+    };
+    use shared::DependsOn;
+
+    #[test]
+    fn test_convert_pipeline_in_processor_out() {
+        let input_yaml = r#"
+name: mine
+version: 1
+nodes:
+  - id: in-http-webhook_17
+    label: in-http-webhook_17
+    type: in-http-webhook
+    position:
+      x: 300
+      'y': 180
+    settings:
+      type: in-http-webhook
+      settings:
+        method: GET
+        path: 'in-http-webhook_17'
+  - id: processor-wasm_18
+    label: processor-wasm_18
+    type: processor-wasm
+    position:
+      x: 548
+      'y': 69
+    source: localhost:5000/nodes/data-processor:0.0.1
+    instances: 10000
+    depends_on:
+      - in-http-webhook_17
+  - id: out-log_19
+    label: out-log_19
+    type: out-log
+    position:
+      x: 660
+      'y': 180
+    depends_on:
+      - processor-wasm_18
+"#;
+
+        let expected_yaml = format!(
+            r#"apiVersion: core.oam.dev/v1beta1
+kind: Application
+metadata:
+  name: default-mine
+  annotations:
+    pipestack.node/in-http-webhook_17: in-http-webhook_17
+    pipestack.node/out-log_19: out-log_19
+    pipestack.node/processor-wasm_18: processor-wasm_18
+    version: '1'
 spec:
   components:
+  - name: httpserver
+    type: capability
+    properties:
+      application:
+        name: default-providers
+        component: httpserver
+    traits:
+    - type: link
+      properties:
+        namespace: wasi
+        package: http
+        interfaces:
+        - incoming-handler
+        source:
+          config:
+          - name: default-mine-httpserver-path-in-http-webhook_17-config-v1
+            properties:
+              path: /mine/in-http-webhook_17
+        target:
+          name: in-http-webhook_17
+        name: httpserver-to-default-in-http-webhook_17-link
   - name: in-http-webhook_17
     type: component
     properties:
@@ -417,17 +1131,15 @@ spec:
         package: out
         interfaces:
         - out
+        - metadata
         target:
           name: out-internal-for-in-http-webhook_17
-  - name: out-internal-for-in-http-webhook_17
+        name: in-http-webhook_17-to-out-internal-for-in-http-webhook_17-out-link
+  - name: in-internal-for-out-log_19
     type: component
     properties:
-      image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
-      id: default_mine-out-internal-for-in-http-webhook_17
-      config:
-      - name: out-internal-for-in-http-webhook_17-config-v1
-        properties:
-          next-step-topic: pipestack.default.mine.step-2-in
+      image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
+      id: default_mine-in-internal-for-out-log_19
     traits:
     - type: spreadscaler
       properties:
@@ -440,11 +1152,34 @@ spec:
         - consumer
         target:
           name: messaging-nats
+        name: in-internal-for-out-log_19-to-messaging-nats-consumer-link
+    - type: link
+      properties:
+        namespace: pipestack
+        package: metrics
+        interfaces:
+        - metrics
+        target:
+          name: metrics
+        name: in-internal-for-out-log_19-to-metrics-metrics-link
+    - type: link
+      properties:
+        namespace: pipestack
+        package: out
+        interfaces:
+        - out
+        target:
+          name: out-log_19
+        name: in-internal-for-out-log_19-to-out-log_19-out-link
   - name: in-internal-for-processor-wasm_18
     type: component
     properties:
       image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
       id: default_mine-in-internal-for-processor-wasm_18
+      config:
+        - name: processor-wasm_18-processor-input-v1
+          properties:
+            processor-input: text
     traits:
     - type: spreadscaler
       properties:
@@ -452,11 +1187,12 @@ spec:
     - type: link
       properties:
         namespace: pipestack
-        package: customer
+        package: metrics
         interfaces:
-        - customer
+        - metrics
         target:
-          name: processor-wasm_18
+          name: metrics
+        name: in-internal-for-processor-wasm_18-to-metrics-metrics-link
     - type: link
       properties:
         namespace: pipestack
@@ -465,24 +1201,70 @@ spec:
         - out
         target:
           name: out-internal-for-processor-wasm_18
-  - name: processor-wasm_18
-    type: component
+        name: in-internal-for-processor-wasm_18-to-out-internal-for-processor-wasm_18-out-link
+    - type: link
+      properties:
+        namespace: pipestack
+        package: customer
+        interfaces:
+        - customer
+        target:
+          name: processor-wasm_18
+        name: in-internal-for-processor-wasm_18-to-processor-wasm_18-customer-link
+  - name: messaging-nats
+    type: capability
     properties:
-      image: http://localhost:5000/default/pipeline/mine/1/builder/components/nodes/processor/wasm/processor-wasm_18:1.0.0
-      id: default_mine-processor-wasm_18
+      application:
+        name: default-providers
+        component: messaging-nats
     traits:
-    - type: spreadscaler
+    - type: link
       properties:
-        instances: 10000
-  - name: out-internal-for-processor-wasm_18
-    type: component
+        namespace: wasmcloud
+        package: messaging
+        interfaces:
+        - handler
+        source:
+          config:
+          - name: subscription-2-config-v1
+            properties:
+              subscriptions: pipestack.default.mine.step-3-in,pipestack.default.mine.out-log_19.replay
+          - name: messaging-nats-cluster-config-v1
+            properties:
+              cluster_uris: localhost:4222
+        target:
+          name: in-internal-for-out-log_19
+        name: messaging-nats-to-default-in-internal-for-out-log_19-link
+    - type: link
+      properties:
+        namespace: wasmcloud
+        package: messaging
+        interfaces:
+        - handler
+        source:
+          config:
+          - name: subscription-1-config-v1
+            properties:
+              subscriptions: pipestack.default.mine.step-2-in,pipestack.default.mine.processor-wasm_18.replay
+          - name: messaging-nats-cluster-config-v1
+        target:
+          name: in-internal-for-processor-wasm_18
+        name: messaging-nats-to-default-in-internal-for-processor-wasm_18-link
+  - name: metrics
+    type: capability
+    properties:
+      application:
+        name: default-providers
+        component: metrics
+  - name: out-internal-for-in-http-webhook_17
+    type: component
     properties:
       image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
-      id: default_mine-out-internal-for-processor-wasm_18
+      id: default_mine-out-internal-for-in-http-webhook_17
       config:
-      - name: out-internal-for-processor-wasm_18-config-v1
+      - name: out-internal-for-in-http-webhook_17-config-v1
         properties:
-          next-step-topic: pipestack.default.mine.step-3-in
+          next-step-topic: pipestack.default.mine.step-2-in
     traits:
     - type: spreadscaler
       properties:
@@ -495,11 +1277,16 @@ spec:
         - consumer
         target:
           name: messaging-nats
-  - name: in-internal-for-out-log_19
+        name: out-internal-for-in-http-webhook_17-to-messaging-nats-consumer-link
+  - name: out-internal-for-processor-wasm_18
     type: component
     properties:
-      image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
-      id: default_mine-in-internal-for-out-log_19
+      image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
+      id: default_mine-out-internal-for-processor-wasm_18
+      config:
+      - name: out-internal-for-processor-wasm_18-config-v1
+        properties:
+          next-step-topic: pipestack.default.mine.step-3-in
     traits:
     - type: spreadscaler
       properties:
@@ -512,14 +1299,7 @@ spec:
         - consumer
         target:
           name: messaging-nats
-    - type: link
-      properties:
-        namespace: pipestack
-        package: out
-        interfaces:
-        - out
-        target:
-          name: out-log_19
+        name: out-internal-for-processor-wasm_18-to-messaging-nats-consumer-link
   - name: out-log_19
     type: component
     properties:
@@ -529,64 +1309,24 @@ spec:
     - type: spreadscaler
       properties:
         instances: 10000
-  - name: httpserver
-    type: capability
-    properties:
-      application:
-        name: default-providers
-        component: httpserver
-    traits:
     - type: link
       properties:
-        namespace: wasi
-        package: http
+        namespace: pipestack
+        package: metrics
         interfaces:
-        - incoming-handler
-        source:
-          config:
-          - name: default-mine-httpserver-path-in-http-webhook_17-config-v1
-            properties:
-              path: /mine/in-http-webhook_17
+        - metrics
         target:
-          name: in-http-webhook_17
-        name: httpserver-to-default-in-http-webhook_17-link
-  - name: messaging-nats
-    type: capability
+          name: metrics
+        name: out-log_19-to-metrics-metrics-link
+  - name: processor-wasm_18
+    type: component
     properties:
-      application:
-        name: default-providers
-        component: messaging-nats
+      image: http://localhost:5000/default/pipeline/mine/1/builder/components/nodes/processor/wasm/processor-wasm_18:1.0.0
+      id: default_mine-processor-wasm_18
     traits:
-    - type: link
-      properties:
-        namespace: wasmcloud
-        package: messaging
-        interfaces:
-        - handler
-        source:
-          config:
-          - name: subscription-1-config-v1
-            properties:
-              subscriptions: pipestack.default.mine.step-2-in
-              cluster_uris: localhost:4222
-        target:
-          name: in-internal-for-processor-wasm_18
-        name: messaging-nats-to-default-in-internal-for-processor-wasm_18-link
-    - type: link
+    - type: spreadscaler
       properties:
-        namespace: wasmcloud
-        package: messaging
-        interfaces:
-        - handler
-        source:
-          config:
-          - name: subscription-2-config-v1
-            properties:
-              subscriptions: pipestack.default.mine.step-3-in
-              cluster_uris: localhost:4222
-        target:
-          name: in-internal-for-out-log_19
-        name: messaging-nats-to-default-in-internal-for-out-log_19-link
+        instances: 10000
 "#
         );
 
@@ -659,9 +1399,34 @@ kind: Application
 metadata:
   name: default-mine
   annotations:
+    pipestack.node/in-http-webhook_17: in-http-webhook_17
+    pipestack.node/out-log_19: out-log_19
+    pipestack.node/out-log_20: out-log_20
+    pipestack.node/processor-wasm_18: processor-wasm_18
     version: '1'
 spec:
   components:
+  - name: httpserver
+    type: capability
+    properties:
+      application:
+        name: default-providers
+        component: httpserver
+    traits:
+    - type: link
+      properties:
+        namespace: wasi
+        package: http
+        interfaces:
+        - incoming-handler
+        source:
+          config:
+          - name: default-mine-httpserver-path-in-http-webhook_17-config-v1
+            properties:
+              path: /mine/in-http-webhook_17
+        target:
+          name: in-http-webhook_17
+        name: httpserver-to-default-in-http-webhook_17-link
   - name: in-http-webhook_17
     type: component
     properties:
@@ -681,17 +1446,15 @@ spec:
         package: out
         interfaces:
         - out
+        - metadata
         target:
           name: out-internal-for-in-http-webhook_17
-  - name: out-internal-for-in-http-webhook_17
+        name: in-http-webhook_17-to-out-internal-for-in-http-webhook_17-out-link
+  - name: in-internal-for-out-log_19
     type: component
     properties:
-      image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
-      id: default_mine-out-internal-for-in-http-webhook_17
-      config:
-      - name: out-internal-for-in-http-webhook_17-config-v1
-        properties:
-          next-step-topic: pipestack.default.mine.step-2-in
+      image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
+      id: default_mine-in-internal-for-out-log_19
     traits:
     - type: spreadscaler
       properties:
@@ -704,23 +1467,16 @@ spec:
         - consumer
         target:
           name: messaging-nats
-  - name: in-internal-for-processor-wasm_18
-    type: component
-    properties:
-      image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
-      id: default_mine-in-internal-for-processor-wasm_18
-    traits:
-    - type: spreadscaler
-      properties:
-        instances: 10000
+        name: in-internal-for-out-log_19-to-messaging-nats-consumer-link
     - type: link
       properties:
         namespace: pipestack
-        package: customer
+        package: metrics
         interfaces:
-        - customer
+        - metrics
         target:
-          name: processor-wasm_18
+          name: metrics
+        name: in-internal-for-out-log_19-to-metrics-metrics-link
     - type: link
       properties:
         namespace: pipestack
@@ -728,25 +1484,13 @@ spec:
         interfaces:
         - out
         target:
-          name: out-internal-for-processor-wasm_18
-  - name: processor-wasm_18
-    type: component
-    properties:
-      image: http://localhost:5000/default/pipeline/mine/1/builder/components/nodes/processor/wasm/processor-wasm_18:1.0.0
-      id: default_mine-processor-wasm_18
-    traits:
-    - type: spreadscaler
-      properties:
-        instances: 10000
-  - name: out-internal-for-processor-wasm_18
+          name: out-log_19
+        name: in-internal-for-out-log_19-to-out-log_19-out-link
+  - name: in-internal-for-out-log_20
     type: component
     properties:
-      image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
-      id: default_mine-out-internal-for-processor-wasm_18
-      config:
-      - name: out-internal-for-processor-wasm_18-config-v1
-        properties:
-          next-step-topic: pipestack.default.mine.step-3-in
+      image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
+      id: default_mine-in-internal-for-out-log_20
     traits:
     - type: spreadscaler
       properties:
@@ -759,23 +1503,16 @@ spec:
         - consumer
         target:
           name: messaging-nats
-  - name: in-internal-for-out-log_19
-    type: component
-    properties:
-      image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
-      id: default_mine-in-internal-for-out-log_19
-    traits:
-    - type: spreadscaler
-      properties:
-        instances: 10000
+        name: in-internal-for-out-log_20-to-messaging-nats-consumer-link
     - type: link
       properties:
-        namespace: wasmcloud
-        package: messaging
+        namespace: pipestack
+        package: metrics
         interfaces:
-        - consumer
+        - metrics
         target:
-          name: messaging-nats
+          name: metrics
+        name: in-internal-for-out-log_20-to-metrics-metrics-link
     - type: link
       properties:
         namespace: pipestack
@@ -783,33 +1520,30 @@ spec:
         interfaces:
         - out
         target:
-          name: out-log_19
-  - name: out-log_19
-    type: component
-    properties:
-      image: http://localhost:5000/nodes/{NODE_OUT_LOG_NAME}:{NODE_OUT_LOG_VERSION}
-      id: default_mine-out-log_19
-    traits:
-    - type: spreadscaler
-      properties:
-        instances: 10000
-  - name: in-internal-for-out-log_20
+          name: out-log_20
+        name: in-internal-for-out-log_20-to-out-log_20-out-link
+  - name: in-internal-for-processor-wasm_18
     type: component
     properties:
       image: http://localhost:5000/nodes/{NODE_IN_INTERNAL_NAME}:{NODE_IN_INTERNAL_VERSION}
-      id: default_mine-in-internal-for-out-log_20
+      id: default_mine-in-internal-for-processor-wasm_18
+      config:
+        - name: processor-wasm_18-processor-input-v1
+          properties:
+            processor-input: text
     traits:
     - type: spreadscaler
       properties:
         instances: 10000
     - type: link
       properties:
-        namespace: wasmcloud
-        package: messaging
+        namespace: pipestack
+        package: metrics
         interfaces:
-        - consumer
+        - metrics
         target:
-          name: messaging-nats
+          name: metrics
+        name: in-internal-for-processor-wasm_18-to-metrics-metrics-link
     - type: link
       properties:
         namespace: pipestack
@@ -817,37 +1551,17 @@ spec:
         interfaces:
         - out
         target:
-          name: out-log_20
-  - name: out-log_20
-    type: component
-    properties:
-      image: http://localhost:5000/nodes/{NODE_OUT_LOG_NAME}:{NODE_OUT_LOG_VERSION}
-      id: default_mine-out-log_20
-    traits:
-    - type: spreadscaler
-      properties:
-        instances: 10000
-  - name: httpserver
-    type: capability
-    properties:
-      application:
-        name: default-providers
-        component: httpserver
-    traits:
+          name: out-internal-for-processor-wasm_18
+        name: in-internal-for-processor-wasm_18-to-out-internal-for-processor-wasm_18-out-link
     - type: link
       properties:
-        namespace: wasi
-        package: http
+        namespace: pipestack
+        package: customer
         interfaces:
-        - incoming-handler
-        source:
-          config:
-          - name: default-mine-httpserver-path-in-http-webhook_17-config-v1
-            properties:
-              path: /mine/in-http-webhook_17
+        - customer
         target:
-          name: in-http-webhook_17
-        name: httpserver-to-default-in-http-webhook_17-link
+          name: processor-wasm_18
+        name: in-internal-for-processor-wasm_18-to-processor-wasm_18-customer-link
   - name: messaging-nats
     type: capability
     properties:
@@ -863,13 +1577,15 @@ spec:
         - handler
         source:
           config:
-          - name: subscription-1-config-v1
+          - name: subscription-2-config-v1
+            properties:
+              subscriptions: pipestack.default.mine.step-3-in,pipestack.default.mine.out-log_19.replay
+          - name: messaging-nats-cluster-config-v1
             properties:
-              subscriptions: pipestack.default.mine.step-2-in
               cluster_uris: localhost:4222
         target:
-          name: in-internal-for-processor-wasm_18
-        name: messaging-nats-to-default-in-internal-for-processor-wasm_18-link
+          name: in-internal-for-out-log_19
+        name: messaging-nats-to-default-in-internal-for-out-log_19-link
     - type: link
       properties:
         namespace: wasmcloud
@@ -878,13 +1594,13 @@ spec:
         - handler
         source:
           config:
-          - name: subscription-2-config-v1
+          - name: subscription-3-config-v1
             properties:
-              subscriptions: pipestack.default.mine.step-3-in
-              cluster_uris: localhost:4222
+              subscriptions: pipestack.default.mine.step-3-in,pipestack.default.mine.out-log_20.replay
+          - name: messaging-nats-cluster-config-v1
         target:
-          name: in-internal-for-out-log_19
-        name: messaging-nats-to-default-in-internal-for-out-log_19-link
+          name: in-internal-for-out-log_20
+        name: messaging-nats-to-default-in-internal-for-out-log_20-link
     - type: link
       properties:
         namespace: wasmcloud
@@ -893,13 +1609,108 @@ spec:
         - handler
         source:
           config:
-          - name: subscription-3-config-v1
+          - name: subscription-1-config-v1
             properties:
-              subscriptions: pipestack.default.mine.step-3-in
-              cluster_uris: localhost:4222
+              subscriptions: pipestack.default.mine.step-2-in,pipestack.default.mine.processor-wasm_18.replay
+          - name: messaging-nats-cluster-config-v1
         target:
-          name: in-internal-for-out-log_20
-        name: messaging-nats-to-default-in-internal-for-out-log_20-link
+          name: in-internal-for-processor-wasm_18
+        name: messaging-nats-to-default-in-internal-for-processor-wasm_18-link
+  - name: metrics
+    type: capability
+    properties:
+      application:
+        name: default-providers
+        component: metrics
+  - name: out-internal-for-in-http-webhook_17
+    type: component
+    properties:
+      image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
+      id: default_mine-out-internal-for-in-http-webhook_17
+      config:
+      - name: out-internal-for-in-http-webhook_17-config-v1
+        properties:
+          next-step-topic: pipestack.default.mine.step-2-in
+    traits:
+    - type: spreadscaler
+      properties:
+        instances: 10000
+    - type: link
+      properties:
+        namespace: wasmcloud
+        package: messaging
+        interfaces:
+        - consumer
+        target:
+          name: messaging-nats
+        name: out-internal-for-in-http-webhook_17-to-messaging-nats-consumer-link
+  - name: out-internal-for-processor-wasm_18
+    type: component
+    properties:
+      image: http://localhost:5000/nodes/{NODE_OUT_INTERNAL_NAME}:{NODE_OUT_INTERNAL_VERSION}
+      id: default_mine-out-internal-for-processor-wasm_18
+      config:
+      - name: out-internal-for-processor-wasm_18-config-v1
+        properties:
+          next-step-topic: pipestack.default.mine.step-3-in
+    traits:
+    - type: spreadscaler
+      properties:
+        instances: 10000
+    - type: link
+      properties:
+        namespace: wasmcloud
+        package: messaging
+        interfaces:
+        - consumer
+        target:
+          name: messaging-nats
+        name: out-internal-for-processor-wasm_18-to-messaging-nats-consumer-link
+  - name: out-log_19
+    type: component
+    properties:
+      image: http://localhost:5000/nodes/{NODE_OUT_LOG_NAME}:{NODE_OUT_LOG_VERSION}
+      id: default_mine-out-log_19
+    traits:
+    - type: spreadscaler
+      properties:
+        instances: 10000
+    - type: link
+      properties:
+        namespace: pipestack
+        package: metrics
+        interfaces:
+        - metrics
+        target:
+          name: metrics
+        name: out-log_19-to-metrics-metrics-link
+  - name: out-log_20
+    type: component
+    properties:
+      image: http://localhost:5000/nodes/{NODE_OUT_LOG_NAME}:{NODE_OUT_LOG_VERSION}
+      id: default_mine-out-log_20
+    traits:
+    - type: spreadscaler
+      properties:
+        instances: 10000
+    - type: link
+      properties:
+        namespace: pipestack
+        package: metrics
+        interfaces:
+        - metrics
+        target:
+          name: metrics
+        name: out-log_20-to-metrics-metrics-link
+  - name: processor-wasm_18
+    type: component
+    properties:
+      image: http://localhost:5000/default/pipeline/mine/1/builder/components/nodes/processor/wasm/processor-wasm_18:1.0.0
+      id: default_mine-processor-wasm_18
+    traits:
+    - type: spreadscaler
+      properties:
+        instances: 10000
 "#
         );
 
@@ -936,14 +1747,19 @@ spec:
                 r2_bucket: "test_bucket".to_string(),
             },
             nats: crate::config::Nats {
-                cluster_uris: "nats://localhost:4222".to_string(),
+                cluster_uris: vec!["nats://localhost:4222".to_string()],
                 jwt: Some("test-jwt".to_string()),
                 nkey: Some("test-nkey".to_string()),
+                replay: None,
             },
             registry: crate::config::Registry {
                 internal_url: "http://localhost:8080".to_string(),
                 url: "http://localhost:8080".to_string(),
+                auth: crate::config::RegistryAuth::Anonymous,
             },
+            limits: crate::config::Limits::default(),
+            scheduler: crate::config::Scheduler::default(),
+            route_prefix: String::new(),
         };
 
         let registry = ProviderBuilderRegistry::new();
@@ -969,6 +1785,8 @@ spec:
             "httpserver".to_string(),
             "httpclient".to_string(),
             "messaging-nats".to_string(),
+            "keyvalue-nats".to_string(),
+            "metrics".to_string(),
         ];
         expected_names.sort();
         assert_eq!(component_names, expected_names);
@@ -987,14 +1805,19 @@ spec:
                 r2_bucket: "test_bucket".to_string(),
             },
             nats: crate::config::Nats {
-                cluster_uris: "nats://localhost:4222".to_string(),
+                cluster_uris: vec!["nats://localhost:4222".to_string()],
                 jwt: Some("test-jwt".to_string()),
                 nkey: Some("test-nkey".to_string()),
+                replay: None,
             },
             registry: crate::config::Registry {
                 internal_url: "http://localhost:8080".to_string(),
                 url: "http://localhost:8080".to_string(),
+                auth: crate::config::RegistryAuth::Anonymous,
             },
+            limits: crate::config::Limits::default(),
+            scheduler: crate::config::Scheduler::default(),
+            route_prefix: String::new(),
         };
 
         let wadm_app = create_providers_wadm("test-workspace", &app_config);
@@ -1004,8 +1827,8 @@ spec:
         assert_eq!(wadm_app.kind, "Application");
         assert_eq!(wadm_app.metadata.name, "test-workspace-providers");
 
-        // Verify we have exactly 3 components (the standard providers)
-        assert_eq!(wadm_app.spec.components.len(), 3);
+        // Verify we have exactly 5 components (the standard providers)
+        assert_eq!(wadm_app.spec.components.len(), 5);
 
         // Verify component names
         let mut component_names: Vec<String> = wadm_app
@@ -1020,12 +1843,66 @@ spec:
             "httpserver".to_string(),
             "httpclient".to_string(),
             "messaging-nats".to_string(),
+            "keyvalue-nats".to_string(),
+            "metrics".to_string(),
         ];
         expected_names.sort();
 
         assert_eq!(component_names, expected_names);
     }
 
+    #[test]
+    fn test_out_http_webhook_requires_httpclient() {
+        assert!(
+            required_capabilities(&PipelineNodeType::OutHttpWebhook)
+                .contains(&Capability::HttpClient)
+        );
+        assert_eq!(
+            required_capabilities(&PipelineNodeType::ProcessorWasm),
+            [Capability::MessagingNats, Capability::Metrics]
+        );
+
+        let mut pipeline = chain_pipeline(2);
+        assert_eq!(
+            pipeline_capabilities(&pipeline),
+            BTreeSet::from([
+                Capability::HttpServer,
+                Capability::MessagingNats,
+                Capability::Metrics
+            ])
+        );
+        // The webhook also keeps its circuit breakers in keyvalue
+        pipeline.nodes[1].step_type = PipelineNodeType::OutHttpWebhook;
+        assert_eq!(
+            pipeline_capabilities(&pipeline),
+            BTreeSet::from([
+                Capability::HttpServer,
+                Capability::HttpClient,
+                Capability::KeyvalueNats,
+                Capability::MessagingNats,
+                Capability::Metrics
+            ])
+        );
+    }
+
+    #[test]
+    fn test_capabilities_must_be_exposed_by_providers() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut providers = create_providers_wadm("acme", &app_config);
+        let capabilities = BTreeSet::from([Capability::HttpClient, Capability::MessagingNats]);
+        assert_eq!(check_providers_expose(&capabilities, &providers), Ok(()));
+
+        providers.spec.components.retain(|c| c.name != "httpclient");
+        assert_eq!(
+            check_providers_expose(&capabilities, &providers),
+            Err(
+                "The acme-providers application doesn't expose the capabilities the pipeline \
+                 requires: httpclient"
+                    .to_string()
+            )
+        );
+    }
+
     #[test]
     fn test_individual_provider_builders() {
         use crate::builders::{ProviderType, providers::ProviderBuilderRegistry};
@@ -1041,14 +1918,19 @@ spec:
                 r2_bucket: "test_bucket".to_string(),
             },
             nats: crate::config::Nats {
-                cluster_uris: "nats://localhost:4222".to_string(),
+                cluster_uris: vec!["nats://localhost:4222".to_string()],
                 jwt: Some("test-jwt".to_string()),
                 nkey: Some("test-nkey".to_string()),
+                replay: None,
             },
             registry: crate::config::Registry {
                 internal_url: "http://localhost:8080".to_string(),
                 url: "http://localhost:8080".to_string(),
+                auth: crate::config::RegistryAuth::Anonymous,
             },
+            limits: crate::config::Limits::default(),
+            scheduler: crate::config::Scheduler::default(),
+            route_prefix: String::new(),
         };
 
         let registry = ProviderBuilderRegistry::new();
@@ -1075,6 +1957,27 @@ spec:
                 .unwrap();
             assert_eq!(component.name, "messaging-nats");
         }
+
+        if let Some(keyvalue_builder) = registry.get_builder(&ProviderType::KeyvalueNats) {
+            let component = keyvalue_builder
+                .build_component(workspace_slug, &app_config)
+                .unwrap();
+            assert_eq!(component.name, "keyvalue-nats");
+        }
+
+        if let Some(metrics_builder) = registry.get_builder(&ProviderType::Metrics) {
+            let component = metrics_builder
+                .build_component(workspace_slug, &app_config)
+                .unwrap();
+            assert_eq!(component.name, "metrics");
+            let Properties::WithImage { image, .. } = &component.properties else {
+                panic!("Metrics provider should have an image");
+            };
+            assert_eq!(
+                image,
+                "http://localhost:8080/providers/metrics_provider:0.1.0"
+            );
+        }
     }
 
     #[test]
@@ -1101,9 +2004,14 @@ spec:
                         path: "api/webhook1".to_string(),
                         content_type: None,
                         request_body_json_schema: None,
+                        event_filter: None,
+                        metadata: None,
+                        reply_timeout_ms: None,
                     })),
                     instances: None,
+                    helper_instances: None,
                     depends_on: None,
+                    resilience: None,
                 },
                 PipelineNode {
                     id: "webhook-2".to_string(),
@@ -1115,9 +2023,14 @@ spec:
                         path: "api/webhook2".to_string(),
                         content_type: None,
                         request_body_json_schema: None,
+                        event_filter: None,
+                        metadata: None,
+                        reply_timeout_ms: None,
                     })),
                     instances: None,
+                    helper_instances: None,
                     depends_on: None,
+                    resilience: None,
                 },
                 PipelineNode {
                     id: "processor".to_string(),
@@ -1126,9 +2039,13 @@ spec:
                     position: XYPosition { x: 300.0, y: 100.0 },
                     settings: None,
                     instances: Some(1000),
-                    depends_on: Some(vec!["webhook-1".to_string(), "webhook-2".to_string()]),
+                    helper_instances: None,
+                    depends_on: Some(vec!["webhook-1".into(), "webhook-2".into()]),
+                    resilience: None,
                 },
             ],
+            dead_letter: None,
+            helper_instances: None,
         };
 
         // Convert to WADM
@@ -1196,4 +2113,2539 @@ spec:
             }
         }
     }
+
+    #[test]
+    fn test_preview_subjects_match_wiring() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(BRANCHES_PIPELINE).expect("Failed to parse input YAML");
+        let workspace = "default".to_string();
+
+        let preview = preview_subjects(&pipeline, &workspace, &app_config)
+            .expect("Failed to preview subjects");
+        let wadm = convert_pipeline(&pipeline, &workspace, &app_config)
+            .expect("Failed to convert pipeline");
+
+        let component = |name: &str| wadm.spec.components.iter().find(|c| c.name == name);
+        let link_configs = |capability: &str, key: &str| -> BTreeMap<String, String> {
+            component(capability)
+                .unwrap_or_else(|| panic!("Should have {capability} component"))
+                .traits
+                .iter()
+                .filter_map(|t| match &t.properties {
+                    TraitProperties::Link(link) => {
+                        let config = &link.source.as_ref()?.config.as_ref()?[0];
+                        let value = config.properties.get(key)?.as_str()?.to_string();
+                        Some((link.target.name.clone(), value))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        let subscriptions = link_configs("messaging-nats", "subscriptions");
+        let paths = link_configs("httpserver", "path");
+
+        for (node_id, subjects) in &preview.nodes {
+            let subscribed: Vec<String> = subscriptions
+                .get(&format!("in-internal-for-{node_id}"))
+                .map(|s| s.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            assert_eq!(subjects.subscriptions, subscribed, "{node_id}");
+
+            let mut published = Vec::new();
+            if let Some(out_internal) = component(&format!("out-internal-for-{node_id}")) {
+                let Properties::WithImage { config, .. } = &out_internal.properties else {
+                    panic!("out-internal should reference an image");
+                };
+                let properties = &config.as_ref().unwrap()[0].properties;
+                published.push(properties["next-step-topic"].as_str().unwrap().to_string());
+                if let Some(topics) = properties.get("output-topics") {
+                    let topics: BTreeMap<String, String> =
+                        serde_json::from_str(topics.as_str().unwrap()).unwrap();
+                    published.extend(topics.into_values());
+                }
+            }
+            assert_eq!(subjects.publishes, published, "{node_id}");
+        }
+
+        assert_eq!(
+            preview.routes,
+            BTreeMap::from([("webhook".to_string(), paths["webhook"].clone())])
+        );
+        assert_eq!(paths["webhook"], "/branches/orders");
+        assert_eq!(
+            preview.nodes["stored"].subscriptions,
+            [
+                "pipestack.default.branches.step-3-in.out-success",
+                "pipestack.default.branches.stored.replay"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route_prefix_is_included_in_webhook_paths() {
+        let mut app_config = AppConfig::new().expect("Could not read app config");
+        app_config.route_prefix = "/hooks/{workspace}/".to_string();
+        let pipeline: Pipeline =
+            serde_yaml::from_str(BRANCHES_PIPELINE).expect("Failed to parse input YAML");
+        let workspace = "acme".to_string();
+
+        let wadm = convert_pipeline(&pipeline, &workspace, &app_config)
+            .expect("Failed to convert pipeline");
+        let httpserver = wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "httpserver")
+            .expect("Should have httpserver component");
+        let TraitProperties::Link(link) = &httpserver.traits[0].properties else {
+            panic!("httpserver should link to the webhook");
+        };
+        let config = &link.source.as_ref().unwrap().config.as_ref().unwrap()[0];
+        assert_eq!(
+            config.properties["path"],
+            serde_yaml::Value::String("/hooks/acme/branches/orders".to_string())
+        );
+
+        let preview = preview_subjects(&pipeline, &workspace, &app_config)
+            .expect("Failed to preview subjects");
+        assert_eq!(preview.routes["webhook"], "/hooks/acme/branches/orders");
+    }
+
+    #[test]
+    fn test_multiple_sources_feed_one_processor() {
+        let input_yaml = r#"
+name: fan-in
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 50
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: 'webhook'
+  - id: rss
+    label: rss
+    type: in-rss-reader
+    position:
+      x: 100
+      'y': 150
+    settings:
+      type: in-rss-reader
+      settings:
+        feedUrl: https://example.com/feed.xml
+        pollIntervalMs: 60000
+  - id: processor
+    label: processor
+    type: processor-wasm
+    position:
+      x: 200
+      'y': 100
+    depends_on:
+      - webhook
+      - rss
+  - id: log
+    label: log
+    type: out-log
+    position:
+      x: 300
+      'y': 100
+    depends_on:
+      - processor
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let next_step_topic = |source: &str| {
+            let component = actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == format!("out-internal-for-{source}"))
+                .unwrap_or_else(|| panic!("Should have an out-internal for {source}"));
+            match &component.properties {
+                Properties::WithImage { config, .. } => {
+                    config.as_ref().unwrap()[0].properties["next-step-topic"].clone()
+                }
+                _ => panic!("out-internal should reference an image"),
+            }
+        };
+
+        // Both sources publish to the processor's single in-topic
+        let processor_topic =
+            serde_yaml::Value::String("pipestack.default.fan-in.step-2-in".to_string());
+        assert_eq!(next_step_topic("webhook"), processor_topic);
+        assert_eq!(next_step_topic("rss"), processor_topic);
+
+        // The processor's in-internal subscribes to it once, besides its replay subject
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        let processor_subscriptions: Vec<_> = nats
+            .traits
+            .iter()
+            .filter_map(|t| match &t.properties {
+                TraitProperties::Link(link) if link.target.name == "in-internal-for-processor" => {
+                    Some(&link.source.as_ref().unwrap().config.as_ref().unwrap()[0].properties)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(processor_subscriptions.len(), 1);
+        assert_eq!(
+            processor_subscriptions[0]["subscriptions"],
+            serde_yaml::Value::String(
+                "pipestack.default.fan-in.step-2-in,pipestack.default.fan-in.processor.replay"
+                    .to_string()
+            )
+        );
+    }
+
+    const BRANCHES_PIPELINE: &str = r#"
+name: branches
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: 'orders'
+  - id: processor
+    label: processor
+    type: processor-wasm
+    position:
+      x: 200
+      'y': 100
+    depends_on:
+      - webhook
+  - id: stored
+    label: stored
+    type: out-log
+    position:
+      x: 300
+      'y': 50
+    depends_on:
+      - node: processor
+        output: success
+  - id: errors
+    label: errors
+    type: out-log
+    position:
+      x: 300
+      'y': 150
+    depends_on:
+      - node: processor
+        output: error
+  - id: audit
+    label: audit
+    type: out-log
+    position:
+      x: 300
+      'y': 250
+    depends_on:
+      - processor
+"#;
+
+    #[test]
+    fn test_processor_outputs_feed_different_sinks() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(BRANCHES_PIPELINE).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        // The processor publishes its default output to the step topic, and each named output to
+        // a subject of its own
+        let out_internal = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "out-internal-for-processor")
+            .expect("Should have an out-internal for the processor");
+        let Properties::WithImage { config, .. } = &out_internal.properties else {
+            panic!("out-internal should reference an image");
+        };
+        let properties = &config.as_ref().unwrap()[0].properties;
+        assert_eq!(
+            properties["next-step-topic"],
+            serde_yaml::Value::String("pipestack.default.branches.step-3-in".to_string())
+        );
+        assert_eq!(
+            properties["output-topics"],
+            serde_yaml::Value::String(
+                r#"{"error":"pipestack.default.branches.step-3-in.out-error","success":"pipestack.default.branches.step-3-in.out-success"}"#
+                    .to_string()
+            )
+        );
+
+        // Each sink subscribes to the output it depends on
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        let subscriptions =
+            |sink: &str| {
+                nats.traits
+                    .iter()
+                    .find_map(|t| match &t.properties {
+                        TraitProperties::Link(link)
+                            if link.target.name == format!("in-internal-for-{sink}") =>
+                        {
+                            Some(
+                                link.source.as_ref().unwrap().config.as_ref().unwrap()[0]
+                                    .properties["subscriptions"]
+                                    .clone(),
+                            )
+                        }
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| panic!("Should have a subscription for {sink}"))
+            };
+        assert_eq!(
+            subscriptions("stored"),
+            serde_yaml::Value::String(
+                "pipestack.default.branches.step-3-in.out-success,pipestack.default.branches.stored.replay"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            subscriptions("errors"),
+            serde_yaml::Value::String(
+                "pipestack.default.branches.step-3-in.out-error,pipestack.default.branches.errors.replay"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            subscriptions("audit"),
+            serde_yaml::Value::String(
+                "pipestack.default.branches.step-3-in,pipestack.default.branches.audit.replay"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_output_dependencies_are_rejected() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        // Sources have no named outputs
+        let mut pipeline: Pipeline =
+            serde_yaml::from_str(BRANCHES_PIPELINE).expect("Failed to parse input YAML");
+        pipeline.nodes[1].depends_on = Some(vec![DependsOn::Output {
+            node: "webhook".to_string(),
+            output: "error".to_string(),
+        }]);
+        let err = validate_pipeline(&pipeline, &app_config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Node processor depends on output error of webhook, but only processors have named outputs"
+        );
+
+        let mut pipeline: Pipeline =
+            serde_yaml::from_str(BRANCHES_PIPELINE).expect("Failed to parse input YAML");
+        pipeline.nodes[2].depends_on = Some(vec![DependsOn::Output {
+            node: "processor".to_string(),
+            output: "Not Found".to_string(),
+        }]);
+        let err = validate_pipeline(&pipeline, &app_config).unwrap_err();
+        assert!(err.to_string().contains("output names must be"), "{err}");
+    }
+
+    #[test]
+    fn test_duplicate_dependency_is_rejected() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        let mut pipeline = chain_pipeline(3);
+        pipeline.nodes[2].depends_on = Some(vec!["node-1".into(), "node-1".into()]);
+        let err = validate_pipeline(&pipeline, &app_config)
+            .expect_err("Duplicate dependency should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "Node node-2 depends on node-1 more than once"
+        );
+    }
+
+    #[test]
+    fn test_fan_out_link_names_are_unique() {
+        let input_yaml = r#"
+name: fan-out
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: 'webhook'
+  - id: log-1
+    label: log-1
+    type: out-log
+    position:
+      x: 200
+      'y': 50
+    depends_on:
+      - webhook
+  - id: log-2
+    label: log-2
+    type: out-log
+    position:
+      x: 200
+      'y': 150
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        for component in &actual_wadm.spec.components {
+            let mut link_names = Vec::new();
+            for component_trait in &component.traits {
+                if let TraitProperties::Link(link_props) = &component_trait.properties {
+                    let name = link_props
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| panic!("Unnamed link on {}", component.name));
+                    assert!(
+                        !link_names.contains(&name),
+                        "Duplicate link name {name} on {}",
+                        component.name
+                    );
+                    link_names.push(name);
+                }
+            }
+        }
+    }
+
+    fn processor_image_for_source(source: &str) -> String {
+        let input_yaml = format!(
+            r#"
+name: mine
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: 'webhook'
+  - id: processor
+    label: processor
+    type: processor-wasm
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: processor-wasm
+      settings:
+        source: '{source}'
+        instances: 1
+    depends_on:
+      - webhook
+"#
+        );
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(&input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        let processor = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "processor")
+            .expect("Should have processor component");
+
+        match &processor.properties {
+            Properties::WithImage { image, .. } => image.clone(),
+            _ => panic!("Processor component should reference an image"),
+        }
+    }
+
+    #[test]
+    fn test_processor_wasm_uses_published_oci_source() {
+        assert_eq!(
+            processor_image_for_source("ghcr.io/acme/processors/enrich:1.2.3"),
+            "ghcr.io/acme/processors/enrich:1.2.3"
+        );
+        assert_eq!(
+            processor_image_for_source("localhost:5000/nodes/enrich@sha256:abc123"),
+            "localhost:5000/nodes/enrich@sha256:abc123"
+        );
+    }
+
+    #[test]
+    fn test_processor_wasm_uses_builder_path_for_uploaded_source() {
+        let expected = "http://localhost:5000/default/pipeline/mine/1/builder/components/nodes/processor/wasm/processor:1.0.0";
+
+        assert_eq!(processor_image_for_source("enrich.wasm"), expected);
+        assert_eq!(processor_image_for_source("localhost:5000"), expected);
+        assert_eq!(processor_image_for_source(""), expected);
+    }
+
+    #[test]
+    fn test_processor_wasm_digest_pin() {
+        use crate::builders::nodes::processor::wasm::{content_digest, verify_digest};
+        use shared::{PipelineNode, PipelineNodeType, ProcessorWasmSettings, XYPosition};
+
+        let wasm = b"\0asm\x01\0\0\0";
+        let digest = content_digest(wasm);
+        assert_eq!(
+            digest,
+            "sha256:93a44bbb96c751218e4c00d479e4c14358122a389acca16205b1e4d0dc5f9476"
+        );
+
+        // Uploaded components are compared against the pin once fetched
+        assert!(verify_digest(None, wasm).is_ok());
+        assert!(verify_digest(Some(&digest), wasm).is_ok());
+        let other = format!("sha256:{}", "0".repeat(64));
+        assert_eq!(
+            verify_digest(Some(&other), wasm).unwrap_err(),
+            format!("Component digest {digest} does not match the pinned digest {other}")
+        );
+
+        // Published references must carry the pin themselves
+        let processor = |source: &str| PipelineNode {
+            id: "processor".to_string(),
+            label: "A processor".to_string(),
+            step_type: PipelineNodeType::ProcessorWasm,
+            position: XYPosition { x: 0.0, y: 0.0 },
+            settings: Some(PipelineNodeSettings::ProcessorWasm(ProcessorWasmSettings {
+                source: source.to_string(),
+                instances: 1,
+                digest: Some(digest.clone()),
+                bytes_input: None,
+            })),
+            instances: None,
+            helper_instances: None,
+            depends_on: None,
+            resilience: None,
+        };
+        assert!(check_published_digest(&processor("enrich.wasm")).is_ok());
+        assert!(
+            check_published_digest(&processor(&format!("ghcr.io/acme/enrich@{digest}"))).is_ok()
+        );
+        assert_eq!(
+            check_published_digest(&processor("ghcr.io/acme/enrich:1.0.0")).unwrap_err(),
+            format!(
+                "Node processor pins digest {digest}, its source ghcr.io/acme/enrich:1.0.0 must reference it as ghcr.io/acme/enrich:1.0.0@{digest}"
+            )
+        );
+        assert!(
+            check_published_digest(&processor(&format!("ghcr.io/acme/enrich@{other}"))).is_err()
+        );
+    }
+
+    #[test]
+    fn test_processor_wasm_instances_precedence() {
+        use crate::builders::nodes::processor::wasm::processor_instances;
+        use shared::{PipelineNode, PipelineNodeType, ProcessorWasmSettings, XYPosition};
+
+        let processor = |instances: Option<u32>, settings_instances: Option<u32>| PipelineNode {
+            id: "processor".to_string(),
+            label: "A processor".to_string(),
+            step_type: PipelineNodeType::ProcessorWasm,
+            position: XYPosition { x: 0.0, y: 0.0 },
+            settings: settings_instances.map(|instances| {
+                PipelineNodeSettings::ProcessorWasm(ProcessorWasmSettings {
+                    source: "enrich.wasm".to_string(),
+                    instances,
+                    digest: None,
+                    bytes_input: None,
+                })
+            }),
+            instances,
+            helper_instances: None,
+            depends_on: None,
+            resilience: None,
+        };
+
+        // Node-level instances win over the processor settings
+        assert_eq!(processor_instances(&processor(Some(5), Some(50))), 5);
+        // Settings apply when the node doesn't set instances
+        assert_eq!(processor_instances(&processor(None, Some(50))), 50);
+        assert_eq!(processor_instances(&processor(Some(5), None)), 5);
+        assert_eq!(processor_instances(&processor(None, None)), 10_000);
+
+        let pipeline = Pipeline {
+            name: "mine".to_string(),
+            version: "1".to_string(),
+            nodes: vec![processor(Some(5), Some(50))],
+            dead_letter: None,
+            helper_instances: None,
+        };
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        let component = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "processor")
+            .expect("Should have processor component");
+        assert_eq!(
+            component.traits[0].properties,
+            TraitProperties::Spreadscaler { instances: 5 }
+        );
+    }
+
+    #[test]
+    fn test_helper_instances_override() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut pipeline = chain_pipeline(3);
+        pipeline.helper_instances = Some(20);
+        pipeline.nodes[1].instances = Some(3);
+        pipeline.nodes[1].helper_instances = Some(4);
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let instances = |name: &str| {
+            let component = actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"));
+            match component.traits[0].properties {
+                TraitProperties::Spreadscaler { instances } => instances,
+                _ => panic!("{name} should be scaled by its first trait"),
+            }
+        };
+
+        // The node's own override applies to its helpers only
+        assert_eq!(instances("node-1"), 3);
+        assert_eq!(instances("in-internal-for-node-1"), 4);
+        assert_eq!(instances("out-internal-for-node-1"), 4);
+        // Other helpers fall back to the pipeline's default
+        assert_eq!(instances("out-internal-for-node-0"), 20);
+        assert_eq!(instances("in-internal-for-node-2"), 20);
+    }
+
+    fn chain_pipeline(length: usize) -> Pipeline {
+        use shared::{PipelineNode, PipelineNodeType, XYPosition};
+
+        let nodes = (0..length)
+            .map(|i| PipelineNode {
+                id: format!("node-{i}"),
+                label: format!("Node {i}"),
+                step_type: if i == 0 {
+                    PipelineNodeType::InHttpWebhook
+                } else {
+                    PipelineNodeType::ProcessorWasm
+                },
+                position: XYPosition { x: 0.0, y: 0.0 },
+                settings: None,
+                instances: None,
+                helper_instances: None,
+                depends_on: (i > 0).then(|| vec![format!("node-{}", i - 1).into()]),
+                resilience: None,
+            })
+            .collect();
+
+        Pipeline {
+            name: "chain".to_string(),
+            version: "1".to_string(),
+            nodes,
+            dead_letter: None,
+            helper_instances: None,
+        }
+    }
+
+    #[test]
+    fn test_node_labels_are_emitted_as_annotations() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut pipeline = chain_pipeline(2);
+        pipeline.nodes[1].label = "Enrich orders".to_string();
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        assert_eq!(
+            actual_wadm
+                .metadata
+                .annotations
+                .get("pipestack.node/node-1")
+                .map(String::as_str),
+            Some("Enrich orders")
+        );
+        assert_eq!(
+            actual_wadm.metadata.annotations.get("version"),
+            Some(&"1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_processor_input_is_emitted_into_in_internal_config() {
+        use shared::{PROCESSOR_INPUT_CONFIG_KEY, ProcessorFilterSettings, ProcessorWasmSettings};
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut pipeline = chain_pipeline(4);
+        pipeline.nodes[2].settings =
+            Some(PipelineNodeSettings::ProcessorWasm(ProcessorWasmSettings {
+                source: "enrich.wasm".to_string(),
+                instances: 1,
+                digest: None,
+                bytes_input: Some(true),
+            }));
+        pipeline.nodes[3].step_type = shared::PipelineNodeType::ProcessorFilter;
+        pipeline.nodes[3].settings = Some(PipelineNodeSettings::ProcessorFilter(
+            ProcessorFilterSettings {
+                predicate: "/status".to_string(),
+            },
+        ));
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let processor_input = |step: &str| {
+            let component = actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == format!("in-internal-for-{step}"))
+                .unwrap_or_else(|| panic!("Should have in-internal for {step}"));
+            match &component.properties {
+                Properties::WithImage { config, .. } => config
+                    .iter()
+                    .flatten()
+                    .find_map(|c| c.properties.get(PROCESSOR_INPUT_CONFIG_KEY).cloned()),
+                _ => panic!("in-internal for {step} should reference an image"),
+            }
+        };
+        let input = |value: &str| Some(serde_yaml::Value::String(value.to_string()));
+
+        // Custom processors take text unless they implement run-bytes, built-in ones take bytes
+        assert_eq!(processor_input("node-1"), input("text"));
+        assert_eq!(processor_input("node-2"), input("bytes"));
+        assert_eq!(processor_input("node-3"), input("bytes"));
+    }
+
+    #[test]
+    fn test_pipeline_name_of_application() {
+        assert_eq!(pipeline_name("acme", "acme-orders"), Some("orders"));
+        assert_eq!(pipeline_name("acme", "acme-providers"), Some("providers"));
+        assert_eq!(
+            pipeline_name("acme", &application_name("acme", "my-pipeline")),
+            Some("my-pipeline")
+        );
+
+        assert_eq!(pipeline_name("acme", "globex-orders"), None);
+        assert_eq!(pipeline_name("acme", "acmeorders"), None);
+        assert_eq!(pipeline_name("acme", "acme-"), None);
+        assert_eq!(pipeline_name("acme", "acme"), None);
+    }
+
+    #[test]
+    fn test_component_id() {
+        use crate::builders::component_id;
+
+        assert_eq!(
+            component_id("default", "mine", "in-http-webhook_17"),
+            "default_mine-in-http-webhook_17"
+        );
+        // Equal when the separator is simply concatenated
+        assert_ne!(
+            component_id("acme_corp", "orders", "log"),
+            component_id("acme", "corp_orders", "log")
+        );
+    }
+
+    #[test]
+    fn test_colliding_component_ids_are_rejected() {
+        use shared::{PipelineNode, PipelineNodeType, XYPosition};
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        // node-1's in-internal component is named in-internal-for-node-1, like this node
+        let mut pipeline = chain_pipeline(2);
+        pipeline.nodes.push(PipelineNode {
+            id: "in-internal-for-node-1".to_string(),
+            label: "Colliding".to_string(),
+            step_type: PipelineNodeType::ProcessorWasm,
+            position: XYPosition { x: 0.0, y: 0.0 },
+            settings: None,
+            instances: None,
+            helper_instances: None,
+            depends_on: Some(vec!["node-1".into()]),
+            resilience: None,
+        });
+
+        // The node id is reserved, so the pipeline is rejected before building components
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Colliding component ids should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "Node id in-internal-for-node-1 is reserved for a component generated for the pipeline"
+        );
+
+        let components = [
+            Component {
+                name: "in-internal-for-node-1".to_string(),
+                component_type: "component".to_string(),
+                properties: Properties::WithImage {
+                    id: Some("default_chain-in-internal-for-node-1".to_string()),
+                    image: "in-internal".to_string(),
+                    config: None,
+                },
+                traits: vec![],
+            },
+            Component {
+                name: "node-2".to_string(),
+                component_type: "component".to_string(),
+                properties: Properties::WithImage {
+                    id: Some("default_chain-in-internal-for-node-1".to_string()),
+                    image: "processor".to_string(),
+                    config: None,
+                },
+                traits: vec![],
+            },
+        ];
+        assert_eq!(
+            check_unique_component_ids(&components),
+            Err("Components in-internal-for-node-1 and node-2 would both be deployed with the id default_chain-in-internal-for-node-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_large_valid_pipeline_converts() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline = chain_pipeline(app_config.limits.max_nodes);
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        // Every processor gets a subscription link on messaging-nats
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        assert_eq!(nats.traits.len(), app_config.limits.max_nodes - 1);
+    }
+
+    #[test]
+    fn test_every_cluster_uri_reaches_subscription_config() {
+        let mut app_config = AppConfig::new().expect("Could not read app config");
+        app_config.nats.cluster_uris = vec![
+            "nats://nats-a:4222".to_string(),
+            "nats://nats-b:4222".to_string(),
+        ];
+        let pipeline = chain_pipeline(2);
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        let TraitProperties::Link(link) = &nats.traits[0].properties else {
+            panic!("Expected a subscription link");
+        };
+        // The first link declares the shared cluster URIs config
+        let config = &link.source.as_ref().unwrap().config.as_ref().unwrap()[1];
+        assert_eq!(
+            config.properties.get("cluster_uris"),
+            Some(&serde_yaml::Value::String(
+                "nats://nats-a:4222,nats://nats-b:4222".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_cluster_uris_config_is_declared_once() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline = chain_pipeline(4);
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        let link_configs: Vec<&Vec<Config>> = nats
+            .traits
+            .iter()
+            .map(|t| match &t.properties {
+                TraitProperties::Link(link) => {
+                    link.source.as_ref().unwrap().config.as_ref().unwrap()
+                }
+                _ => panic!("Expected a subscription link"),
+            })
+            .collect();
+        assert_eq!(link_configs.len(), 3);
+
+        // Every link references the config by name, and only one declares its properties
+        for configs in &link_configs {
+            assert!(
+                configs
+                    .iter()
+                    .any(|c| c.name == "messaging-nats-cluster-config-v1")
+            );
+            assert!(
+                configs
+                    .iter()
+                    .filter(|c| c.name != "messaging-nats-cluster-config-v1")
+                    .all(|c| !c.properties.contains_key("cluster_uris"))
+            );
+        }
+        let declared: Vec<&Config> = link_configs
+            .iter()
+            .flat_map(|configs| configs.iter())
+            .filter(|c| c.name == "messaging-nats-cluster-config-v1" && !c.properties.is_empty())
+            .collect();
+        assert_eq!(declared.len(), 1);
+        assert_eq!(
+            declared[0].properties["cluster_uris"],
+            serde_yaml::Value::String(app_config.nats.joined_cluster_uris())
+        );
+
+        // A reference is written as a bare name
+        let yaml = serde_yaml::to_string(&actual_wadm).unwrap();
+        assert_eq!(yaml.matches("cluster_uris:").count(), 1);
+        assert_eq!(
+            yaml.matches("- name: messaging-nats-cluster-config-v1")
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_pipeline_exceeding_max_nodes_is_rejected() {
+        let mut app_config = AppConfig::new().expect("Could not read app config");
+        app_config.limits.max_nodes = 10;
+        let pipeline = chain_pipeline(11);
+
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Pipeline above the node limit should be rejected");
+        assert!(err.to_string().contains("exceeds the maximum of 10"));
+    }
+
+    #[test]
+    fn test_validate_pipeline_node_count_boundary() {
+        let mut app_config = AppConfig::new().expect("Could not read app config");
+        app_config.limits.max_nodes = 10;
+
+        assert!(validate_pipeline(&chain_pipeline(10), &app_config).is_ok());
+        let err = validate_pipeline(&chain_pipeline(11), &app_config)
+            .expect_err("Pipeline above the node limit should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "Pipeline has 11 nodes, which exceeds the maximum of 10"
+        );
+    }
+
+    #[test]
+    fn test_disconnected_node_is_rejected() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        // Depends on a node that doesn't exist
+        let mut pipeline = chain_pipeline(3);
+        pipeline.nodes[2].depends_on = Some(vec!["missing".into()]);
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Disconnected node should be rejected");
+        assert!(err.to_string().contains("node-2"));
+
+        // Part of a cycle
+        let mut pipeline = chain_pipeline(3);
+        pipeline.nodes[1].depends_on = Some(vec!["node-2".into()]);
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Cyclic nodes should be rejected");
+        assert!(err.to_string().contains("node-1, node-2"));
+        assert!(matches!(err, DeployError::Validation(_)), "{err:?}");
+    }
+
+    #[test]
+    fn test_dry_validate_reports_every_problem() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut pipeline: Pipeline =
+            serde_yaml::from_str(RESILIENT_PIPELINE_YAML).expect("Failed to parse input YAML");
+        pipeline.nodes[1].depends_on = Some(vec!["notify".into()]);
+        match &mut pipeline.nodes[2].settings {
+            Some(PipelineNodeSettings::OutHttpWebhook(settings)) => {
+                settings.url = "example.com/hook".to_string()
+            }
+            _ => panic!("notify should be an out-http-webhook node"),
+        }
+
+        let problems = dry_validate(&pipeline, &app_config);
+        assert_eq!(
+            problems,
+            [
+                Problem {
+                    node_id: Some("notify".to_string()),
+                    field: Some("url".to_string()),
+                    message: "Invalid settings for node notify: url: \"example.com/hook\" must start with http:// or https://".to_string(),
+                    severity: Severity::Error,
+                },
+                Problem {
+                    node_id: None,
+                    field: None,
+                    message: "Nodes are not reachable from any source node (missing dependency or cycle): processor, notify".to_string(),
+                    severity: Severity::Error,
+                },
+                Problem {
+                    node_id: Some("webhook".to_string()),
+                    field: None,
+                    message: "No node depends on node webhook, its output is dropped".to_string(),
+                    severity: Severity::Warning,
+                },
+            ]
+        );
+
+        // Deploying fails on the first of them
+        assert_eq!(
+            validate_pipeline(&pipeline, &app_config),
+            Err(DeployError::Validation(problems[0].message.clone()))
+        );
+    }
+
+    #[test]
+    fn test_warnings_do_not_fail_validation() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline = chain_pipeline(2);
+
+        let problems = dry_validate(&pipeline, &app_config);
+        assert_eq!(
+            serde_json::to_value(&problems).unwrap(),
+            serde_json::json!([{
+                "nodeId": "node-1",
+                "message": "No node depends on node node-1, its output is dropped",
+                "severity": "warning"
+            }])
+        );
+        assert_eq!(validate_pipeline(&pipeline, &app_config), Ok(()));
+    }
+
+    #[test]
+    fn test_convert_pipeline_rss_reader() {
+        let input_yaml = r#"
+name: feeds
+version: 1
+nodes:
+  - id: rss
+    label: rss
+    type: in-rss-reader
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-rss-reader
+      settings:
+        feedUrl: https://example.com/feed.xml
+        pollIntervalMs: 60000
+  - id: log
+    label: log
+    type: out-log
+    position:
+      x: 200
+      'y': 100
+    depends_on:
+      - rss
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        // The reader gets its settings and a stable state key
+        match &component("rss").properties {
+            Properties::WithImage { config, .. } => {
+                let properties = &config.as_ref().unwrap()[0].properties;
+                assert_eq!(
+                    properties.get("state-key"),
+                    Some(&serde_yaml::Value::String("default-feeds-rss".to_string()))
+                );
+                assert!(properties.contains_key("json"));
+            }
+            _ => panic!("RSS reader should reference an image"),
+        }
+
+        // It publishes through out-internal and needs the http client and keyvalue capabilities
+        component("out-internal-for-rss");
+        component("httpclient");
+        component("keyvalue-nats");
+
+        // messaging-nats drives the reader through its poll subject
+        let poll_link = component("messaging-nats")
+            .traits
+            .iter()
+            .find_map(|t| match &t.properties {
+                TraitProperties::Link(link) if link.target.name == "rss" => Some(link),
+                _ => None,
+            })
+            .expect("Should have a poll link to the reader");
+        let subscriptions = &poll_link.source.as_ref().unwrap().config.as_ref().unwrap()[0]
+            .properties["subscriptions"];
+        assert_eq!(
+            subscriptions,
+            &serde_yaml::Value::String("pipestack.default.feeds.rss.poll".to_string())
+        );
+
+        // ...which the poll scheduler publishes ticks to
+        assert_eq!(
+            crate::builders::nodes::r#in::poll_subjects(&pipeline, "default"),
+            vec!["pipestack.default.feeds.rss.poll".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_convert_pipeline_in_rabbitmq() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: rabbitmq
+    label: rabbitmq
+    type: in-rabbitmq
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-rabbitmq
+      settings:
+        url: https://rabbitmq.example.com:15672
+        queue: orders
+        exchange: events
+        bindingKey: orders.*
+        ackMode: manual
+  - id: log
+    label: log
+    type: out-log
+    position:
+      x: 200
+      'y': 100
+    depends_on:
+      - rabbitmq
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        match &component("rabbitmq").properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/in_rabbitmq_s.wasm:0.1.0"));
+                assert!(config.as_ref().unwrap()[0].properties.contains_key("json"));
+            }
+            _ => panic!("RabbitMQ source should reference an image"),
+        }
+
+        // It consumes through the management API and publishes through out-internal
+        component("out-internal-for-rabbitmq");
+        component("httpclient");
+
+        // messaging-nats drives the consumer through its poll subject
+        let poll_link = component("messaging-nats")
+            .traits
+            .iter()
+            .find_map(|t| match &t.properties {
+                TraitProperties::Link(link) if link.target.name == "rabbitmq" => Some(link),
+                _ => None,
+            })
+            .expect("Should have a poll link to the consumer");
+        let subscriptions = &poll_link.source.as_ref().unwrap().config.as_ref().unwrap()[0]
+            .properties["subscriptions"];
+        assert_eq!(
+            subscriptions,
+            &serde_yaml::Value::String("pipestack.default.orders.rabbitmq.poll".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_pipeline_in_graphql_poller() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: graphql
+    label: graphql
+    type: in-graphql-poller
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-graphql-poller
+      settings:
+        url: https://api.example.com/graphql
+        query: 'query Orders($cursor: String) { orders(after: $cursor) { id } }'
+        intervalMs: 30000
+        cursor: $.data.orders.pageInfo.endCursor
+  - id: log
+    label: log
+    type: out-log
+    position:
+      x: 200
+      'y': 100
+    depends_on:
+      - graphql
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        let poller = component("graphql");
+        match &poller.properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/in_graphql_poller_s.wasm:0.1.0"));
+                let properties = &config.as_ref().unwrap()[0].properties;
+                assert_eq!(
+                    properties.get("state-key"),
+                    Some(&serde_yaml::Value::String(
+                        "default-orders-graphql".to_string()
+                    ))
+                );
+                assert!(properties.contains_key("json"));
+            }
+            _ => panic!("GraphQL poller should reference an image"),
+        }
+
+        // It queries through the http client, keeps its cursor in keyvalue and publishes through
+        // out-internal
+        component("out-internal-for-graphql");
+        component("httpclient");
+        component("keyvalue-nats");
+        for interface in ["outgoing-handler", "store", "out"] {
+            assert!(
+                poller.traits.iter().any(|t| matches!(
+                    &t.properties,
+                    TraitProperties::Link(link) if link.interfaces == [interface]
+                )),
+                "Should link the poller's {interface} import"
+            );
+        }
+
+        // messaging-nats drives the poller through its poll subject
+        let poll_link = component("messaging-nats")
+            .traits
+            .iter()
+            .find_map(|t| match &t.properties {
+                TraitProperties::Link(link) if link.target.name == "graphql" => Some(link),
+                _ => None,
+            })
+            .expect("Should have a poll link to the poller");
+        let subscriptions = &poll_link.source.as_ref().unwrap().config.as_ref().unwrap()[0]
+            .properties["subscriptions"];
+        assert_eq!(
+            subscriptions,
+            &serde_yaml::Value::String("pipestack.default.orders.graphql.poll".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_pipeline_processor_format() {
+        let input_yaml = r#"
+name: convert
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /convert
+  - id: to-csv
+    label: to-csv
+    type: processor-format
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: processor-format
+      settings:
+        from: json
+        to: csv
+        csvOptions:
+          delimiter: ;
+    depends_on:
+      - webhook
+  - id: log
+    label: log
+    type: out-log
+    position:
+      x: 300
+      'y': 100
+    depends_on:
+      - to-csv
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        // The built-in node image is used and receives its settings as config
+        match &component("to-csv").properties {
+            Properties::WithImage { image, config, .. } => {
+                assert_eq!(
+                    image,
+                    "http://localhost:5000/nodes/processor_format_s.wasm:0.1.1"
+                );
+                let json = config.as_ref().unwrap()[0].properties["json"]
+                    .as_str()
+                    .unwrap();
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(json).unwrap(),
+                    serde_json::json!({"from": "json", "to": "csv", "csvOptions": {"delimiter": ";"}})
+                );
+            }
+            _ => panic!("Format processor should reference an image"),
+        }
+
+        // It is wired like any other processor
+        component("in-internal-for-to-csv");
+        component("out-internal-for-to-csv");
+        let subscription_link =
+            component("messaging-nats")
+                .traits
+                .iter()
+                .find_map(|t| match &t.properties {
+                    TraitProperties::Link(link) if link.target.name == "in-internal-for-to-csv" => {
+                        Some(link)
+                    }
+                    _ => None,
+                });
+        assert!(subscription_link.is_some());
+    }
+
+    #[test]
+    fn test_processor_format_requires_settings() {
+        use shared::PipelineNodeType;
+
+        let mut pipeline = chain_pipeline(2);
+        pipeline.nodes[1].step_type = PipelineNodeType::ProcessorFormat;
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        assert!(convert_pipeline(&pipeline, &"default".to_string(), &app_config).is_err());
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_mongodb() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /orders
+  - id: mongo
+    label: mongo
+    type: out-mongodb
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-mongodb
+      settings:
+        connectionString: mongodb://mongo:27017
+        database: shop
+        collection: orders
+        writeMode: upsert
+        idField: orderId
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        component("in-internal-for-mongo");
+        match &component("mongo").properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/out_mongodb_s.wasm:0.1.0"));
+                let config = &config.as_ref().unwrap()[0];
+                assert_eq!(config.name, "mongo-config-v1");
+                let json: serde_json::Value = serde_json::from_str(
+                    config.properties["json"]
+                        .as_str()
+                        .expect("Settings should be passed as JSON"),
+                )
+                .unwrap();
+                assert_eq!(
+                    json,
+                    serde_json::json!({
+                        "connectionString": "mongodb://mongo:27017",
+                        "database": "shop",
+                        "collection": "orders",
+                        "writeMode": "upsert",
+                        "idField": "orderId"
+                    })
+                );
+            }
+            _ => panic!("out-mongodb should reference an image"),
+        }
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_snowflake() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /orders
+  - id: warehouse
+    label: warehouse
+    type: out-snowflake
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-snowflake
+      settings:
+        account: acme-analytics
+        warehouse: LOADING
+        database: SHOP
+        schema: PUBLIC
+        table: ORDERS
+        auth:
+          type: key-pair
+          user: PIPESTACK
+          privateKeySecret: snowflake-private-key
+        writeMode: insert
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        component("in-internal-for-warehouse");
+        match &component("warehouse").properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/out_snowflake_s.wasm:0.1.0"));
+                let config = &config.as_ref().unwrap()[0];
+                assert_eq!(config.name, "warehouse-config-v1");
+                let json: serde_json::Value = serde_json::from_str(
+                    config.properties["json"]
+                        .as_str()
+                        .expect("Settings should be passed as JSON"),
+                )
+                .unwrap();
+                assert_eq!(
+                    json,
+                    serde_json::json!({
+                        "account": "acme-analytics",
+                        "warehouse": "LOADING",
+                        "database": "SHOP",
+                        "schema": "PUBLIC",
+                        "table": "ORDERS",
+                        "auth": {
+                            "type": "key-pair",
+                            "user": "PIPESTACK",
+                            "privateKeySecret": "snowflake-private-key"
+                        },
+                        "writeMode": "insert"
+                    })
+                );
+            }
+            _ => panic!("out-snowflake should reference an image"),
+        }
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_google_bigquery() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /orders
+  - id: bigquery
+    label: bigquery
+    type: out-google-bigquery
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-google-bigquery
+      settings:
+        projectId: acme-analytics
+        dataset: shop
+        table: orders
+        credentialsSecret: bigquery-service-account
+        writeMode: streaming
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        component("in-internal-for-bigquery");
+        match &component("bigquery").properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/out_google_bigquery_s.wasm:0.1.0"));
+                let config = &config.as_ref().unwrap()[0];
+                assert_eq!(config.name, "bigquery-config-v1");
+                let json: serde_json::Value = serde_json::from_str(
+                    config.properties["json"]
+                        .as_str()
+                        .expect("Settings should be passed as JSON"),
+                )
+                .unwrap();
+                assert_eq!(
+                    json,
+                    serde_json::json!({
+                        "projectId": "acme-analytics",
+                        "dataset": "shop",
+                        "table": "orders",
+                        "credentialsSecret": "bigquery-service-account",
+                        "writeMode": "streaming"
+                    })
+                );
+            }
+            _ => panic!("out-google-bigquery should reference an image"),
+        }
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_aws_lambda() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /orders
+  - id: lambda
+    label: lambda
+    type: out-aws-lambda
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-aws-lambda
+      settings:
+        functionName: ship-order
+        region: eu-west-1
+        credentials:
+          accessKeyIdSecret: aws-access-key-id
+          secretAccessKeySecret: aws-secret-access-key
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        component("in-internal-for-lambda");
+        let lambda = component("lambda");
+        match &lambda.properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/out_aws_lambda_s.wasm:0.1.0"));
+                assert_eq!(config.as_ref().unwrap()[0].name, "lambda-config-v1");
+            }
+            _ => panic!("out-aws-lambda should reference an image"),
+        }
+
+        // The function is invoked through the http client capability
+        component("httpclient");
+        assert!(lambda.traits.iter().any(|t| matches!(
+            &t.properties,
+            TraitProperties::Link(link)
+                if link.target.name == "httpclient" && link.interfaces == ["outgoing-handler"]
+        )));
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_graphql_mutation() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /orders
+  - id: mutation
+    label: mutation
+    type: out-graphql-mutation
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-graphql-mutation
+      settings:
+        url: https://api.example.com/graphql
+        mutation: 'mutation Ship($id: ID!) { ship(id: $id) { id } }'
+        variablesTemplate:
+          id: '{{/order/id}}'
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+
+        component("in-internal-for-mutation");
+        let mutation = component("mutation");
+        match &mutation.properties {
+            Properties::WithImage { image, .. } => {
+                assert!(image.ends_with("/nodes/out_graphql_mutation_s.wasm:0.1.0"));
+            }
+            _ => panic!("out-graphql-mutation should reference an image"),
+        }
+
+        // The mutation is sent through the http client capability
+        component("httpclient");
+        assert!(mutation.traits.iter().any(|t| matches!(
+            &t.properties,
+            TraitProperties::Link(link)
+                if link.target.name == "httpclient" && link.interfaces == ["outgoing-handler"]
+        )));
+    }
+
+    #[test]
+    fn test_unsupported_node_type_fails_conversion() {
+        let mut pipeline = chain_pipeline(2);
+        pipeline.nodes[1].step_type = PipelineNodeType::OutInfluxdb;
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        let error = convert_pipeline(&pipeline, &"default".to_string(), &app_config).unwrap_err();
+        assert!(matches!(error, DeployError::Conversion(_)));
+        assert!(error.to_string().contains("OutInfluxdb"));
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_loki() {
+        let input_yaml = r#"
+name: logs
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /logs
+  - id: loki
+    label: loki
+    type: out-loki
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-loki
+      settings:
+        url: https://loki.example.com
+        labels:
+          app: shop
+          level: '{{/level}}'
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let loki = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "loki")
+            .expect("Should have loki component");
+        match &loki.properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/out_loki_s.wasm:0.1.1"));
+                let json: serde_json::Value = serde_json::from_str(
+                    config.as_ref().unwrap()[0].properties["json"]
+                        .as_str()
+                        .expect("Settings should be passed as JSON"),
+                )
+                .unwrap();
+                assert_eq!(
+                    json,
+                    serde_json::json!({
+                        "url": "https://loki.example.com",
+                        "labels": { "app": "shop", "level": "{{/level}}" }
+                    })
+                );
+            }
+            _ => panic!("out-loki should reference an image"),
+        }
+
+        // Entries are pushed through the http client capability
+        assert!(
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .any(|c| c.name == "httpclient")
+        );
+        assert!(loki.traits.iter().any(|t| matches!(
+            &t.properties,
+            TraitProperties::Link(link)
+                if link.target.name == "httpclient" && link.interfaces == ["outgoing-handler"]
+        )));
+        assert!(pipeline_capabilities(&pipeline).contains(&Capability::HttpClient));
+
+        // The sink's in-internal also subscribes to the flush subject and forwards it to the sink
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        let subscriptions = nats
+            .traits
+            .iter()
+            .find_map(|t| match &t.properties {
+                TraitProperties::Link(link) if link.target.name == "in-internal-for-loki" => {
+                    Some(&link.source.as_ref().unwrap().config.as_ref().unwrap()[0].properties)
+                }
+                _ => None,
+            })
+            .expect("Should subscribe in-internal-for-loki")["subscriptions"]
+            .clone();
+        assert_eq!(
+            subscriptions,
+            serde_yaml::Value::String(
+                "pipestack.default.logs.step-2-in,pipestack.default.logs.step-2-in.flush,pipestack.default.logs.loki.replay"
+                    .to_string()
+            )
+        );
+
+        let in_internal = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "in-internal-for-loki")
+            .expect("Should have in-internal-for-loki component");
+        assert!(in_internal.traits.iter().any(|t| matches!(
+            &t.properties,
+            TraitProperties::Link(link)
+                if link.target.name == "loki" && link.interfaces == ["out", "flush"]
+        )));
+    }
+
+    #[test]
+    fn test_convert_pipeline_out_rabbitmq() {
+        let input_yaml = r#"
+name: orders
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /orders
+  - id: rabbitmq
+    label: rabbitmq
+    type: out-rabbitmq
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: out-rabbitmq
+      settings:
+        url: https://rabbitmq.example.com:15672
+        exchange: orders
+        routingKey: '{{/region}}'
+        deliveryMode: transient
+    depends_on:
+      - webhook
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let rabbitmq = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "rabbitmq")
+            .expect("Should have rabbitmq component");
+        match &rabbitmq.properties {
+            Properties::WithImage { image, config, .. } => {
+                assert!(image.ends_with("/nodes/out_rabbitmq_s.wasm:0.1.0"));
+                let json: serde_json::Value = serde_json::from_str(
+                    config.as_ref().unwrap()[0].properties["json"]
+                        .as_str()
+                        .expect("Settings should be passed as JSON"),
+                )
+                .unwrap();
+                assert_eq!(
+                    json,
+                    serde_json::json!({
+                        "url": "https://rabbitmq.example.com:15672",
+                        "exchange": "orders",
+                        "routingKey": "{{/region}}",
+                        "deliveryMode": "transient"
+                    })
+                );
+            }
+            _ => panic!("out-rabbitmq should reference an image"),
+        }
+        assert!(rabbitmq.traits.iter().any(|t| matches!(
+            &t.properties,
+            TraitProperties::Link(link) if link.target.name == "httpclient"
+        )));
+        assert!(
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .any(|c| c.name == "in-internal-for-rabbitmq")
+        );
+    }
+    #[test]
+    fn test_node_order_does_not_change_wadm() {
+        let input_yaml = r#"
+name: mine
+version: 1
+nodes:
+  - id: in-http-webhook_17
+    label: in-http-webhook_17
+    type: in-http-webhook
+    position:
+      x: 300
+      'y': 180
+    settings:
+      type: in-http-webhook
+      settings:
+        method: GET
+        path: 'in-http-webhook_17'
+  - id: processor-wasm_18
+    label: processor-wasm_18
+    type: processor-wasm
+    position:
+      x: 548
+      'y': 69
+    source: localhost:5000/nodes/data-processor:0.0.1
+    instances: 10000
+    depends_on:
+      - in-http-webhook_17
+  - id: out-log_19
+    label: out-log_19
+    type: out-log
+    position:
+      x: 660
+      'y': 180
+    depends_on:
+      - processor-wasm_18
+  - id: out-log_20
+    label: out-log_20
+    type: out-log
+    position:
+      x: 960
+      'y': 180
+    depends_on:
+      - processor-wasm_18
+"#;
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let pipeline: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+        let mut reordered: Pipeline =
+            serde_yaml::from_str(input_yaml).expect("Failed to parse input YAML");
+        reordered.nodes.reverse();
+        reordered.nodes.swap(0, 1);
+
+        let wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let reordered_wadm = convert_pipeline(&reordered, &"default".to_string(), &app_config)
+            .expect("Failed to convert reordered pipeline");
+
+        assert_eq!(
+            serde_yaml::to_string(&wadm).unwrap(),
+            serde_yaml::to_string(&reordered_wadm).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dead_letter_sink_receives_every_dlq_subject() {
+        use shared::{PipelineNode, PipelineNodeType, XYPosition};
+
+        let mut pipeline = chain_pipeline(3);
+        pipeline.nodes.push(PipelineNode {
+            id: "dead-letters".to_string(),
+            label: "Dead letters".to_string(),
+            step_type: PipelineNodeType::OutLog,
+            position: XYPosition { x: 0.0, y: 0.0 },
+            settings: None,
+            instances: None,
+            helper_instances: None,
+            depends_on: None,
+            resilience: None,
+        });
+        pipeline.dead_letter = Some("dead-letters".to_string());
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+
+        let nats = actual_wadm
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "messaging-nats")
+            .expect("Should have messaging-nats component");
+        let dlq_subscriptions: Vec<&str> = nats
+            .traits
+            .iter()
+            .filter_map(|t| {
+                match &t.properties {
+                TraitProperties::Link(link)
+                    if link.target.name == "in-internal-for-dead-letters" =>
+                {
+                    link.source.as_ref().unwrap().config.as_ref().unwrap()[0].properties
+                        ["subscriptions"]
+                        .as_str()
+                }
+                _ => None,
+            }
+            })
+            .collect();
+
+        // Every node but the sink itself is routed to it
+        assert_eq!(
+            dlq_subscriptions,
+            vec![
+                "pipestack.default.chain.node-0.dlq",
+                "pipestack.default.chain.node-1.dlq",
+                "pipestack.default.chain.node-2.dlq",
+            ]
+        );
+
+        // Nodes with an in-internal component publish to their own dead-letter subject
+        let component = |name: &str| {
+            actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"))
+        };
+        let dead_letter_subjects = |name: &str| -> Vec<String> {
+            let Properties::WithImage { config, .. } = &component(name).properties else {
+                panic!("{name} should have an image");
+            };
+            config
+                .iter()
+                .flatten()
+                .filter_map(|config| config.properties.get(DEAD_LETTER_SUBJECT_CONFIG_KEY))
+                .map(|subject| subject.as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            dead_letter_subjects("in-internal-for-node-2"),
+            ["pipestack.default.chain.node-2.dlq"]
+        );
+        assert!(
+            component("in-internal-for-node-2")
+                .traits
+                .contains(&messaging_consumer_link("in-internal-for-node-2"))
+        );
+        assert!(dead_letter_subjects("in-internal-for-dead-letters").is_empty());
+    }
+
+    #[test]
+    fn test_replay_target_from_dead_letter_subject() {
+        let pipeline = chain_pipeline(3);
+        let workspace = "default".to_string();
+
+        assert_eq!(
+            replay_target(&pipeline, &workspace, "pipestack.default.chain.node-2.dlq").unwrap(),
+            "pipestack.default.chain.node-2.replay"
+        );
+        assert_eq!(
+            replay_target(&pipeline, &workspace, "pipestack.default.chain.node-1.dlq").unwrap(),
+            "pipestack.default.chain.node-1.replay"
+        );
+
+        for (subject, err) in [
+            (
+                "pipestack.default.chain.node-0.dlq",
+                "Node node-0 receives no messages from other nodes, there is nothing to replay to",
+            ),
+            (
+                "pipestack.default.chain.node-7.dlq",
+                "Node node-7 is not a node of the pipeline",
+            ),
+            (
+                "pipestack.default.orders.node-1.dlq",
+                "pipestack.default.orders.node-1.dlq is not a dead-letter subject of pipeline chain",
+            ),
+            (
+                "pipestack.default.chain.step-2-in",
+                "pipestack.default.chain.step-2-in is not a dead-letter subject of pipeline chain",
+            ),
+        ] {
+            assert_eq!(
+                replay_target(&pipeline, &workspace, subject)
+                    .unwrap_err()
+                    .to_string(),
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_dead_letter_must_reference_a_sink() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        let mut pipeline = chain_pipeline(2);
+        pipeline.dead_letter = Some("missing".to_string());
+        assert!(convert_pipeline(&pipeline, &"default".to_string(), &app_config).is_err());
+
+        pipeline.dead_letter = Some("node-1".to_string());
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config).unwrap_err();
+        assert!(err.to_string().contains("must be a sink node"));
+    }
+
+    fn webhook_pipeline(method: &str, url: &str) -> Pipeline {
+        let input_yaml = format!(
+            r#"
+name: webhooks
+version: 1
+nodes:
+  - id: in-http-webhook_1
+    label: in-http-webhook_1
+    type: in-http-webhook
+    position:
+      x: 300
+      'y': 180
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: 'in-http-webhook_1'
+  - id: out-http-webhook_2
+    label: out-http-webhook_2
+    type: out-http-webhook
+    position:
+      x: 660
+      'y': 180
+    settings:
+      type: out-http-webhook
+      settings:
+        method: {method}
+        url: '{url}'
+    depends_on:
+      - in-http-webhook_1
+"#
+        );
+        serde_yaml::from_str(&input_yaml).expect("Failed to parse input YAML")
+    }
+
+    #[test]
+    fn test_duplicate_webhook_paths_are_rejected() {
+        use shared::{InHttpWebhookSettings, PipelineNode, XYPosition};
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let webhook = |id: &str, path: &str| PipelineNode {
+            id: id.to_string(),
+            label: id.to_string(),
+            step_type: PipelineNodeType::InHttpWebhook,
+            position: XYPosition { x: 0.0, y: 0.0 },
+            settings: Some(PipelineNodeSettings::InHttpWebhook(InHttpWebhookSettings {
+                method: "POST".to_string(),
+                path: path.to_string(),
+                content_type: None,
+                request_body_json_schema: None,
+                event_filter: None,
+                metadata: None,
+                reply_timeout_ms: None,
+            })),
+            instances: None,
+            helper_instances: None,
+            depends_on: None,
+            resilience: None,
+        };
+        let mut pipeline = chain_pipeline(2);
+        pipeline.nodes[0] = webhook("node-0", "orders");
+        pipeline.nodes.push(webhook("webhook-2", "refunds"));
+        pipeline.nodes[1].depends_on = Some(vec!["node-0".into(), "webhook-2".into()]);
+
+        assert!(validate_pipeline(&pipeline, &app_config).is_ok());
+
+        pipeline.nodes[2] = webhook("webhook-2", "orders");
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Webhooks sharing a path should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "Webhook nodes node-0 and webhook-2 both serve the path /chain/orders"
+        );
+
+        // Paths differing only in leading or trailing slashes are the same route
+        pipeline.nodes[2] = webhook("webhook-2", "/orders/");
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Webhooks sharing a normalized path should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "Webhook nodes node-0 and webhook-2 both serve the path /chain/orders"
+        );
+    }
+
+    #[test]
+    fn test_in_http_metadata_is_passed_through_metadata_interface() {
+        use shared::{InHttpWebhookSettings, RequestMetadata};
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut pipeline = chain_pipeline(2);
+        let link_interfaces = |pipeline: &Pipeline| {
+            let actual_wadm = convert_pipeline(pipeline, &"default".to_string(), &app_config)
+                .expect("Failed to convert pipeline");
+            let webhook = actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == "node-0")
+                .expect("Should have webhook component");
+            match &webhook.traits[1].properties {
+                TraitProperties::Link(link) => link.interfaces.clone(),
+                _ => panic!("Webhook should link to its out-internal component"),
+            }
+        };
+        // The request id is forwarded even without metadata settings
+        assert_eq!(link_interfaces(&pipeline), ["out", "metadata"]);
+
+        pipeline.nodes[0].settings =
+            Some(PipelineNodeSettings::InHttpWebhook(InHttpWebhookSettings {
+                method: "POST".to_string(),
+                path: "orders".to_string(),
+                content_type: None,
+                request_body_json_schema: None,
+                event_filter: None,
+                metadata: Some(RequestMetadata {
+                    include_method: Some(true),
+                    headers: Some(vec!["X-Tenant".to_string()]),
+                }),
+                reply_timeout_ms: None,
+            }));
+        assert_eq!(link_interfaces(&pipeline), ["out", "metadata"]);
+    }
+
+    #[test]
+    fn test_webhook_reply_timeout_puts_out_internal_in_request_mode() {
+        use shared::{InHttpWebhookSettings, REQUEST_TIMEOUT_CONFIG_KEY};
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let mut pipeline = chain_pipeline(3);
+        let convert = |pipeline: &Pipeline| {
+            convert_pipeline(pipeline, &"default".to_string(), &app_config)
+                .expect("Failed to convert pipeline")
+        };
+        let request_timeouts = |wadm: &WadmApplication| -> Vec<Option<serde_yaml::Value>> {
+            ["out-internal-for-node-0", "out-internal-for-node-1"]
+                .iter()
+                .map(|name| {
+                    let component = wadm
+                        .spec
+                        .components
+                        .iter()
+                        .find(|c| c.name == *name)
+                        .unwrap_or_else(|| panic!("Should have {name} component"));
+                    match &component.properties {
+                        Properties::WithImage { config, .. } => config
+                            .iter()
+                            .flatten()
+                            .find_map(|c| c.properties.get(REQUEST_TIMEOUT_CONFIG_KEY).cloned()),
+                        _ => panic!("{name} should reference an image"),
+                    }
+                })
+                .collect()
+        };
+        let replies = |wadm: &WadmApplication| {
+            wadm.spec
+                .components
+                .iter()
+                .find(|c| c.name == "in-internal-for-node-1")
+                .expect("Should have in-internal component")
+                .traits
+                .iter()
+                .any(|t| matches!(&t.properties, TraitProperties::Link(link) if link.package == "messaging"))
+        };
+
+        let wadm = convert(&pipeline);
+        assert_eq!(request_timeouts(&wadm), [None, None]);
+        assert!(!replies(&wadm));
+
+        pipeline.nodes[0].settings =
+            Some(PipelineNodeSettings::InHttpWebhook(InHttpWebhookSettings {
+                method: "POST".to_string(),
+                path: "orders".to_string(),
+                content_type: None,
+                request_body_json_schema: None,
+                event_filter: None,
+                metadata: None,
+                reply_timeout_ms: Some(5000),
+            }));
+        let wadm = convert(&pipeline);
+        // Every hop downstream of the webhook waits for the reply of the next one
+        let timeout = Some(serde_yaml::Value::String("5000".to_string()));
+        assert_eq!(request_timeouts(&wadm), [timeout.clone(), timeout]);
+        assert!(replies(&wadm));
+    }
+
+    #[test]
+    fn test_out_http_webhook_malformed_url_is_rejected() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        let pipeline = webhook_pipeline("POST", "https://example.com/hook");
+        assert!(convert_pipeline(&pipeline, &"default".to_string(), &app_config).is_ok());
+
+        let pipeline = webhook_pipeline("POST", "example.com/hook");
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Malformed webhook URL should be rejected");
+        assert!(
+            err.to_string()
+                .starts_with("Invalid settings for node out-http-webhook_2: url:")
+        );
+    }
+
+    #[test]
+    fn test_out_http_webhook_invalid_method_is_rejected() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        let pipeline = webhook_pipeline("FETCH", "https://example.com/hook");
+        let err = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect_err("Unsupported webhook method should be rejected");
+        assert!(
+            err.to_string()
+                .starts_with("Invalid settings for node out-http-webhook_2: method:")
+        );
+    }
+
+    const RESILIENT_PIPELINE_YAML: &str = r#"
+name: resilient
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /hook
+  - id: processor
+    label: processor
+    type: processor-wasm
+    position:
+      x: 200
+      'y': 100
+    settings:
+      type: processor-wasm
+      settings:
+        source: enrich.wasm
+        instances: 1
+    resilience:
+      retries: 2
+    depends_on:
+      - webhook
+  - id: notify
+    label: notify
+    type: out-http-webhook
+    position:
+      x: 300
+      'y': 100
+    settings:
+      type: out-http-webhook
+      settings:
+        method: POST
+        url: https://example.com/hook
+    resilience:
+      retries: 3
+      timeoutMs: 5000
+      backoff:
+        initialMs: 100
+        maxMs: 1000
+    depends_on:
+      - processor
+"#;
+
+    #[test]
+    fn test_resilience_round_trips() {
+        use shared::{Backoff, Resilience};
+
+        let pipeline: Pipeline =
+            serde_yaml::from_str(RESILIENT_PIPELINE_YAML).expect("Failed to parse input YAML");
+        let expected = Resilience {
+            retries: Some(3),
+            timeout_ms: Some(5000),
+            backoff: Some(Backoff {
+                initial_ms: 100,
+                max_ms: Some(1000),
+            }),
+        };
+        assert_eq!(pipeline.nodes[2].resilience, Some(expected));
+        assert_eq!(pipeline.nodes[0].resilience, None);
+
+        let json = serde_json::to_string(&pipeline).expect("Failed to serialize pipeline");
+        let reparsed: Pipeline = serde_json::from_str(&json).expect("Failed to parse pipeline");
+        for (node, reparsed_node) in pipeline.nodes.iter().zip(&reparsed.nodes) {
+            assert_eq!(node.resilience, reparsed_node.resilience);
+        }
+        assert!(!json.contains(r#""resilience":null"#));
+    }
+
+    #[test]
+    fn test_resilience_is_emitted_into_component_configs() {
+        let pipeline: Pipeline =
+            serde_yaml::from_str(RESILIENT_PIPELINE_YAML).expect("Failed to parse input YAML");
+        let app_config = AppConfig::new().expect("Could not read app config");
+
+        let actual_wadm = convert_pipeline(&pipeline, &"default".to_string(), &app_config)
+            .expect("Failed to convert pipeline");
+        let configs = |name: &str| -> Vec<(String, Option<serde_yaml::Value>)> {
+            let component = actual_wadm
+                .spec
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("Should have {name} component"));
+            match &component.properties {
+                Properties::WithImage { config, .. } => config
+                    .iter()
+                    .flatten()
+                    .map(|c| (c.name.clone(), c.properties.get("resilience").cloned()))
+                    .collect(),
+                _ => panic!("{name} should reference an image"),
+            }
+        };
+        let resilience = |json: &str| Some(serde_yaml::Value::String(json.to_string()));
+
+        // Redelivery caps for the in-internal components feeding each node
+        assert_eq!(
+            configs("in-internal-for-processor"),
+            vec![
+                ("processor-processor-input-v1".to_string(), None),
+                (
+                    "processor-resilience-v1".to_string(),
+                    resilience(r#"{"retries":2}"#)
+                )
+            ]
+        );
+        let notify_resilience = resilience(
+            r#"{"retries":3,"timeoutMs":5000,"backoff":{"initialMs":100,"maxMs":1000}}"#,
+        );
+        // The webhook retries its own requests, so its in-internal doesn't redeliver on top of them
+        assert!(configs("in-internal-for-notify").is_empty());
+
+        // Retries and timeouts of the webhook requests, next to its settings
+        assert_eq!(
+            configs("notify"),
+            vec![
+                ("notify-config-v1".to_string(), None),
+                ("notify-resilience-v1".to_string(), notify_resilience),
+            ]
+        );
+
+        // Nodes without resilience settings get no resilience config
+        assert!(configs("webhook").iter().all(|(_, r)| r.is_none()));
+    }
 }
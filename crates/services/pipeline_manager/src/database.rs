@@ -1,7 +1,86 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::Pipeline;
 use sqlx::PgPool;
 use tracing::{error, info};
 
+/// Deployments returned by `list_deployments` when no limit is requested
+pub const DEFAULT_DEPLOYMENTS_LIMIT: u32 = 100;
+
+/// Most deployments `list_deployments` returns at once
+pub const MAX_DEPLOYMENTS_LIMIT: u32 = 1_000;
+
+/// Outcome of a deploy request, as recorded in the `deployments` audit table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeployAuditStatus {
+    Deployed,
+    /// The request was refused, e.g. redeploying a changed pipeline under the same version
+    Rejected,
+    Failed,
+    /// The application was deployed, but wasn't running yet when the request stopped waiting
+    Pending,
+}
+
+impl DeployAuditStatus {
+    /// Status of a deploy request from the HTTP status code it was answered with
+    pub fn from_status_code(status_code: u16) -> Self {
+        match status_code {
+            200..300 => Self::Deployed,
+            504 => Self::Pending,
+            400..500 => Self::Rejected,
+            _ => Self::Failed,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Deployed => "deployed",
+            Self::Rejected => "rejected",
+            Self::Failed => "failed",
+            Self::Pending => "pending",
+        }
+    }
+}
+
+/// Audit record of a deploy request
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DeploymentRecord {
+    pub workspace: String,
+    pub pipeline: String,
+    pub version: String,
+    #[serde(rename = "contentHash")]
+    pub content_hash: String,
+    pub status: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+type DeploymentRow = (String, String, String, String, String, DateTime<Utc>);
+
+impl From<DeploymentRow> for DeploymentRecord {
+    fn from(
+        (workspace, pipeline, version, content_hash, status, timestamp): DeploymentRow,
+    ) -> Self {
+        Self {
+            workspace,
+            pipeline,
+            version,
+            content_hash,
+            status,
+            timestamp,
+        }
+    }
+}
+
+/// Number of deployments to return for a requested limit, capped at `MAX_DEPLOYMENTS_LIMIT`
+pub fn deployments_limit(limit: Option<u32>) -> i64 {
+    i64::from(
+        limit
+            .unwrap_or(DEFAULT_DEPLOYMENTS_LIMIT)
+            .min(MAX_DEPLOYMENTS_LIMIT),
+    )
+}
+
 pub async fn get_workspace_nats_account(
     pool: &PgPool,
     workspace_slug: &str,
@@ -64,3 +143,338 @@ pub async fn test_connection(pool: &PgPool) -> Result<(), sqlx::Error> {
         }
     }
 }
+
+pub async fn setup_pipeline_deployments_table(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let query = r#"
+        CREATE TABLE IF NOT EXISTS pipeline_deployments (
+            workspace_slug TEXT NOT NULL,
+            pipeline_name TEXT NOT NULL,
+            pipeline_version TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            deployed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (workspace_slug, pipeline_name, pipeline_version)
+        )
+    "#;
+
+    sqlx::query(query).execute(pool).await?;
+
+    // Added after the table, so tables created before it are migrated
+    let query = r#"
+        ALTER TABLE pipeline_deployments ADD COLUMN IF NOT EXISTS application_hash TEXT
+    "#;
+    sqlx::query(query).execute(pool).await?;
+    let query = r#"
+        ALTER TABLE pipeline_deployments ADD COLUMN IF NOT EXISTS poll_subjects TEXT[] NOT NULL DEFAULT '{}'
+    "#;
+    sqlx::query(query).execute(pool).await?;
+    let query = r#"
+        ALTER TABLE pipeline_deployments ADD COLUMN IF NOT EXISTS pipeline TEXT
+    "#;
+    sqlx::query(query).execute(pool).await?;
+    info!("Pipeline deployments table is ready");
+    Ok(())
+}
+
+/// Set up the append-only audit table recording every deploy request
+pub async fn setup_deployments_table(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let query = r#"
+        CREATE TABLE IF NOT EXISTS deployments (
+            id BIGSERIAL PRIMARY KEY,
+            workspace_slug TEXT NOT NULL,
+            pipeline_name TEXT NOT NULL,
+            pipeline_version TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )
+    "#;
+
+    sqlx::query(query).execute(pool).await?;
+    info!("Deployments audit table is ready");
+    Ok(())
+}
+
+pub async fn get_deployed_pipeline_hash(
+    pool: &PgPool,
+    workspace_slug: &str,
+    pipeline_name: &str,
+    pipeline_version: &str,
+) -> Result<Option<String>> {
+    let query = r#"
+        SELECT content_hash
+        FROM pipeline_deployments
+        WHERE workspace_slug = $1 AND pipeline_name = $2 AND pipeline_version = $3
+    "#;
+
+    sqlx::query_scalar::<_, String>(query)
+        .bind(workspace_slug)
+        .bind(pipeline_name)
+        .bind(pipeline_version)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Database error while fetching deployed hash of pipeline '{}' version {}: {}",
+                pipeline_name, pipeline_version, e
+            );
+            anyhow::anyhow!("Database error: {}", e)
+        })
+}
+
+/// Hash of the wadm application last deployed for a pipeline, whichever version it was. Unset when
+/// the pipeline was never deployed or the deploy was recorded before the hash was tracked.
+pub async fn get_latest_application_hash(
+    pool: &PgPool,
+    workspace_slug: &str,
+    pipeline_name: &str,
+) -> Result<Option<String>> {
+    let query = r#"
+        SELECT application_hash
+        FROM pipeline_deployments
+        WHERE workspace_slug = $1 AND pipeline_name = $2
+        ORDER BY deployed_at DESC
+        LIMIT 1
+    "#;
+
+    sqlx::query_as::<_, (Option<String>,)>(query)
+        .bind(workspace_slug)
+        .bind(pipeline_name)
+        .fetch_optional(pool)
+        .await
+        .map(|row| row.and_then(|(application_hash,)| application_hash))
+        .map_err(|e| {
+            error!(
+                "Database error while fetching the latest deployment of pipeline '{}': {}",
+                pipeline_name, e
+            );
+            anyhow::anyhow!("Database error: {}", e)
+        })
+}
+
+/// Latest deployed version of a pipeline. Unset when the pipeline was never deployed or the deploy
+/// was recorded before pipelines were stored.
+pub async fn get_deployed_pipeline(
+    pool: &PgPool,
+    workspace_slug: &str,
+    pipeline_name: &str,
+) -> Result<Option<Pipeline>> {
+    let query = r#"
+        SELECT pipeline
+        FROM pipeline_deployments
+        WHERE workspace_slug = $1 AND pipeline_name = $2
+        ORDER BY deployed_at DESC
+        LIMIT 1
+    "#;
+
+    let pipeline = sqlx::query_as::<_, (Option<String>,)>(query)
+        .bind(workspace_slug)
+        .bind(pipeline_name)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Database error while fetching the deployed pipeline '{}': {}",
+                pipeline_name, e
+            );
+            anyhow::anyhow!("Database error: {}", e)
+        })?
+        .and_then(|(pipeline,)| pipeline);
+    pipeline
+        .map(|pipeline| serde_json::from_str(&pipeline))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Stored pipeline '{}' is invalid: {}", pipeline_name, e))
+}
+
+/// Poll subjects of the polling sources of the latest deployed version of every pipeline
+pub async fn list_poll_subjects(pool: &PgPool) -> Result<Vec<String>> {
+    let query = r#"
+        SELECT DISTINCT ON (workspace_slug, pipeline_name) poll_subjects
+        FROM pipeline_deployments
+        ORDER BY workspace_slug, pipeline_name, deployed_at DESC
+    "#;
+
+    sqlx::query_scalar::<_, Vec<String>>(query)
+        .fetch_all(pool)
+        .await
+        .map(|subjects| subjects.into_iter().flatten().collect())
+        .map_err(|e| {
+            error!("Database error while listing poll subjects: {}", e);
+            anyhow::anyhow!("Database error: {}", e)
+        })
+}
+
+/// Latest deployed version of each pipeline of a workspace, as `(name, version)` ordered by name
+pub async fn list_pipeline_deployments(
+    pool: &PgPool,
+    workspace_slug: &str,
+) -> Result<Vec<(String, String)>> {
+    let query = r#"
+        SELECT DISTINCT ON (pipeline_name) pipeline_name, pipeline_version
+        FROM pipeline_deployments
+        WHERE workspace_slug = $1
+        ORDER BY pipeline_name, deployed_at DESC
+    "#;
+
+    sqlx::query_as::<_, (String, String)>(query)
+        .bind(workspace_slug)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Database error while listing pipelines of workspace '{}': {}",
+                workspace_slug, e
+            );
+            anyhow::anyhow!("Database error: {}", e)
+        })
+}
+
+pub async fn record_pipeline_deployment(
+    pool: &PgPool,
+    workspace_slug: &str,
+    pipeline: &Pipeline,
+    content_hash: &str,
+    application_hash: &str,
+    poll_subjects: &[String],
+) -> Result<()> {
+    let query = r#"
+        INSERT INTO pipeline_deployments (workspace_slug, pipeline_name, pipeline_version, content_hash, application_hash, poll_subjects, pipeline)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (workspace_slug, pipeline_name, pipeline_version)
+        DO UPDATE SET content_hash = EXCLUDED.content_hash, application_hash = EXCLUDED.application_hash, poll_subjects = EXCLUDED.poll_subjects, pipeline = EXCLUDED.pipeline, deployed_at = now()
+    "#;
+    let pipeline_json = serde_json::to_string(pipeline)?;
+
+    sqlx::query(query)
+        .bind(workspace_slug)
+        .bind(&pipeline.name)
+        .bind(&pipeline.version)
+        .bind(content_hash)
+        .bind(application_hash)
+        .bind(poll_subjects)
+        .bind(pipeline_json)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    info!(
+        "Recorded deployment of pipeline '{}' version {} in workspace '{}'",
+        pipeline.name, pipeline.version, workspace_slug
+    );
+    Ok(())
+}
+
+pub async fn record_deployment_audit(
+    pool: &PgPool,
+    workspace_slug: &str,
+    pipeline_name: &str,
+    pipeline_version: &str,
+    content_hash: &str,
+    status: DeployAuditStatus,
+) -> Result<()> {
+    let query = r#"
+        INSERT INTO deployments (workspace_slug, pipeline_name, pipeline_version, content_hash, status)
+        VALUES ($1, $2, $3, $4, $5)
+    "#;
+
+    sqlx::query(query)
+        .bind(workspace_slug)
+        .bind(pipeline_name)
+        .bind(pipeline_version)
+        .bind(content_hash)
+        .bind(status.as_str())
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Deploy requests of a workspace, optionally of a single pipeline, newest first
+pub async fn list_deployments(
+    pool: &PgPool,
+    workspace_slug: &str,
+    pipeline_name: Option<&str>,
+    limit: Option<u32>,
+) -> Result<Vec<DeploymentRecord>> {
+    let query = r#"
+        SELECT workspace_slug, pipeline_name, pipeline_version, content_hash, status, created_at
+        FROM deployments
+        WHERE workspace_slug = $1 AND ($2::TEXT IS NULL OR pipeline_name = $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+    "#;
+
+    let rows = sqlx::query_as::<_, DeploymentRow>(query)
+        .bind(workspace_slug)
+        .bind(pipeline_name)
+        .bind(deployments_limit(limit))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Database error while listing deployments of workspace '{}': {}",
+                workspace_slug, e
+            );
+            anyhow::anyhow!("Database error: {}", e)
+        })?;
+    Ok(rows.into_iter().map(DeploymentRecord::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deployment_status_from_status_code() {
+        assert_eq!(
+            DeployAuditStatus::from_status_code(200),
+            DeployAuditStatus::Deployed
+        );
+        assert_eq!(
+            DeployAuditStatus::from_status_code(409),
+            DeployAuditStatus::Rejected
+        );
+        assert_eq!(
+            DeployAuditStatus::from_status_code(500),
+            DeployAuditStatus::Failed
+        );
+        assert_eq!(
+            DeployAuditStatus::from_status_code(504),
+            DeployAuditStatus::Pending
+        );
+        assert_eq!(DeployAuditStatus::Rejected.as_str(), "rejected");
+    }
+
+    #[test]
+    fn test_deployments_limit() {
+        assert_eq!(deployments_limit(None), 100);
+        assert_eq!(deployments_limit(Some(5)), 5);
+        assert_eq!(deployments_limit(Some(50_000)), 1_000);
+    }
+
+    #[test]
+    fn test_deployment_row_round_trip() {
+        let timestamp = DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let record = DeploymentRecord::from((
+            "acme".to_string(),
+            "orders".to_string(),
+            "2".to_string(),
+            "abc123".to_string(),
+            DeployAuditStatus::Deployed.as_str().to_string(),
+            timestamp,
+        ));
+
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            serde_json::json!({
+                "workspace": "acme",
+                "pipeline": "orders",
+                "version": "2",
+                "contentHash": "abc123",
+                "status": "deployed",
+                "timestamp": "2025-06-01T12:00:00Z"
+            })
+        );
+    }
+}
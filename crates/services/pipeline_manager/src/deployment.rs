@@ -0,0 +1,240 @@
+use crate::builders::WadmApplication;
+use axum::http::StatusCode;
+use sha2::{Digest, Sha256};
+use shared::Pipeline;
+
+/// Failure of a stage of deploying a pipeline
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeployError {
+    /// The pipeline can't be deployed as sent
+    Validation(String),
+    /// A valid pipeline couldn't be turned into a wadm manifest
+    Conversion(String),
+    /// Publishing the pipeline's components to the registry failed
+    Registry(String),
+}
+
+impl DeployError {
+    /// HTTP status a deploy request failing with this error is answered with
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Validation(_) => StatusCode::BAD_REQUEST,
+            Self::Conversion(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Registry(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+impl std::fmt::Display for DeployError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Validation(message) | Self::Conversion(message) | Self::Registry(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeployError {}
+
+/// Hash of everything in a pipeline that affects its deployment.
+///
+/// Nodes are hashed in id order and without their editor position, so moving nodes around in
+/// the UI doesn't count as a change.
+pub fn pipeline_content_hash(pipeline: &Pipeline) -> Result<String, serde_json::Error> {
+    let mut value = serde_json::to_value(pipeline)?;
+
+    if let Some(nodes) = value.get_mut("nodes").and_then(|n| n.as_array_mut()) {
+        for node in nodes.iter_mut() {
+            if let Some(node) = node.as_object_mut() {
+                node.remove("position");
+            }
+        }
+        nodes.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+    }
+
+    let bytes = serde_json::to_vec(&value)?;
+    Ok(hex::encode(Sha256::digest(bytes)))
+}
+
+/// Result of a deploy skipped because the application is already deployed as is
+pub const NO_CHANGES_RESULT: &str = "no changes";
+
+/// Hash of a converted wadm application. Components are sorted when converting, so equivalent
+/// pipelines convert to the same application and hash. Unlike the pipeline hash it changes with
+/// the app config, e.g. the NATS servers, as those are part of the application.
+pub fn application_hash(application: &WadmApplication) -> Result<String, serde_json::Error> {
+    let bytes = serde_json::to_vec(application)?;
+    Ok(hex::encode(Sha256::digest(bytes)))
+}
+
+/// Reject deploying a pipeline whose content differs from what was last deployed under the same
+/// version. Config names embed the version, so wadm would otherwise keep serving stale config.
+pub fn check_redeploy(
+    pipeline: &Pipeline,
+    deployed_hash: Option<&str>,
+    hash: &str,
+) -> Result<(), String> {
+    match deployed_hash {
+        Some(deployed_hash) if deployed_hash != hash => Err(format!(
+            "Pipeline '{}' version {} was already deployed with different content. Bump the pipeline version to deploy the changes.",
+            pipeline.name, pipeline.version
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Longest workspace slug or pipeline name accepted for deployment
+pub const MAX_NAME_LEN: usize = 63;
+
+/// Check a name matches `^[a-z0-9][a-z0-9-]*$` and is at most [`MAX_NAME_LEN`] long, so it can be
+/// embedded in wadm application names and NATS subjects as is.
+fn check_name(kind: &str, name: &str) -> Result<(), String> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !name.starts_with('-');
+    if name.is_empty() || !valid {
+        return Err(format!(
+            "Invalid {kind} '{name}': must be lowercase letters, digits and dashes, not starting with a dash"
+        ));
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Invalid {kind} '{name}': must be at most {MAX_NAME_LEN} characters"
+        ));
+    }
+    Ok(())
+}
+
+/// Reject a deploy whose workspace slug or pipeline name can't safely be used in wadm names and
+/// NATS subjects.
+pub fn check_deploy_names(workspace_slug: &str, pipeline_name: &str) -> Result<(), String> {
+    check_name("workspace slug", workspace_slug)?;
+    check_name("pipeline name", pipeline_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::config_converter::convert_pipeline;
+
+    const PIPELINE: &str = r#"
+name: mine
+version: 1
+nodes:
+  - id: webhook
+    label: webhook
+    type: in-http-webhook
+    position:
+      x: 100
+      'y': 100
+    settings:
+      type: in-http-webhook
+      settings:
+        method: POST
+        path: /hook
+  - id: log
+    label: log
+    type: out-log
+    position:
+      x: 200
+      'y': 100
+    depends_on:
+      - webhook
+"#;
+
+    fn pipeline() -> Pipeline {
+        serde_yaml::from_str(PIPELINE).expect("Failed to parse pipeline")
+    }
+
+    #[test]
+    fn test_hash_ignores_node_order_and_position() {
+        let hash = pipeline_content_hash(&pipeline()).unwrap();
+
+        let mut moved = pipeline();
+        moved.nodes.reverse();
+        moved.nodes[0].position.x = 500.0;
+
+        assert_eq!(pipeline_content_hash(&moved).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_hash_changes_with_content() {
+        let hash = pipeline_content_hash(&pipeline()).unwrap();
+
+        let mut changed = pipeline();
+        changed.nodes[1].instances = Some(2);
+
+        assert_ne!(pipeline_content_hash(&changed).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_application_hash_is_stable_across_equivalent_pipelines() {
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let convert = |pipeline: &Pipeline| {
+            let application = convert_pipeline(pipeline, &"acme".to_string(), &app_config)
+                .expect("Failed to convert pipeline");
+            application_hash(&application).unwrap()
+        };
+        let hash = convert(&pipeline());
+
+        let mut moved = pipeline();
+        moved.nodes.reverse();
+        moved.nodes[0].position.x = 500.0;
+        assert_eq!(convert(&moved), hash);
+
+        let mut changed = pipeline();
+        changed.nodes[1].instances = Some(2);
+        assert_ne!(convert(&changed), hash);
+    }
+
+    #[test]
+    fn test_deploy_error_status_codes() {
+        let message = || "failed".to_string();
+        assert_eq!(
+            DeployError::Validation(message()).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            DeployError::Conversion(message()).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            DeployError::Registry(message()).status_code(),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(DeployError::Registry(message()).to_string(), "failed");
+    }
+
+    #[test]
+    fn test_check_redeploy() {
+        let pipeline = pipeline();
+        let hash = pipeline_content_hash(&pipeline).unwrap();
+
+        // First deploy of a version and redeploying identical content are fine
+        assert!(check_redeploy(&pipeline, None, &hash).is_ok());
+        assert!(check_redeploy(&pipeline, Some(&hash), &hash).is_ok());
+
+        let err = check_redeploy(&pipeline, Some("other-hash"), &hash).unwrap_err();
+        assert!(err.contains("Bump the pipeline version"));
+    }
+
+    #[test]
+    fn test_check_deploy_names() {
+        assert!(check_deploy_names("acme-corp", "orders-2").is_ok());
+        assert!(check_deploy_names("acme", &"a".repeat(MAX_NAME_LEN)).is_ok());
+
+        let err = check_deploy_names("Acme", "orders").unwrap_err();
+        assert!(err.contains("workspace slug 'Acme'"), "{err}");
+
+        let err = check_deploy_names("acme", "orders.v2").unwrap_err();
+        assert!(err.contains("pipeline name 'orders.v2'"), "{err}");
+
+        for name in ["", "-orders", "orders>", "orders *"] {
+            assert!(check_deploy_names("acme", name).is_err(), "{name:?}");
+        }
+        assert!(check_deploy_names("acme", &"a".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
@@ -2,3 +2,7 @@ pub mod builders;
 pub mod config;
 pub mod config_converter;
 pub mod database;
+pub mod deployment;
+pub mod node_types;
+pub mod rate_limit;
+pub mod request_body;
@@ -1,28 +1,39 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc, time::Instant};
 
 use axum::{
     Json, Router,
-    extract::State,
-    http::StatusCode,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, FromRequest, Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use shared::Pipeline;
 use tokio::net::TcpListener;
 
-use crate::config::AppConfig;
+use crate::{config::AppConfig, rate_limit::RateLimiter};
 
 mod builders;
 mod config;
 mod config_converter;
 mod database;
+mod deployment;
+mod nats;
+mod node_types;
+mod poll_scheduler;
+mod rate_limit;
 mod registry;
+mod replay;
+mod request_body;
 mod wadm;
 
 #[derive(Clone)]
 struct AppState {
     app_config: AppConfig,
     db_pool: sqlx::PgPool,
+    deploy_rate_limiter: Arc<RateLimiter>,
 }
 
 #[tokio::main]
@@ -41,15 +52,56 @@ async fn main() {
         panic!("Failed to establish database connection");
     }
 
+    if let Err(e) = database::setup_pipeline_deployments_table(&db_pool).await {
+        tracing::error!("Failed to set up pipeline deployments table: {}", e);
+        panic!("Failed to set up pipeline deployments table");
+    }
+
+    if let Err(e) = database::setup_deployments_table(&db_pool).await {
+        tracing::error!("Failed to set up deployments audit table: {}", e);
+        panic!("Failed to set up deployments audit table");
+    }
+
+    let deploy_rate_limiter = Arc::new(RateLimiter::new(
+        app_config.limits.deploys_per_minute,
+        app_config.limits.deploy_burst,
+    ));
+    tokio::spawn(poll_scheduler::run(app_config.clone(), db_pool.clone()));
+
+    // Dead letters are only kept for replay once the stream exists
+    if app_config.nats.replay.is_some()
+        && let Err(e) = replay::ensure_dead_letter_stream(&app_config.nats).await
+    {
+        tracing::error!("Failed to set up the dead-letter stream: {:#}", e);
+    }
+
     let state = AppState {
         app_config,
         db_pool,
+        deploy_rate_limiter,
     };
 
-    let app = Router::new()
+    let deploy_routes = Router::new()
         .route("/deploy", post(deploy_pipeline))
         .route("/deploy-providers", post(deploy_providers))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit_deploys,
+        ))
+        .layer(DefaultBodyLimit::max(
+            state.app_config.limits.max_body_bytes,
+        ));
+
+    let app = Router::new()
+        .merge(deploy_routes)
         .route("/health", get(health))
+        .route("/status", get(pipeline_status))
+        .route("/pipelines", get(list_pipelines))
+        .route("/deployments", get(list_deployments))
+        .route("/replay", post(replay_dead_letter))
+        .route("/validate", post(validate_pipeline))
+        .route("/subjects", post(preview_subjects))
+        .route("/node-types", get(node_types))
         .with_state(state);
 
     let port: u16 = std::env::var("PORT")
@@ -72,25 +124,447 @@ async fn health() -> (StatusCode, Json<serde_json::Value>) {
     )
 }
 
+async fn node_types() -> (StatusCode, Json<Vec<node_types::NodeTypeDescription>>) {
+    (StatusCode::OK, Json(node_types::describe_node_types()))
+}
+
+/// Rate limit deploy requests per workspace. Requests without a readable `workspaceSlug` are
+/// passed through for the handler to reject.
+async fn rate_limit_deploys(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, app_state.app_config.limits.max_body_bytes).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(DeployResponse {
+                    result: format!("Failed to read request body: {e}"),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    if let Ok(workspace) = request_body::parse_body::<DeployProvidersRequest>(content_type, &bytes)
+        && let Err(wait) = app_state
+            .deploy_rate_limiter
+            .check(&workspace.workspace_slug, Instant::now())
+    {
+        let retry_after = rate_limit::retry_after_seconds(wait);
+        tracing::warn!(
+            "Rate limited deploy request for workspace '{}', retry after {}s",
+            workspace.workspace_slug,
+            retry_after
+        );
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(DeployResponse {
+                result: format!("Too many deploy requests. Retry after {retry_after} seconds."),
+            }),
+        )
+            .into_response();
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
 async fn deploy_pipeline(
     State(app_state): State<AppState>,
-    Json(payload): Json<DeployRequest>,
+    JsonOrYaml(payload): JsonOrYaml<DeployRequest>,
 ) -> (StatusCode, Json<DeployResponse>) {
     tracing::info!("Received deploy request: {:?}", payload);
 
-    if let Err(e) = crate::registry::publish_wasm_components(&payload, &app_state.app_config).await
+    if let Err(e) = deployment::check_deploy_names(&payload.workspace_slug, &payload.pipeline.name)
+    {
+        tracing::warn!("Rejected deploy request: {}", e);
+        return (StatusCode::BAD_REQUEST, Json(DeployResponse { result: e }));
+    }
+
+    // Reject oversized or invalid pipelines before doing any work for them
+    if let Err(e) = config_converter::validate_pipeline(&payload.pipeline, &app_state.app_config) {
+        tracing::warn!("Rejected deploy request: {}", e);
+        return (
+            e.status_code(),
+            Json(DeployResponse {
+                result: e.to_string(),
+            }),
+        );
+    }
+
+    let content_hash = match deployment::pipeline_content_hash(&payload.pipeline) {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Failed to hash pipeline: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Failed to hash pipeline: {e}"),
+                }),
+            );
+        }
+    };
+
+    let response = deploy_hashed_pipeline(&app_state, &payload, &content_hash).await;
+
+    if let Err(e) = database::record_deployment_audit(
+        &app_state.db_pool,
+        &payload.workspace_slug,
+        &payload.pipeline.name,
+        &payload.pipeline.version,
+        &content_hash,
+        database::DeployAuditStatus::from_status_code(response.0.as_u16()),
+    )
+    .await
+    {
+        tracing::error!("Failed to record deployment audit: {}", e);
+    }
+
+    response
+}
+
+/// Deploy a valid pipeline with the given content hash
+async fn deploy_hashed_pipeline(
+    app_state: &AppState,
+    payload: &DeployRequest,
+    content_hash: &str,
+) -> (StatusCode, Json<DeployResponse>) {
+    let deployed = match database::get_deployed_pipeline_hash(
+        &app_state.db_pool,
+        &payload.workspace_slug,
+        &payload.pipeline.name,
+        &payload.pipeline.version,
+    )
+    .await
+    {
+        Ok(deployed) => deployed,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Failed to look up previous deployment: {e}"),
+                }),
+            );
+        }
+    };
+
+    if let Err(e) = deployment::check_redeploy(&payload.pipeline, deployed.as_deref(), content_hash)
     {
+        tracing::warn!("Rejected deploy request: {}", e);
+        return (StatusCode::CONFLICT, Json(DeployResponse { result: e }));
+    }
+
+    let application = match config_converter::convert_pipeline(
+        &payload.pipeline,
+        &payload.workspace_slug,
+        &app_state.app_config,
+    ) {
+        Ok(application) => application,
+        Err(e) => {
+            tracing::error!("Failed to convert pipeline: {}", e);
+            return (
+                e.status_code(),
+                Json(DeployResponse {
+                    result: format!("Error converting pipeline: {e}"),
+                }),
+            );
+        }
+    };
+    let application_hash = match deployment::application_hash(&application) {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Failed to hash wadm application: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Failed to hash wadm application: {e}"),
+                }),
+            );
+        }
+    };
+
+    if let Err(e) = crate::registry::publish_wasm_components(payload, &app_state.app_config).await {
         tracing::error!("Failed to publish WASM components: {}", e);
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            e.status_code(),
             Json(DeployResponse {
                 result: format!("Failed to publish WASM components: {e}"),
             }),
         );
     }
 
-    crate::wadm::deploy_pipeline_to_wasm_cloud(&payload, &app_state.app_config, &app_state.db_pool)
+    // Skip the wadm deploy only when the application running now is the one just converted: the
+    // last deploy of the pipeline, whichever version it was, and it wasn't undeployed since
+    let latest_application_hash = match database::get_latest_application_hash(
+        &app_state.db_pool,
+        &payload.workspace_slug,
+        &payload.pipeline.name,
+    )
+    .await
+    {
+        Ok(hash) => hash,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Failed to look up previous deployment: {e}"),
+                }),
+            );
+        }
+    };
+    if latest_application_hash.as_deref() == Some(application_hash.as_str())
+        && crate::wadm::is_pipeline_deployed(
+            &payload.workspace_slug,
+            &payload.pipeline.name,
+            &app_state.app_config,
+            &app_state.db_pool,
+        )
+        .await
+    {
+        tracing::info!(
+            "Pipeline '{}' version {} is already deployed as is, skipping the wadm deploy",
+            payload.pipeline.name,
+            payload.pipeline.version
+        );
+        return (
+            StatusCode::OK,
+            Json(DeployResponse {
+                result: deployment::NO_CHANGES_RESULT.to_string(),
+            }),
+        );
+    }
+
+    let response = crate::wadm::deploy_pipeline_to_wasm_cloud(
+        payload,
+        &application,
+        &app_state.app_config,
+        &app_state.db_pool,
+    )
+    .await;
+
+    // A deploy that timed out waiting was still handed to wadm, which keeps deploying it
+    let deployed = matches!(response.0, StatusCode::OK | StatusCode::GATEWAY_TIMEOUT);
+    if deployed
+        && let Err(e) = database::record_pipeline_deployment(
+            &app_state.db_pool,
+            &payload.workspace_slug,
+            &payload.pipeline,
+            content_hash,
+            &application_hash,
+            &crate::builders::nodes::r#in::poll_subjects(
+                &payload.pipeline,
+                &payload.workspace_slug,
+            ),
+        )
         .await
+    {
+        tracing::error!("Failed to record pipeline deployment: {}", e);
+    }
+
+    response
+}
+
+async fn list_deployments(
+    State(app_state): State<AppState>,
+    Query(query): Query<DeploymentsQuery>,
+) -> Result<Json<Vec<database::DeploymentRecord>>, (StatusCode, Json<DeployResponse>)> {
+    database::list_deployments(
+        &app_state.db_pool,
+        &query.workspace_slug,
+        query.pipeline.as_deref(),
+        query.limit,
+    )
+    .await
+    .map(Json)
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(DeployResponse {
+                result: format!("Failed to list deployments: {e}"),
+            }),
+        )
+    })
+}
+
+/// Re-publish a message kept in the dead-letter stream to the node it was dead-lettered by
+async fn replay_dead_letter(
+    State(app_state): State<AppState>,
+    Json(payload): Json<ReplayRequest>,
+) -> (StatusCode, Json<DeployResponse>) {
+    let rejected = |status: StatusCode, result: String| {
+        tracing::warn!("Rejected replay request: {}", result);
+        (status, Json(DeployResponse { result }))
+    };
+
+    if let Err(e) = deployment::check_deploy_names(&payload.workspace_slug, &payload.pipeline) {
+        return rejected(StatusCode::BAD_REQUEST, e);
+    }
+    let Some(replay_user) = &app_state.app_config.nats.replay else {
+        return rejected(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Replaying is disabled, no replay user is configured".to_string(),
+        );
+    };
+
+    // Replay to the pipeline as deployed, not as the caller describes it
+    let pipeline = match database::get_deployed_pipeline(
+        &app_state.db_pool,
+        &payload.workspace_slug,
+        &payload.pipeline,
+    )
+    .await
+    {
+        Ok(Some(pipeline)) => pipeline,
+        Ok(None) => {
+            return rejected(
+                StatusCode::NOT_FOUND,
+                format!("Pipeline '{}' is not deployed", payload.pipeline),
+            );
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Failed to look up deployed pipeline: {e}"),
+                }),
+            );
+        }
+    };
+
+    let subject =
+        match config_converter::replay_target(&pipeline, &payload.workspace_slug, &payload.subject)
+        {
+            Ok(subject) => subject,
+            Err(e) => return rejected(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+
+    // Only messages that were dead-lettered on the subject can be replayed
+    let dead_letter =
+        match replay::dead_letter(&app_state.app_config.nats, &payload.subject, payload.id).await {
+            Ok(Some(dead_letter)) => dead_letter,
+            Ok(None) => {
+                return rejected(
+                    StatusCode::NOT_FOUND,
+                    format!("No dead letter {} on {}", payload.id, payload.subject),
+                );
+            }
+            Err(e) => {
+                tracing::error!("Failed to look up dead letter {}: {:#}", payload.id, e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(DeployResponse {
+                        result: format!("Failed to look up dead letter: {e:#}"),
+                    }),
+                );
+            }
+        };
+
+    match replay::publish(
+        &app_state.app_config.nats,
+        replay_user,
+        subject.clone(),
+        dead_letter.message,
+    )
+    .await
+    {
+        Ok(()) => {
+            tracing::info!(
+                "Replayed dead letter {} from {} to {}",
+                payload.id,
+                payload.subject,
+                subject
+            );
+            (
+                StatusCode::OK,
+                Json(DeployResponse {
+                    result: format!("Replayed message to {subject}"),
+                }),
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to replay message to {}: {:#}", subject, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Failed to replay message: {e:#}"),
+                }),
+            )
+        }
+    }
+}
+
+/// Problems of a pipeline being edited, without deploying it. Answered with 200 whether or not
+/// the pipeline has problems.
+async fn validate_pipeline(
+    State(app_state): State<AppState>,
+    JsonOrYaml(payload): JsonOrYaml<ValidateRequest>,
+) -> (StatusCode, Json<ValidateResponse>) {
+    let problems = config_converter::dry_validate(&payload.pipeline, &app_state.app_config);
+    let valid = problems
+        .iter()
+        .all(|problem| problem.severity != config_converter::Severity::Error);
+    (StatusCode::OK, Json(ValidateResponse { valid, problems }))
+}
+
+/// NATS subjects and httpserver routes a pipeline would use, without deploying it
+async fn preview_subjects(
+    State(app_state): State<AppState>,
+    JsonOrYaml(payload): JsonOrYaml<SubjectsRequest>,
+) -> Response {
+    if let Err(result) =
+        deployment::check_deploy_names(&payload.workspace_slug, &payload.pipeline.name)
+    {
+        return (StatusCode::BAD_REQUEST, Json(DeployResponse { result })).into_response();
+    }
+
+    match config_converter::preview_subjects(
+        &payload.pipeline,
+        &payload.workspace_slug,
+        &app_state.app_config,
+    ) {
+        Ok(subjects) => (StatusCode::OK, Json(subjects)).into_response(),
+        Err(e) => (
+            e.status_code(),
+            Json(DeployResponse {
+                result: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn pipeline_status(
+    State(app_state): State<AppState>,
+    Query(query): Query<StatusQuery>,
+) -> Result<Json<wadm::ApplicationStatus>, (StatusCode, Json<DeployResponse>)> {
+    crate::wadm::get_pipeline_status(
+        &query.workspace_slug,
+        &query.pipeline,
+        &app_state.app_config,
+        &app_state.db_pool,
+    )
+    .await
+}
+
+async fn list_pipelines(
+    State(app_state): State<AppState>,
+    Query(query): Query<WorkspaceQuery>,
+) -> Result<Json<Vec<wadm::PipelineSummary>>, (StatusCode, Json<DeployResponse>)> {
+    crate::wadm::list_pipelines(
+        &query.workspace_slug,
+        &app_state.app_config,
+        &app_state.db_pool,
+    )
+    .await
 }
 
 async fn deploy_providers(
@@ -107,11 +581,51 @@ async fn deploy_providers(
     .await
 }
 
+/// Request body parsed as YAML when sent with a YAML `Content-Type`, and as JSON otherwise.
+/// With `?strict=true`, fields the body doesn't have are rejected instead of ignored.
+struct JsonOrYaml<T>(T);
+
+impl<T: DeserializeOwned + Serialize, S: Send + Sync> FromRequest<S> for JsonOrYaml<T> {
+    type Rejection = (StatusCode, Json<DeployResponse>);
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let strict = request_body::is_strict(request.uri().query());
+        let content_type = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let bytes = Bytes::from_request(request, state).await.map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(DeployResponse {
+                    result: format!("Failed to read request body: {e}"),
+                }),
+            )
+        })?;
+
+        let body = if strict {
+            request_body::parse_body_strict(content_type.as_deref(), &bytes)
+        } else {
+            request_body::parse_body(content_type.as_deref(), &bytes)
+        };
+        body.map(JsonOrYaml)
+            .map_err(|result| (StatusCode::BAD_REQUEST, Json(DeployResponse { result })))
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct DeployRequest {
     pipeline: Pipeline,
     #[serde(rename = "workspaceSlug")]
     workspace_slug: String,
+    /// Wait up to this long for the pipeline to be deployed before responding
+    #[serde(
+        rename = "waitTimeoutMs",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    wait_timeout_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -120,6 +634,58 @@ struct DeployProvidersRequest {
     workspace_slug: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct StatusQuery {
+    #[serde(rename = "workspaceSlug")]
+    workspace_slug: String,
+    pipeline: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkspaceQuery {
+    #[serde(rename = "workspaceSlug")]
+    workspace_slug: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentsQuery {
+    #[serde(rename = "workspaceSlug")]
+    workspace_slug: String,
+    pipeline: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ReplayRequest {
+    #[serde(rename = "workspaceSlug")]
+    workspace_slug: String,
+    /// Name of the deployed pipeline the message was dead-lettered by
+    pipeline: String,
+    /// Dead-letter subject the message was received on
+    subject: String,
+    /// Sequence of the dead letter in the dead-letter stream
+    id: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ValidateRequest {
+    pipeline: Pipeline,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SubjectsRequest {
+    pipeline: Pipeline,
+    #[serde(rename = "workspaceSlug")]
+    workspace_slug: String,
+}
+
+#[derive(Serialize)]
+struct ValidateResponse {
+    /// Whether the pipeline can be deployed, i.e. none of the problems is an error
+    valid: bool,
+    problems: Vec<config_converter::Problem>,
+}
+
 #[derive(Deserialize, Serialize)]
 struct DeployResponse {
     result: String,
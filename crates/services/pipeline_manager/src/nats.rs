@@ -0,0 +1,38 @@
+//! Connections to NATS
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::config::{Nats, NatsUser};
+
+/// Connect to the NATS cluster with the pipeline manager's credentials, if any
+pub async fn connect(nats: &Nats) -> Result<async_nats::Client> {
+    let options = match (&nats.jwt, &nats.nkey) {
+        (Some(jwt), Some(nkey)) => with_credentials(jwt, nkey)?,
+        _ => async_nats::ConnectOptions::new(),
+    };
+    options
+        .connect(nats.joined_cluster_uris())
+        .await
+        .context("Failed to connect to NATS")
+}
+
+/// Connect to the NATS cluster as `user`
+pub async fn connect_as(nats: &Nats, user: &NatsUser) -> Result<async_nats::Client> {
+    with_credentials(&user.jwt, &user.nkey)?
+        .connect(nats.joined_cluster_uris())
+        .await
+        .context("Failed to connect to NATS")
+}
+
+fn with_credentials(jwt: &str, nkey: &str) -> Result<async_nats::ConnectOptions> {
+    let key_pair = Arc::new(nkeys::KeyPair::from_seed(nkey)?);
+    Ok(async_nats::ConnectOptions::with_jwt(
+        jwt.to_string(),
+        move |nonce| {
+            let key_pair = key_pair.clone();
+            async move { key_pair.sign(&nonce).map_err(async_nats::AuthError::new) }
+        },
+    ))
+}
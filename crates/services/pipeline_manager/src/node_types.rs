@@ -0,0 +1,112 @@
+use schemars::{Schema, schema_for};
+use serde::Serialize;
+use shared::{
+    InGraphqlPollerSettings, InHttpWebhookSettings, InMongodbSettings, InRabbitmqSettings,
+    InRssReaderSettings, NodeCategory, OutAwsLambdaSettings, OutFileSettings,
+    OutGoogleBigquerySettings, OutGraphqlMutationSettings, OutHttpWebhookSettings, OutLogSettings,
+    OutLokiSettings, OutMongodbSettings, OutRabbitmqSettings, OutSnowflakeSettings,
+    PipelineNodeType, ProcessorFilterSettings, ProcessorFormatSettings, ProcessorWasmSettings,
+};
+
+#[derive(Debug, Serialize)]
+pub struct NodeTypeDescription {
+    #[serde(rename = "type")]
+    pub node_type: PipelineNodeType,
+    pub category: NodeCategory,
+    #[serde(rename = "settingsSchema")]
+    pub settings_schema: Option<Schema>,
+}
+
+/// Describe every supported node type along with the JSON schema of its settings
+pub fn describe_node_types() -> Vec<NodeTypeDescription> {
+    PipelineNodeType::ALL
+        .iter()
+        .map(|node_type| NodeTypeDescription {
+            node_type: *node_type,
+            category: node_type.category(),
+            settings_schema: settings_schema(node_type),
+        })
+        .collect()
+}
+
+fn settings_schema(node_type: &PipelineNodeType) -> Option<Schema> {
+    match node_type {
+        PipelineNodeType::InHttpWebhook => Some(schema_for!(InHttpWebhookSettings)),
+        PipelineNodeType::InRssReader => Some(schema_for!(InRssReaderSettings)),
+        PipelineNodeType::InGraphqlPoller => Some(schema_for!(InGraphqlPollerSettings)),
+        PipelineNodeType::InMongodb => Some(schema_for!(InMongodbSettings)),
+        PipelineNodeType::InRabbitmq => Some(schema_for!(InRabbitmqSettings)),
+        PipelineNodeType::ProcessorWasm => Some(schema_for!(ProcessorWasmSettings)),
+        PipelineNodeType::ProcessorFormat => Some(schema_for!(ProcessorFormatSettings)),
+        PipelineNodeType::ProcessorFilter => Some(schema_for!(ProcessorFilterSettings)),
+        PipelineNodeType::OutHttpWebhook => Some(schema_for!(OutHttpWebhookSettings)),
+        PipelineNodeType::OutFile => Some(schema_for!(OutFileSettings)),
+        PipelineNodeType::OutLog => Some(schema_for!(OutLogSettings)),
+        PipelineNodeType::OutMongodb => Some(schema_for!(OutMongodbSettings)),
+        PipelineNodeType::OutLoki => Some(schema_for!(OutLokiSettings)),
+        PipelineNodeType::OutRabbitmq => Some(schema_for!(OutRabbitmqSettings)),
+        PipelineNodeType::OutSnowflake => Some(schema_for!(OutSnowflakeSettings)),
+        PipelineNodeType::OutGoogleBigquery => Some(schema_for!(OutGoogleBigquerySettings)),
+        PipelineNodeType::OutAwsLambda => Some(schema_for!(OutAwsLambdaSettings)),
+        PipelineNodeType::OutGraphqlMutation => Some(schema_for!(OutGraphqlMutationSettings)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_node_types_lists_every_variant() {
+        // The schemars enum for PipelineNodeType is derived from the enum itself, so any
+        // variant missing from `PipelineNodeType::ALL` shows up here.
+        let schema = serde_json::to_value(schema_for!(PipelineNodeType)).unwrap();
+        let expected: Vec<String> = schema["enum"]
+            .as_array()
+            .expect("node type schema should be a string enum")
+            .iter()
+            .map(|v| v.as_str().unwrap().to_string())
+            .collect();
+
+        let described = serde_json::to_value(describe_node_types()).unwrap();
+        let actual: Vec<String> = described
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["type"].as_str().unwrap().to_string())
+            .collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_describe_node_types_categories_and_schemas() {
+        let described = describe_node_types();
+        let find = |node_type: PipelineNodeType| {
+            described.iter().find(|d| d.node_type == node_type).unwrap()
+        };
+
+        let in_http = find(PipelineNodeType::InHttpWebhook);
+        assert_eq!(in_http.category, NodeCategory::Source);
+        assert!(in_http.settings_schema.is_some());
+
+        let processor = find(PipelineNodeType::ProcessorWasm);
+        assert_eq!(processor.category, NodeCategory::Processor);
+        assert!(processor.settings_schema.is_some());
+
+        let out_log = find(PipelineNodeType::OutLog);
+        assert_eq!(out_log.category, NodeCategory::Sink);
+        assert!(out_log.settings_schema.is_some());
+
+        let out_influxdb = find(PipelineNodeType::OutInfluxdb);
+        assert_eq!(out_influxdb.category, NodeCategory::Sink);
+        assert!(out_influxdb.settings_schema.is_none());
+
+        // Categories are described by name
+        assert_eq!(
+            serde_json::to_value(in_http).unwrap()["category"],
+            serde_json::json!("source")
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Ticks for polling sources. RSS readers, GraphQL pollers and RabbitMQ consumers only fetch
+//! when a message arrives on their poll subject, so every `scheduler.poll_tick_ms` an empty message
+//! is published to the poll subject of every polling source of the latest deployed version of
+//! each pipeline. Sources skip ticks until their own poll interval has elapsed.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::{config::AppConfig, database};
+
+/// Publish poll ticks until the process exits. Returns immediately when the scheduler is disabled.
+pub async fn run(app_config: AppConfig, db_pool: PgPool) {
+    let tick_ms = app_config.scheduler.poll_tick_ms;
+    if tick_ms == 0 {
+        tracing::info!("Poll scheduler is disabled");
+        return;
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut client = None;
+    loop {
+        interval.tick().await;
+
+        if client.is_none() {
+            match crate::nats::connect(&app_config.nats).await {
+                Ok(connected) => client = Some(connected),
+                Err(e) => {
+                    tracing::error!("Poll scheduler failed to connect to NATS: {:#}", e);
+                    continue;
+                }
+            }
+        }
+        let Some(client) = &client else { continue };
+
+        let subjects = match database::list_poll_subjects(&db_pool).await {
+            Ok(subjects) => subjects,
+            Err(e) => {
+                tracing::error!("Poll scheduler failed to list poll subjects: {}", e);
+                continue;
+            }
+        };
+        for subject in subjects {
+            if let Err(e) = client.publish(subject.clone(), "".into()).await {
+                tracing::warn!("Failed to publish poll tick to {}: {}", subject, e);
+            }
+        }
+    }
+}
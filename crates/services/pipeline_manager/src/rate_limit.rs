@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    pruned_at: Option<Instant>,
+}
+
+/// Token bucket rate limiter keyed by an arbitrary string, e.g. a workspace slug.
+///
+/// Every key starts with `burst` tokens and regains `per_minute` tokens per minute, up to
+/// `burst`. A limiter with a `per_minute` of 0 lets everything through.
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32, burst: u32) -> Self {
+        Self {
+            per_second: f64::from(per_minute) / 60.0,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// How long an empty bucket takes to fill up again
+    fn refill_time(&self) -> Duration {
+        Duration::from_secs_f64(self.burst / self.per_second)
+    }
+
+    /// Take a token for `key`, or return how long to wait until one is available
+    pub fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        if self.per_second <= 0.0 {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        // A bucket idle long enough to be full again is the same as a missing one, so those are
+        // dropped, at most once per refill time, for keys that stopped sending requests
+        let refill_time = self.refill_time();
+        if buckets
+            .pruned_at
+            .is_none_or(|pruned_at| now.saturating_duration_since(pruned_at) >= refill_time)
+        {
+            buckets
+                .by_key
+                .retain(|_, bucket| now.saturating_duration_since(bucket.updated_at) < refill_time);
+            buckets.pruned_at = Some(now);
+        }
+
+        let bucket = buckets.by_key.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.per_second).min(self.burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+/// Value for a `Retry-After` header, in whole seconds rounded up
+pub fn retry_after_seconds(wait: Duration) -> u64 {
+    wait.as_secs_f64().ceil().max(1.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_requests_over_burst_and_refills() {
+        let limiter = RateLimiter::new(6, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check("acme", start).is_ok());
+        }
+        let wait = limiter.check("acme", start).unwrap_err();
+        assert_eq!(retry_after_seconds(wait), 10);
+
+        // One token comes back every 10 seconds
+        assert!(
+            limiter
+                .check("acme", start + Duration::from_secs(5))
+                .is_err()
+        );
+        assert!(
+            limiter
+                .check("acme", start + Duration::from_secs(10))
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check("acme", start + Duration::from_secs(10))
+                .is_err()
+        );
+
+        // The bucket never holds more than the burst
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..3 {
+            assert!(limiter.check("acme", later).is_ok());
+        }
+        assert!(limiter.check("acme", later).is_err());
+    }
+
+    #[test]
+    fn test_workspaces_are_limited_independently() {
+        let limiter = RateLimiter::new(1, 1);
+        let now = Instant::now();
+
+        assert!(limiter.check("acme", now).is_ok());
+        assert!(limiter.check("acme", now).is_err());
+        assert!(limiter.check("globex", now).is_ok());
+    }
+
+    #[test]
+    fn test_idle_keys_are_pruned() {
+        // Refills in 2 seconds
+        let limiter = RateLimiter::new(60, 2);
+        let start = Instant::now();
+        let keys = || {
+            let buckets = limiter.buckets.lock().unwrap();
+            let mut keys: Vec<String> = buckets.by_key.keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+
+        assert!(limiter.check("acme", start).is_ok());
+        assert!(
+            limiter
+                .check("globex", start + Duration::from_secs(1))
+                .is_ok()
+        );
+        assert_eq!(keys(), ["acme", "globex"]);
+
+        // acme has been idle for the refill time, globex hasn't
+        assert!(
+            limiter
+                .check("initech", start + Duration::from_secs(2))
+                .is_ok()
+        );
+        assert_eq!(keys(), ["globex", "initech"]);
+
+        // A pruned key starts over with a full bucket
+        for _ in 0..2 {
+            assert!(
+                limiter
+                    .check("acme", start + Duration::from_secs(2))
+                    .is_ok()
+            );
+        }
+        assert!(
+            limiter
+                .check("acme", start + Duration::from_secs(2))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_zero_rate_disables_limiting() {
+        let limiter = RateLimiter::new(0, 1);
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert!(limiter.check("acme", now).is_ok());
+        }
+    }
+}
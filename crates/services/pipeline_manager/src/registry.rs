@@ -1,4 +1,9 @@
-use crate::{DeployRequest, config::AppConfig};
+use crate::{
+    DeployRequest,
+    builders::nodes::processor::wasm::{pinned_digest, published_source, verify_digest},
+    config::{AppConfig, RegistryAuth},
+    deployment::DeployError,
+};
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
 use shared::PipelineNodeType;
@@ -7,6 +12,7 @@ use wash::lib::registry::{OciPushOptions, push_oci_artifact};
 
 pub async fn test_registry_connectivity(
     registry_url: &str,
+    auth: &RegistryAuth,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
@@ -16,7 +22,12 @@ pub async fn test_registry_connectivity(
 
     info!("Testing registry connectivity at: {}", version_url);
 
-    let response = match client.get(&version_url).send().await {
+    let mut request = client.get(&version_url);
+    if let Some(authorization) = auth.authorization_header() {
+        request = request.header(reqwest::header::AUTHORIZATION, authorization);
+    }
+
+    let response = match request.send().await {
         Ok(resp) => resp,
         Err(e) => {
             error!("Failed to connect to registry at {}: {}", registry_url, e);
@@ -41,18 +52,21 @@ pub async fn test_registry_connectivity(
 pub async fn publish_wasm_components(
     payload: &DeployRequest,
     app_config: &AppConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), DeployError> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
         .connect_timeout(std::time::Duration::from_secs(10))
-        .build()?;
+        .build()
+        .map_err(|e| DeployError::Registry(format!("Failed to create HTTP client: {e}")))?;
 
-    // Filter nodes to only processor-wasm types
+    // Filter nodes to only processor-wasm types built from an upload; nodes referencing an
+    // already-published OCI image don't need to be pushed
     let wasm_nodes: Vec<_> = payload
         .pipeline
         .nodes
         .iter()
         .filter(|node| matches!(node.step_type, PipelineNodeType::ProcessorWasm))
+        .filter(|node| published_source(node).is_none())
         .collect();
 
     if wasm_nodes.is_empty() {
@@ -102,6 +116,14 @@ pub async fn publish_wasm_components(
             }
         };
 
+        // Refuse to publish a component that isn't the one the node is pinned to
+        if let Err(e) = verify_digest(pinned_digest(node), &wasm_data) {
+            error!("Rejected WASM component of node {}: {}", node_id, e);
+            return Err(DeployError::Validation(format!(
+                "Rejected component of node {node_id}: {e}"
+            )));
+        }
+
         // Publish to OCI registry
         info!(
             "Publishing node {} to registry at: {}",
@@ -113,7 +135,10 @@ pub async fn publish_wasm_components(
             "Testing registry connectivity before publishing node: {}",
             node_id
         );
-        if let Err(e) = test_registry_connectivity(&app_config.registry.internal_url).await {
+        if let Err(e) =
+            test_registry_connectivity(&app_config.registry.internal_url, &app_config.registry.auth)
+                .await
+        {
             error!(
                 "Registry connectivity test failed for node {}: {}",
                 node_id, e
@@ -154,8 +179,11 @@ pub async fn publish_wasm_components(
         );
         info!("Full image ref to push: {}", &full_image_ref);
 
+        let (user, password) = app_config.registry.auth.push_credentials().unzip();
         let push_options = OciPushOptions {
             insecure: app_config.registry.internal_url.starts_with("http://"),
+            user,
+            password,
             ..Default::default()
         };
 
@@ -179,12 +207,11 @@ pub async fn publish_wasm_components(
     }
 
     if !failed_nodes.is_empty() {
-        return Err(format!(
+        return Err(DeployError::Registry(format!(
             "Failed to publish {} nodes: {:?}",
             failed_nodes.len(),
             failed_nodes
-        )
-        .into());
+        )));
     }
 
     Ok(())
@@ -0,0 +1,125 @@
+//! Replaying dead-lettered messages. Dead letters are published on core NATS subjects, which the
+//! dead-letter stream captures, so a replay names a dead letter by its sequence in the stream and
+//! only messages that were actually dead-lettered can be replayed.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, stream::DirectGetErrorKind};
+use shared::DeadLetter;
+
+use crate::config::{Nats, NatsUser};
+
+/// JetStream stream keeping the dead letters of every pipeline
+pub const DEAD_LETTER_STREAM: &str = "DEAD_LETTERS";
+
+/// Subjects the dead-letter stream captures, the dead-letter subject of every node
+const DEAD_LETTER_SUBJECTS: &str = "pipestack.*.*.*.dlq";
+
+/// How long dead letters can be replayed for
+const DEAD_LETTER_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Create the dead-letter stream if it doesn't exist yet
+pub async fn ensure_dead_letter_stream(nats: &Nats) -> Result<()> {
+    let client = crate::nats::connect(nats).await?;
+
+    jetstream::new(client)
+        .get_or_create_stream(jetstream::stream::Config {
+            name: DEAD_LETTER_STREAM.to_string(),
+            subjects: vec![DEAD_LETTER_SUBJECTS.to_string()],
+            max_age: DEAD_LETTER_MAX_AGE,
+            allow_direct: true,
+            ..Default::default()
+        })
+        .await
+        .context("Failed to create the dead-letter stream")?;
+    Ok(())
+}
+
+/// Dead letter `id` of the dead-letter stream, `None` when there's no such dead letter on
+/// `subject`
+pub async fn dead_letter(nats: &Nats, subject: &str, id: u64) -> Result<Option<DeadLetter>> {
+    let client = crate::nats::connect(nats).await?;
+    let stream = jetstream::new(client)
+        .get_stream(DEAD_LETTER_STREAM)
+        .await
+        .context("Failed to get the dead-letter stream")?;
+
+    match stream.direct_get(id).await {
+        Ok(message) => stored_dead_letter(subject, message.subject.as_str(), &message.payload),
+        Err(e) if e.kind() == DirectGetErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).context("Failed to get the dead letter"),
+    }
+}
+
+/// The dead letter stored on `stored_subject`, if that's the subject it's looked up on
+fn stored_dead_letter(
+    subject: &str,
+    stored_subject: &str,
+    payload: &[u8],
+) -> Result<Option<DeadLetter>> {
+    if stored_subject != subject {
+        return Ok(None);
+    }
+    serde_json::from_slice(payload)
+        .map(Some)
+        .context("Failed to parse the dead letter")
+}
+
+/// Publish `message` on `subject` as the replay user, rather than with the pipeline manager's own
+/// credentials, so a replay can't reach any subject but a replay subject
+pub async fn publish(nats: &Nats, user: &NatsUser, subject: String, message: String) -> Result<()> {
+    let client = crate::nats::connect_as(nats, user).await?;
+
+    client
+        .publish(subject, message.into())
+        .await
+        .context("Failed to publish message")?;
+    client.flush().await.context("Failed to flush message")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUBJECT: &str = "pipestack.default.chain.node-1.dlq";
+
+    #[test]
+    fn test_stored_dead_letter() {
+        let payload = DeadLetter {
+            error: "Error: invalid input".to_string(),
+            message: r#"{"id":1}"#.to_string(),
+        }
+        .to_json();
+
+        let dead_letter = stored_dead_letter(SUBJECT, SUBJECT, payload.as_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(dead_letter.message, r#"{"id":1}"#);
+    }
+
+    #[test]
+    fn test_dead_letter_of_another_subject_is_not_found() {
+        let payload = DeadLetter {
+            error: "Error: invalid input".to_string(),
+            message: r#"{"id":1}"#.to_string(),
+        }
+        .to_json();
+
+        assert!(
+            stored_dead_letter(
+                SUBJECT,
+                "pipestack.other.orders.node-1.dlq",
+                payload.as_bytes()
+            )
+            .unwrap()
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_payload_that_is_not_a_dead_letter_is_rejected() {
+        assert!(stored_dead_letter(SUBJECT, SUBJECT, b"not a dead letter").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Serialize, de::DeserializeOwned};
+
+const YAML_MEDIA_TYPES: [&str; 3] = ["application/yaml", "application/x-yaml", "text/yaml"];
+
+/// Whether a `Content-Type` header value names YAML
+pub fn is_yaml(content_type: Option<&str>) -> bool {
+    let Some(content_type) = content_type else {
+        return false;
+    };
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    YAML_MEDIA_TYPES
+        .iter()
+        .any(|yaml| media_type.eq_ignore_ascii_case(yaml))
+}
+
+/// Parse a request body as YAML when its content type says so, and as JSON otherwise
+pub fn parse_body<T: DeserializeOwned>(
+    content_type: Option<&str>,
+    bytes: &[u8],
+) -> Result<T, String> {
+    if is_yaml(content_type) {
+        serde_yaml::from_slice(bytes).map_err(|e| format!("Failed to parse YAML body: {e}"))
+    } else {
+        serde_json::from_slice(bytes).map_err(|e| format!("Failed to parse JSON body: {e}"))
+    }
+}
+
+/// Whether a request's query string asks for strict parsing of its body, with `strict=true`
+pub fn is_strict(query: Option<&str>) -> bool {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .any(|pair| pair == "strict" || pair == "strict=true")
+}
+
+/// Parse a request body like `parse_body`, rejecting fields the body doesn't have, such as a
+/// typo in a node's settings that would otherwise be ignored
+pub fn parse_body_strict<T: DeserializeOwned + Serialize>(
+    content_type: Option<&str>,
+    bytes: &[u8],
+) -> Result<T, String> {
+    let body: T = parse_body(content_type, bytes)?;
+    let input: serde_json::Value = parse_body(content_type, bytes)?;
+    let unknown = shared::unknown_fields(&input, &body)?;
+    if !unknown.is_empty() {
+        return Err(format!("Unknown fields: {}", unknown.join(", ")));
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::AppConfig, config_converter::convert_pipeline};
+    use serde::{Deserialize, Serialize};
+    use shared::Pipeline;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Body {
+        pipeline: Pipeline,
+        #[serde(rename = "workspaceSlug")]
+        workspace_slug: String,
+    }
+
+    const YAML_BODY: &str = r#"
+workspaceSlug: acme
+pipeline:
+  name: orders
+  version: '1'
+  nodes:
+    - id: webhook
+      label: webhook
+      type: in-http-webhook
+      position:
+        x: 100
+        'y': 100
+      settings:
+        type: in-http-webhook
+        settings:
+          method: POST
+          path: /orders
+    - id: log
+      label: log
+      type: out-log
+      position:
+        x: 200
+        'y': 100
+      depends_on:
+        - webhook
+"#;
+
+    #[test]
+    fn test_is_yaml() {
+        assert!(is_yaml(Some("application/yaml")));
+        assert!(is_yaml(Some("application/x-yaml; charset=utf-8")));
+        assert!(is_yaml(Some("Text/YAML")));
+        assert!(!is_yaml(Some("application/json")));
+        assert!(!is_yaml(None));
+    }
+
+    #[test]
+    fn test_yaml_body_converts_like_json_body() {
+        let value: serde_json::Value = serde_yaml::from_str(YAML_BODY).unwrap();
+        let json_body = serde_json::to_vec(&value).unwrap();
+
+        let from_yaml: Body = parse_body(Some("application/yaml"), YAML_BODY.as_bytes()).unwrap();
+        let from_json: Body = parse_body(Some("application/json"), &json_body).unwrap();
+        let from_default: Body = parse_body(None, &json_body).unwrap();
+
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let convert = |body: &Body| {
+            let wadm = convert_pipeline(&body.pipeline, &body.workspace_slug, &app_config)
+                .expect("Failed to convert pipeline");
+            serde_yaml::to_string(&wadm).unwrap()
+        };
+        assert_eq!(convert(&from_yaml), convert(&from_json));
+        assert_eq!(convert(&from_yaml), convert(&from_default));
+    }
+
+    #[test]
+    fn test_json_body_is_not_parsed_as_yaml_by_default() {
+        let err = parse_body::<Body>(None, YAML_BODY.as_bytes()).unwrap_err();
+        assert!(err.starts_with("Failed to parse JSON body"), "{err}");
+    }
+
+    #[test]
+    fn test_strict_parsing_rejects_typos() {
+        let body = YAML_BODY.replace(
+            "      label: log\n",
+            "      label: log\n      instance: 5\n",
+        );
+
+        // Lenient parsing ignores the typo and runs the node with the default instances
+        let lenient: Body = parse_body(Some("application/yaml"), body.as_bytes()).unwrap();
+        assert_eq!(lenient.pipeline.nodes[1].instances, None);
+
+        let err = parse_body_strict::<Body>(Some("application/yaml"), body.as_bytes()).unwrap_err();
+        assert_eq!(err, "Unknown fields: pipeline.nodes[1].instance");
+        assert!(parse_body_strict::<Body>(Some("application/yaml"), YAML_BODY.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_is_strict() {
+        assert!(is_strict(Some("strict=true")));
+        assert!(is_strict(Some("workspaceSlug=acme&strict")));
+        assert!(!is_strict(Some("strict=false")));
+        assert!(!is_strict(None));
+    }
+}
@@ -1,36 +1,175 @@
+use std::{collections::HashMap, time::Duration};
+
 use axum::{Json, http::StatusCode};
+use serde::Serialize;
 use sqlx::PgPool;
 
-use crate::{DeployRequest, DeployResponse, config::AppConfig, config_converter, database};
+use crate::{
+    DeployRequest, DeployResponse, builders::WadmApplication, config::AppConfig, config_converter,
+    database,
+};
+
+/// How often the status of an application is checked while waiting for it to deploy
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Upper bound on how long a deploy request may wait for its pipeline to become ready
+const MAX_DEPLOY_WAIT: Duration = Duration::from_secs(300);
+
+/// Deployment state of an application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeploymentStatus {
+    Undeployed,
+    Reconciling,
+    Deployed,
+    Failed,
+    Waiting,
+    Unhealthy,
+    Unknown,
+}
+
+impl DeploymentStatus {
+    /// Map a wadm status type, e.g. `Deployed` or `reconciling`, to a deployment status
+    pub fn from_wadm(status_type: &str) -> Self {
+        match status_type.to_ascii_lowercase().as_str() {
+            "undeployed" => Self::Undeployed,
+            "reconciling" => Self::Reconciling,
+            "deployed" => Self::Deployed,
+            "failed" => Self::Failed,
+            "waiting" => Self::Waiting,
+            "unhealthy" => Self::Unhealthy,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApplicationStatus {
+    pub status: DeploymentStatus,
+    /// Details from wadm on the reconciliation, e.g. why a deploy failed
+    pub message: String,
+}
+
+/// An application stored in wadm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplicationSummary {
+    pub name: String,
+    pub status: ApplicationStatus,
+}
+
+/// A pipeline of a workspace and where its deployment stands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PipelineSummary {
+    pub name: String,
+    pub version: String,
+    pub status: DeploymentStatus,
+    pub message: String,
+}
+
+/// Operations the deploy flow needs from wadm
+pub trait WadmClient {
+    /// Store a manifest, returning the name and version wadm stored it under
+    async fn put_application(&self, manifest: &str) -> anyhow::Result<(String, String)>;
+    /// Deploy a stored manifest. Deploys the latest version when `version` is `None`.
+    async fn deploy_application(&self, name: &str, version: Option<&str>) -> anyhow::Result<()>;
+    /// Delete every stored version of an application. Returns false if it didn't exist.
+    #[allow(dead_code)] // Nothing removes pipelines yet
+    async fn delete_application(&self, name: &str) -> anyhow::Result<bool>;
+    /// Current deployment status of an application
+    async fn get_application_status(&self, name: &str) -> anyhow::Result<ApplicationStatus>;
+    /// Every application stored in the lattice
+    async fn list_applications(&self) -> anyhow::Result<Vec<ApplicationSummary>>;
+}
+
+/// [`WadmClient`] talking to the wadm API of a workspace over NATS
+pub struct NatsWadmClient {
+    client: wadm_client::Client,
+}
+
+impl NatsWadmClient {
+    pub async fn connect(
+        workspace_slug: &str,
+        wadm_subject: &str,
+        app_config: &AppConfig,
+    ) -> anyhow::Result<Self> {
+        let client = wadm_client::Client::new(
+            workspace_slug,
+            Some(wadm_subject),
+            wadm_client::ClientConnectOptions {
+                ca_path: None,
+                creds_path: None,
+                jwt: app_config.nats.jwt.clone(),
+                seed: app_config.nats.nkey.clone(),
+                url: Some(app_config.nats.joined_cluster_uris()),
+            },
+        )
+        .await?;
+        Ok(Self { client })
+    }
+}
+
+impl WadmClient for NatsWadmClient {
+    async fn put_application(&self, manifest: &str) -> anyhow::Result<(String, String)> {
+        Ok(self.client.put_manifest(manifest.as_bytes()).await?)
+    }
+
+    async fn deploy_application(&self, name: &str, version: Option<&str>) -> anyhow::Result<()> {
+        self.client.deploy_manifest(name, version).await?;
+        Ok(())
+    }
+
+    async fn delete_application(&self, name: &str) -> anyhow::Result<bool> {
+        Ok(self.client.delete_manifest(name, None).await?)
+    }
+
+    async fn get_application_status(&self, name: &str) -> anyhow::Result<ApplicationStatus> {
+        let status = self.client.get_manifest_status(name).await?;
+        Ok(ApplicationStatus {
+            status: DeploymentStatus::from_wadm(&format!("{:?}", status.info.status_type)),
+            message: status.info.message,
+        })
+    }
+
+    async fn list_applications(&self) -> anyhow::Result<Vec<ApplicationSummary>> {
+        let summaries = self.client.list_manifests().await?;
+        Ok(summaries
+            .into_iter()
+            .map(|summary| ApplicationSummary {
+                name: summary.name,
+                status: ApplicationStatus {
+                    status: DeploymentStatus::from_wadm(&format!(
+                        "{:?}",
+                        summary.detailed_status.info.status_type
+                    )),
+                    message: summary.detailed_status.info.message,
+                },
+            })
+            .collect())
+    }
+}
 
 pub async fn deploy_pipeline_to_wasm_cloud(
     payload: &DeployRequest,
+    wadm_config: &WadmApplication,
     app_config: &AppConfig,
     db_pool: &PgPool,
 ) -> (StatusCode, Json<DeployResponse>) {
-    // Convert payload to a valid wadm file
-    let wadm_config = match config_converter::convert_pipeline(
-        &payload.pipeline,
-        &payload.workspace_slug,
-        app_config,
-    ) {
-        Ok(config) => {
-            tracing::info!("Successfully converted pipeline to WADM config");
-            config
-        }
-        Err(e) => {
-            tracing::error!("Failed to convert pipeline: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(DeployResponse {
-                    result: format!("Error converting pipeline: {e}"),
-                }),
-            );
-        }
+    let client = match connect(&payload.workspace_slug, app_config, db_pool).await {
+        Ok(client) => client,
+        Err(response) => return response,
     };
 
+    deploy_pipeline(&client, payload, wadm_config).await
+}
+
+/// Store and deploy the wadm application a pipeline was converted to
+pub async fn deploy_pipeline(
+    client: &impl WadmClient,
+    payload: &DeployRequest,
+    wadm_config: &WadmApplication,
+) -> (StatusCode, Json<DeployResponse>) {
     // Convert to YAML string
-    let wadm_yaml = match serde_yaml::to_string(&wadm_config) {
+    let wadm_yaml = match serde_yaml::to_string(wadm_config) {
         Ok(yaml) => yaml,
         Err(e) => {
             tracing::error!("Failed to serialize WADM config to YAML: {}", e);
@@ -45,48 +184,37 @@ pub async fn deploy_pipeline_to_wasm_cloud(
 
     tracing::info!("WADM yaml generated successfully: {wadm_yaml}");
 
-    let wadm_subject = if payload.workspace_slug == "default" {
-        "wadm.api".to_string()
-    } else {
-        let nats_account = match get_nats_account(&payload.workspace_slug, db_pool).await {
-            Ok(value) => value,
-            Err(value) => return value,
-        };
-        format!("{}.wadm.api", nats_account)
-    };
-    let client = match wadm_client::Client::new(
-        &payload.workspace_slug,
-        Some(&wadm_subject),
-        wadm_client::ClientConnectOptions {
-            ca_path: None,
-            creds_path: None,
-            jwt: app_config.nats.jwt.clone(),
-            seed: app_config.nats.nkey.clone(),
-            url: Some(app_config.nats.cluster_uris.clone()),
-        },
-    )
-    .await
-    {
-        Ok(client) => client,
+    tracing::info!(
+        "Putting and deploying manifest: {}",
+        &wadm_config.metadata.name
+    );
+    let name = match put_and_deploy(client, &wadm_yaml).await {
+        Ok(name) => name,
         Err(e) => {
-            tracing::error!("Failed to create WADM client: {}", e);
+            tracing::error!("Failed to deploy pipeline: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(DeployResponse {
-                    result: format!("Error creating WADM client: {e}"),
+                    result: format!("Error deploying pipeline: {e}"),
                 }),
             );
         }
     };
 
-    tracing::info!(
-        "Putting and deploying manifest: {}",
-        &wadm_config.metadata.name
-    );
-    client
-        .put_and_deploy_manifest(wadm_yaml.as_bytes())
-        .await
-        .unwrap();
+    match payload.wait_timeout_ms {
+        Some(timeout_ms) => {
+            let timeout = Duration::from_millis(timeout_ms).min(MAX_DEPLOY_WAIT);
+            if let Err(response) =
+                wait_until_deployed(client, &name, timeout, STATUS_POLL_INTERVAL).await
+            {
+                return response;
+            }
+        }
+        None => match client.get_application_status(&name).await {
+            Ok(status) => tracing::info!("Application {} status: {:?}", name, status),
+            Err(e) => tracing::warn!("Failed to get status of application {}: {}", name, e),
+        },
+    }
 
     (
         StatusCode::OK,
@@ -120,38 +248,9 @@ pub async fn deploy_providers_to_wasm_cloud(
 
     tracing::info!("Providers WADM yaml generated successfully: {wadm_yaml}");
 
-    let wadm_subject = if workspace_slug == "default" {
-        "wadm.api".to_string()
-    } else {
-        let nats_account = match get_nats_account(workspace_slug, db_pool).await {
-            Ok(value) => value,
-            Err(value) => return value,
-        };
-        format!("{}.wadm.api", nats_account)
-    };
-    let client = match wadm_client::Client::new(
-        workspace_slug,
-        Some(&wadm_subject),
-        wadm_client::ClientConnectOptions {
-            ca_path: None,
-            creds_path: None,
-            jwt: app_config.nats.jwt.clone(),
-            seed: app_config.nats.nkey.clone(),
-            url: Some(app_config.nats.cluster_uris.clone()),
-        },
-    )
-    .await
-    {
+    let client = match connect(workspace_slug, app_config, db_pool).await {
         Ok(client) => client,
-        Err(e) => {
-            tracing::error!("Failed to create WADM client: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(DeployResponse {
-                    result: format!("Error creating WADM client: {e}"),
-                }),
-            );
-        }
+        Err(response) => return response,
     };
 
     tracing::info!(
@@ -159,7 +258,7 @@ pub async fn deploy_providers_to_wasm_cloud(
         &wadm_config.metadata.name
     );
 
-    match client.put_and_deploy_manifest(wadm_yaml.as_bytes()).await {
+    match put_and_deploy(&client, &wadm_yaml).await {
         Ok(_) => {
             tracing::info!("Providers deployed successfully");
             (
@@ -181,6 +280,222 @@ pub async fn deploy_providers_to_wasm_cloud(
     }
 }
 
+/// Poll the status of an application until it's deployed, it failed or `timeout` passes
+async fn wait_until_deployed(
+    client: &impl WadmClient,
+    name: &str,
+    timeout: Duration,
+    interval: Duration,
+) -> Result<(), (StatusCode, Json<DeployResponse>)> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut last_status = None;
+
+    loop {
+        match client.get_application_status(name).await {
+            Ok(ApplicationStatus {
+                status: DeploymentStatus::Deployed,
+                ..
+            }) => {
+                tracing::info!("Application {} is deployed", name);
+                return Ok(());
+            }
+            Ok(ApplicationStatus {
+                status: DeploymentStatus::Failed,
+                message,
+            }) => {
+                tracing::error!("Application {} failed to deploy: {}", name, message);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(DeployResponse {
+                        result: format!("Pipeline failed to deploy: {message}"),
+                    }),
+                ));
+            }
+            Ok(status) => last_status = Some(status),
+            Err(e) => tracing::warn!("Failed to get status of application {}: {}", name, e),
+        }
+
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            tracing::warn!("Timed out waiting for application {} to deploy", name);
+            return Err((
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(DeployResponse {
+                    result: format!(
+                        "Pipeline was not deployed within {}ms, last status: {:?}",
+                        timeout.as_millis(),
+                        last_status
+                            .map(|s| s.status)
+                            .unwrap_or(DeploymentStatus::Unknown)
+                    ),
+                }),
+            ));
+        }
+        tokio::time::sleep(interval.min(deadline - now)).await;
+    }
+}
+
+/// Whether a pipeline's application is currently deployed in the lattice of its workspace
+pub async fn is_pipeline_deployed(
+    workspace_slug: &str,
+    pipeline_name: &str,
+    app_config: &AppConfig,
+    db_pool: &PgPool,
+) -> bool {
+    match connect(workspace_slug, app_config, db_pool).await {
+        Ok(client) => {
+            is_deployed(
+                &client,
+                &config_converter::application_name(workspace_slug, pipeline_name),
+            )
+            .await
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether wadm reports an application as deployed. An application whose status can't be read,
+/// e.g. because it was deleted, counts as not deployed.
+pub async fn is_deployed(client: &impl WadmClient, name: &str) -> bool {
+    match client.get_application_status(name).await {
+        Ok(status) => status.status == DeploymentStatus::Deployed,
+        Err(e) => {
+            tracing::warn!("Failed to get status of application {}: {}", name, e);
+            false
+        }
+    }
+}
+
+/// Look up the deployment status of a pipeline
+pub async fn get_pipeline_status(
+    workspace_slug: &str,
+    pipeline_name: &str,
+    app_config: &AppConfig,
+    db_pool: &PgPool,
+) -> Result<Json<ApplicationStatus>, (StatusCode, Json<DeployResponse>)> {
+    let client = connect(workspace_slug, app_config, db_pool).await?;
+    let name = config_converter::application_name(workspace_slug, pipeline_name);
+
+    client
+        .get_application_status(&name)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to get status of application {}: {}", name, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Error getting pipeline status: {e}"),
+                }),
+            )
+        })
+}
+
+/// List the pipelines deployed in a workspace with their current deployment status
+pub async fn list_pipelines(
+    workspace_slug: &str,
+    app_config: &AppConfig,
+    db_pool: &PgPool,
+) -> Result<Json<Vec<PipelineSummary>>, (StatusCode, Json<DeployResponse>)> {
+    let deployments = database::list_pipeline_deployments(db_pool, workspace_slug)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Error listing pipelines: {e}"),
+                }),
+            )
+        })?;
+
+    let client = connect(workspace_slug, app_config, db_pool).await?;
+    let applications = client.list_applications().await.map_err(|e| {
+        tracing::error!(
+            "Failed to list applications of workspace {}: {}",
+            workspace_slug,
+            e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(DeployResponse {
+                result: format!("Error listing pipelines: {e}"),
+            }),
+        )
+    })?;
+
+    Ok(Json(pipeline_summaries(
+        workspace_slug,
+        deployments,
+        applications,
+    )))
+}
+
+/// Pair the recorded deployments of a workspace with the status of their wadm application.
+/// Pipelines whose application is missing from wadm are reported as undeployed.
+fn pipeline_summaries(
+    workspace_slug: &str,
+    deployments: Vec<(String, String)>,
+    applications: Vec<ApplicationSummary>,
+) -> Vec<PipelineSummary> {
+    let mut statuses: HashMap<String, ApplicationStatus> = applications
+        .into_iter()
+        .filter_map(|application| {
+            let pipeline = config_converter::pipeline_name(workspace_slug, &application.name)?;
+            Some((pipeline.to_string(), application.status))
+        })
+        .collect();
+
+    deployments
+        .into_iter()
+        .map(|(name, version)| {
+            let status = statuses.remove(&name).unwrap_or(ApplicationStatus {
+                status: DeploymentStatus::Undeployed,
+                message: "Not found in wadm".to_string(),
+            });
+            PipelineSummary {
+                name,
+                version,
+                status: status.status,
+                message: status.message,
+            }
+        })
+        .collect()
+}
+
+/// Store a manifest and deploy the version that was stored, returning the application name
+async fn put_and_deploy(client: &impl WadmClient, manifest: &str) -> anyhow::Result<String> {
+    let (name, version) = client.put_application(manifest).await?;
+    client.deploy_application(&name, Some(&version)).await?;
+    Ok(name)
+}
+
+/// Connect to the wadm API of a workspace. The default workspace uses the unprefixed subject,
+/// every other workspace the one under its NATS account.
+async fn connect(
+    workspace_slug: &str,
+    app_config: &AppConfig,
+    db_pool: &PgPool,
+) -> Result<NatsWadmClient, (StatusCode, Json<DeployResponse>)> {
+    let wadm_subject = if workspace_slug == "default" {
+        "wadm.api".to_string()
+    } else {
+        let nats_account = get_nats_account(workspace_slug, db_pool).await?;
+        format!("{}.wadm.api", nats_account)
+    };
+
+    NatsWadmClient::connect(workspace_slug, &wadm_subject, app_config)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create WADM client: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(DeployResponse {
+                    result: format!("Error creating WADM client: {e}"),
+                }),
+            )
+        })
+}
+
 async fn get_nats_account(
     workspace_slug: &str,
     db_pool: &sqlx::Pool<sqlx::Postgres>,
@@ -215,3 +530,250 @@ async fn get_nats_account(
     };
     Ok(nats_account)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockWadmClient {
+        calls: Mutex<Vec<String>>,
+        manifests: Mutex<Vec<String>>,
+        /// Statuses returned in turn, after which the application reports as deployed
+        statuses: Mutex<VecDeque<DeploymentStatus>>,
+    }
+
+    impl MockWadmClient {
+        fn with_statuses(statuses: &[DeploymentStatus]) -> Self {
+            Self {
+                statuses: Mutex::new(statuses.iter().copied().collect()),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl WadmClient for MockWadmClient {
+        async fn put_application(&self, manifest: &str) -> anyhow::Result<(String, String)> {
+            let application: crate::builders::WadmApplication = serde_yaml::from_str(manifest)?;
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("put {}", application.metadata.name));
+            self.manifests.lock().unwrap().push(manifest.to_string());
+            Ok((application.metadata.name, "v1".to_string()))
+        }
+
+        async fn deploy_application(
+            &self,
+            name: &str,
+            version: Option<&str>,
+        ) -> anyhow::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("deploy {name} {}", version.unwrap_or("latest")));
+            Ok(())
+        }
+
+        async fn delete_application(&self, name: &str) -> anyhow::Result<bool> {
+            self.calls.lock().unwrap().push(format!("delete {name}"));
+            Ok(true)
+        }
+
+        async fn get_application_status(&self, name: &str) -> anyhow::Result<ApplicationStatus> {
+            self.calls.lock().unwrap().push(format!("status {name}"));
+            let status = self
+                .statuses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or(DeploymentStatus::Deployed);
+            Ok(ApplicationStatus {
+                status,
+                message: format!("{status:?}"),
+            })
+        }
+
+        async fn list_applications(&self) -> anyhow::Result<Vec<ApplicationSummary>> {
+            self.calls.lock().unwrap().push("list".to_string());
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deploy_pipeline_puts_then_deploys_converted_application() {
+        let payload: DeployRequest = serde_yaml::from_str(
+            r#"
+workspaceSlug: default
+pipeline:
+  name: mine
+  version: 1
+  nodes:
+    - id: webhook
+      label: webhook
+      type: in-http-webhook
+      position:
+        x: 100
+        'y': 100
+      settings:
+        type: in-http-webhook
+        settings:
+          method: POST
+          path: /hook
+    - id: log
+      label: log
+      type: out-log
+      position:
+        x: 200
+        'y': 100
+      depends_on:
+        - webhook
+"#,
+        )
+        .expect("Failed to parse deploy request");
+        let app_config = AppConfig::new().expect("Could not read app config");
+        let client = MockWadmClient::default();
+        let expected = config_converter::convert_pipeline(
+            &payload.pipeline,
+            &payload.workspace_slug,
+            &app_config,
+        )
+        .expect("Failed to convert pipeline");
+
+        let (status, _) = deploy_pipeline(&client, &payload, &expected).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            *client.calls.lock().unwrap(),
+            vec![
+                "put default-mine",
+                "deploy default-mine v1",
+                "status default-mine"
+            ]
+        );
+
+        let manifests = client.manifests.lock().unwrap();
+        let deployed: crate::builders::WadmApplication =
+            serde_yaml::from_str(&manifests[0]).expect("Failed to parse deployed manifest");
+        assert_eq!(deployed, expected);
+    }
+
+    #[tokio::test]
+    async fn test_undeployed_application_is_not_deployed() {
+        let client = MockWadmClient::with_statuses(&[
+            DeploymentStatus::Undeployed,
+            DeploymentStatus::Reconciling,
+        ]);
+
+        assert!(!is_deployed(&client, "default-mine").await);
+        assert!(!is_deployed(&client, "default-mine").await);
+        assert!(is_deployed(&client, "default-mine").await);
+    }
+
+    #[test]
+    fn test_deployment_status_from_wadm() {
+        assert_eq!(
+            DeploymentStatus::from_wadm("Deployed"),
+            DeploymentStatus::Deployed
+        );
+        assert_eq!(
+            DeploymentStatus::from_wadm("reconciling"),
+            DeploymentStatus::Reconciling
+        );
+        assert_eq!(
+            DeploymentStatus::from_wadm("Undeployed"),
+            DeploymentStatus::Undeployed
+        );
+        assert_eq!(
+            DeploymentStatus::from_wadm("Failed"),
+            DeploymentStatus::Failed
+        );
+        assert_eq!(
+            DeploymentStatus::from_wadm("Waiting"),
+            DeploymentStatus::Waiting
+        );
+        assert_eq!(
+            DeploymentStatus::from_wadm("Unhealthy"),
+            DeploymentStatus::Unhealthy
+        );
+        assert_eq!(
+            DeploymentStatus::from_wadm("Something new"),
+            DeploymentStatus::Unknown
+        );
+        assert_eq!(
+            serde_json::to_value(DeploymentStatus::Deployed).unwrap(),
+            "deployed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_deployed() {
+        let interval = Duration::from_millis(1);
+
+        let client = MockWadmClient::with_statuses(&[
+            DeploymentStatus::Undeployed,
+            DeploymentStatus::Reconciling,
+        ]);
+        assert!(
+            wait_until_deployed(&client, "app", Duration::from_secs(5), interval)
+                .await
+                .is_ok()
+        );
+        assert_eq!(client.calls.lock().unwrap().len(), 3);
+
+        let client = MockWadmClient::with_statuses(&[DeploymentStatus::Failed]);
+        let (status, Json(response)) =
+            wait_until_deployed(&client, "app", Duration::from_secs(5), interval)
+                .await
+                .unwrap_err();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.result.contains("failed to deploy"));
+
+        let client = MockWadmClient::with_statuses(&[DeploymentStatus::Reconciling; 1000]);
+        let (status, Json(response)) =
+            wait_until_deployed(&client, "app", Duration::from_millis(20), interval)
+                .await
+                .unwrap_err();
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert!(response.result.contains("Reconciling"));
+    }
+
+    #[test]
+    fn test_pipeline_summaries_only_include_workspace_pipelines() {
+        let application = |name: &str, status| ApplicationSummary {
+            name: name.to_string(),
+            status: ApplicationStatus {
+                status,
+                message: String::new(),
+            },
+        };
+        let applications = vec![
+            application("acme-providers", DeploymentStatus::Deployed),
+            application("acme-orders", DeploymentStatus::Deployed),
+            application("acme-invoices", DeploymentStatus::Failed),
+            application("globex-orders", DeploymentStatus::Reconciling),
+        ];
+        let deployments = vec![
+            ("invoices".to_string(), "2".to_string()),
+            ("orders".to_string(), "1".to_string()),
+            ("removed".to_string(), "3".to_string()),
+        ];
+
+        let summaries = pipeline_summaries("acme", deployments, applications);
+
+        let actual: Vec<_> = summaries
+            .iter()
+            .map(|s| (s.name.as_str(), s.version.as_str(), s.status))
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("invoices", "2", DeploymentStatus::Failed),
+                ("orders", "1", DeploymentStatus::Deployed),
+                ("removed", "3", DeploymentStatus::Undeployed),
+            ]
+        );
+    }
+}
@@ -0,0 +1,588 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type Authentication = { 
+/**
+ * `api_key`, `bearer` or `basic`
+ */
+type: string, config?: AuthenticationConfig, };
+
+export type AuthenticationConfig = { 
+/**
+ * Where an API key is sent, `header` or `query`
+ */
+location: string, 
+/**
+ * Name of the header or query parameter carrying an API key
+ */
+name: string, value: string, 
+/**
+ * Prepended to an API key sent in a header, e.g. `Token`
+ */
+prefix: string, };
+
+/**
+ * AWS credentials, given as the names of secrets holding them
+ */
+export type AwsCredentials = { accessKeyIdSecret: string, secretAccessKeySecret: string, 
+/**
+ * Secret holding the session token of temporary credentials
+ */
+sessionTokenSecret?: string, };
+
+/**
+ * Delay between retries, doubling from `initialMs` after every retry up to `maxMs`, a minute by
+ * default
+ */
+export type Backoff = { initialMs: bigint, maxMs?: bigint, };
+
+export type BigqueryWriteMode = "streaming" | "load";
+
+/**
+ * Stop sending to a destination for `cooldownMs` after `failureThreshold` consecutive failures
+ */
+export type CircuitBreakerSettings = { failureThreshold: number, cooldownMs: bigint, };
+
+/**
+ * Encoding applied to request bodies
+ */
+export type Compression = "none" | "gzip";
+
+export type CsvOptions = { 
+/**
+ * Field delimiter. Defaults to `,`.
+ */
+delimiter?: string, 
+/**
+ * Whether the first record holds the field names. Defaults to true.
+ */
+hasHeaders?: boolean, };
+
+/**
+ * A node another node receives messages from
+ */
+export type DependsOn = string | { node: string, output: string, };
+
+/**
+ * Only forward requests whose event header holds one of the allowed events, e.g. the
+ * `X-GitHub-Event` header of GitHub webhooks
+ */
+export type EventFilter = { 
+/**
+ * Header carrying the event type. Defaults to `X-GitHub-Event`.
+ */
+eventHeader?: string, 
+/**
+ * Events that are forwarded, all others are dropped
+ */
+allowedEvents: Array<string>, };
+
+export type FileFormat = "raw" | "jsonl";
+
+/**
+ * Start a new file once either threshold trips
+ */
+export type FileRotation = { 
+/**
+ * Size a file may grow to
+ */
+maxBytes?: bigint, 
+/**
+ * Time a file is written to
+ */
+intervalMs?: bigint, };
+
+export type Format = "json" | "csv";
+
+export type HttpHeader = { key: string, value: string, };
+
+export type InGraphqlPollerSettings = { 
+/**
+ * URL of the GraphQL endpoint
+ */
+url: string, 
+/**
+ * Query sent on every poll
+ */
+query: string, 
+/**
+ * Variables of the query, as a JSON object
+ */
+variables?: JsonValue, 
+/**
+ * Headers added to every request
+ */
+headers?: Array<HttpHeader>, authentication?: Authentication, 
+/**
+ * Time between two polls of the endpoint
+ */
+intervalMs: bigint, 
+/**
+ * JSONPath of the cursor of the next page in the response, e.g.
+ * `$.data.orders.pageInfo.endCursor`. The cursor found is passed to the next poll as its
+ * `cursor` variable.
+ */
+cursor?: string, };
+
+export type InHttpWebhookSettings = { 
+/**
+ * HTTP method the webhook accepts, e.g. `POST`
+ */
+method: string, 
+/**
+ * Path the webhook is served on
+ */
+path: string, 
+/**
+ * Expected content type of request bodies. Text bodies must be valid UTF-8.
+ */
+contentType?: string, 
+/**
+ * JSON schema request bodies must match
+ */
+requestBodyJsonSchema?: JsonValue, eventFilter?: EventFilter, 
+/**
+ * Request metadata passed to the next node as message metadata, leaving the body unchanged
+ */
+metadata?: RequestMetadata, 
+/**
+ * Respond with the result of the nodes the request flows to, waiting up to this long for
+ * it at each hop. Without it, requests are answered once they're forwarded.
+ */
+replyTimeoutMs?: number, };
+
+export type InMongodbSettings = { 
+/**
+ * `mongodb://` or `mongodb+srv://` connection string
+ */
+connectionString: string, database: string, collection: string, mode: MongodbReadMode, 
+/**
+ * Aggregation stages applied to the change stream or query, e.g. a `$match`
+ */
+pipeline?: Array<JsonValue>, 
+/**
+ * Only used by change streams. Defaults to `default`.
+ */
+fullDocument?: MongodbFullDocument, };
+
+export type InRabbitmqSettings = { 
+/**
+ * Base URL of the RabbitMQ management API messages are consumed through, e.g.
+ * `https://rabbitmq.example.com:15672`
+ */
+url: string, 
+/**
+ * Virtual host of the queue. Defaults to `/`.
+ */
+vhost?: string, queue: string, 
+/**
+ * Exchange the queue is bound to. When set, the queue is declared and bound to it before
+ * consuming.
+ */
+exchange?: string, 
+/**
+ * Routing key the binding to `exchange` matches. Defaults to the queue name.
+ */
+bindingKey?: string, 
+/**
+ * Messages fetched per poll. Defaults to 10.
+ */
+prefetch?: number, 
+/**
+ * Defaults to `auto`
+ */
+ackMode?: RabbitmqAckMode, auth?: Authentication, };
+
+export type InRssReaderSettings = { 
+/**
+ * URL of the RSS or Atom feed
+ */
+feedUrl: string, 
+/**
+ * Time between two polls of the feed
+ */
+pollIntervalMs: bigint, 
+/**
+ * Send `If-None-Match` and `If-Modified-Since` headers to skip unchanged feeds. Defaults to
+ * true.
+ */
+conditionalRequests?: boolean, 
+/**
+ * Emit at most this many new items per poll
+ */
+maxItemsPerPoll?: number, };
+
+export type LambdaInvocationType = "request-response" | "event";
+
+/**
+ * Limits on the size of a single push request. Entries beyond them are sent in further requests.
+ */
+export type LokiBatchSettings = { 
+/**
+ * Entries per request. Defaults to 1000.
+ */
+maxEntries?: number, 
+/**
+ * Size of the log lines in a request. Defaults to 1 MiB.
+ */
+maxBytes?: bigint, 
+/**
+ * Buffer entries across messages, pushing them once a request is full or the oldest entry
+ * has waited this long. Buffered entries are also pushed when the node is flushed. Off by
+ * default, so every message is pushed right away.
+ */
+maxWaitMs?: bigint, };
+
+/**
+ * Which version of a changed document a change stream event carries
+ */
+export type MongodbFullDocument = "default" | "updateLookup" | "whenAvailable" | "required";
+
+export type MongodbReadMode = "changestream" | "poll";
+
+export type MongodbWriteMode = "insert" | "upsert";
+
+export type NoSettings = null;
+
+export type NodeType = "in-aws-s3" | "in-google-gcs" | "in-azure-blob" | "in-postgresql" | "in-mongodb" | "in-mysql" | "in-sqlite" | "in-kafka" | "in-nats" | "in-rabbitmq" | "in-redis" | "in-http-webhook" | "in-http-poller" | "in-graphql-poller" | "in-rss-reader" | "in-google-pubsub" | "in-aws-kinesis" | "in-stripe" | "in-github-webhook" | "processor-wasm" | "processor-format" | "processor-filter" | "out-postgresql" | "out-mongodb" | "out-mysql" | "out-redis" | "out-aws-s3" | "out-google-gcs" | "out-azure-blob" | "out-kafka" | "out-nats" | "out-rabbitmq" | "out-google-pubsub" | "out-graphql-mutation" | "out-slack" | "out-twilio-sms" | "out-http-webhook" | "out-prometheus" | "out-loki" | "out-elasticsearch" | "out-influxdb" | "out-google-bigquery" | "out-snowflake" | "out-aws-lambda" | "out-log" | "out-file";
+
+export type OutAwsLambdaSettings = { 
+/**
+ * Name or ARN of the function
+ */
+functionName: string, 
+/**
+ * e.g. `eu-west-1`
+ */
+region: string, 
+/**
+ * Defaults to `request-response`
+ */
+invocationType?: LambdaInvocationType, credentials: AwsCredentials, 
+/**
+ * Version or alias of the function to invoke. Defaults to `$LATEST`.
+ */
+qualifier?: string, };
+
+export type OutFileSettings = { 
+/**
+ * Path relative to the host's preopened directory; `{{date}}` expands to `YYYY-MM-DD`
+ */
+pathTemplate: string, rotation?: FileRotation, 
+/**
+ * Defaults to `raw`
+ */
+format?: FileFormat, };
+
+export type OutGoogleBigquerySettings = { projectId: string, dataset: string, table: string, 
+/**
+ * Secret holding the JSON key of the service account rows are written as
+ */
+credentialsSecret: string, writeMode: BigqueryWriteMode, 
+/**
+ * Infer the schema of the table from the loaded rows. Only used by the load write mode.
+ * Defaults to false.
+ */
+schemaAutodetect?: boolean, };
+
+export type OutGraphqlMutationSettings = { 
+/**
+ * URL of the GraphQL endpoint
+ */
+url: string, 
+/**
+ * Mutation executed for every message
+ */
+mutation: string, 
+/**
+ * Variables of the mutation. String values, at any depth, are templates rendered with the
+ * message. A string that's a single placeholder, e.g. `{{/order/items}}`, keeps the type of
+ * its value. Defaults to the message itself, which must then be a JSON object.
+ */
+variablesTemplate?: { [key in string]?: JsonValue }, 
+/**
+ * Headers added to every request
+ */
+headers?: Array<HttpHeader>, authentication?: Authentication, };
+
+export type OutHttpWebhookSettings = { 
+/**
+ * HTTP method of the request, e.g. `POST`
+ */
+method: string, 
+/**
+ * URL the request is sent to
+ */
+url: string, 
+/**
+ * Content type of the request body. Defaults to `application/json`.
+ */
+contentType?: string, 
+/**
+ * Set the `Content-Type` header on requests with a body. On by default, turn off to let the
+ * receiving server infer the content type.
+ */
+setContentType?: boolean, 
+/**
+ * Headers added to every request
+ */
+headers?: Array<HttpHeader>, authentication?: Authentication, validation?: Validation, 
+/**
+ * Include request and response bodies in the logs. Off by default as bodies may hold
+ * personal data or secrets.
+ */
+logBodies?: boolean, sign?: WebhookSigning, circuitBreaker?: CircuitBreakerSettings, 
+/**
+ * Compress request bodies, sent with a matching `Content-Encoding`. Off by default.
+ */
+compress?: Compression, };
+
+export type OutLogSettings = { 
+/**
+ * Messages longer than this are truncated in the log
+ */
+maxLogBytes?: number, 
+/**
+ * JSON pointers of the fields to log, as a compact object. Logs the whole message when
+ * unset.
+ */
+fields?: Array<string>, };
+
+export type OutLokiSettings = { 
+/**
+ * Base URL of Loki, e.g. `https://logs.example.com`. Entries are pushed to
+ * `/loki/api/v1/push`.
+ */
+url: string, 
+/**
+ * Labels of the stream an entry is added to, so entries are grouped into a stream per label
+ * set. Values are templates rendered with the message, e.g. `{{/level}}`. A label whose
+ * value is missing from the message is left out.
+ */
+labels: { [key in string]?: string }, auth?: Authentication, 
+/**
+ * JSON pointer to a field holding the time of the entry in Unix epoch milliseconds. Defaults
+ * to the time the message is sent.
+ */
+timestampField?: string, batch?: LokiBatchSettings, };
+
+export type OutMongodbSettings = { 
+/**
+ * `mongodb://` or `mongodb+srv://` connection string
+ */
+connectionString: string, database: string, collection: string, writeMode: MongodbWriteMode, 
+/**
+ * Field of the message documents are matched on when upserting. Defaults to `_id`.
+ */
+idField?: string, };
+
+export type OutRabbitmqSettings = { 
+/**
+ * Base URL of the RabbitMQ management API messages are published through, e.g.
+ * `https://rabbitmq.example.com:15672`
+ */
+url: string, 
+/**
+ * Virtual host of the exchange. Defaults to `/`.
+ */
+vhost?: string, 
+/**
+ * Exchange messages are published to. An empty name is the default exchange, which routes a
+ * message to the queue named by its routing key.
+ */
+exchange: string, 
+/**
+ * Routing key of a message, a template whose `{{/pointer}}` placeholders are read from the
+ * message, e.g. `orders.{{/region}}`
+ */
+routingKey: string, 
+/**
+ * Defaults to `persistent`
+ */
+deliveryMode?: RabbitmqDeliveryMode, auth?: Authentication, declare?: RabbitmqDeclareSettings, };
+
+export type OutSnowflakeSettings = { 
+/**
+ * Account identifier, e.g. `myorg-myaccount`
+ */
+account: string, warehouse: string, database: string, schema: string, table: string, auth: SnowflakeAuth, writeMode: SnowflakeWriteMode, 
+/**
+ * Columns rows are matched on when merging
+ */
+keyColumns?: Array<string>, };
+
+/**
+ * A pipeline of nodes passing messages from sources through processors to sinks
+ */
+export type Pipeline = { name: string, version: string, nodes: Array<PipelineNode>, 
+/**
+ * Id of the sink node receiving the messages dead-lettered by any other node
+ */
+deadLetter?: string, 
+/**
+ * Number of instances of the in-internal and out-internal components passing messages
+ * between nodes. Nodes can override it with their own `helper_instances`.
+ */
+helperInstances?: number, };
+
+export type PipelineNode = { 
+/**
+ * Unique within the pipeline
+ */
+id: string, label: string, type: NodeType, 
+/**
+ * Number of instances to run. Takes precedence over instances set in the settings.
+ */
+instances?: number, 
+/**
+ * Number of instances of the node's in-internal and out-internal components. Takes
+ * precedence over the pipeline's `helperInstances`. The host handles at most this many of the
+ * node's messages at once, which is how a slow node pushes back on the nodes before it.
+ */
+helper_instances?: number, position: XYPosition, settings?: PipelineNodeSettings, 
+/**
+ * Nodes this node receives messages from
+ */
+depends_on?: Array<DependsOn>, resilience?: Resilience, };
+
+/**
+ * Settings of a node, tagged with its node type
+ */
+export type PipelineNodeSettings = { "type": "in-aws-s3", "settings": NoSettings } | { "type": "in-google-gcs", "settings": NoSettings } | { "type": "in-azure-blob", "settings": NoSettings } | { "type": "in-postgresql", "settings": NoSettings } | { "type": "in-mongodb", "settings": InMongodbSettings } | { "type": "in-mysql", "settings": NoSettings } | { "type": "in-sqlite", "settings": NoSettings } | { "type": "in-kafka", "settings": NoSettings } | { "type": "in-nats", "settings": NoSettings } | { "type": "in-rabbitmq", "settings": InRabbitmqSettings } | { "type": "in-redis", "settings": NoSettings } | { "type": "in-http-webhook", "settings": InHttpWebhookSettings } | { "type": "in-http-poller", "settings": NoSettings } | { "type": "in-graphql-poller", "settings": InGraphqlPollerSettings } | { "type": "in-rss-reader", "settings": InRssReaderSettings } | { "type": "in-google-pubsub", "settings": NoSettings } | { "type": "in-aws-kinesis", "settings": NoSettings } | { "type": "in-stripe", "settings": NoSettings } | { "type": "in-github-webhook", "settings": NoSettings } | { "type": "processor-wasm", "settings": ProcessorWasmSettings } | { "type": "processor-format", "settings": ProcessorFormatSettings } | { "type": "processor-filter", "settings": ProcessorFilterSettings } | { "type": "out-postgresql", "settings": NoSettings } | { "type": "out-mongodb", "settings": OutMongodbSettings } | { "type": "out-mysql", "settings": NoSettings } | { "type": "out-redis", "settings": NoSettings } | { "type": "out-aws-s3", "settings": NoSettings } | { "type": "out-google-gcs", "settings": NoSettings } | { "type": "out-azure-blob", "settings": NoSettings } | { "type": "out-kafka", "settings": NoSettings } | { "type": "out-nats", "settings": NoSettings } | { "type": "out-rabbitmq", "settings": OutRabbitmqSettings } | { "type": "out-google-pubsub", "settings": NoSettings } | { "type": "out-graphql-mutation", "settings": OutGraphqlMutationSettings } | { "type": "out-slack", "settings": NoSettings } | { "type": "out-twilio-sms", "settings": NoSettings } | { "type": "out-http-webhook", "settings": OutHttpWebhookSettings } | { "type": "out-prometheus", "settings": NoSettings } | { "type": "out-loki", "settings": OutLokiSettings } | { "type": "out-elasticsearch", "settings": NoSettings } | { "type": "out-influxdb", "settings": NoSettings } | { "type": "out-google-bigquery", "settings": OutGoogleBigquerySettings } | { "type": "out-snowflake", "settings": OutSnowflakeSettings } | { "type": "out-aws-lambda", "settings": OutAwsLambdaSettings } | { "type": "out-log", "settings": OutLogSettings } | { "type": "out-file", "settings": OutFileSettings };
+
+export type ProcessorFilterSettings = { 
+/**
+ * A JSON pointer, optionally compared to a JSON value, e.g. `/status == "active"` or
+ * `/amount >= 100`. A bare pointer passes when the value exists and isn't `null` or `false`.
+ */
+predicate: string, };
+
+export type ProcessorFormatSettings = { 
+/**
+ * Format of incoming messages
+ */
+from: Format, 
+/**
+ * Format messages are converted to
+ */
+to: Format, csvOptions?: CsvOptions, };
+
+export type ProcessorWasmSettings = { 
+/**
+ * OCI reference of a published component, or the file name of an uploaded one
+ */
+source: string, 
+/**
+ * Number of instances to run
+ */
+instances: number, 
+/**
+ * `sha256:` digest the component is pinned to. An uploaded component must hash to it, a
+ * published reference must be pinned to it, e.g. `ghcr.io/acme/processor@sha256:...`.
+ */
+digest?: string, 
+/**
+ * Whether the component implements `run-bytes`, to receive messages that aren't UTF-8 text.
+ * Components built before `pipestack:customer@0.1.1` only implement `run`. Defaults to false.
+ */
+bytesInput?: boolean, };
+
+/**
+ * When a consumed message is removed from its queue
+ */
+export type RabbitmqAckMode = "auto" | "manual";
+
+/**
+ * Declare the exchange before publishing to it, creating it if it doesn't exist
+ */
+export type RabbitmqDeclareSettings = { 
+/**
+ * `direct`, `fanout`, `topic` or `headers`. Defaults to `direct`.
+ */
+exchangeType?: string, 
+/**
+ * Keep the exchange when the broker restarts. Defaults to true.
+ */
+durable?: boolean, 
+/**
+ * Delete the exchange once no queue is bound to it. Defaults to false.
+ */
+autoDelete?: boolean, };
+
+/**
+ * How the broker stores a published message
+ */
+export type RabbitmqDeliveryMode = "persistent" | "transient";
+
+/**
+ * Request metadata in-http forwards along with the raw body. The request id is always included.
+ */
+export type RequestMetadata = { 
+/**
+ * Include the request method, under `x-request-method`
+ */
+includeMethod?: boolean, 
+/**
+ * Names of the request headers to include. Headers the request doesn't have are left out.
+ */
+headers?: Array<string>, };
+
+/**
+ * How a node handles failures, for any node type
+ */
+export type Resilience = { 
+/**
+ * Attempts after the first before a message is given up on
+ */
+retries?: number, 
+/**
+ * Time allowed for a single attempt of a node making requests, e.g. out-http-webhook
+ */
+timeoutMs?: bigint, backoff?: Backoff, };
+
+export type SigningAlgorithm = "sha256" | "sha512";
+
+/**
+ * How a Snowflake user authenticates. Credentials are given as the names of secrets holding
+ * them, so they aren't part of the pipeline.
+ */
+export type SnowflakeAuth = { "type": "key-pair", user: string, 
+/**
+ * Secret holding the PEM-encoded private key
+ */
+privateKeySecret: string, 
+/**
+ * Secret holding the passphrase of an encrypted private key
+ */
+passphraseSecret?: string, } | { "type": "password", user: string, 
+/**
+ * Secret holding the user's password
+ */
+passwordSecret: string, };
+
+export type SnowflakeWriteMode = "insert" | "merge";
+
+export type Validation = { timeout: number, };
+
+/**
+ * Sign outgoing request bodies with an HMAC so receivers can verify them. The signature is sent
+ * as `<algorithm>=<hex digest>`, e.g. `sha256=...`, like GitHub webhook signatures.
+ */
+export type WebhookSigning = { 
+/**
+ * Header the signature is sent in, e.g. `X-Hub-Signature-256`
+ */
+headerName: string, 
+/**
+ * Key shared with the receiver
+ */
+secret: string, 
+/**
+ * Defaults to `sha256`
+ */
+algorithm?: SigningAlgorithm, };
+
+/**
+ * Position of a node in the pipeline editor
+ */
+export type XYPosition = { x: number, y: number, };
@@ -0,0 +1,128 @@
+use crate::Authentication;
+
+/// Apply `auth` to an outgoing HTTP request, appending its headers to `headers` and returning
+/// `url` with any query parameter it adds.
+///
+/// `api_key` auth is sent in the header or query parameter it names, `bearer` and `basic` auth
+/// as an `Authorization` header. Nothing is applied when an error is returned.
+pub fn apply_authentication(
+    headers: &mut Vec<(String, String)>,
+    url: &str,
+    auth: &Authentication,
+) -> Result<String, String> {
+    let Some(config) = &auth.config else {
+        return Err(format!(
+            "Authentication config is missing for auth type: {}",
+            auth.auth_type
+        ));
+    };
+
+    match auth.auth_type.as_str() {
+        "api_key" => match config.location.as_str() {
+            "header" => {
+                let value = if config.prefix.is_empty() {
+                    config.value.clone()
+                } else {
+                    format!("{} {}", config.prefix, config.value)
+                };
+                headers.push((config.name.clone(), value));
+                Ok(url.to_string())
+            }
+            "query" => {
+                let separator = if url.contains('?') { "&" } else { "?" };
+                Ok(format!("{url}{separator}{}={}", config.name, config.value))
+            }
+            location => Err(format!("Unsupported API key location: {location}")),
+        },
+        "bearer" => {
+            headers.push((
+                "Authorization".to_string(),
+                format!("Bearer {}", config.value),
+            ));
+            Ok(url.to_string())
+        }
+        "basic" => {
+            headers.push((
+                "Authorization".to_string(),
+                format!("Basic {}", config.value),
+            ));
+            Ok(url.to_string())
+        }
+        auth_type => Err(format!("Unsupported authentication type: {auth_type}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuthenticationConfig;
+
+    fn auth(auth_type: &str, location: &str, prefix: &str) -> Authentication {
+        Authentication {
+            auth_type: auth_type.to_string(),
+            config: Some(AuthenticationConfig {
+                location: location.to_string(),
+                name: "X-Api-Key".to_string(),
+                value: "secret".to_string(),
+                prefix: prefix.to_string(),
+            }),
+        }
+    }
+
+    fn header(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_api_key_header() {
+        let mut headers = vec![];
+        let url = apply_authentication(&mut headers, "/hook", &auth("api_key", "header", ""));
+        assert_eq!(url.unwrap(), "/hook");
+        assert_eq!(headers, vec![header("X-Api-Key", "secret")]);
+
+        let mut headers = vec![];
+        apply_authentication(&mut headers, "/hook", &auth("api_key", "header", "Token")).unwrap();
+        assert_eq!(headers, vec![header("X-Api-Key", "Token secret")]);
+    }
+
+    #[test]
+    fn test_api_key_query() {
+        let mut headers = vec![];
+        let url = apply_authentication(&mut headers, "/hook", &auth("api_key", "query", ""));
+        assert_eq!(url.unwrap(), "/hook?X-Api-Key=secret");
+        assert!(headers.is_empty());
+
+        let url = apply_authentication(&mut headers, "/hook?a=b", &auth("api_key", "query", ""));
+        assert_eq!(url.unwrap(), "/hook?a=b&X-Api-Key=secret");
+    }
+
+    #[test]
+    fn test_bearer_and_basic() {
+        let mut headers = vec![];
+        let url = apply_authentication(&mut headers, "/hook", &auth("bearer", "", ""));
+        assert_eq!(url.unwrap(), "/hook");
+        assert_eq!(headers, vec![header("Authorization", "Bearer secret")]);
+
+        let mut headers = vec![];
+        apply_authentication(&mut headers, "/hook", &auth("basic", "", "")).unwrap();
+        assert_eq!(headers, vec![header("Authorization", "Basic secret")]);
+    }
+
+    #[test]
+    fn test_unsupported_or_incomplete_auth() {
+        let mut headers = vec![];
+        let err = apply_authentication(&mut headers, "/hook", &auth("digest", "", "")).unwrap_err();
+        assert_eq!(err, "Unsupported authentication type: digest");
+
+        let err = apply_authentication(&mut headers, "/hook", &auth("api_key", "cookie", ""))
+            .unwrap_err();
+        assert_eq!(err, "Unsupported API key location: cookie");
+
+        let missing_config = Authentication {
+            auth_type: "bearer".to_string(),
+            config: None,
+        };
+        assert!(apply_authentication(&mut headers, "/hook", &missing_config).is_err());
+        assert!(headers.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+//! Check a node's settings before deploying a pipeline using them:
+//!
+//! ```sh
+//! cargo run -p shared --bin validate-node -- out-http-webhook settings.json
+//! ```
+use shared::PipelineNodeSettings;
+use std::{path::Path, process::ExitCode};
+
+/// Read the settings of a `node_type` node from a JSON file and validate them
+fn validate_file(node_type: &str, path: &Path) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let settings: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not valid JSON: {e}", path.display()))?;
+
+    let settings: PipelineNodeSettings = serde_json::from_value(serde_json::json!({
+        "type": node_type,
+        "settings": settings,
+    }))
+    .map_err(|e| format!("Invalid {node_type} settings: {e}"))?;
+    settings
+        .validate()
+        .map_err(|e| format!("Invalid {node_type} settings: {e}"))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [node_type, path] = args.as_slice() else {
+        eprintln!("Usage: validate-node <node-type> <settings.json>");
+        return ExitCode::from(2);
+    };
+
+    match validate_file(node_type, Path::new(path)) {
+        Ok(()) => {
+            println!("{path}: valid {node_type} settings");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("validate-node-{}-{name}.json", std::process::id()));
+        std::fs::write(&path, contents).expect("Failed to write settings file");
+        path
+    }
+
+    #[test]
+    fn test_valid_out_http_webhook_settings() {
+        let path = settings_file(
+            "good",
+            r#"{"method": "POST", "url": "https://example.com/hook"}"#,
+        );
+
+        assert_eq!(validate_file("out-http-webhook", &path), Ok(()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_out_http_webhook_settings() {
+        let path = settings_file("bad", r#"{"method": "POST", "url": "example.com/hook"}"#);
+
+        let err = validate_file("out-http-webhook", &path).unwrap_err();
+        assert_eq!(
+            err,
+            r#"Invalid out-http-webhook settings: url: "example.com/hook" must start with http:// or https://"#
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_settings_of_unknown_node_type() {
+        let path = settings_file("unknown", "{}");
+
+        let err = validate_file("out-carrier-pigeon", &path).unwrap_err();
+        assert!(
+            err.starts_with("Invalid out-carrier-pigeon settings"),
+            "{err}"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Envelope version written by this version of the nodes
+pub const ENVELOPE_VERSION: u32 = 2;
+
+/// Header carrying the correlation id of the request a message originates from
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Metadata key carrying the method of the request a message originates from
+pub const METHOD_METADATA_KEY: &str = "x-request-method";
+
+/// A message passed between nodes together with the request metadata it arrived with.
+///
+/// Versions:
+/// - 1: `body` and `headers`, without a `v` field
+/// - 2: adds `v` and `query`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Envelope {
+    pub body: String,
+    pub headers: BTreeMap<String, String>,
+    pub query: BTreeMap<String, String>,
+}
+
+/// An envelope read by [`Envelope::decode`], along with the version it was written as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedEnvelope {
+    pub envelope: Envelope,
+    pub version: u32,
+}
+
+impl DecodedEnvelope {
+    /// Describes what was lost reading an envelope written as another version, for the caller
+    /// to log. `None` when the envelope was written as the current version.
+    pub fn downgrade_note(&self) -> Option<String> {
+        match self.version {
+            ENVELOPE_VERSION => None,
+            1 => Some("Read a v1 envelope, it carries no query parameters".to_string()),
+            version => Some(format!(
+                "Read a v{version} envelope as v{ENVELOPE_VERSION}, fields added after v{ENVELOPE_VERSION} are ignored"
+            )),
+        }
+    }
+}
+
+/// Every field any envelope version has had. Unknown fields are ignored so newer envelopes can
+/// still be read.
+#[derive(Deserialize)]
+struct AnyVersion {
+    #[serde(default = "first_version")]
+    v: u32,
+    body: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default)]
+    query: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct CurrentVersion<'a> {
+    v: u32,
+    body: &'a str,
+    headers: &'a BTreeMap<String, String>,
+    query: &'a BTreeMap<String, String>,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+impl Envelope {
+    pub fn new(body: impl Into<String>) -> Self {
+        Self {
+            body: body.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Correlation id of the request the message originates from
+    pub fn request_id(&self) -> Option<&str> {
+        self.headers.get(REQUEST_ID_HEADER).map(String::as_str)
+    }
+
+    /// Serialize as the current envelope version
+    pub fn encode(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&CurrentVersion {
+            v: ENVELOPE_VERSION,
+            body: &self.body,
+            headers: &self.headers,
+            query: &self.query,
+        })
+    }
+
+    /// Read an envelope written as any version, including ones newer than this reader
+    pub fn decode(input: &str) -> Result<DecodedEnvelope, serde_json::Error> {
+        let any: AnyVersion = serde_json::from_str(input)?;
+        let query = if any.v < 2 {
+            BTreeMap::new()
+        } else {
+            any.query
+        };
+
+        Ok(DecodedEnvelope {
+            envelope: Envelope {
+                body: any.body,
+                headers: any.headers,
+                query,
+            },
+            version: any.v,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_v1_envelope() {
+        let decoded =
+            Envelope::decode(r#"{"body":"hello","headers":{"content-type":"text/plain"}}"#)
+                .unwrap();
+
+        assert_eq!(decoded.version, 1);
+        assert_eq!(decoded.envelope.body, "hello");
+        assert_eq!(decoded.envelope.headers["content-type"], "text/plain");
+        assert!(decoded.envelope.query.is_empty());
+        assert!(decoded.downgrade_note().is_some());
+    }
+
+    #[test]
+    fn test_decode_v2_envelope() {
+        let decoded = Envelope::decode(
+            r#"{"v":2,"body":"hello","headers":{"content-type":"text/plain"},"query":{"page":"2"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(decoded.version, 2);
+        assert_eq!(decoded.envelope.body, "hello");
+        assert_eq!(decoded.envelope.headers["content-type"], "text/plain");
+        assert_eq!(decoded.envelope.query["page"], "2");
+        assert_eq!(decoded.downgrade_note(), None);
+    }
+
+    #[test]
+    fn test_decode_newer_envelope_ignores_unknown_fields() {
+        let decoded = Envelope::decode(r#"{"v":3,"body":"hello","trace":{"id":"abc"}}"#).unwrap();
+
+        assert_eq!(decoded.version, 3);
+        assert_eq!(decoded.envelope, Envelope::new("hello"));
+        assert!(decoded.downgrade_note().unwrap().contains("v3"));
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        let mut envelope = Envelope::new("hello");
+        envelope
+            .headers
+            .insert("x-request-id".to_string(), "1".to_string());
+        envelope.query.insert("page".to_string(), "2".to_string());
+
+        let encoded = envelope.encode().unwrap();
+        assert!(encoded.starts_with(r#"{"v":2,"#));
+
+        let decoded = Envelope::decode(&encoded).unwrap();
+        assert_eq!(decoded.version, ENVELOPE_VERSION);
+        assert_eq!(decoded.envelope, envelope);
+        assert_eq!(decoded.envelope.request_id(), Some("1"));
+    }
+}
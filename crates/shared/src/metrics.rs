@@ -0,0 +1,51 @@
+/// Metrics a node reports through the `pipestack:metrics` interface. Nodes implement it on top of
+/// their bindings, ignoring failures as metrics are best effort.
+pub trait MetricsRecorder {
+    fn record_processed(&self, count: u64);
+    fn record_latency(&self, ms: u64);
+    fn record_error(&self, kind: &str);
+}
+
+/// Record a message that took `latency_ms` to handle, as processed when `error_kind` is `None`
+/// and as an error of that kind otherwise
+pub fn record_outcome(recorder: &impl MetricsRecorder, latency_ms: u64, error_kind: Option<&str>) {
+    recorder.record_latency(latency_ms);
+    match error_kind {
+        None => recorder.record_processed(1),
+        Some(kind) => recorder.record_error(kind),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Recorded(RefCell<Vec<String>>);
+
+    impl MetricsRecorder for Recorded {
+        fn record_processed(&self, count: u64) {
+            self.0.borrow_mut().push(format!("processed {count}"));
+        }
+
+        fn record_latency(&self, ms: u64) {
+            self.0.borrow_mut().push(format!("latency {ms}"));
+        }
+
+        fn record_error(&self, kind: &str) {
+            self.0.borrow_mut().push(format!("error {kind}"));
+        }
+    }
+
+    #[test]
+    fn test_record_outcome() {
+        let recorded = Recorded::default();
+        record_outcome(&recorded, 12, None);
+        assert_eq!(*recorded.0.borrow(), ["latency 12", "processed 1"]);
+
+        let recorded = Recorded::default();
+        record_outcome(&recorded, 30, Some("http-status"));
+        assert_eq!(*recorded.0.borrow(), ["latency 30", "error http-status"]);
+    }
+}
@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+/// Key naming the output a processor sends its result on, `{"$output": "error", "data": ...}`.
+/// Results without it are sent on the default output.
+pub const OUTPUT_KEY: &str = "$output";
+
+/// Key of the message in a result sent on a named output
+const DATA_KEY: &str = "data";
+
+/// Config key out-internal gets the subjects of a node's named outputs under, as a JSON object
+/// by output name
+pub const OUTPUT_TOPICS_CONFIG_KEY: &str = "output-topics";
+
+/// Result of a processor sent on a named output, as its output name and message. `None` for
+/// results on the default output. A string message is sent as is, any other JSON value as JSON.
+pub fn split_named_output(result: &str) -> Option<(String, String)> {
+    let Ok(Value::Object(mut object)) = serde_json::from_str::<Value>(result) else {
+        return None;
+    };
+    if object.len() != 2 {
+        return None;
+    }
+    let Some(Value::String(output)) = object.remove(OUTPUT_KEY) else {
+        return None;
+    };
+    let message = match object.remove(DATA_KEY)? {
+        Value::String(message) => message,
+        data => data.to_string(),
+    };
+    Some((output, message))
+}
+
+/// Subject the nodes depending on the named `output` of a node subscribe to, instead of the step
+/// topic they'd get the node's default output on
+pub fn output_subject(topic: &str, output: &str) -> String {
+    format!("{topic}.out-{output}")
+}
+
+/// Whether `output` can name an output: lowercase letters, digits, dashes and underscores, so
+/// it can be part of a subject
+pub fn is_valid_output_name(output: &str) -> bool {
+    !output.is_empty()
+        && output
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_named_output() {
+        assert_eq!(
+            split_named_output(r#"{"$output": "error", "data": {"code": 7}}"#),
+            Some(("error".to_string(), r#"{"code":7}"#.to_string()))
+        );
+        assert_eq!(
+            split_named_output(r#"{"$output": "success", "data": "plain text"}"#),
+            Some(("success".to_string(), "plain text".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_default_output() {
+        for result in [
+            "plain text",
+            r#"{"data": 1}"#,
+            r#"{"$output": "error"}"#,
+            r#"{"$output": 1, "data": 1}"#,
+            r#"{"$output": "error", "data": 1, "other": 2}"#,
+        ] {
+            assert_eq!(split_named_output(result), None, "{result:?}");
+        }
+    }
+
+    #[test]
+    fn test_output_names() {
+        assert!(is_valid_output_name("error"));
+        assert!(is_valid_output_name("not_found-2"));
+        assert!(!is_valid_output_name(""));
+        assert!(!is_valid_output_name("Error"));
+        assert!(!is_valid_output_name("a.b"));
+        assert_eq!(
+            output_subject("pipestack.acme.orders.step-3-in", "error"),
+            "pipestack.acme.orders.step-3-in.out-error"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Paths of the fields of `input` that were ignored when deserializing it into `parsed`, e.g.
+/// `nodes[0].instance` for a typo of `instances`. Fields are found by serializing `parsed` back,
+/// as every field a type knows is serialized unless it's unset, so fields set to `null` are
+/// never reported.
+pub fn unknown_fields(input: &Value, parsed: &impl Serialize) -> Result<Vec<String>, String> {
+    let known = serde_json::to_value(parsed).map_err(|e| e.to_string())?;
+    let mut unknown = Vec::new();
+    collect_unknown(input, &known, "", &mut unknown);
+    Ok(unknown)
+}
+
+fn collect_unknown(input: &Value, known: &Value, path: &str, unknown: &mut Vec<String>) {
+    match (input, known) {
+        (Value::Object(input), Value::Object(known)) => {
+            for (key, value) in input {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match known.get(key) {
+                    Some(known) => collect_unknown(value, known, &field_path, unknown),
+                    None if !value.is_null() => unknown.push(field_path),
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(input), Value::Array(known)) => {
+            for (index, (value, known)) in input.iter().zip(known).enumerate() {
+                collect_unknown(value, known, &format!("{path}[{index}]"), unknown);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pipeline;
+
+    const PIPELINE: &str = r#"{
+        "name": "orders",
+        "version": "1",
+        "nodes": [
+            {
+                "id": "webhook",
+                "label": "webhook",
+                "type": "in-http-webhook",
+                "position": {"x": 0, "y": 0},
+                "settings": {"type": "in-http-webhook", "settings": {"method": "POST", "path": "orders"}}
+            },
+            {
+                "id": "log",
+                "label": "log",
+                "type": "out-log",
+                "instance": 5,
+                "position": {"x": 0, "y": 0},
+                "settings": {"type": "out-log", "settings": {"maxLogBytes": 100}},
+                "depends_on": ["webhook"]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_typo_is_an_unknown_field() {
+        let input: Value = serde_json::from_str(PIPELINE).unwrap();
+        // Lenient deserialization drops the typo
+        let pipeline: Pipeline = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(pipeline.nodes[1].instances, None);
+
+        assert_eq!(
+            unknown_fields(&input, &pipeline),
+            Ok(vec!["nodes[1].instance".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_unknown_settings_fields() {
+        let mut input: Value = serde_json::from_str(PIPELINE).unwrap();
+        input["nodes"][0]["settings"]["settings"]["methd"] = "GET".into();
+        input["nodes"][1]["instance"] = Value::Null;
+        input["deadLetter"] = Value::Null;
+        let pipeline: Pipeline = serde_json::from_value(input.clone()).unwrap();
+
+        assert_eq!(
+            unknown_fields(&input, &pipeline),
+            Ok(vec!["nodes[0].settings.settings.methd".to_string()])
+        );
+    }
+}
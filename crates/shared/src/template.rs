@@ -0,0 +1,331 @@
+//! Rendering text from a message with `{{/pointer}}` placeholders, so every sink reshapes
+//! messages with the same syntax, e.g. `Order {{/order/id}} for {{/name | default: "guest"}}`.
+//!
+//! A placeholder reads the value at a JSON pointer of the message, `{{}}` being the whole
+//! message. Strings are rendered as is and other values as JSON. Its value goes through the
+//! filters that follow it, in order:
+//! - `upper` uppercases the text
+//! - `json` renders the value as JSON, quoting strings, e.g. to embed it in a JSON body
+//! - `default: <JSON value>` replaces a missing or null value
+//!
+//! Templates only read the message: there are no expressions, loops or includes.
+
+use serde_json::Value;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The template can't be parsed, e.g. a placeholder isn't closed
+    Syntax(String),
+    /// A placeholder's value is missing or null and has no default
+    MissingValue(String),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "Invalid template: {message}"),
+            Self::MissingValue(pointer) => {
+                write!(f, "Template value {pointer:?} is missing from the message")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Upper,
+    Json,
+    Default(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder {
+        pointer: String,
+        filters: Vec<Filter>,
+    },
+}
+
+/// A parsed template, which can be rendered for any number of messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let placeholder = &rest[start + 2..];
+            let end = placeholder.find("}}").ok_or_else(|| {
+                TemplateError::Syntax(format!("placeholder at byte {start} isn't closed"))
+            })?;
+            segments.push(parse_placeholder(&placeholder[..end])?);
+            rest = &placeholder[end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Whether the template has no placeholders, so it renders the same for every message
+    pub fn is_literal(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|segment| matches!(segment, Segment::Text(_)))
+    }
+
+    pub fn render(&self, input: &Value) -> Result<String, TemplateError> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Placeholder { pointer, filters } => {
+                    rendered.push_str(&text(placeholder_value(input, pointer, filters)?));
+                }
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Render to a JSON value. A template that's a single placeholder keeps the type of its
+    /// value, e.g. `{{/order/items}}` renders an array, anything else renders a string.
+    pub fn render_value(&self, input: &Value) -> Result<Value, TemplateError> {
+        match self.segments.as_slice() {
+            [Segment::Placeholder { pointer, filters }] => {
+                placeholder_value(input, pointer, filters)
+            }
+            _ => self.render(input).map(Value::String),
+        }
+    }
+}
+
+/// Render `template` for a single message
+pub fn render_template(template: &str, input: &Value) -> Result<String, TemplateError> {
+    Template::parse(template)?.render(input)
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Segment, TemplateError> {
+    let mut parts = split_filters(placeholder).into_iter();
+    let pointer = parts.next().unwrap_or_default().trim().to_string();
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(TemplateError::Syntax(format!(
+            "{pointer:?} must be a JSON pointer starting with /"
+        )));
+    }
+    let filters = parts
+        .map(|filter| parse_filter(filter.trim()))
+        .collect::<Result<_, _>>()?;
+    Ok(Segment::Placeholder { pointer, filters })
+}
+
+fn parse_filter(filter: &str) -> Result<Filter, TemplateError> {
+    match filter {
+        "upper" => Ok(Filter::Upper),
+        "json" => Ok(Filter::Json),
+        _ => {
+            let argument = filter
+                .strip_prefix("default")
+                .and_then(|rest| rest.trim_start().strip_prefix(':'))
+                .ok_or_else(|| TemplateError::Syntax(format!("unknown filter {filter:?}")))?;
+            serde_json::from_str(argument.trim())
+                .map(Filter::Default)
+                .map_err(|_| {
+                    TemplateError::Syntax(format!(
+                        "default {:?} isn't a JSON value, e.g. \"guest\"",
+                        argument.trim()
+                    ))
+                })
+        }
+    }
+}
+
+/// Split a placeholder on the `|` separating its filters, leaving those in quoted strings
+fn split_filters(placeholder: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (index, c) in placeholder.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '|' if !quoted => {
+                parts.push(&placeholder[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&placeholder[start..]);
+    parts
+}
+
+fn placeholder_value(
+    input: &Value,
+    pointer: &str,
+    filters: &[Filter],
+) -> Result<Value, TemplateError> {
+    let value = input.pointer(pointer).filter(|value| !value.is_null());
+    apply_filters(value.cloned(), filters)
+        .ok_or_else(|| TemplateError::MissingValue(pointer.to_string()))
+}
+
+/// Value after `filters`, None when it's missing
+fn apply_filters(mut value: Option<Value>, filters: &[Filter]) -> Option<Value> {
+    for filter in filters {
+        value = match filter {
+            Filter::Default(default) => value.or_else(|| Some(default.clone())),
+            Filter::Upper => value.map(|value| Value::String(text(value).to_uppercase())),
+            Filter::Json => value.map(|value| Value::String(value.to_string())),
+        };
+    }
+    value
+}
+
+fn text(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn order() -> Value {
+        json!({
+            "order": {
+                "id": "o-42",
+                "total": 19.5,
+                "items": [{"sku": "A1", "name": "Mug \"large\""}],
+                "tags": ["gift", "express"],
+                "notes": null
+            },
+            "customer": {"name": "Ada", "a/b": "slash"}
+        })
+    }
+
+    #[test]
+    fn test_nested_pointers() {
+        assert_eq!(
+            render_template(
+                "Order {{/order/id}} ({{ /order/total }}): {{/order/items/0/sku}}, {{/customer/a~1b}}",
+                &order()
+            ),
+            Ok("Order o-42 (19.5): A1, slash".to_string())
+        );
+        assert_eq!(
+            render_template("{{/order/tags}}", &order()),
+            Ok(r#"["gift","express"]"#.to_string())
+        );
+        assert_eq!(
+            render_template("{{}}", &json!("plain")),
+            Ok("plain".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_values_and_default() {
+        assert_eq!(
+            render_template("Hi {{/customer/nickname}}", &order()),
+            Err(TemplateError::MissingValue(
+                "/customer/nickname".to_string()
+            ))
+        );
+        assert_eq!(
+            render_template(r#"Hi {{/customer/nickname | default: "friend"}}"#, &order()),
+            Ok("Hi friend".to_string())
+        );
+        // Null values count as missing, and defaults may be any JSON value
+        assert_eq!(
+            render_template("{{/order/notes | default: 0}}", &order()),
+            Ok("0".to_string())
+        );
+        assert_eq!(
+            render_template(r#"{{/customer/name | default: "guest"}}"#, &order()),
+            Ok("Ada".to_string())
+        );
+        assert_eq!(
+            render_template(r#"{{/customer/title | default: "a | b"}}"#, &order()),
+            Ok("a | b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_filter() {
+        assert_eq!(
+            render_template(r#"{"text": {{/order/items/0/name | json}}}"#, &order()),
+            Ok(r#"{"text": "Mug \"large\""}"#.to_string())
+        );
+        assert_eq!(
+            render_template("{{/order/total | json}}", &order()),
+            Ok("19.5".to_string())
+        );
+        assert_eq!(
+            render_template("{{/order/notes | default: null | json}}", &order()),
+            Ok("null".to_string())
+        );
+    }
+
+    #[test]
+    fn test_filters_are_chained() {
+        assert_eq!(
+            render_template("{{/customer/name | upper}}", &order()),
+            Ok("ADA".to_string())
+        );
+        assert_eq!(
+            render_template(
+                r#"{{/customer/nickname | default: "guest" | upper | json}}"#,
+                &order()
+            ),
+            Ok(r#""GUEST""#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        for template in [
+            "Order {{/order/id",
+            "{{order.id}}",
+            "{{/order/id | lower}}",
+            "{{/order/id | default: guest}}",
+        ] {
+            assert!(
+                matches!(Template::parse(template), Err(TemplateError::Syntax(_))),
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_literal_templates() {
+        let template = Template::parse("orders.created").unwrap();
+        assert!(template.is_literal());
+        assert_eq!(
+            template.render(&Value::Null),
+            Ok("orders.created".to_string())
+        );
+        assert!(!Template::parse("orders.{{/region}}").unwrap().is_literal());
+    }
+
+    #[test]
+    fn test_render_value_keeps_single_placeholder_types() {
+        let render = |template: &str| Template::parse(template).unwrap().render_value(&order());
+        assert_eq!(render("{{/order/total}}"), Ok(json!(19.5)));
+        assert_eq!(render("{{/order/tags}}"), Ok(json!(["gift", "express"])));
+        assert_eq!(render("{{/order/notes | default: 0}}"), Ok(json!(0)));
+        assert_eq!(render("{{/order/total | json}}"), Ok(json!("19.5")));
+        assert_eq!(render("Total {{/order/total}}"), Ok(json!("Total 19.5")));
+        assert_eq!(render("ups"), Ok(json!("ups")));
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+
+use crate::{Pipeline, PipelineNode};
+
+/// Nodes that can't be ordered because they are part of a cycle or depend on a missing node
+#[derive(Debug, PartialEq)]
+pub struct CycleError {
+    /// Ids of the unordered nodes, in pipeline order
+    pub node_ids: Vec<String>,
+}
+
+impl std::fmt::Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Nodes are not reachable from any source node (missing dependency or cycle): {}",
+            self.node_ids.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+impl Pipeline {
+    /// Nodes ordered so that every node comes after the nodes it depends on. Nodes that are
+    /// ready at the same time keep their pipeline order.
+    pub fn topologically_sorted_nodes(&self) -> Result<Vec<&PipelineNode>, CycleError> {
+        let mut sorted: Vec<&PipelineNode> = Vec::with_capacity(self.nodes.len());
+        let mut placed: HashSet<&str> = HashSet::with_capacity(self.nodes.len());
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in &self.nodes {
+                if placed.contains(node.id.as_str()) {
+                    continue;
+                }
+                let ready = node
+                    .depends_on
+                    .iter()
+                    .flatten()
+                    .all(|dep| placed.contains(dep.node()));
+                if ready {
+                    placed.insert(&node.id);
+                    sorted.push(node);
+                    changed = true;
+                }
+            }
+        }
+
+        if sorted.len() < self.nodes.len() {
+            return Err(CycleError {
+                node_ids: self
+                    .nodes
+                    .iter()
+                    .filter(|node| !placed.contains(node.id.as_str()))
+                    .map(|node| node.id.clone())
+                    .collect(),
+            });
+        }
+        Ok(sorted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PipelineNodeType, XYPosition};
+
+    fn node(id: &str, depends_on: &[&str]) -> PipelineNode {
+        PipelineNode {
+            id: id.to_string(),
+            label: id.to_string(),
+            step_type: PipelineNodeType::OutLog,
+            instances: None,
+            helper_instances: None,
+            position: XYPosition { x: 0.0, y: 0.0 },
+            settings: None,
+            depends_on: (!depends_on.is_empty())
+                .then(|| depends_on.iter().map(|&dep| dep.into()).collect()),
+            resilience: None,
+        }
+    }
+
+    fn pipeline(nodes: Vec<PipelineNode>) -> Pipeline {
+        Pipeline {
+            name: "pipeline".to_string(),
+            version: "1".to_string(),
+            nodes,
+            dead_letter: None,
+            helper_instances: None,
+        }
+    }
+
+    fn sorted_ids(pipeline: &Pipeline) -> Vec<&str> {
+        pipeline
+            .topologically_sorted_nodes()
+            .unwrap()
+            .into_iter()
+            .map(|node| node.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_chain() {
+        let pipeline = pipeline(vec![
+            node("sink", &["processor"]),
+            node("processor", &["source"]),
+            node("source", &[]),
+        ]);
+        assert_eq!(sorted_ids(&pipeline), ["source", "processor", "sink"]);
+    }
+
+    #[test]
+    fn test_diamond() {
+        let pipeline = pipeline(vec![
+            node("sink", &["left", "right"]),
+            node("right", &["source"]),
+            node("left", &["source"]),
+            node("source", &[]),
+        ]);
+        assert_eq!(sorted_ids(&pipeline), ["source", "right", "left", "sink"]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let pipeline = pipeline(vec![
+            node("source", &[]),
+            node("a", &["source", "b"]),
+            node("b", &["a"]),
+            node("sink", &["b"]),
+        ]);
+        let err = pipeline.topologically_sorted_nodes().unwrap_err();
+        assert_eq!(err.node_ids, ["a", "b", "sink"]);
+        assert_eq!(
+            err.to_string(),
+            "Nodes are not reachable from any source node (missing dependency or cycle): a, b, sink"
+        );
+    }
+
+    #[test]
+    fn test_missing_dependency() {
+        let pipeline = pipeline(vec![node("source", &[]), node("sink", &["missing"])]);
+        let err = pipeline.topologically_sorted_nodes().unwrap_err();
+        assert_eq!(err.node_ids, ["sink"]);
+    }
+}
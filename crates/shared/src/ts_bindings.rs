@@ -0,0 +1,155 @@
+//! Checks that the committed `pipeline.ts` matches the TypeScript generated from this crate.
+//!
+//! Run with `cargo test -p shared --features ts-check`. After changing an exported type,
+//! regenerate the committed file with `UPDATE_PIPELINE_TS=1 cargo test -p shared --features
+//! ts-check` (or `just ts-bindings-update`) and commit the result.
+
+use std::path::{Path, PathBuf};
+
+use ts_rs::TS;
+
+use crate::*;
+
+/// Committed copy of the generated bindings, relative to the crate root
+const COMMITTED_PIPELINE_TS: &str = "pipeline.ts";
+
+macro_rules! export_all_to {
+    ($dir:expr, $($ty:ty),+ $(,)?) => {
+        $(<$ty>::export_all_to($dir).expect(concat!("Failed to export ", stringify!($ty)));)+
+    };
+}
+
+fn generate_pipeline_ts(dir: &Path) -> String {
+    export_all_to!(
+        dir,
+        Pipeline,
+        PipelineNode,
+        DependsOn,
+        PipelineNodeType,
+        PipelineNodeSettings,
+        XYPosition,
+        InHttpWebhookSettings,
+        EventFilter,
+        RequestMetadata,
+        InRssReaderSettings,
+        InGraphqlPollerSettings,
+        MongodbReadMode,
+        MongodbFullDocument,
+        InMongodbSettings,
+        RabbitmqAckMode,
+        InRabbitmqSettings,
+        HttpHeader,
+        AuthenticationConfig,
+        Authentication,
+        Validation,
+        ProcessorWasmSettings,
+        Format,
+        CsvOptions,
+        ProcessorFormatSettings,
+        ProcessorFilterSettings,
+        OutHttpWebhookSettings,
+        CircuitBreakerSettings,
+        Compression,
+        SigningAlgorithm,
+        WebhookSigning,
+        OutLogSettings,
+        FileFormat,
+        FileRotation,
+        OutFileSettings,
+        MongodbWriteMode,
+        OutMongodbSettings,
+        LokiBatchSettings,
+        OutLokiSettings,
+        RabbitmqDeliveryMode,
+        RabbitmqDeclareSettings,
+        OutRabbitmqSettings,
+        SnowflakeAuth,
+        SnowflakeWriteMode,
+        OutSnowflakeSettings,
+        BigqueryWriteMode,
+        OutGoogleBigquerySettings,
+        LambdaInvocationType,
+        AwsCredentials,
+        OutAwsLambdaSettings,
+        OutGraphqlMutationSettings,
+        NoSettings,
+        Backoff,
+        Resilience,
+    );
+
+    let generated = std::fs::read_to_string(dir.join(PIPELINE_TS_FILE_PATH))
+        .expect("Failed to read generated pipeline.ts");
+    normalize_line_endings(&generated)
+}
+
+fn normalize_line_endings(contents: &str) -> String {
+    contents.replace("\r\n", "\n")
+}
+
+/// Line diff of `expected` against `actual`, with removed lines prefixed by `-` and added lines
+/// by `+`
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence lengths of the suffixes
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            diff.push_str(&format!("{:>5} + {}\n", j + 1, actual[j]));
+            j += 1;
+        } else {
+            diff.push_str(&format!("{:>5} - {}\n", i + 1, expected[i]));
+            i += 1;
+        }
+    }
+    diff
+}
+
+#[test]
+fn test_line_diff() {
+    assert_eq!(line_diff("a\nb\nc\n", "a\nb\nc\n"), "");
+    assert_eq!(
+        line_diff("a\nb\nc\n", "a\nx\nc\nd\n"),
+        "    2 + x\n    2 - b\n    4 + d\n"
+    );
+}
+
+#[test]
+fn test_committed_pipeline_ts_is_up_to_date() {
+    let out_dir = std::env::temp_dir().join(format!("pipestack-ts-check-{}", std::process::id()));
+    let generated = generate_pipeline_ts(&out_dir);
+    let _ = std::fs::remove_dir_all(&out_dir);
+
+    let committed_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(COMMITTED_PIPELINE_TS);
+    if std::env::var_os("UPDATE_PIPELINE_TS").is_some() {
+        std::fs::write(&committed_path, &generated).expect("Failed to update pipeline.ts");
+        return;
+    }
+
+    let committed = std::fs::read_to_string(&committed_path)
+        .map(|contents| normalize_line_endings(&contents))
+        .unwrap_or_default();
+    assert!(
+        committed == generated,
+        "{} is out of date. Regenerate it with `UPDATE_PIPELINE_TS=1 cargo test -p shared \
+         --features ts-check`.\n\n{}",
+        committed_path.display(),
+        line_diff(&committed, &generated)
+    );
+}
@@ -0,0 +1,1093 @@
+use crate::{
+    BigqueryWriteMode, InGraphqlPollerSettings, InHttpWebhookSettings, InMongodbSettings,
+    InRabbitmqSettings, InRssReaderSettings, MongodbReadMode, OutAwsLambdaSettings,
+    OutFileSettings, OutGoogleBigquerySettings, OutGraphqlMutationSettings, OutHttpWebhookSettings,
+    OutLogSettings, OutLokiSettings, OutMongodbSettings, OutRabbitmqSettings, OutSnowflakeSettings,
+    Pipeline, PipelineNode, PipelineNodeSettings, PipelineNodeType, ProcessorFilterSettings,
+    ProcessorFormatSettings, ProcessorWasmSettings, SnowflakeAuth, SnowflakeWriteMode, Template,
+};
+
+const IN_HTTP_METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+const OUT_HTTP_METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// A settings field holding a value the node can't run with
+#[derive(Debug, PartialEq, Eq)]
+pub struct SettingsError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl SettingsError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+fn require_non_empty(field: &'static str, value: &str) -> Result<(), SettingsError> {
+    if value.trim().is_empty() {
+        return Err(SettingsError::new(field, "must not be empty"));
+    }
+    Ok(())
+}
+
+fn require_method(
+    field: &'static str,
+    method: &str,
+    allowed: &[&str],
+) -> Result<(), SettingsError> {
+    if !allowed.contains(&method) {
+        return Err(SettingsError::new(
+            field,
+            format!("{method:?} is not one of {}", allowed.join(", ")),
+        ));
+    }
+    Ok(())
+}
+
+/// Require an absolute `http` or `https` URL with a host
+fn require_http_url(field: &'static str, url: &str) -> Result<(), SettingsError> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| {
+            SettingsError::new(
+                field,
+                format!("{url:?} must start with http:// or https://"),
+            )
+        })?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    if host.is_empty() || host.starts_with(':') {
+        return Err(SettingsError::new(field, format!("{url:?} has no host")));
+    }
+    if url.chars().any(char::is_whitespace) {
+        return Err(SettingsError::new(
+            field,
+            format!("{url:?} must not contain whitespace"),
+        ));
+    }
+    Ok(())
+}
+
+impl InHttpWebhookSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_method("method", &self.method, &IN_HTTP_METHODS)?;
+        require_non_empty("path", &self.path)?;
+        if let Some(filter) = &self.event_filter
+            && filter.allowed_events.is_empty()
+        {
+            return Err(SettingsError::new(
+                "eventFilter.allowedEvents",
+                "must list at least one event",
+            ));
+        }
+        if let Some(metadata) = &self.metadata {
+            for header in metadata.headers.iter().flatten() {
+                require_non_empty("metadata.headers", header)?;
+            }
+        }
+        if self.reply_timeout_ms == Some(0) {
+            return Err(SettingsError::new(
+                "replyTimeoutMs",
+                "must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl InRssReaderSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_http_url("feedUrl", &self.feed_url)?;
+        if self.poll_interval_ms == 0 {
+            return Err(SettingsError::new(
+                "pollIntervalMs",
+                "must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl InGraphqlPollerSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_http_url("url", &self.url)?;
+        require_non_empty("query", &self.query)?;
+        if let Some(variables) = &self.variables
+            && !variables.is_object()
+        {
+            return Err(SettingsError::new("variables", "must be a JSON object"));
+        }
+        for header in self.headers.iter().flatten() {
+            require_non_empty("headers.key", &header.key)?;
+        }
+        if self.interval_ms == 0 {
+            return Err(SettingsError::new("intervalMs", "must be greater than 0"));
+        }
+        if let Some(cursor) = &self.cursor
+            && !(cursor.starts_with("$.") || cursor.starts_with("$["))
+        {
+            return Err(SettingsError::new(
+                "cursor",
+                format!("{cursor:?} is not a JSONPath starting at `$`"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl ProcessorFormatSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if let Some(delimiter) = self.csv_options.as_ref().and_then(|o| o.delimiter)
+            && !delimiter.is_ascii()
+        {
+            return Err(SettingsError::new(
+                "csvOptions.delimiter",
+                format!("{delimiter:?} must be an ASCII character"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Require a `sha256:` digest of 64 lowercase hex digits
+fn require_sha256_digest(field: &'static str, digest: &str) -> Result<(), SettingsError> {
+    let is_valid = digest.strip_prefix("sha256:").is_some_and(|hex| {
+        hex.len() == 64 && hex.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+    });
+    if !is_valid {
+        return Err(SettingsError::new(
+            field,
+            format!("{digest:?} must be sha256: followed by 64 lowercase hex digits"),
+        ));
+    }
+    Ok(())
+}
+
+impl ProcessorWasmSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if let Some(digest) = &self.digest {
+            require_sha256_digest("digest", digest)?;
+        }
+        Ok(())
+    }
+}
+
+impl ProcessorFilterSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_non_empty("predicate", &self.predicate)
+    }
+}
+
+impl OutHttpWebhookSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_method("method", &self.method, &OUT_HTTP_METHODS)?;
+        require_http_url("url", &self.url)?;
+        for header in self.headers.iter().flatten() {
+            require_non_empty("headers.key", &header.key)?;
+        }
+        if let Some(sign) = &self.sign {
+            require_non_empty("sign.headerName", &sign.header_name)?;
+            require_non_empty("sign.secret", &sign.secret)?;
+        }
+        if let Some(circuit_breaker) = &self.circuit_breaker
+            && circuit_breaker.failure_threshold == 0
+        {
+            return Err(SettingsError::new(
+                "circuitBreaker.failureThreshold",
+                "must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl OutFileSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_non_empty("pathTemplate", &self.path_template)
+    }
+}
+
+impl OutLogSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if self.max_log_bytes == Some(0) {
+            return Err(SettingsError::new("maxLogBytes", "must be greater than 0"));
+        }
+        if let Some(fields) = &self.fields {
+            if fields.is_empty() {
+                return Err(SettingsError::new("fields", "must not be empty"));
+            }
+            for field in fields {
+                require_json_pointer("fields", field)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Require a MongoDB connection string, without echoing it as it may hold credentials
+fn require_mongodb_uri(field: &'static str, uri: &str) -> Result<(), SettingsError> {
+    if !uri.starts_with("mongodb://") && !uri.starts_with("mongodb+srv://") {
+        return Err(SettingsError::new(
+            field,
+            "must start with mongodb:// or mongodb+srv://",
+        ));
+    }
+    Ok(())
+}
+
+impl InMongodbSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_mongodb_uri("connectionString", &self.connection_string)?;
+        require_non_empty("database", &self.database)?;
+        require_non_empty("collection", &self.collection)?;
+        if self.full_document.is_some() && self.mode != MongodbReadMode::ChangeStream {
+            return Err(SettingsError::new(
+                "fullDocument",
+                "is only used by the changestream mode",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl OutMongodbSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_mongodb_uri("connectionString", &self.connection_string)?;
+        require_non_empty("database", &self.database)?;
+        require_non_empty("collection", &self.collection)?;
+        if let Some(id_field) = &self.id_field {
+            require_non_empty("idField", id_field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Require a Loki label name, which follows the Prometheus rules: `[a-zA-Z_][a-zA-Z0-9_]*`
+fn require_label_name(field: &'static str, name: &str) -> Result<(), SettingsError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(SettingsError::new(
+            field,
+            format!("{name:?} is not a valid label name"),
+        ));
+    }
+    Ok(())
+}
+
+fn require_json_pointer(field: &'static str, pointer: &str) -> Result<(), SettingsError> {
+    if !pointer.starts_with('/') {
+        return Err(SettingsError::new(
+            field,
+            format!("{pointer:?} must be a JSON pointer starting with /"),
+        ));
+    }
+    Ok(())
+}
+
+/// Require `template` to be a valid `Template`
+fn require_template(field: &'static str, template: &str) -> Result<(), SettingsError> {
+    Template::parse(template)
+        .map(drop)
+        .map_err(|e| SettingsError::new(field, e.to_string()))
+}
+
+impl OutLokiSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_http_url("url", &self.url)?;
+        if self.labels.is_empty() {
+            return Err(SettingsError::new("labels", "must not be empty"));
+        }
+        for (name, value) in &self.labels {
+            require_label_name("labels", name)?;
+            require_template("labels", value)?;
+        }
+        if let Some(timestamp_field) = &self.timestamp_field {
+            require_json_pointer("timestampField", timestamp_field)?;
+        }
+        if let Some(batch) = &self.batch {
+            if batch.max_entries == Some(0) {
+                return Err(SettingsError::new(
+                    "batch.maxEntries",
+                    "must be greater than 0",
+                ));
+            }
+            if batch.max_bytes == Some(0) {
+                return Err(SettingsError::new(
+                    "batch.maxBytes",
+                    "must be greater than 0",
+                ));
+            }
+            if batch.max_wait_ms == Some(0) {
+                return Err(SettingsError::new(
+                    "batch.maxWaitMs",
+                    "must be greater than 0",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+const RABBITMQ_EXCHANGE_TYPES: [&str; 4] = ["direct", "fanout", "topic", "headers"];
+
+/// Longest exchange name or routing key RabbitMQ accepts, in bytes
+const RABBITMQ_MAX_NAME_BYTES: usize = 255;
+
+impl OutRabbitmqSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_http_url("url", &self.url)?;
+        if self.exchange.len() > RABBITMQ_MAX_NAME_BYTES {
+            return Err(SettingsError::new(
+                "exchange",
+                format!("must be at most {RABBITMQ_MAX_NAME_BYTES} bytes"),
+            ));
+        }
+        let routing_key = Template::parse(&self.routing_key)
+            .map_err(|e| SettingsError::new("routingKey", e.to_string()))?;
+        if routing_key.is_literal() && self.routing_key.len() > RABBITMQ_MAX_NAME_BYTES {
+            return Err(SettingsError::new(
+                "routingKey",
+                format!("must be at most {RABBITMQ_MAX_NAME_BYTES} bytes"),
+            ));
+        }
+        if let Some(declare) = &self.declare {
+            if self.exchange.is_empty() {
+                return Err(SettingsError::new(
+                    "declare",
+                    "the default exchange can't be declared",
+                ));
+            }
+            if let Some(exchange_type) = &declare.exchange_type
+                && !RABBITMQ_EXCHANGE_TYPES.contains(&exchange_type.as_str())
+            {
+                return Err(SettingsError::new(
+                    "declare.exchangeType",
+                    format!(
+                        "{exchange_type:?} must be one of {}",
+                        RABBITMQ_EXCHANGE_TYPES.join(", ")
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InRabbitmqSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_http_url("url", &self.url)?;
+        require_non_empty("queue", &self.queue)?;
+        if self.queue.len() > RABBITMQ_MAX_NAME_BYTES {
+            return Err(SettingsError::new(
+                "queue",
+                format!("must be at most {RABBITMQ_MAX_NAME_BYTES} bytes"),
+            ));
+        }
+        match &self.exchange {
+            Some(exchange) if exchange.is_empty() => {
+                return Err(SettingsError::new(
+                    "exchange",
+                    "the default exchange can't be bound",
+                ));
+            }
+            None if self.binding_key.is_some() => {
+                return Err(SettingsError::new(
+                    "bindingKey",
+                    "is only used when an exchange is set",
+                ));
+            }
+            _ => {}
+        }
+        if self.prefetch == Some(0) {
+            return Err(SettingsError::new("prefetch", "must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+impl OutSnowflakeSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_non_empty("account", &self.account)?;
+        require_non_empty("warehouse", &self.warehouse)?;
+        require_non_empty("database", &self.database)?;
+        require_non_empty("schema", &self.schema)?;
+        require_non_empty("table", &self.table)?;
+        match &self.auth {
+            SnowflakeAuth::KeyPair {
+                user,
+                private_key_secret,
+                ..
+            } => {
+                require_non_empty("auth.user", user)?;
+                require_non_empty("auth.privateKeySecret", private_key_secret)?;
+            }
+            SnowflakeAuth::Password {
+                user,
+                password_secret,
+            } => {
+                require_non_empty("auth.user", user)?;
+                require_non_empty("auth.passwordSecret", password_secret)?;
+            }
+        }
+        let has_key_columns = self
+            .key_columns
+            .as_ref()
+            .is_some_and(|columns| !columns.is_empty());
+        match self.write_mode {
+            SnowflakeWriteMode::Merge if !has_key_columns => Err(SettingsError::new(
+                "keyColumns",
+                "must not be empty when merging",
+            )),
+            SnowflakeWriteMode::Insert if self.key_columns.is_some() => Err(SettingsError::new(
+                "keyColumns",
+                "is only used by the merge write mode",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Require a BigQuery dataset or table name: letters, digits and underscores
+fn require_bigquery_name(field: &'static str, name: &str) -> Result<(), SettingsError> {
+    require_non_empty(field, name)?;
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(SettingsError::new(
+            field,
+            format!("{name:?} may only contain letters, digits and underscores"),
+        ));
+    }
+    Ok(())
+}
+
+impl OutGoogleBigquerySettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_non_empty("projectId", &self.project_id)?;
+        require_bigquery_name("dataset", &self.dataset)?;
+        require_bigquery_name("table", &self.table)?;
+        require_non_empty("credentialsSecret", &self.credentials_secret)?;
+        if self.schema_autodetect.is_some() && self.write_mode != BigqueryWriteMode::Load {
+            return Err(SettingsError::new(
+                "schemaAutodetect",
+                "is only used by the load write mode",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl OutAwsLambdaSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_non_empty("functionName", &self.function_name)?;
+        if !self
+            .function_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+        {
+            return Err(SettingsError::new(
+                "functionName",
+                format!("{:?} is not a function name or ARN", self.function_name),
+            ));
+        }
+        // Regions look like `eu-west-1` or `us-gov-east-1`
+        let parts: Vec<&str> = self.region.split('-').collect();
+        if parts.len() < 3
+            || !parts.iter().all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            })
+        {
+            return Err(SettingsError::new(
+                "region",
+                format!("{:?} is not an AWS region", self.region),
+            ));
+        }
+        require_non_empty(
+            "credentials.accessKeyIdSecret",
+            &self.credentials.access_key_id_secret,
+        )?;
+        require_non_empty(
+            "credentials.secretAccessKeySecret",
+            &self.credentials.secret_access_key_secret,
+        )?;
+        if let Some(session_token_secret) = &self.credentials.session_token_secret {
+            require_non_empty("credentials.sessionTokenSecret", session_token_secret)?;
+        }
+        if let Some(qualifier) = &self.qualifier {
+            require_non_empty("qualifier", qualifier)?;
+            if !qualifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '$'))
+            {
+                return Err(SettingsError::new(
+                    "qualifier",
+                    format!("{qualifier:?} is not a function version or alias"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OutGraphqlMutationSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        require_http_url("url", &self.url)?;
+        if !self.mutation.trim_start().starts_with("mutation") {
+            return Err(SettingsError::new(
+                "mutation",
+                "must be a GraphQL mutation, starting with `mutation`",
+            ));
+        }
+        for value in self
+            .variables_template
+            .iter()
+            .flat_map(|template| template.values())
+        {
+            require_templates("variablesTemplate", value)?;
+        }
+        for header in self.headers.iter().flatten() {
+            require_non_empty("headers.key", &header.key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Require every string in a JSON template to be a valid `Template`
+fn require_templates(
+    field: &'static str,
+    template: &serde_json::Value,
+) -> Result<(), SettingsError> {
+    match template {
+        serde_json::Value::String(value) => require_template(field, value),
+        serde_json::Value::Array(values) => values
+            .iter()
+            .try_for_each(|value| require_templates(field, value)),
+        serde_json::Value::Object(values) => values
+            .values()
+            .try_for_each(|value| require_templates(field, value)),
+        _ => Ok(()),
+    }
+}
+
+/// Names of the capability components generated for every pipeline
+pub const RESERVED_COMPONENT_NAMES: [&str; 5] = [
+    "httpserver",
+    "httpclient",
+    "messaging-nats",
+    "keyvalue-nats",
+    "metrics",
+];
+
+/// Prefixes of the names of the glue components generated for a node, followed by its id
+pub const RESERVED_COMPONENT_PREFIXES: [&str; 2] = ["in-internal-for-", "out-internal-for-"];
+
+impl Pipeline {
+    /// Reject node ids that are names of components generated for the pipeline, as the node
+    /// would be deployed as a second component with the same name
+    pub fn validate_names(&self) -> Result<(), String> {
+        for node in &self.nodes {
+            if RESERVED_COMPONENT_NAMES.contains(&node.id.as_str())
+                || RESERVED_COMPONENT_PREFIXES
+                    .iter()
+                    .any(|prefix| node.id.starts_with(prefix))
+            {
+                return Err(format!(
+                    "Node id {} is reserved for a component generated for the pipeline",
+                    node.id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Name of a node type as written in pipelines, e.g. `in-http-webhook`
+fn type_name(node_type: PipelineNodeType) -> String {
+    serde_json::to_value(node_type)
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{node_type:?}"))
+}
+
+impl PipelineNode {
+    /// Reject settings tagged with another type than the node's, e.g. `out-log` settings on an
+    /// `in-http-webhook` node, which would otherwise be deployed with settings it can't read
+    pub fn validate_settings_type(&self) -> Result<(), String> {
+        match &self.settings {
+            Some(settings) if settings.node_type() != self.step_type => Err(format!(
+                "Node {} has type {} but settings of type {}",
+                self.id,
+                type_name(self.step_type),
+                type_name(settings.node_type())
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PipelineNodeSettings {
+    /// Check the settings hold values the node can run with. Node types without settings of
+    /// their own always pass.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        match self {
+            PipelineNodeSettings::InHttpWebhook(settings) => settings.validate(),
+            PipelineNodeSettings::InRssReader(settings) => settings.validate(),
+            PipelineNodeSettings::InGraphqlPoller(settings) => settings.validate(),
+            PipelineNodeSettings::InMongodb(settings) => settings.validate(),
+            PipelineNodeSettings::InRabbitmq(settings) => settings.validate(),
+            PipelineNodeSettings::ProcessorWasm(settings) => settings.validate(),
+            PipelineNodeSettings::ProcessorFormat(settings) => settings.validate(),
+            PipelineNodeSettings::ProcessorFilter(settings) => settings.validate(),
+            PipelineNodeSettings::OutHttpWebhook(settings) => settings.validate(),
+            PipelineNodeSettings::OutFile(settings) => settings.validate(),
+            PipelineNodeSettings::OutLog(settings) => settings.validate(),
+            PipelineNodeSettings::OutMongodb(settings) => settings.validate(),
+            PipelineNodeSettings::OutLoki(settings) => settings.validate(),
+            PipelineNodeSettings::OutRabbitmq(settings) => settings.validate(),
+            PipelineNodeSettings::OutSnowflake(settings) => settings.validate(),
+            PipelineNodeSettings::OutGoogleBigquery(settings) => settings.validate(),
+            PipelineNodeSettings::OutAwsLambda(settings) => settings.validate(),
+            PipelineNodeSettings::OutGraphqlMutation(settings) => settings.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn out_http_webhook(method: &str, url: &str) -> OutHttpWebhookSettings {
+        OutHttpWebhookSettings {
+            method: method.to_string(),
+            url: url.to_string(),
+            content_type: None,
+            set_content_type: None,
+            headers: None,
+            authentication: None,
+            validation: None,
+            log_bodies: None,
+            sign: None,
+            circuit_breaker: None,
+            compress: None,
+        }
+    }
+
+    #[test]
+    fn test_processor_wasm_digest() {
+        let settings = |digest: Option<&str>| ProcessorWasmSettings {
+            source: "enrich.wasm".to_string(),
+            instances: 1,
+            digest: digest.map(str::to_string),
+            bytes_input: None,
+        };
+        let digest = format!("sha256:{}", "ab12".repeat(16));
+
+        assert!(settings(None).validate().is_ok());
+        assert!(settings(Some(&digest)).validate().is_ok());
+        for invalid in [
+            "ab12".repeat(16),
+            format!("sha256:{}", "AB12".repeat(16)),
+            format!("sha256:{}", "ab12".repeat(15)),
+            format!("sha512:{}", "ab12".repeat(16)),
+        ] {
+            assert_eq!(
+                settings(Some(&invalid)).validate().unwrap_err().field,
+                "digest"
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_http_webhook_url() {
+        assert!(
+            out_http_webhook("POST", "https://example.com/hook?a=b")
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            out_http_webhook("POST", "http://localhost:8080")
+                .validate()
+                .is_ok()
+        );
+
+        for url in [
+            "example.com/hook",
+            "https://",
+            "https:///hook",
+            "http://exa mple.com",
+            "",
+        ] {
+            let err = out_http_webhook("POST", url).validate().unwrap_err();
+            assert_eq!(err.field, "url", "{url:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_out_http_webhook_method() {
+        let err = out_http_webhook("post", "https://example.com")
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.field, "method");
+        assert_eq!(
+            err.to_string(),
+            r#"method: "post" is not one of GET, POST, PUT, PATCH, DELETE, HEAD, OPTIONS"#
+        );
+    }
+
+    fn pipeline(ids: &[&str]) -> Pipeline {
+        serde_json::from_value(serde_json::json!({
+            "name": "orders",
+            "version": "1",
+            "nodes": ids
+                .iter()
+                .map(|id| serde_json::json!({
+                    "id": id,
+                    "label": id,
+                    "type": "out-log",
+                    "position": {"x": 0.0, "y": 0.0}
+                }))
+                .collect::<Vec<_>>()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_settings_type_matches_node_type() {
+        let mut pipeline = pipeline(&["webhook"]);
+        let node = &mut pipeline.nodes[0];
+        node.step_type = PipelineNodeType::OutLog;
+        node.settings = Some(PipelineNodeSettings::OutLog(OutLogSettings::default()));
+        assert_eq!(node.validate_settings_type(), Ok(()));
+
+        node.settings = None;
+        assert_eq!(node.validate_settings_type(), Ok(()));
+    }
+
+    #[test]
+    fn test_mismatched_settings_type_is_rejected() {
+        let mut pipeline = pipeline(&["webhook"]);
+        let node = &mut pipeline.nodes[0];
+        node.step_type = PipelineNodeType::InHttpWebhook;
+        node.settings = Some(PipelineNodeSettings::OutLog(OutLogSettings::default()));
+        assert_eq!(
+            node.validate_settings_type(),
+            Err("Node webhook has type in-http-webhook but settings of type out-log".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reserved_node_ids() {
+        assert!(pipeline(&["log", "internal-log"]).validate_names().is_ok());
+
+        assert_eq!(
+            pipeline(&["log", "messaging-nats"]).validate_names(),
+            Err(
+                "Node id messaging-nats is reserved for a component generated for the pipeline"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            pipeline(&["in-internal-for-x"]).validate_names(),
+            Err(
+                "Node id in-internal-for-x is reserved for a component generated for the pipeline"
+                    .to_string()
+            )
+        );
+        assert!(pipeline(&["httpserver"]).validate_names().is_err());
+        assert!(
+            pipeline(&["out-internal-for-log"])
+                .validate_names()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_required_fields() {
+        let err = ProcessorFilterSettings {
+            predicate: " ".to_string(),
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err.field, "predicate");
+
+        let err = InHttpWebhookSettings {
+            method: "POST".to_string(),
+            path: String::new(),
+            content_type: None,
+            request_body_json_schema: None,
+            event_filter: None,
+            metadata: None,
+            reply_timeout_ms: None,
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err.field, "path");
+
+        let err = InHttpWebhookSettings {
+            method: "POST".to_string(),
+            path: "orders".to_string(),
+            content_type: None,
+            request_body_json_schema: None,
+            event_filter: None,
+            metadata: None,
+            reply_timeout_ms: Some(0),
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err.field, "replyTimeoutMs");
+    }
+
+    fn out_loki(labels: &[(&str, &str)]) -> OutLokiSettings {
+        OutLokiSettings {
+            url: "https://logs.example.com".to_string(),
+            labels: labels
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            auth: None,
+            timestamp_field: None,
+            batch: None,
+        }
+    }
+
+    #[test]
+    fn test_out_loki_labels() {
+        assert!(
+            out_loki(&[("app", "pipestack"), ("level", "{{/level}}")])
+                .validate()
+                .is_ok()
+        );
+
+        for labels in [
+            &[][..],
+            &[("1app", "pipestack")][..],
+            &[("app-name", "pipestack")][..],
+            &[("level", "{{level}}")][..],
+            &[("level", "{{/level | lower}}")][..],
+        ] {
+            let err = out_loki(labels).validate().unwrap_err();
+            assert_eq!(err.field, "labels", "{labels:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_out_loki_timestamp_field_and_batch() {
+        let mut settings = out_loki(&[("app", "pipestack")]);
+        settings.timestamp_field = Some("ts".to_string());
+        assert_eq!(settings.validate().unwrap_err().field, "timestampField");
+
+        settings.timestamp_field = Some("/ts".to_string());
+        settings.batch = Some(crate::LokiBatchSettings {
+            max_entries: Some(0),
+            max_bytes: None,
+            max_wait_ms: None,
+        });
+        assert_eq!(settings.validate().unwrap_err().field, "batch.maxEntries");
+    }
+
+    #[test]
+    fn test_out_rabbitmq_settings() {
+        let mut settings = OutRabbitmqSettings {
+            url: "https://rabbitmq.example.com:15672".to_string(),
+            vhost: None,
+            exchange: "orders".to_string(),
+            routing_key: "{{/region}}".to_string(),
+            delivery_mode: None,
+            auth: None,
+            declare: Some(crate::RabbitmqDeclareSettings::default()),
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.routing_key = "{{region}}".to_string();
+        assert_eq!(settings.validate().unwrap_err().field, "routingKey");
+
+        settings.routing_key = "orders.created".to_string();
+        settings.declare = Some(crate::RabbitmqDeclareSettings {
+            exchange_type: Some("fan-out".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            settings.validate().unwrap_err().field,
+            "declare.exchangeType"
+        );
+
+        settings.exchange = String::new();
+        assert_eq!(settings.validate().unwrap_err().field, "declare");
+    }
+
+    #[test]
+    fn test_in_rabbitmq_settings() {
+        let mut settings = InRabbitmqSettings {
+            url: "https://rabbitmq.example.com:15672".to_string(),
+            vhost: None,
+            queue: "orders".to_string(),
+            exchange: None,
+            binding_key: Some("orders.*".to_string()),
+            prefetch: None,
+            ack_mode: None,
+            auth: None,
+        };
+        assert_eq!(settings.validate().unwrap_err().field, "bindingKey");
+
+        settings.exchange = Some("events".to_string());
+        assert!(settings.validate().is_ok());
+
+        settings.prefetch = Some(0);
+        assert_eq!(settings.validate().unwrap_err().field, "prefetch");
+    }
+
+    #[test]
+    fn test_out_snowflake_key_columns() {
+        let mut settings = OutSnowflakeSettings {
+            account: "acme-analytics".to_string(),
+            warehouse: "LOADING".to_string(),
+            database: "SHOP".to_string(),
+            schema: "PUBLIC".to_string(),
+            table: "ORDERS".to_string(),
+            auth: SnowflakeAuth::Password {
+                user: "PIPESTACK".to_string(),
+                password_secret: "snowflake-password".to_string(),
+            },
+            write_mode: SnowflakeWriteMode::Merge,
+            key_columns: None,
+        };
+        assert_eq!(settings.validate().unwrap_err().field, "keyColumns");
+
+        settings.key_columns = Some(vec!["ORDER_ID".to_string()]);
+        assert!(settings.validate().is_ok());
+
+        settings.write_mode = SnowflakeWriteMode::Insert;
+        assert_eq!(settings.validate().unwrap_err().field, "keyColumns");
+    }
+
+    #[test]
+    fn test_out_google_bigquery_settings() {
+        let mut settings = OutGoogleBigquerySettings {
+            project_id: "acme-analytics".to_string(),
+            dataset: "shop".to_string(),
+            table: "orders".to_string(),
+            credentials_secret: "bigquery-service-account".to_string(),
+            write_mode: BigqueryWriteMode::Streaming,
+            schema_autodetect: Some(true),
+        };
+        assert_eq!(settings.validate().unwrap_err().field, "schemaAutodetect");
+
+        settings.write_mode = BigqueryWriteMode::Load;
+        assert!(settings.validate().is_ok());
+
+        settings.table = "orders-2024".to_string();
+        assert_eq!(settings.validate().unwrap_err().field, "table");
+    }
+
+    #[test]
+    fn test_in_graphql_poller_settings() {
+        let mut settings = InGraphqlPollerSettings {
+            url: "https://api.example.com/graphql".to_string(),
+            query: "{ orders { id } }".to_string(),
+            variables: Some(serde_json::json!({"status": "PAID"})),
+            headers: None,
+            authentication: None,
+            interval_ms: 30_000,
+            cursor: Some("$.data.orders.pageInfo.endCursor".to_string()),
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.cursor = Some("data.orders.pageInfo.endCursor".to_string());
+        assert_eq!(settings.validate().unwrap_err().field, "cursor");
+
+        settings.cursor = None;
+        settings.variables = Some(serde_json::json!(["PAID"]));
+        assert_eq!(settings.validate().unwrap_err().field, "variables");
+    }
+
+    #[test]
+    fn test_out_aws_lambda_settings() {
+        let mut settings = OutAwsLambdaSettings {
+            function_name: "arn:aws:lambda:eu-west-1:123456789012:function:process-order"
+                .to_string(),
+            region: "eu-west-1".to_string(),
+            invocation_type: None,
+            credentials: crate::AwsCredentials {
+                access_key_id_secret: "aws-access-key-id".to_string(),
+                secret_access_key_secret: "aws-secret-access-key".to_string(),
+                session_token_secret: None,
+            },
+            qualifier: Some("$LATEST".to_string()),
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.region = "Ireland".to_string();
+        assert_eq!(settings.validate().unwrap_err().field, "region");
+
+        settings.region = "eu-west-1".to_string();
+        settings.qualifier = Some("live/v2".to_string());
+        assert_eq!(settings.validate().unwrap_err().field, "qualifier");
+
+        settings.qualifier = None;
+        settings.credentials.secret_access_key_secret = String::new();
+        assert_eq!(
+            settings.validate().unwrap_err().field,
+            "credentials.secretAccessKeySecret"
+        );
+    }
+
+    #[test]
+    fn test_out_graphql_mutation_settings() {
+        let mut settings = OutGraphqlMutationSettings {
+            url: "https://api.example.com/graphql".to_string(),
+            mutation: "mutation Ship($input: ShipInput!) { ship(input: $input) { id } }"
+                .to_string(),
+            variables_template: Some(BTreeMap::from([(
+                "input".to_string(),
+                serde_json::json!({"id": "{{/order/id}}", "items": ["{{/order/items}}"]}),
+            )])),
+            headers: None,
+            authentication: None,
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.variables_template = Some(BTreeMap::from([(
+            "input".to_string(),
+            serde_json::json!({"id": "{{order.id}}"}),
+        )]));
+        assert_eq!(settings.validate().unwrap_err().field, "variablesTemplate");
+
+        settings.variables_template = None;
+        settings.mutation = "query { orders { id } }".to_string();
+        assert_eq!(settings.validate().unwrap_err().field, "mutation");
+    }
+
+    #[test]
+    fn test_out_log_fields() {
+        let mut settings = OutLogSettings {
+            max_log_bytes: None,
+            fields: Some(vec!["/id".to_string(), "/customer/email".to_string()]),
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.fields = Some(vec!["id".to_string()]);
+        assert_eq!(settings.validate().unwrap_err().field, "fields");
+
+        settings.fields = Some(Vec::new());
+        assert_eq!(settings.validate().unwrap_err().field, "fields");
+    }
+}